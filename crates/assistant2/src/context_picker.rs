@@ -1,6 +1,7 @@
 mod directory_context_picker;
 mod fetch_context_picker;
 mod file_context_picker;
+mod resource_context_picker;
 mod thread_context_picker;
 
 use std::path::PathBuf;
@@ -19,6 +20,7 @@ use crate::context::ContextKind;
 use crate::context_picker::directory_context_picker::DirectoryContextPicker;
 use crate::context_picker::fetch_context_picker::FetchContextPicker;
 use crate::context_picker::file_context_picker::FileContextPicker;
+use crate::context_picker::resource_context_picker::ResourceContextPicker;
 use crate::context_picker::thread_context_picker::ThreadContextPicker;
 use crate::context_store::ContextStore;
 use crate::thread_store::ThreadStore;
@@ -37,6 +39,7 @@ enum ContextPickerMode {
     Directory(Entity<DirectoryContextPicker>),
     Fetch(Entity<FetchContextPicker>),
     Thread(Entity<ThreadContextPicker>),
+    Resource(Entity<ResourceContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -95,6 +98,7 @@ impl ContextPicker {
             ];
             if self.allow_threads() {
                 context_kinds.push(ContextKind::Thread);
+                context_kinds.push(ContextKind::Resource);
             }
 
             let menu = menu
@@ -198,6 +202,25 @@ impl ContextPicker {
                     }));
                 }
             }
+            ContextKind::Resource => {
+                if let Some(thread_store) = self
+                    .thread_store
+                    .as_ref()
+                    .and_then(|thread_store| thread_store.upgrade())
+                {
+                    let context_server_manager = thread_store.read(cx).context_server_manager();
+                    self.mode = ContextPickerMode::Resource(cx.new(|cx| {
+                        ResourceContextPicker::new(
+                            context_server_manager,
+                            context_picker.clone(),
+                            self.context_store.clone(),
+                            self.confirm_behavior,
+                            window,
+                            cx,
+                        )
+                    }));
+                }
+            }
         }
 
         cx.notify();
@@ -397,6 +420,7 @@ impl Focusable for ContextPicker {
             ContextPickerMode::Directory(directory_picker) => directory_picker.focus_handle(cx),
             ContextPickerMode::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
             ContextPickerMode::Thread(thread_picker) => thread_picker.focus_handle(cx),
+            ContextPickerMode::Resource(resource_picker) => resource_picker.focus_handle(cx),
         }
     }
 }
@@ -414,6 +438,9 @@ impl Render for ContextPicker {
                 }
                 ContextPickerMode::Fetch(fetch_picker) => parent.child(fetch_picker.clone()),
                 ContextPickerMode::Thread(thread_picker) => parent.child(thread_picker.clone()),
+                ContextPickerMode::Resource(resource_picker) => {
+                    parent.child(resource_picker.clone())
+                }
             })
     }
 }
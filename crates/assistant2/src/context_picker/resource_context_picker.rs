@@ -0,0 +1,278 @@
+use std::sync::Arc;
+
+use context_server::manager::ContextServerManager;
+use context_server::protocol::ServerCapability;
+use context_server::types::Resource;
+use fuzzy::StringMatchCandidate;
+use gpui::{App, DismissEvent, Entity, FocusHandle, Focusable, Task, WeakEntity};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem};
+use util::ResultExt as _;
+use workspace::notifications::NotifyResultExt;
+
+use crate::context_picker::{ConfirmBehavior, ContextPicker};
+use crate::context_store::{self, ContextStore};
+
+pub struct ResourceContextPicker {
+    picker: Entity<Picker<ResourceContextPickerDelegate>>,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = ResourceContextPickerDelegate::new(
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+        );
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+
+        ResourceContextPicker { picker }
+    }
+}
+
+impl Focusable for ResourceContextPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceContextEntry {
+    pub server_id: Arc<str>,
+    pub resource: Resource,
+}
+
+pub struct ResourceContextPickerDelegate {
+    context_server_manager: Entity<ContextServerManager>,
+    context_picker: WeakEntity<ContextPicker>,
+    context_store: WeakEntity<context_store::ContextStore>,
+    confirm_behavior: ConfirmBehavior,
+    /// Resources listed by the running context servers, fetched on the first search.
+    resources: Option<Arc<Vec<ResourceContextEntry>>>,
+    matches: Vec<ResourceContextEntry>,
+    selected_index: usize,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        context_server_manager: Entity<ContextServerManager>,
+        context_picker: WeakEntity<ContextPicker>,
+        context_store: WeakEntity<context_store::ContextStore>,
+        confirm_behavior: ConfirmBehavior,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            context_server_manager,
+            context_picker,
+            context_store,
+            confirm_behavior,
+            resources: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search resources…".into()
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let resources_task = if let Some(resources) = self.resources.clone() {
+            Task::ready(resources)
+        } else {
+            let servers = self.context_server_manager.read(cx).servers();
+            cx.background_spawn(async move {
+                let mut resources = Vec::new();
+                for server in servers {
+                    let Some(protocol) = server.client() else {
+                        continue;
+                    };
+                    if !protocol.capable(ServerCapability::Resources) {
+                        continue;
+                    }
+                    if let Some(response) = protocol.list_resources().await.log_err() {
+                        resources.extend(response.resources.into_iter().map(|resource| {
+                            ResourceContextEntry {
+                                server_id: server.id(),
+                                resource,
+                            }
+                        }));
+                    }
+                }
+                Arc::new(resources)
+            })
+        };
+
+        let executor = cx.background_executor().clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let resources = resources_task.await;
+            let matches = if query.is_empty() {
+                resources.iter().cloned().collect()
+            } else {
+                let candidates = resources
+                    .iter()
+                    .enumerate()
+                    .map(|(id, entry)| StringMatchCandidate::new(id, &entry.resource.name))
+                    .collect::<Vec<_>>();
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+
+                matches
+                    .into_iter()
+                    .map(|mat| resources[mat.candidate_id].clone())
+                    .collect()
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.delegate.resources = Some(resources);
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+
+        let context_server_manager = self.context_server_manager.clone();
+        let Some(task) = self
+            .context_store
+            .update(cx, |context_store, cx| {
+                context_store.add_resource(
+                    context_server_manager,
+                    entry.server_id,
+                    entry.resource,
+                    cx,
+                )
+            })
+            .ok()
+        else {
+            return;
+        };
+
+        let confirm_behavior = self.confirm_behavior;
+        cx.spawn_in(window, |this, mut cx| async move {
+            match task.await.notify_async_err(&mut cx) {
+                None => anyhow::Ok(()),
+                Some(()) => this.update_in(&mut cx, |this, window, cx| match confirm_behavior {
+                    ConfirmBehavior::KeepOpen => {}
+                    ConfirmBehavior::Close => this.delegate.dismissed(window, cx),
+                }),
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |_, cx| {
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.matches[ix];
+
+        Some(ListItem::new(ix).inset(true).toggle_state(selected).child(
+            render_resource_context_entry(entry, self.context_store.clone(), cx),
+        ))
+    }
+}
+
+pub fn render_resource_context_entry(
+    entry: &ResourceContextEntry,
+    context_store: WeakEntity<ContextStore>,
+    cx: &mut App,
+) -> Div {
+    let added = context_store.upgrade().map_or(false, |ctx_store| {
+        ctx_store
+            .read(cx)
+            .includes_resource(&entry.server_id, &entry.resource.uri)
+            .is_some()
+    });
+
+    h_flex()
+        .gap_1p5()
+        .w_full()
+        .child(
+            Icon::new(IconName::Server)
+                .size(IconSize::XSmall)
+                .color(Color::Muted),
+        )
+        .child(Label::new(entry.resource.name.clone()))
+        .child(
+            Label::new(entry.server_id.to_string())
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+        .child(div().w_full())
+        .when(added, |el| {
+            el.child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::Check)
+                            .size(IconSize::Small)
+                            .color(Color::Success),
+                    )
+                    .child(Label::new("Added").size(LabelSize::Small)),
+            )
+        })
+}
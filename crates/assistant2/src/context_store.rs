@@ -3,17 +3,23 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, bail, Result};
 use collections::{BTreeMap, HashMap, HashSet};
+use context_server::manager::ContextServerManager;
+use context_server::types::Resource;
 use futures::{self, future, Future, FutureExt};
-use gpui::{App, AppContext as _, AsyncApp, Context, Entity, SharedString, Task, WeakEntity};
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, SharedString, Subscription, Task, WeakEntity,
+};
 use language::Buffer;
 use project::{ProjectPath, Worktree};
 use rope::Rope;
 use text::BufferId;
+use url::Url;
+use util::ResultExt as _;
 use workspace::Workspace;
 
 use crate::context::{
     AssistantContext, ContextBuffer, ContextId, ContextSnapshot, DirectoryContext,
-    FetchedUrlContext, FileContext, ThreadContext,
+    FetchedUrlContext, FileContext, ResourceContext, ThreadContext,
 };
 use crate::context_strip::SuggestedContext;
 use crate::thread::{Thread, ThreadId};
@@ -27,6 +33,10 @@ pub struct ContextStore {
     directories: HashMap<PathBuf, ContextId>,
     threads: HashMap<ThreadId, ContextId>,
     fetched_urls: HashMap<String, ContextId>,
+    resources: HashMap<(Arc<str>, Url), ContextId>,
+    /// Resources the context server reported as changed since they were last read.
+    stale_resources: HashSet<ContextId>,
+    context_server_manager: Option<(Entity<ContextServerManager>, Subscription)>,
 }
 
 impl ContextStore {
@@ -39,6 +49,9 @@ impl ContextStore {
             directories: HashMap::default(),
             threads: HashMap::default(),
             fetched_urls: HashMap::default(),
+            resources: HashMap::default(),
+            stale_resources: HashSet::default(),
+            context_server_manager: None,
         }
     }
 
@@ -58,6 +71,8 @@ impl ContextStore {
         self.directories.clear();
         self.threads.clear();
         self.fetched_urls.clear();
+        self.resources.clear();
+        self.stale_resources.clear();
     }
 
     pub fn add_file_from_path(
@@ -284,6 +299,62 @@ impl ContextStore {
             }));
     }
 
+    pub fn add_resource(
+        &mut self,
+        context_server_manager: Entity<ContextServerManager>,
+        server_id: Arc<str>,
+        resource: Resource,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if let Some(context_id) = self.includes_resource(&server_id, &resource.uri) {
+            self.remove_context(context_id);
+            return Task::ready(Ok(()));
+        }
+
+        let Some(server) = context_server_manager.read(cx).get_server(&server_id) else {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        };
+
+        if self.context_server_manager.is_none() {
+            let subscription =
+                cx.subscribe(&context_server_manager, Self::handle_context_server_event);
+            self.context_server_manager = Some((context_server_manager, subscription));
+        }
+
+        cx.spawn(|this, mut cx| async move {
+            let text = server.read_resource_text(resource.uri.clone()).await?;
+
+            this.update(&mut cx, |this, _cx| {
+                let id = this.next_context_id.post_inc();
+                this.resources
+                    .insert((server_id.clone(), resource.uri.clone()), id);
+                this.context
+                    .push(AssistantContext::Resource(ResourceContext {
+                        id,
+                        server_id,
+                        uri: resource.uri,
+                        name: resource.name.into(),
+                        text: text.into(),
+                    }));
+            })?;
+
+            anyhow::Ok(())
+        })
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        _context_server_manager: Entity<ContextServerManager>,
+        event: &context_server::manager::Event,
+        _cx: &mut Context<Self>,
+    ) {
+        if let context_server::manager::Event::ResourceUpdated { server_id, uri } = event {
+            if let Some(context_id) = self.includes_resource(server_id, uri) {
+                self.stale_resources.insert(context_id);
+            }
+        }
+    }
+
     pub fn accept_suggested_context(
         &mut self,
         suggested: &SuggestedContext,
@@ -326,6 +397,10 @@ impl ContextStore {
             AssistantContext::Thread(_) => {
                 self.threads.retain(|_, context_id| *context_id != id);
             }
+            AssistantContext::Resource(_) => {
+                self.resources.retain(|_, context_id| *context_id != id);
+                self.stale_resources.remove(&id);
+            }
         }
     }
 
@@ -391,6 +466,12 @@ impl ContextStore {
         self.fetched_urls.get(url).copied()
     }
 
+    pub fn includes_resource(&self, server_id: &Arc<str>, uri: &Url) -> Option<ContextId> {
+        self.resources
+            .get(&(server_id.clone(), uri.clone()))
+            .copied()
+    }
+
     /// Replaces the context that matches the ID of the new context, if any match.
     fn replace_context(&mut self, new_context: AssistantContext) {
         let id = new_context.id();
@@ -412,7 +493,8 @@ impl ContextStore {
                 }
                 AssistantContext::Directory(_)
                 | AssistantContext::FetchedUrl(_)
-                | AssistantContext::Thread(_) => None,
+                | AssistantContext::Thread(_)
+                | AssistantContext::Resource(_) => None,
             })
             .collect()
     }
@@ -550,6 +632,12 @@ pub fn refresh_context_store_text(
             // and doing the caching properly could be tricky (unless it's already handled by
             // the HttpClient?).
             AssistantContext::FetchedUrl(_) => {}
+            AssistantContext::Resource(resource_context) => {
+                let context_store = context_store.clone();
+                if let Some(task) = refresh_resource_text(context_store, resource_context, cx) {
+                    tasks.push(task);
+                }
+            }
         }
     }
 
@@ -637,6 +725,44 @@ fn refresh_thread_text(
     })
 }
 
+fn refresh_resource_text(
+    context_store: Entity<ContextStore>,
+    resource_context: &ResourceContext,
+    cx: &App,
+) -> Option<Task<()>> {
+    let id = resource_context.id;
+    let store = context_store.read(cx);
+    if !store.stale_resources.contains(&id) {
+        return None;
+    }
+    let (context_server_manager, _) = store.context_server_manager.as_ref()?;
+    let server = context_server_manager
+        .read(cx)
+        .get_server(&resource_context.server_id)?;
+
+    let server_id = resource_context.server_id.clone();
+    let uri = resource_context.uri.clone();
+    let name = resource_context.name.clone();
+    Some(cx.spawn(|mut cx| async move {
+        // Keep the previous text if the resource can no longer be read.
+        let Some(text) = server.read_resource_text(uri.clone()).await.log_err() else {
+            return;
+        };
+        context_store
+            .update(&mut cx, |context_store, _| {
+                context_store.stale_resources.remove(&id);
+                context_store.replace_context(AssistantContext::Resource(ResourceContext {
+                    id,
+                    server_id,
+                    uri,
+                    name,
+                    text: text.into(),
+                }));
+            })
+            .ok();
+    }))
+}
+
 fn refresh_context_buffer(
     context_buffer: &ContextBuffer,
     cx: &App,
@@ -67,6 +67,10 @@ impl ThreadStore {
         self.threads.len()
     }
 
+    pub fn context_server_manager(&self) -> Entity<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
     pub fn threads(&self) -> Vec<SavedThreadMetadata> {
         let mut threads = self.threads.iter().cloned().collect::<Vec<_>>();
         threads.sort_unstable_by_key(|thread| std::cmp::Reverse(thread.updated_at));
@@ -239,6 +243,8 @@ impl ThreadStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            // Context stores that include the resource re-read it before the next message is sent.
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -184,7 +184,8 @@ impl RenderOnce for ContextPill {
                             ContextKind::File => "Active Tab",
                             ContextKind::Thread
                             | ContextKind::Directory
-                            | ContextKind::FetchedUrl => "Active",
+                            | ContextKind::FetchedUrl
+                            | ContextKind::Resource => "Active",
                         })
                         .size(LabelSize::XSmall)
                         .color(Color::Muted),
@@ -17,6 +17,21 @@ pub enum CliRequest {
         open_new_workspace: Option<bool>,
         env: Option<HashMap<String, String>>,
     },
+    /// Shows the differences between two files, with `right` opened for editing.
+    Diff {
+        left: String,
+        right: String,
+        wait: bool,
+    },
+    /// Opens `merged` to resolve its conflicts, alongside diffs of `base` against `local` and
+    /// `remote`. Fails if `merged` still contains conflict markers when `wait` ends.
+    Merge {
+        local: String,
+        remote: String,
+        base: String,
+        merged: String,
+        wait: bool,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,7 +44,11 @@ Examples:
     `zed path-to-your-project`
           Open your project in Zed
     `zed -n path-to-file `
-          Open file/folder in a new window",
+          Open file/folder in a new window
+    `zed --wait --diff old-file new-file`
+          Compare two files, e.g. as a git difftool
+    `zed --wait --merge local remote base merged`
+          Resolve merge conflicts, e.g. as a git mergetool",
     after_help = "To read from stdin, append '-', e.g. 'ps axf | zed -'"
 )]
 struct Args {
@@ -61,6 +65,24 @@ struct Args {
     ///
     /// Use `path:line:column` syntax to open a file at the given line and column.
     paths_with_position: Vec<String>,
+    /// Show the differences between two files.
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["OLD_PATH", "NEW_PATH"],
+        conflicts_with = "paths_with_position"
+    )]
+    diff: Vec<String>,
+    /// Resolve the conflicts in a merged file, comparing both sides against their merge base.
+    ///
+    /// Exits with an error if the merged file still contains conflict markers once it is closed.
+    #[arg(
+        long,
+        num_args = 4,
+        value_names = ["LOCAL", "REMOTE", "BASE", "MERGED"],
+        conflicts_with_all = ["paths_with_position", "diff"]
+    )]
+    merge: Vec<String>,
     /// Print Zed's version and the app path.
     #[arg(short, long)]
     version: bool,
@@ -110,6 +132,12 @@ fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
     Ok(canonicalized.to_string(|path| path.to_string_lossy().to_string()))
 }
 
+fn canonicalize_compared_path(argument_str: &str) -> anyhow::Result<String> {
+    let path =
+        fs::canonicalize(argument_str).with_context(|| format!("resolving path {argument_str}"))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 fn main() -> Result<()> {
     // Exit flatpak sandbox if needed
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -216,6 +244,23 @@ fn main() -> Result<()> {
         }
     }
 
+    let diff = match args.diff.as_slice() {
+        [left, right] => Some((
+            canonicalize_compared_path(left)?,
+            canonicalize_compared_path(right)?,
+        )),
+        _ => None,
+    };
+    let merge = match args.merge.as_slice() {
+        [local, remote, base, merged] => Some((
+            canonicalize_compared_path(local)?,
+            canonicalize_compared_path(remote)?,
+            canonicalize_compared_path(base)?,
+            canonicalize_compared_path(merged)?,
+        )),
+        _ => None,
+    };
+
     if let Some(_) = args.dev_server_token {
         return Err(anyhow::anyhow!(
             "Dev servers were removed in v0.157.x please upgrade to SSH remoting: https://zed.dev/docs/remote-development"
@@ -228,13 +273,28 @@ fn main() -> Result<()> {
             let (_, handshake) = server.accept().context("Handshake after Zed spawn")?;
             let (tx, rx) = (handshake.requests, handshake.responses);
 
-            tx.send(CliRequest::Open {
-                paths,
-                urls,
-                wait: args.wait,
-                open_new_workspace,
-                env,
-            })?;
+            let request = match (diff, merge) {
+                (Some((left, right)), _) => CliRequest::Diff {
+                    left,
+                    right,
+                    wait: args.wait,
+                },
+                (None, Some((local, remote, base, merged))) => CliRequest::Merge {
+                    local,
+                    remote,
+                    base,
+                    merged,
+                    wait: args.wait,
+                },
+                (None, None) => CliRequest::Open {
+                    paths,
+                    urls,
+                    wait: args.wait,
+                    open_new_workspace,
+                    env,
+                },
+            };
+            tx.send(request)?;
 
             while let Ok(response) = rx.recv() {
                 match response {
@@ -0,0 +1 @@
+ALTER TABLE worktree_repositories ADD COLUMN operation_in_progress TEXT NULL;
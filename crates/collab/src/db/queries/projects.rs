@@ -345,6 +345,12 @@ impl Database {
                             current_merge_conflicts: ActiveValue::Set(Some(
                                 serde_json::to_string(&repository.current_merge_conflicts).unwrap(),
                             )),
+                            operation_in_progress: ActiveValue::Set(
+                                repository
+                                    .operation_in_progress
+                                    .as_ref()
+                                    .map(|operation| serde_json::to_string(operation).unwrap()),
+                            ),
                         }
                     },
                 ))
@@ -359,6 +365,7 @@ impl Database {
                         worktree_repository::Column::Branch,
                         worktree_repository::Column::BranchSummary,
                         worktree_repository::Column::CurrentMergeConflicts,
+                        worktree_repository::Column::OperationInProgress,
                     ])
                     .to_owned(),
                 )
@@ -798,6 +805,12 @@ impl Database {
                         .transpose()?
                         .unwrap_or_default();
 
+                    let operation_in_progress = db_repository_entry
+                        .operation_in_progress
+                        .as_ref()
+                        .map(|operation| serde_json::from_str(&operation))
+                        .transpose()?;
+
                     worktree.repository_entries.insert(
                         db_repository_entry.work_directory_id as u64,
                         proto::RepositoryEntry {
@@ -807,6 +820,7 @@ impl Database {
                             removed_statuses: Vec::new(),
                             current_merge_conflicts,
                             branch_summary,
+                            operation_in_progress,
                         },
                     );
                 }
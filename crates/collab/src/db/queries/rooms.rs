@@ -750,6 +750,12 @@ impl Database {
                             .transpose()?
                             .unwrap_or_default();
 
+                        let operation_in_progress = db_repository
+                            .operation_in_progress
+                            .as_ref()
+                            .map(|operation| serde_json::from_str(&operation))
+                            .transpose()?;
+
                         worktree.updated_repositories.push(proto::RepositoryEntry {
                             work_directory_id: db_repository.work_directory_id as u64,
                             branch: db_repository.branch,
@@ -757,6 +763,7 @@ impl Database {
                             removed_statuses,
                             current_merge_conflicts,
                             branch_summary,
+                            operation_in_progress,
                         });
                     }
                 }
@@ -17,6 +17,8 @@ pub struct Model {
     pub current_merge_conflicts: Option<String>,
     // A JSON object representing the current Branch values
     pub branch_summary: Option<String>,
+    // A JSON object describing the rebase or cherry-pick in progress
+    pub operation_in_progress: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -32,6 +32,7 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncApp)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -46,6 +47,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -78,13 +80,22 @@ struct Request<'a, T> {
     params: T,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct AnyRequest<'a> {
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
     id: RequestId,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     error: Option<Error>,
-    #[serde(borrow)]
+    #[serde(borrow, skip_serializing_if = "Option::is_none")]
     result: Option<&'a RawValue>,
 }
 
@@ -124,6 +135,8 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -158,22 +171,46 @@ impl Client {
             .unwrap_or_else(String::new);
 
         let transport = Arc::new(StdioTransport::new(binary, &cx)?);
+        Ok(Self::with_transport(
+            server_id,
+            server_name.into(),
+            transport,
+            cx,
+        ))
+    }
 
+    /// Creates a new Client instance for a context server reached through the given transport.
+    pub fn with_transport(
+        server_id: ContextServerId,
+        name: Arc<str>,
+        transport: Arc<dyn Transport>,
+        cx: AsyncApp,
+    ) -> Self {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
             let transport = transport.clone();
             move |cx| {
-                Self::handle_input(transport, notification_handlers, response_handlers, cx)
-                    .log_err()
+                Self::handle_input(
+                    transport,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
             }
         });
         let stderr_input_task = cx.spawn(|_| Self::handle_stderr(transport.clone()).log_err());
@@ -193,36 +230,68 @@ impl Client {
             .log_err()
         });
 
-        Ok(Self {
+        Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: server_name.into(),
+            name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
             transport,
-        })
+        }
     }
 
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches them
+    /// to the appropriate handlers. It processes requests (which are answered by registered
+    /// handlers), responses (which are matched to pending requests) and notifications
+    /// (which trigger registered handlers).
     async fn handle_input(
         transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
 
         while let Some(message) = receiver.next().await {
-            if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
+            if let Ok(request) = serde_json::from_str::<AnyRequest>(&message) {
+                let params = request.params.unwrap_or(Value::Null);
+                let response = request_handlers
+                    .lock()
+                    .get_mut(request.method.as_str())
+                    .map(|handler| handler(params, cx.clone()));
+                let id = request.id;
+                let method = request.method;
+                let outbound_tx = outbound_tx.clone();
+                cx.spawn(|_| {
+                    async move {
+                        let result = match response {
+                            Some(response) => response.await.map_err(|error| Error {
+                                code: INTERNAL_ERROR,
+                                message: format!("{error:#}"),
+                            }),
+                            None => Err(Error {
+                                code: METHOD_NOT_FOUND,
+                                message: format!("method not found: {method}"),
+                            }),
+                        };
+                        let response = Self::serialize_response(id, result)?;
+                        outbound_tx.send(response).await?;
+                        anyhow::Ok(())
+                    }
+                    .log_err()
+                })
+                .detach();
+            } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut() {
                     if let Some(handler) = handlers.remove(&response.id) {
                         handler(Ok(message.to_string()));
@@ -241,6 +310,27 @@ impl Client {
         Ok(())
     }
 
+    fn serialize_response(id: RequestId, result: Result<Value, Error>) -> Result<String> {
+        let response = match result {
+            Ok(value) => {
+                let value = serde_json::value::to_raw_value(&value)?;
+                serde_json::to_string(&AnyResponse {
+                    jsonrpc: JSON_RPC_VERSION,
+                    id,
+                    error: None,
+                    result: Some(&value),
+                })?
+            }
+            Err(error) => serde_json::to_string(&AnyResponse {
+                jsonrpc: JSON_RPC_VERSION,
+                id,
+                error: Some(error),
+                result: None,
+            })?,
+        };
+        Ok(response)
+    }
+
     /// Handles the stderr output from the context server.
     /// Continuously reads and logs any error messages from the server.
     async fn handle_stderr(transport: Arc<dyn Transport>) -> anyhow::Result<()> {
@@ -363,6 +453,16 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler for requests the context server sends to us. The handler's
+    /// result is sent back as the response, and requests without a handler are
+    /// answered with a "method not found" error.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncApp) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -386,3 +486,85 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::Stream;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::pin::Pin;
+
+    struct FakeTransport {
+        incoming_rx: channel::Receiver<String>,
+        outgoing_tx: channel::Sender<String>,
+    }
+
+    #[async_trait]
+    impl Transport for FakeTransport {
+        async fn send(&self, message: String) -> Result<()> {
+            Ok(self.outgoing_tx.send(message).await?)
+        }
+
+        fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(self.incoming_rx.clone())
+        }
+
+        fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(futures::stream::pending())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_server_requests(cx: &mut TestAppContext) {
+        let (incoming_tx, incoming_rx) = channel::unbounded();
+        let (outgoing_tx, outgoing_rx) = channel::unbounded();
+        let client = Client::with_transport(
+            ContextServerId("test".into()),
+            "test".into(),
+            Arc::new(FakeTransport {
+                incoming_rx,
+                outgoing_tx,
+            }),
+            cx.to_async(),
+        );
+        client.on_request("roots/list", |_, _| {
+            Task::ready(Ok(json!({ "roots": [{ "uri": "file:///project" }] })))
+        });
+        client.on_request("sampling/createMessage", |_, _| {
+            Task::ready(Err(anyhow!("the user declined the sampling request")))
+        });
+
+        let request = |message: Value| {
+            let incoming_tx = incoming_tx.clone();
+            let outgoing_rx = outgoing_rx.clone();
+            async move {
+                incoming_tx.send(message.to_string()).await.unwrap();
+                serde_json::from_str::<Value>(&outgoing_rx.recv().await.unwrap()).unwrap()
+            }
+        };
+
+        assert_eq!(
+            request(json!({ "jsonrpc": "2.0", "id": 0, "method": "roots/list" })).await,
+            json!({ "jsonrpc": "2.0", "id": 0, "result": { "roots": [{ "uri": "file:///project" }] } })
+        );
+        assert_eq!(
+            request(json!({ "jsonrpc": "2.0", "id": "a", "method": "sampling/createMessage" }))
+                .await,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "a",
+                "error": { "code": INTERNAL_ERROR, "message": "the user declined the sampling request" },
+            })
+        );
+        assert_eq!(
+            request(json!({ "jsonrpc": "2.0", "id": 1, "method": "elicitation/create" })).await,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": { "code": METHOD_NOT_FOUND, "message": "method not found: elicitation/create" },
+            })
+        );
+    }
+}
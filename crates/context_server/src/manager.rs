@@ -17,20 +17,23 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
-use collections::HashMap;
+use anyhow::{bail, Context as _, Result};
+use collections::{HashMap, HashSet};
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
+use gpui::{App, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity};
 use log;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use project::Project;
 use settings::{Settings, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
 use crate::{ContextServerSettings, ServerConfig};
 
 use crate::{
     client::{self, Client},
+    sampling,
+    transport::HttpTransport,
     types, ContextServerFactoryRegistry, CONTEXT_SERVERS_NAMESPACE,
 };
 
@@ -38,6 +41,7 @@ pub struct ContextServer {
     pub id: Arc<str>,
     pub config: Arc<ServerConfig>,
     pub client: RwLock<Option<Arc<crate::protocol::InitializedContextServerProtocol>>>,
+    subscribed_resources: Mutex<HashSet<Url>>,
 }
 
 impl ContextServer {
@@ -46,6 +50,7 @@ impl ContextServer {
             id,
             config,
             client: RwLock::new(None),
+            subscribed_resources: Mutex::default(),
         }
     }
 
@@ -61,20 +66,71 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncApp) -> Result<()> {
+    /// Reads the text contents of a resource, and subscribes to its updates if the server
+    /// supports it, so that [`Event::ResourceUpdated`] is emitted when the resource changes.
+    pub async fn read_resource_text(&self, uri: Url) -> Result<String> {
+        let protocol = self.client().context("context server is not running")?;
+        let response = protocol.read_resource(uri.clone()).await?;
+        let text = response
+            .contents
+            .into_iter()
+            .filter_map(|contents| match contents {
+                types::ResourceContentsType::Text(contents) => Some(contents.text),
+                types::ResourceContentsType::Blob(_) => None,
+            })
+            .collect::<Vec<_>>();
+        if text.is_empty() {
+            bail!("resource {uri} has no text contents");
+        }
+
+        if protocol.supports_resource_subscriptions()
+            && self.subscribed_resources.lock().insert(uri.clone())
+        {
+            if let Err(error) = protocol.subscribe_resource(uri.clone()).await {
+                self.subscribed_resources.lock().remove(&uri);
+                log::error!("failed to subscribe to resource {uri}: {error:#}");
+            }
+        }
+
+        Ok(text.join("\n"))
+    }
+
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakEntity<ContextServerManager>,
+        cx: &AsyncApp,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let client = if let Some(url) = &self.config.url {
+            let url = Url::parse(url)
+                .with_context(|| format!("invalid url for server {}: {url}", self.id))?;
+            let http_client = cx.update(|cx| cx.http_client())?;
+            let transport = Arc::new(HttpTransport::new(
+                url.clone(),
+                self.config.headers.clone().unwrap_or_default(),
+                http_client,
+                cx,
+            ));
+            Client::with_transport(
+                client::ContextServerId(self.id.clone()),
+                url.as_str().into(),
+                transport,
+                cx.clone(),
+            )
+        } else if let Some(command) = &self.config.command {
+            Client::new(
+                client::ContextServerId(self.id.clone()),
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or url specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        self.handle_server_messages(&client, manager);
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -93,6 +149,50 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Registers handlers for the requests and notifications the server sends to us.
+    fn handle_server_messages(&self, client: &Client, manager: WeakEntity<ContextServerManager>) {
+        client.on_request(types::RequestType::Ping.as_str(), |_, _| {
+            Task::ready(Ok(serde_json::json!({})))
+        });
+
+        client.on_request(types::RequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx));
+                Task::ready(roots.and_then(|roots| {
+                    Ok(serde_json::to_value(types::ListRootsResponse {
+                        roots,
+                        meta: None,
+                    })?)
+                }))
+            }
+        });
+
+        client.on_request(types::RequestType::CreateMessage.as_str(), {
+            let server_id = self.id.clone();
+            move |params, cx| sampling::create_message(server_id.clone(), params, cx)
+        });
+
+        client.on_notification(types::NotificationType::ResourcesUpdated.as_str(), {
+            let server_id = self.id.clone();
+            move |params, mut cx| {
+                let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                else {
+                    return;
+                };
+                manager
+                    .update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id: server_id.clone(),
+                            uri: params.uri,
+                        })
+                    })
+                    .ok();
+            }
+        });
+    }
+
     pub fn stop(&self) -> Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -112,8 +212,17 @@ pub struct ContextServerManager {
 }
 
 pub enum Event {
-    ServerStarted { server_id: Arc<str> },
-    ServerStopped { server_id: Arc<str> },
+    ServerStarted {
+        server_id: Arc<str>,
+    },
+    ServerStopped {
+        server_id: Arc<str>,
+    },
+    /// A resource read with [`ContextServer::read_resource_text`] has changed.
+    ResourceUpdated {
+        server_id: Arc<str>,
+        uri: Url,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -132,6 +241,12 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| match event {
+                    project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                        this.roots_changed();
+                    }
+                    _ => {}
+                }),
             ],
             project,
             registry,
@@ -173,6 +288,34 @@ impl ContextServerManager {
         }
     }
 
+    /// The project's worktrees, which are exposed to servers as roots.
+    fn roots(&self, cx: &App) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.servers.values() {
+            if let Some(client) = server.client() {
+                client
+                    .notify(
+                        types::NotificationType::RootsListChanged.as_str(),
+                        serde_json::json!({}),
+                    )
+                    .log_err();
+            }
+        }
+    }
+
     pub fn get_server(&self, id: &str) -> Option<Arc<ContextServer>> {
         self.servers
             .get(id)
@@ -191,7 +334,7 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(&mut cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
@@ -234,7 +377,7 @@ impl ContextServerManager {
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.url.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -276,7 +419,7 @@ impl ContextServerManager {
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
                 this.update(&mut cx, |_, cx| {
                     cx.emit(Event::ServerStarted { server_id: id })
                 })?;
@@ -7,6 +7,7 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
@@ -34,8 +35,10 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: Some(serde_json::json!({})),
+                roots: Some(types::RootsCapabilities {
+                    list_changed: Some(true),
+                }),
             },
             meta: None,
             client_info,
@@ -136,6 +139,63 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// Reads the contents of an MCP resource.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server sends `notifications/resources/updated` for subscribed resources.
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Asks the server to send `notifications/resources/updated` whenever the resource changes.
+    pub async fn subscribe_resource(&self, uri: Url) -> Result<()> {
+        self.check_resource_subscriptions()?;
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stops notifications about changes to a resource.
+    pub async fn unsubscribe_resource(&self, uri: Url) -> Result<()> {
+        self.check_resource_subscriptions()?;
+
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    fn check_resource_subscriptions(&self) -> Result<()> {
+        if self.supports_resource_subscriptions() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Server does not support resource subscriptions"
+            ))
+        }
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
@@ -231,4 +291,8 @@ impl InitializedContextServerProtocol {
     ) -> Result<R> {
         self.inner.request(method, params).await
     }
+
+    pub fn notify(&self, method: &str, params: impl serde::Serialize) -> Result<()> {
+        self.inner.notify(method, params)
+    }
 }
@@ -0,0 +1,151 @@
+//! Handles `sampling/createMessage` requests, which context servers use to ask
+//! the user's configured language model for a completion.
+//!
+//! Every request has to be approved by the user before it is sent to the model.
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use futures::StreamExt as _;
+use gpui::{AsyncApp, PromptLevel, Task};
+use language_model::{
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage, Role,
+};
+use serde_json::Value;
+
+use crate::types;
+
+/// The number of characters of each message shown when asking for approval.
+const MAX_PREVIEW_LEN: usize = 500;
+
+pub(crate) fn create_message(
+    server_id: Arc<str>,
+    params: Value,
+    cx: AsyncApp,
+) -> Task<Result<Value>> {
+    cx.spawn(|mut cx| async move {
+        let request = serde_json::from_value::<types::CreateMessageRequest>(params)
+            .context("invalid sampling request")?;
+        let model = cx
+            .update(|cx| LanguageModelRegistry::read_global(cx).active_model())?
+            .context("no language model is configured")?;
+
+        if !request_approval(&server_id, &request, model.as_ref(), &mut cx).await? {
+            bail!("the user declined the sampling request");
+        }
+
+        let mut stream = model
+            .stream_completion_text(to_language_model_request(request)?, &cx)
+            .await?
+            .stream;
+        let mut text = String::new();
+        while let Some(chunk) = stream.next().await {
+            text.push_str(&chunk?);
+        }
+
+        let result = types::CreateMessageResult {
+            role: types::Role::Assistant,
+            content: types::MessageContent::Text {
+                text,
+                annotations: None,
+            },
+            model: model.id().0.to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        };
+        Ok(serde_json::to_value(result)?)
+    })
+}
+
+async fn request_approval(
+    server_id: &str,
+    request: &types::CreateMessageRequest,
+    model: &dyn LanguageModel,
+    cx: &mut AsyncApp,
+) -> Result<bool> {
+    // Requests can arrive while Zed isn't focused, in which case there is no active window,
+    // so fall back to any open window rather than failing.
+    let window = cx
+        .update(|cx| {
+            cx.active_window()
+                .or_else(|| cx.windows().into_iter().next())
+        })?
+        .context("no window is open to ask for approval of the sampling request")?;
+
+    let message = format!(
+        "Context server \"{server_id}\" wants to send a request to {}.",
+        model.name().0
+    );
+    let mut detail = String::new();
+    if let Some(system_prompt) = &request.system_prompt {
+        writeln!(detail, "System: {}\n", preview(system_prompt)).ok();
+    }
+    for message in &request.messages {
+        let role = match message.role {
+            types::Role::User => "User",
+            types::Role::Assistant => "Assistant",
+        };
+        let content = match &message.content {
+            types::MessageContent::Text { text, .. } => preview(text),
+            types::MessageContent::Image { .. } => "[image]".to_string(),
+            types::MessageContent::Resource { resource, .. } => {
+                format!("[resource {}]", resource.uri)
+            }
+        };
+        writeln!(detail, "{role}: {content}\n").ok();
+    }
+
+    let answer = window.update(cx, |_, window, cx| {
+        window.prompt(
+            PromptLevel::Info,
+            &message,
+            Some(detail.trim_end()),
+            &["Allow", "Deny"],
+            cx,
+        )
+    })?;
+    Ok(answer.await.ok() == Some(0))
+}
+
+fn to_language_model_request(request: types::CreateMessageRequest) -> Result<LanguageModelRequest> {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+        });
+    }
+    for message in request.messages {
+        let text = match message.content {
+            types::MessageContent::Text { text, .. } => text,
+            types::MessageContent::Image { .. } | types::MessageContent::Resource { .. } => {
+                return Err(anyhow!(
+                    "only text content is supported in sampling requests"
+                ))
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![text.into()],
+            cache: false,
+        });
+    }
+
+    Ok(LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+    })
+}
+
+fn preview(text: &str) -> String {
+    match text.char_indices().nth(MAX_PREVIEW_LEN) {
+        Some((ix, _)) => format!("{}…", &text[..ix]),
+        None => text.to_string(),
+    }
+}
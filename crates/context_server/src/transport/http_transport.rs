@@ -0,0 +1,1030 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::io::BufReader;
+use futures::{AsyncBufReadExt as _, AsyncReadExt as _, Stream, StreamExt as _};
+use gpui::{AsyncApp, BackgroundExecutor, Task};
+use http_client::{http, AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url};
+use parking_lot::Mutex;
+use postage::watch;
+use serde_json::Value;
+use smol::channel;
+
+use crate::client::INTERNAL_ERROR;
+use crate::transport::Transport;
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const JSON_CONTENT_TYPE: &str = "application/json";
+const EVENT_STREAM_CONTENT_TYPE: &str = "text/event-stream";
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+/// How many times in a row the legacy event stream may fail to announce an endpoint
+/// before we give up on the server.
+const MAX_LEGACY_RECONNECT_ATTEMPTS: usize = 5;
+/// The id of the `initialize` request sent when starting a new session on the client's
+/// behalf, whose response isn't forwarded to the client.
+const REINITIALIZE_REQUEST_ID: &str = "zed-reinitialize";
+
+/// A transport for context servers that are reachable over HTTP.
+///
+/// Messages are posted using the Streamable HTTP transport. If the server rejects
+/// the first message, we fall back to the legacy HTTP+SSE transport, where the
+/// server's messages arrive on a long-lived event stream and ours are posted to
+/// an endpoint announced on that stream.
+pub struct HttpTransport {
+    state: Arc<State>,
+    inbound_rx: channel::Receiver<String>,
+    stderr_rx: channel::Receiver<String>,
+}
+
+struct State {
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    headers: HashMap<String, String>,
+    executor: BackgroundExecutor,
+    inbound_tx: channel::Sender<String>,
+    stderr_tx: channel::Sender<String>,
+    mode: Mutex<Mode>,
+    session_id: Mutex<Option<String>>,
+    /// The client's `initialize` request, replayed when the server expires the session.
+    initialize_request: Mutex<Option<Value>>,
+    listening: AtomicBool,
+    /// Held while posting a message, so the server receives messages in the order they were sent.
+    send_lock: smol::lock::Mutex<()>,
+    /// Tasks maintaining the server's event streams, cancelled when the transport is dropped.
+    tasks: Mutex<Vec<Task<()>>>,
+}
+
+#[derive(Clone)]
+enum Mode {
+    /// Nothing has been sent yet, so we don't know which transport the server supports.
+    Unknown,
+    Streamable,
+    /// The legacy HTTP+SSE transport. The endpoint is `None` while the event stream is
+    /// (re)connecting.
+    Legacy(watch::Receiver<Option<Url>>),
+}
+
+impl HttpTransport {
+    pub fn new(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        cx: &AsyncApp,
+    ) -> Self {
+        let (inbound_tx, inbound_rx) = channel::unbounded::<String>();
+        let (stderr_tx, stderr_rx) = channel::unbounded::<String>();
+
+        Self {
+            state: Arc::new(State {
+                http_client,
+                url,
+                headers,
+                executor: cx.background_executor().clone(),
+                inbound_tx,
+                stderr_tx,
+                mode: Mutex::new(Mode::Unknown),
+                session_id: Mutex::new(None),
+                initialize_request: Mutex::new(None),
+                listening: AtomicBool::new(false),
+                send_lock: smol::lock::Mutex::new(()),
+                tasks: Mutex::new(Vec::new()),
+            }),
+            inbound_rx,
+            stderr_rx,
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let _guard = self.state.send_lock.lock().await;
+        log::trace!("outgoing message: {}", message);
+
+        // A failed request shouldn't tear down the connection, since the next one may
+        // well succeed. Instead, answer the request with an error so that the caller
+        // doesn't wait for a response that will never arrive.
+        if let Err(error) = self.state.post(&message).await {
+            self.state.fail_request(&message, error);
+        }
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.inbound_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stderr_rx.clone())
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        self.state.tasks.lock().clear();
+    }
+}
+
+impl State {
+    async fn post(self: &Arc<Self>, message: &str) -> Result<()> {
+        let mode = self.mode.lock().clone();
+        if let Mode::Legacy(endpoint) = mode {
+            return self.post_legacy(endpoint, message).await;
+        }
+
+        if let Ok(request) = serde_json::from_str::<Value>(message) {
+            if request["method"] == "initialize" {
+                *self.initialize_request.lock() = Some(request);
+            }
+        }
+
+        let mut response = self.post_streamable(message).await?;
+        let mut status = response.status();
+
+        if matches!(mode, Mode::Unknown)
+            && matches!(
+                status,
+                StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+            )
+        {
+            log::info!(
+                "context server at {} does not support Streamable HTTP, falling back to HTTP+SSE",
+                self.url
+            );
+            let endpoint = self.connect_legacy();
+            *self.mode.lock() = Mode::Legacy(endpoint.clone());
+            return self.post_legacy(endpoint, message).await;
+        }
+
+        // The server has expired our session, so start a new one and try again.
+        if status == StatusCode::NOT_FOUND && self.session_id.lock().take().is_some() {
+            log::info!(
+                "context server session at {} expired, reinitializing",
+                self.url
+            );
+            self.reinitialize()
+                .await
+                .context("failed to start a new context server session")?;
+            response = self.post_streamable(message).await?;
+            status = response.status();
+        }
+        if !status.is_success() {
+            return Err(error_for_response(response).await);
+        }
+
+        if let Some(session_id) = header_value(&response, SESSION_ID_HEADER) {
+            *self.session_id.lock() = Some(session_id);
+        }
+        if matches!(mode, Mode::Unknown) {
+            *self.mode.lock() = Mode::Streamable;
+        }
+
+        if status == StatusCode::ACCEPTED {
+            // The server has accepted a notification or response, so initialization is
+            // complete and we can open the stream for messages the server initiates.
+            if !self.listening.swap(true, SeqCst) {
+                self.listen();
+            }
+            return Ok(());
+        }
+
+        let is_event_stream = header_value(&response, http::header::CONTENT_TYPE.as_str())
+            .map_or(false, |content_type| {
+                content_type.starts_with(EVENT_STREAM_CONTENT_TYPE)
+            });
+        let inbound_tx = self.inbound_tx.clone();
+        let stderr_tx = self.stderr_tx.clone();
+        let mut body = response.into_body();
+
+        // Responses may take a while to stream in, so read them in the background
+        // rather than holding up the next message.
+        self.executor
+            .spawn(async move {
+                let result = if is_event_stream {
+                    read_event_stream(body, |event| forward_messages(&inbound_tx, &event.data))
+                        .await
+                } else {
+                    let mut text = String::new();
+                    body.read_to_string(&mut text)
+                        .await
+                        .map(|_| {
+                            forward_messages(&inbound_tx, &text);
+                        })
+                        .map_err(Into::into)
+                };
+                if let Err(error) = result {
+                    stderr_tx
+                        .try_send(format!("failed to read response: {error:#}"))
+                        .ok();
+                }
+            })
+            .detach();
+
+        Ok(())
+    }
+
+    async fn post_streamable(&self, message: &str) -> Result<Response<AsyncBody>> {
+        let request = self
+            .request(Method::POST, &self.url)
+            .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+            .header(
+                http::header::ACCEPT,
+                format!("{JSON_CONTENT_TYPE}, {EVENT_STREAM_CONTENT_TYPE}"),
+            )
+            .body(AsyncBody::from(message.to_string()))?;
+        self.http_client.send(request).await
+    }
+
+    /// Starts a new session by replaying the client's `initialize` request, followed by the
+    /// `initialized` notification. The server's response is discarded, as the client already
+    /// has one.
+    async fn reinitialize(&self) -> Result<()> {
+        let mut initialize = self
+            .initialize_request
+            .lock()
+            .clone()
+            .context("the session expired before it was initialized")?;
+        initialize["id"] = REINITIALIZE_REQUEST_ID.into();
+
+        let mut response = self.post_streamable(&initialize.to_string()).await?;
+        if !response.status().is_success() {
+            return Err(error_for_response(response).await);
+        }
+        if let Some(session_id) = header_value(&response, SESSION_ID_HEADER) {
+            *self.session_id.lock() = Some(session_id);
+        }
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+
+        let initialized = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/initialized",
+        });
+        let response = self.post_streamable(&initialized.to_string()).await?;
+        if !response.status().is_success() {
+            return Err(error_for_response(response).await);
+        }
+        Ok(())
+    }
+
+    async fn post_legacy(
+        &self,
+        mut endpoint: watch::Receiver<Option<Url>>,
+        message: &str,
+    ) -> Result<()> {
+        let endpoint = loop {
+            if let Some(endpoint) = endpoint.borrow().clone() {
+                break endpoint;
+            }
+            endpoint
+                .next()
+                .await
+                .context("context server event stream closed")?;
+        };
+
+        let request = self
+            .request(Method::POST, &endpoint)
+            .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+            .body(AsyncBody::from(message.to_string()))?;
+        let response = self.http_client.send(request).await?;
+        if !response.status().is_success() {
+            return Err(error_for_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Opens the event stream on which a Streamable HTTP server sends requests and
+    /// notifications of its own, reconnecting whenever it is closed.
+    fn listen(self: &Arc<Self>) {
+        let this = self.clone();
+        let task = self.executor.spawn(async move {
+            let mut last_event_id = None::<String>;
+            let mut session_id = this.session_id.lock().clone();
+            let mut delay = INITIAL_RECONNECT_DELAY;
+            loop {
+                // Event ids are only meaningful within the session that produced them.
+                let current_session_id = this.session_id.lock().clone();
+                if current_session_id != session_id {
+                    session_id = current_session_id;
+                    last_event_id = None;
+                }
+
+                let mut request = this
+                    .request(Method::GET, &this.url)
+                    .header(http::header::ACCEPT, EVENT_STREAM_CONTENT_TYPE);
+                if let Some(last_event_id) = &last_event_id {
+                    request = request.header(LAST_EVENT_ID_HEADER, last_event_id.as_str());
+                }
+
+                match this.open_event_stream(request).await {
+                    Ok(Some(body)) => {
+                        delay = INITIAL_RECONNECT_DELAY;
+                        let result = read_event_stream(body, |event| {
+                            if let Some(id) = &event.id {
+                                last_event_id = Some(id.clone());
+                            }
+                            forward_messages(&this.inbound_tx, &event.data)
+                        })
+                        .await;
+                        if let Err(error) = result {
+                            this.log_error(error.context("context server event stream failed"));
+                        }
+                    }
+                    // The server doesn't offer a stream of its own messages.
+                    Ok(None) => return,
+                    Err(error) => this.log_error(error),
+                }
+
+                if this.inbound_tx.is_closed() {
+                    return;
+                }
+                this.executor.timer(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+        self.tasks.lock().push(task);
+    }
+
+    /// Opens the event stream of a legacy HTTP+SSE server, returning a receiver for
+    /// the endpoint to which messages should be posted. The stream is reopened
+    /// whenever it is closed, at which point the server announces a new endpoint.
+    /// After `MAX_LEGACY_RECONNECT_ATTEMPTS` attempts in a row that don't yield an
+    /// endpoint, the receiver is closed.
+    fn connect_legacy(self: &Arc<Self>) -> watch::Receiver<Option<Url>> {
+        let (mut endpoint_tx, endpoint_rx) = watch::channel();
+        let this = self.clone();
+        let task = self.executor.spawn(async move {
+            let mut delay = INITIAL_RECONNECT_DELAY;
+            let mut failed_attempts = 0;
+            loop {
+                *endpoint_tx.borrow_mut() = None;
+                let mut received_endpoint = false;
+
+                let request = this
+                    .request(Method::GET, &this.url)
+                    .header(http::header::ACCEPT, EVENT_STREAM_CONTENT_TYPE);
+                match this.open_event_stream(request).await {
+                    Ok(Some(body)) => {
+                        let result = read_event_stream(body, |event| {
+                            match event.event.as_deref() {
+                                Some("endpoint") => match this.url.join(event.data.trim()) {
+                                    Ok(endpoint) => {
+                                        received_endpoint = true;
+                                        *endpoint_tx.borrow_mut() = Some(endpoint);
+                                    }
+                                    Err(error) => this.log_error(anyhow!(
+                                        "invalid endpoint {:?}: {error}",
+                                        event.data
+                                    )),
+                                },
+                                None | Some("message") => {
+                                    return forward_messages(&this.inbound_tx, &event.data)
+                                }
+                                Some(_) => {}
+                            }
+                            true
+                        })
+                        .await;
+                        if let Err(error) = result {
+                            this.log_error(error.context("context server event stream failed"));
+                        }
+                    }
+                    Ok(None) => {
+                        this.log_error(anyhow!("context server does not support HTTP+SSE either"))
+                    }
+                    Err(error) => this.log_error(error),
+                }
+
+                if this.inbound_tx.is_closed() {
+                    return;
+                }
+                if received_endpoint {
+                    failed_attempts = 0;
+                    delay = INITIAL_RECONNECT_DELAY;
+                } else {
+                    failed_attempts += 1;
+                    if failed_attempts >= MAX_LEGACY_RECONNECT_ATTEMPTS {
+                        this.log_error(anyhow!(
+                            "giving up on context server event stream after {failed_attempts} attempts"
+                        ));
+                        return;
+                    }
+                }
+                this.executor.timer(delay).await;
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        });
+        self.tasks.lock().push(task);
+        endpoint_rx
+    }
+
+    /// Sends a GET request for an event stream, returning `None` if the server
+    /// doesn't provide one.
+    async fn open_event_stream(
+        &self,
+        request: http::request::Builder,
+    ) -> Result<Option<AsyncBody>> {
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await?;
+        match response.status() {
+            StatusCode::METHOD_NOT_ALLOWED => Ok(None),
+            status if status.is_success() => Ok(Some(response.into_body())),
+            _ => Err(error_for_response(response).await),
+        }
+    }
+
+    fn request(&self, method: Method, url: &Url) -> http::request::Builder {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(session_id) = self.session_id.lock().as_ref() {
+            request = request.header(SESSION_ID_HEADER, session_id.as_str());
+        }
+        request
+    }
+
+    fn fail_request(&self, message: &str, error: anyhow::Error) {
+        let error = format!("{error:#}");
+        self.stderr_tx.try_send(error.clone()).ok();
+
+        let Ok(Value::Object(message)) = serde_json::from_str::<Value>(message) else {
+            return;
+        };
+        if let (Some(id), true) = (message.get("id"), message.contains_key("method")) {
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": INTERNAL_ERROR, "message": error },
+            });
+            self.inbound_tx.try_send(response.to_string()).ok();
+        }
+    }
+
+    fn log_error(&self, error: anyhow::Error) {
+        self.stderr_tx.try_send(format!("{error:#}")).ok();
+    }
+}
+
+/// Forwards the JSON-RPC message or batch of messages in `data` to the client,
+/// returning `false` once the client has gone away.
+fn forward_messages(inbound_tx: &channel::Sender<String>, data: &str) -> bool {
+    if data.trim().is_empty() {
+        return !inbound_tx.is_closed();
+    }
+    match serde_json::from_str::<Value>(data) {
+        Ok(Value::Array(messages)) => messages
+            .into_iter()
+            .all(|message| inbound_tx.try_send(message.to_string()).is_ok()),
+        _ => inbound_tx.try_send(data.to_string()).is_ok(),
+    }
+}
+
+fn header_value(response: &Response<AsyncBody>, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+async fn error_for_response(mut response: Response<AsyncBody>) -> anyhow::Error {
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await.ok();
+    anyhow!(
+        "context server responded with {}: {}",
+        response.status(),
+        body.trim()
+    )
+}
+
+/// Reads server-sent events from `body` until it ends or `handle_event` returns `false`.
+async fn read_event_stream(
+    body: AsyncBody,
+    mut handle_event: impl FnMut(SseEvent) -> bool,
+) -> Result<()> {
+    let mut lines = BufReader::new(body).lines();
+    let mut parser = SseParser::default();
+    while let Some(line) = lines.next().await {
+        if let Some(event) = parser.push_line(&line?) {
+            if !handle_event(event) {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+struct SseEvent {
+    event: Option<String>,
+    data: String,
+    id: Option<String>,
+}
+
+/// An incremental parser for the `text/event-stream` format.
+#[derive(Default)]
+struct SseParser {
+    event: Option<String>,
+    data: Option<String>,
+    last_event_id: Option<String>,
+}
+
+impl SseParser {
+    /// Feeds a line of the stream to the parser, returning the event that is
+    /// dispatched by a blank line.
+    fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let event = self.event.take();
+            return Some(SseEvent {
+                event,
+                data: self.data.take()?,
+                id: self.last_event_id.clone(),
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            },
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+    use serde_json::json;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        let events = [
+            ": keep-alive",
+            "",
+            "event: endpoint",
+            "data: /messages?session_id=1",
+            "",
+            "id: 7",
+            "data:{\"a\":",
+            "data: 1}",
+            "",
+            "event: ignored",
+            "",
+            "data",
+            "",
+        ]
+        .into_iter()
+        .filter_map(|line| parser.push_line(line))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    event: Some("endpoint".into()),
+                    data: "/messages?session_id=1".into(),
+                    id: None,
+                },
+                SseEvent {
+                    event: None,
+                    data: "{\"a\":\n1}".into(),
+                    id: Some("7".into()),
+                },
+                SseEvent {
+                    event: None,
+                    data: "".into(),
+                    id: Some("7".into()),
+                },
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let (method, session_id, last_event_id, body) = read_request(request).await?;
+                    requests.lock().push((
+                        method.clone(),
+                        session_id.clone(),
+                        last_event_id.clone(),
+                    ));
+
+                    if method == Method::GET {
+                        // Serve a single event, then refuse the reconnection.
+                        return if last_event_id.is_none() {
+                            event_stream_response(
+                                "id: 3\ndata: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/tools/list_changed\"}\n\n",
+                            )
+                        } else {
+                            status_response(StatusCode::METHOD_NOT_ALLOWED)
+                        };
+                    }
+
+                    let message = serde_json::from_str::<Value>(&body)?;
+                    match message["method"].as_str() {
+                        Some("initialize") => Ok(Response::builder()
+                            .header(SESSION_ID_HEADER, "session-1")
+                            .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+                            .body(
+                                json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} })
+                                    .to_string()
+                                    .into(),
+                            )?),
+                        Some("notifications/initialized") => status_response(StatusCode::ACCEPTED),
+                        Some("tools/list") => event_stream_response(&format!(
+                            "event: message\ndata: {}\n\ndata: {}\n\n",
+                            json!({ "jsonrpc": "2.0", "method": "notifications/progress" }),
+                            json!({ "jsonrpc": "2.0", "id": message["id"], "result": { "tools": [] } }),
+                        )),
+                        _ => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            Url::parse("http://localhost:3000/mcp").unwrap(),
+            HashMap::default(),
+            http_client,
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "id": 0, "result": {} })
+        );
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "method": "notifications/tools/list_changed" })
+        );
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "method": "notifications/progress" })
+        );
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "tools": [] } })
+        );
+
+        // Once the server closes its event stream, we reconnect and resume after the last event.
+        cx.executor().advance_clock(INITIAL_RECONNECT_DELAY);
+        cx.run_until_parked();
+
+        let session_id = Some("session-1".to_string());
+        assert_eq!(
+            *requests.lock(),
+            [
+                (Method::POST, None, None),
+                (Method::POST, session_id.clone(), None),
+                (Method::GET, session_id.clone(), None),
+                (Method::POST, session_id.clone(), None),
+                (Method::GET, session_id.clone(), Some("3".to_string())),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_failed_request(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            let (_, _, _, body) = read_request(request).await?;
+            let message = serde_json::from_str::<Value>(&body)?;
+            match message["method"].as_str() {
+                Some("initialize") => Ok(Response::builder()
+                    .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+                    .body(
+                        json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} })
+                            .to_string()
+                            .into(),
+                    )?),
+                _ => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+            }
+        });
+
+        let transport = HttpTransport::new(
+            Url::parse("http://localhost:3000/mcp").unwrap(),
+            HashMap::default(),
+            http_client,
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" }).to_string())
+            .await
+            .unwrap();
+        next_message(&mut messages).await;
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }).to_string())
+            .await
+            .unwrap();
+        let response = next_message(&mut messages).await;
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+    }
+
+    #[gpui::test]
+    async fn test_legacy_sse(cx: &mut TestAppContext) {
+        let (events_tx, events_rx) = channel::unbounded::<std::io::Result<Vec<u8>>>();
+        let events_rx = Arc::new(Mutex::new(Some(events_rx)));
+        let posted_urls = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let posted_urls = posted_urls.clone();
+            move |request| {
+                let events_tx = events_tx.clone();
+                let events_rx = events_rx.clone();
+                let posted_urls = posted_urls.clone();
+                async move {
+                    let url = request.uri().to_string();
+                    let authorization = request
+                        .headers()
+                        .get(http::header::AUTHORIZATION)
+                        .and_then(|value| value.to_str().ok())
+                        .map(ToString::to_string);
+                    assert_eq!(authorization.as_deref(), Some("Bearer secret"));
+
+                    let (method, _, _, body) = read_request(request).await?;
+                    if method == Method::GET {
+                        let events = events_rx.lock().take().context("stream already open")?;
+                        events_tx
+                            .send(Ok(
+                                b"event: endpoint\ndata: /messages?session_id=1\n\n".to_vec()
+                            ))
+                            .await?;
+                        return Ok(Response::builder()
+                            .header(http::header::CONTENT_TYPE, EVENT_STREAM_CONTENT_TYPE)
+                            .body(AsyncBody::from_reader(
+                                futures::TryStreamExt::into_async_read(Box::pin(events)),
+                            ))?);
+                    }
+
+                    posted_urls.lock().push(url.clone());
+                    if url == "http://localhost:3000/sse" {
+                        return status_response(StatusCode::METHOD_NOT_ALLOWED);
+                    }
+                    let message = serde_json::from_str::<Value>(&body)?;
+                    let response = json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} });
+                    events_tx
+                        .send(Ok(
+                            format!("event: message\ndata: {response}\n\n").into_bytes()
+                        ))
+                        .await?;
+                    status_response(StatusCode::ACCEPTED)
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            Url::parse("http://localhost:3000/sse").unwrap(),
+            HashMap::from_iter([("Authorization".to_string(), "Bearer secret".to_string())]),
+            http_client,
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "id": 0, "result": {} })
+        );
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "id": 1, "result": {} })
+        );
+
+        assert_eq!(
+            *posted_urls.lock(),
+            [
+                "http://localhost:3000/sse",
+                "http://localhost:3000/messages?session_id=1",
+                "http://localhost:3000/messages?session_id=1",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_expired_session(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let (method, session_id, _, body) = read_request(request).await?;
+                    if method == Method::GET {
+                        return status_response(StatusCode::METHOD_NOT_ALLOWED);
+                    }
+                    let message = serde_json::from_str::<Value>(&body)?;
+                    requests
+                        .lock()
+                        .push((message["method"].clone(), session_id.clone()));
+
+                    match message["method"].as_str() {
+                        Some("initialize") => {
+                            let session_id = if message["id"] == REINITIALIZE_REQUEST_ID {
+                                "session-2"
+                            } else {
+                                "session-1"
+                            };
+                            Ok(Response::builder()
+                                .header(SESSION_ID_HEADER, session_id)
+                                .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+                                .body(
+                                    json!({ "jsonrpc": "2.0", "id": message["id"], "result": {} })
+                                        .to_string()
+                                        .into(),
+                                )?)
+                        }
+                        Some("notifications/initialized") => status_response(StatusCode::ACCEPTED),
+                        Some("tools/list") if session_id.as_deref() == Some("session-1") => {
+                            status_response(StatusCode::NOT_FOUND)
+                        }
+                        Some("tools/list") => Ok(Response::builder()
+                            .header(http::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+                            .body(
+                                json!({ "jsonrpc": "2.0", "id": message["id"], "result": { "tools": [] } })
+                                    .to_string()
+                                    .into(),
+                            )?),
+                        _ => status_response(StatusCode::INTERNAL_SERVER_ERROR),
+                    }
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            Url::parse("http://localhost:3000/mcp").unwrap(),
+            HashMap::default(),
+            http_client,
+            &cx.to_async(),
+        );
+        let mut messages = transport.receive();
+
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" }).to_string())
+            .await
+            .unwrap();
+        next_message(&mut messages).await;
+        transport
+            .send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }).to_string())
+            .await
+            .unwrap();
+
+        // The session has expired, so a new one is started before the request is retried. The
+        // response to the repeated `initialize` request isn't forwarded.
+        transport
+            .send(json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }).to_string())
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut messages).await,
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "tools": [] } })
+        );
+
+        let session = |id: &str| Some(id.to_string());
+        assert_eq!(
+            *requests.lock(),
+            [
+                (json!("initialize"), None),
+                (json!("notifications/initialized"), session("session-1")),
+                (json!("tools/list"), session("session-1")),
+                (json!("initialize"), None),
+                (json!("notifications/initialized"), session("session-2")),
+                (json!("tools/list"), session("session-2")),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_legacy_sse_gives_up(cx: &mut TestAppContext) {
+        let stream_requests = Arc::new(AtomicUsize::new(0));
+        let http_client = FakeHttpClient::create({
+            let stream_requests = stream_requests.clone();
+            move |request| {
+                let stream_requests = stream_requests.clone();
+                async move {
+                    if request.method() == Method::GET {
+                        stream_requests.fetch_add(1, SeqCst);
+                        return status_response(StatusCode::INTERNAL_SERVER_ERROR);
+                    }
+                    status_response(StatusCode::METHOD_NOT_ALLOWED)
+                }
+            }
+        });
+
+        let transport = Arc::new(HttpTransport::new(
+            Url::parse("http://localhost:3000/sse").unwrap(),
+            HashMap::default(),
+            http_client,
+            &cx.to_async(),
+        ));
+        let mut messages = transport.receive();
+
+        let send = cx.executor().spawn({
+            let transport = transport.clone();
+            async move {
+                transport
+                    .send(json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize" }).to_string())
+                    .await
+            }
+        });
+        // The backoff between attempts adds up to less than the maximum delay.
+        cx.executor().advance_clock(MAX_RECONNECT_DELAY);
+        send.await.unwrap();
+
+        assert_eq!(stream_requests.load(SeqCst), MAX_LEGACY_RECONNECT_ATTEMPTS);
+        let response = next_message(&mut messages).await;
+        assert_eq!(response["id"], 0);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+    }
+
+    async fn read_request(
+        request: Request<AsyncBody>,
+    ) -> Result<(Method, Option<String>, Option<String>, String)> {
+        let header = |name: &str| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToString::to_string)
+        };
+        let session_id = header(SESSION_ID_HEADER);
+        let last_event_id = header(LAST_EVENT_ID_HEADER);
+        let method = request.method().clone();
+        let mut body = String::new();
+        request.into_body().read_to_string(&mut body).await?;
+        Ok((method, session_id, last_event_id, body))
+    }
+
+    fn status_response(status: StatusCode) -> Result<Response<AsyncBody>> {
+        Ok(Response::builder()
+            .status(status)
+            .body(AsyncBody::empty())?)
+    }
+
+    fn event_stream_response(events: &str) -> Result<Response<AsyncBody>> {
+        Ok(Response::builder()
+            .header(http::header::CONTENT_TYPE, EVENT_STREAM_CONTENT_TYPE)
+            .body(events.to_string().into())?)
+    }
+
+    async fn next_message(messages: &mut Pin<Box<dyn Stream<Item = String> + Send>>) -> Value {
+        serde_json::from_str(&messages.next().await.unwrap()).unwrap()
+    }
+}
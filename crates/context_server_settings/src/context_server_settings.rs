@@ -18,6 +18,15 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The URL of a context server that is reachable over HTTP.
+    ///
+    /// When set, Zed connects to this URL using the Streamable HTTP transport,
+    /// falling back to the legacy HTTP+SSE transport for older servers, instead
+    /// of running a command.
+    pub url: Option<String>,
+    /// Additional HTTP headers to send with every request to `url`, such as
+    /// an `Authorization` header.
+    pub headers: Option<HashMap<String, String>>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
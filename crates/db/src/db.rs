@@ -12,13 +12,16 @@ pub use sqlez;
 pub use sqlez_macros;
 
 pub use release_channel::RELEASE_CHANNEL;
+use sqlez::connection::Connection;
 use sqlez::domain::Migrator;
 use sqlez::thread_safe_connection::ThreadSafeConnection;
 use sqlez_macros::sql;
+use std::collections::HashSet;
 use std::future::Future;
-use std::path::Path;
-use std::sync::{atomic::Ordering, LazyLock};
-use std::{env, sync::atomic::AtomicBool};
+use std::path::{Path, PathBuf};
+use std::sync::{atomic::Ordering, LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs, sync::atomic::AtomicBool};
 use util::{maybe, ResultExt};
 
 const CONNECTION_INITIALIZE_QUERY: &str = sql!(
@@ -41,11 +44,27 @@ pub static ZED_STATELESS: LazyLock<bool> =
 
 pub static ALL_FILE_DB_FAILED: LazyLock<AtomicBool> = LazyLock::new(|| AtomicBool::new(false));
 
+/// The database files whose integrity is being, or has been, checked by this process.
+static CHECKED_DB_FILES: LazyLock<Mutex<HashSet<PathBuf>>> = LazyLock::new(Default::default);
+
 /// Open or create a database at the given directory path.
-/// This will retry a couple times if there are failures. If opening fails once, the db directory
-/// is moved to a backup folder and a new one is created. If that fails, a shared in memory db is created.
-/// In either case, static variables are set so that the user can be notified.
+/// The first time a database file is opened, its integrity is checked in the background, and a
+/// corrupted file is moved aside the next time it's opened, so that a new one is created. A file
+/// that can't be opened at all is checked right away instead. When a domain's migrations don't
+/// match the ones stored in the database, the domain's tables are backed up and recreated. If
+/// opening still fails, a shared in memory db is created, and static variables are set so that
+/// the user can be notified.
 pub async fn open_db<M: Migrator + 'static>(db_dir: &Path, scope: &str) -> ThreadSafeConnection<M> {
+    open_db_file(db_dir, scope, DB_FILE_NAME).await
+}
+
+/// Like [`open_db`], but stores the database in a file with the given name next to the main
+/// database file, for domains whose size or write volume shouldn't weigh on the main database.
+pub async fn open_db_file<M: Migrator + 'static>(
+    db_dir: &Path,
+    scope: &str,
+    file_name: &str,
+) -> ThreadSafeConnection<M> {
     if *ZED_STATELESS {
         return open_fallback_db().await;
     }
@@ -57,8 +76,17 @@ pub async fn open_db<M: Migrator + 'static>(db_dir: &Path, scope: &str) -> Threa
             .await
             .context("Could not create db directory")
             .log_err()?;
-        let db_path = main_db_dir.join(Path::new(DB_FILE_NAME));
-        open_main_db(&db_path).await
+        let db_path = main_db_dir.join(Path::new(file_name));
+        back_up_if_marked_corrupted(&db_path).log_err();
+        if let Some(connection) = open_main_db(&db_path).await {
+            check_integrity_in_background(&db_path, &connection);
+            return Some(connection);
+        }
+        if back_up_if_corrupted(&db_path).log_err()? {
+            open_main_db(&db_path).await
+        } else {
+            None
+        }
     })
     .await;
 
@@ -78,11 +106,112 @@ async fn open_main_db<M: Migrator>(db_path: &Path) -> Option<ThreadSafeConnectio
     ThreadSafeConnection::<M>::builder(db_path.to_string_lossy().as_ref(), true)
         .with_db_initialization_query(DB_INITIALIZE_QUERY)
         .with_connection_initialize_query(CONNECTION_INITIALIZE_QUERY)
+        .with_migration_recovery()
         .build()
         .await
         .log_err()
 }
 
+/// Runs SQLite's integrity check on a database file that was opened successfully, without
+/// holding up its users, since the check reads the whole file. If it finds problems, a marker is
+/// written next to the file, so that it's moved aside the next time it's opened.
+fn check_integrity_in_background<M: Migrator>(
+    db_path: &Path,
+    connection: &ThreadSafeConnection<M>,
+) {
+    if !CHECKED_DB_FILES
+        .lock()
+        .unwrap()
+        .insert(db_path.to_path_buf())
+    {
+        return;
+    }
+
+    let db_path = db_path.to_path_buf();
+    let connection = connection.clone();
+    smol::unblock(move || {
+        maybe!({
+            let problems = connection.integrity_problems()?;
+            if !problems.is_empty() {
+                let problems = problems.join("\n");
+                log::error!(
+                    "Database {db_path:?} is corrupted, it will be reset the next time it is opened: {problems}"
+                );
+                fs::write(corrupted_marker_path(&db_path), problems)?;
+            }
+            anyhow::Ok(())
+        })
+        .log_err();
+    })
+    .detach();
+}
+
+/// Moves the database file at the given path aside if a previous integrity check found it to be
+/// corrupted.
+fn back_up_if_marked_corrupted(db_path: &Path) -> anyhow::Result<()> {
+    let marker_path = corrupted_marker_path(db_path);
+    if !marker_path.exists() {
+        return Ok(());
+    }
+    if db_path.exists() {
+        back_up_corrupted_db(db_path)?;
+    }
+    fs::remove_file(&marker_path)
+        .with_context(|| format!("removing corrupted database marker {marker_path:?}"))
+}
+
+/// Moves the database file at the given path aside if SQLite finds it to be corrupted, so
+/// that a new one is created in its place. Returns whether the file was moved.
+fn back_up_if_corrupted(db_path: &Path) -> anyhow::Result<bool> {
+    if !db_path.exists() {
+        return Ok(false);
+    }
+
+    // Set up the connection like the ones of `open_main_db`, so that the check waits for the
+    // locks of other connections rather than failing. A file that isn't a database fails these
+    // queries, and the check reports why.
+    let connection = Connection::open_file(&db_path.to_string_lossy());
+    for query in [DB_INITIALIZE_QUERY, CONNECTION_INITIALIZE_QUERY] {
+        connection.exec(query).and_then(|mut query| query()).ok();
+    }
+    let problems = connection.integrity_problems()?;
+    drop(connection);
+    if problems.is_empty() {
+        return Ok(false);
+    }
+
+    log::error!("Database {db_path:?} is corrupted: {}", problems.join("\n"));
+    back_up_corrupted_db(db_path)?;
+    Ok(true)
+}
+
+fn back_up_corrupted_db(db_path: &Path) -> anyhow::Result<()> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut backup_path = PathBuf::from(format!("{}.corrupted-{timestamp}", db_path.display()));
+    let mut attempt = 1;
+    while backup_path.exists() {
+        attempt += 1;
+        backup_path = PathBuf::from(format!(
+            "{}.corrupted-{timestamp}-{attempt}",
+            db_path.display()
+        ));
+    }
+
+    log::error!("Moving corrupted database {db_path:?} to {backup_path:?}");
+    for suffix in ["", "-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{suffix}", db_path.display()));
+        if path.exists() {
+            fs::rename(&path, format!("{}{suffix}", backup_path.display()))
+                .with_context(|| format!("moving corrupted database file {path:?}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn corrupted_marker_path(db_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.corrupted", db_path.display()))
+}
+
 async fn open_fallback_db<M: Migrator>() -> ThreadSafeConnection<M> {
     log::info!("Opening fallback db");
     ThreadSafeConnection::<M>::builder(FALLBACK_DB_NAME, false)
@@ -109,10 +238,13 @@ pub async fn open_test_db<M: Migrator>(db_name: &str) -> ThreadSafeConnection<M>
         .unwrap()
 }
 
-/// Implements a basic DB wrapper for a given domain
+/// Implements a basic DB wrapper for a given domain.
+///
+/// Migrations can be followed by `down: [(step, sql!(...)), ...]`, listing the migrations that
+/// revert some of the steps, which lets builds with diverging migrations share a database.
 #[macro_export]
 macro_rules! define_connection {
-    (pub static ref $id:ident: $t:ident<()> = $migrations:expr; $($global:ident)?) => {
+    (pub static ref $id:ident: $t:ident<()> = $migrations:expr $(, down: [$($down_migration:expr),* $(,)?])?; $($global:ident)?) => {
         pub struct $t($crate::sqlez::thread_safe_connection::ThreadSafeConnection<$t>);
 
         impl ::std::ops::Deref for $t {
@@ -131,6 +263,10 @@ macro_rules! define_connection {
             fn migrations() -> &'static [&'static str] {
                 $migrations
             }
+
+            fn down_migrations() -> &'static [(usize, &'static str)] {
+                &[$($($down_migration),*)?]
+            }
         }
 
         #[cfg(any(test, feature = "test-support"))]
@@ -149,7 +285,7 @@ macro_rules! define_connection {
             $t($crate::smol::block_on($crate::open_db(db_dir, scope)))
         });
     };
-    (pub static ref $id:ident: $t:ident<$($d:ty),+> = $migrations:expr; $($global:ident)?) => {
+    (pub static ref $id:ident: $t:ident<$($d:ty),+> = $migrations:expr $(, down: [$($down_migration:expr),* $(,)?])?; $($global:ident)?) => {
         pub struct $t($crate::sqlez::thread_safe_connection::ThreadSafeConnection<( $($d),+, $t )>);
 
         impl ::std::ops::Deref for $t {
@@ -168,6 +304,10 @@ macro_rules! define_connection {
             fn migrations() -> &'static [&'static str] {
                 $migrations
             }
+
+            fn down_migrations() -> &'static [(usize, &'static str)] {
+                &[$($($down_migration),*)?]
+            }
         }
 
         #[cfg(any(test, feature = "test-support"))]
@@ -200,10 +340,10 @@ where
 mod tests {
     use std::thread;
 
-    use sqlez::domain::Domain;
+    use sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection};
     use sqlez_macros::sql;
 
-    use crate::open_db;
+    use crate::{corrupted_marker_path, open_db, DB_FILE_NAME};
 
     // Test bad migration panics
     #[gpui::test]
@@ -290,6 +430,160 @@ mod tests {
         );
     }
 
+    /// Test that a domain whose migrations changed has its tables backed up and recreated
+    #[gpui::test]
+    async fn test_migration_mismatch_recovery(cx: &mut gpui::TestAppContext) {
+        cx.executor().allow_parking();
+
+        enum OldDB {}
+
+        impl Domain for OldDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(value);)]
+            }
+        }
+
+        enum NewDB {}
+
+        impl Domain for NewDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(value, other_value);)]
+            }
+        }
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("DbTests")
+            .tempdir()
+            .unwrap();
+        let db_path = tempdir.path().join("0-test").join(DB_FILE_NAME);
+        {
+            let old_db = open_db::<OldDB>(tempdir.path(), "test").await;
+            old_db
+                .write(|connection| connection.exec(sql!(INSERT INTO test(value) VALUES (1);))?())
+                .await
+                .unwrap();
+        }
+
+        let new_db = ThreadSafeConnection::<NewDB>::builder(&db_path.to_string_lossy(), true)
+            .with_migration_recovery()
+            .build()
+            .await
+            .unwrap();
+        assert!(new_db.persistent());
+        assert_eq!(
+            new_db
+                .select::<(usize, Option<usize>)>(sql!(SELECT value, other_value FROM test))
+                .unwrap()()
+            .unwrap(),
+            Vec::new()
+        );
+        let backup_tables = new_db
+            .select::<String>("SELECT name FROM sqlite_master WHERE name LIKE 'test_backup_%'")
+            .unwrap()()
+        .unwrap();
+        assert_eq!(backup_tables.len(), 1);
+        assert_eq!(
+            new_db
+                .select::<usize>(&format!("SELECT value FROM {}", backup_tables[0]))
+                .unwrap()()
+            .unwrap(),
+            &[1]
+        );
+    }
+
+    /// Test that a corrupted DB file is moved aside and replaced
+    #[gpui::test]
+    async fn test_corrupted_db_file(cx: &mut gpui::TestAppContext) {
+        cx.executor().allow_parking();
+
+        enum TestDB {}
+
+        impl Domain for TestDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(value);)]
+            }
+        }
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("DbTests")
+            .tempdir()
+            .unwrap();
+        let db_dir = tempdir.path().join("0-test");
+        std::fs::create_dir_all(&db_dir).unwrap();
+        std::fs::write(db_dir.join(DB_FILE_NAME), "not a database".repeat(1000)).unwrap();
+
+        let db = open_db::<TestDB>(tempdir.path(), "test").await;
+        assert!(db.persistent());
+        assert!(db.select_row::<usize>("SELECT * FROM test").unwrap()()
+            .unwrap()
+            .is_none());
+
+        let backups = std::fs::read_dir(&db_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("db.sqlite.corrupted-"))
+            .collect::<Vec<_>>();
+        assert_eq!(backups.len(), 1);
+    }
+
+    /// Test that a DB file found to be corrupted by a previous check is moved aside when opened
+    #[gpui::test]
+    async fn test_db_file_marked_corrupted(cx: &mut gpui::TestAppContext) {
+        cx.executor().allow_parking();
+
+        enum TestDB {}
+
+        impl Domain for TestDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(value);)]
+            }
+        }
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("DbTests")
+            .tempdir()
+            .unwrap();
+        let db_dir = tempdir.path().join("0-test");
+        {
+            let db = open_db::<TestDB>(tempdir.path(), "test").await;
+            db.write(|connection| connection.exec(sql!(INSERT INTO test(value) VALUES (1);))?())
+                .await
+                .unwrap();
+        }
+        let marker_path = corrupted_marker_path(&db_dir.join(DB_FILE_NAME));
+        std::fs::write(&marker_path, "corrupted").unwrap();
+
+        let db = open_db::<TestDB>(tempdir.path(), "test").await;
+        assert!(db.persistent());
+        assert!(db.select_row::<usize>("SELECT * FROM test").unwrap()()
+            .unwrap()
+            .is_none());
+        assert!(!marker_path.exists());
+
+        let backups = std::fs::read_dir(&db_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("db.sqlite.corrupted-"))
+            .collect::<Vec<_>>();
+        assert_eq!(backups.len(), 1);
+    }
+
     /// Test that DB exists but corrupted (causing recreate)
     #[gpui::test(iterations = 30)]
     async fn test_simultaneous_db_corruption(cx: &mut gpui::TestAppContext) {
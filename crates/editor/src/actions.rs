@@ -267,6 +267,9 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptConflictBoth,
+        AcceptConflictOurs,
+        AcceptConflictTheirs,
         AcceptEditPrediction,
         AcceptPartialCopilotSuggestion,
         AcceptPartialEditPrediction,
@@ -325,6 +328,8 @@ gpui::actions!(
         GoToDiagnostic,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToNextConflict,
+        GoToPreviousConflict,
         GoToPreviousDiagnostic,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
@@ -411,9 +416,11 @@ gpui::actions!(
         SelectToPreviousWordStart,
         SelectToStartOfParagraph,
         SelectUp,
+        ShowCallHierarchy,
         ShowCharacterPalette,
         ShowEditPrediction,
         ShowSignatureHelp,
+        ShowTypeHierarchy,
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
@@ -429,6 +436,7 @@ gpui::actions!(
         ToggleInlineDiagnostics,
         ToggleEditPrediction,
         ToggleLineNumbers,
+        ToggleMinimap,
         SwapSelectionEnds,
         SetMark,
         ToggleRelativeLineNumbers,
@@ -0,0 +1,375 @@
+use std::{collections::BTreeMap, mem, sync::Arc, time::Duration};
+
+use collections::{HashMap, HashSet};
+use futures::future::join_all;
+use gpui::{App, Context, Entity, Task, WeakEntity, Window};
+use language::{language_settings::language_settings, Bias, Buffer, Point, ToOffset as _};
+use multi_buffer::ToPoint as _;
+use project::CodeLens;
+use text::{AnchorRangeExt as _, BufferId};
+use ui::{prelude::*, ButtonLike};
+use util::{post_inc, ResultExt};
+
+use crate::{
+    display_map::{
+        BlockContext, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, RenderBlock,
+    },
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The code lenses shown in an editor, rendered as blocks above the lines they apply to.
+#[derive(Default)]
+pub(super) struct CodeLensState {
+    buffers: HashMap<BufferId, BufferCodeLens>,
+    next_fetch_id: usize,
+    fetch_task: Option<Task<Option<()>>>,
+}
+
+struct BufferCodeLens {
+    buffer: Entity<Buffer>,
+    /// Identifies the request the lenses came from, so that resolved lenses
+    /// from a previous request are not mixed into the current ones.
+    fetch_id: usize,
+    /// Sorted by their start in the buffer.
+    lenses: Vec<CodeLens>,
+    resolving: HashSet<usize>,
+    /// Lenses that were resolved without a command, and are not shown.
+    unresolvable: HashSet<usize>,
+    block_ids: Vec<CustomBlockId>,
+}
+
+impl CodeLensState {
+    pub(super) fn retain_buffers(
+        &mut self,
+        mut f: impl FnMut(BufferId) -> bool,
+    ) -> Vec<CustomBlockId> {
+        let mut removed_blocks = Vec::new();
+        self.buffers.retain(|buffer_id, buffer_lens| {
+            let retain = f(*buffer_id);
+            if !retain {
+                removed_blocks.append(&mut buffer_lens.block_ids);
+            }
+            retain
+        });
+        removed_blocks
+    }
+}
+
+fn code_lens_enabled(buffer: &Buffer, cx: &App) -> bool {
+    language_settings(
+        buffer.language().map(|language| language.name()),
+        buffer.file(),
+        cx,
+    )
+    .code_lens
+}
+
+/// Fetches the code lenses of every buffer in the editor that has them enabled.
+/// Only the lenses that are visible get resolved.
+pub(super) fn refresh_code_lens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.code_lens.fetch_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let requests = editor
+            .update(&mut cx, |editor, cx| {
+                let buffers = editor.buffer.read(cx).all_buffers();
+                project
+                    .update(cx, |project, cx| {
+                        buffers
+                            .into_iter()
+                            .filter(|buffer| code_lens_enabled(buffer.read(cx), cx))
+                            .map(|buffer| {
+                                let request = project.code_lens(&buffer, cx);
+                                async move { (buffer, request.await) }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()
+            })
+            .ok()??;
+        let responses = join_all(requests).await;
+
+        editor
+            .update_in(&mut cx, |editor, window, cx| {
+                let mut fetched_buffers = HashSet::default();
+                for (buffer, response) in responses {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    fetched_buffers.insert(buffer_id);
+                    let Some(lenses) = response.log_err() else {
+                        continue;
+                    };
+                    set_code_lens(editor, buffer, lenses, cx);
+                }
+
+                let removed_blocks = editor
+                    .code_lens
+                    .retain_buffers(|buffer_id| fetched_buffers.contains(&buffer_id));
+                editor.remove_blocks(removed_blocks.into_iter().collect(), None, cx);
+                resolve_visible_code_lens(editor, window, cx);
+            })
+            .ok()
+    }));
+    None
+}
+
+fn set_code_lens(
+    editor: &mut Editor,
+    buffer: Entity<Buffer>,
+    mut lenses: Vec<CodeLens>,
+    cx: &mut Context<Editor>,
+) {
+    let snapshot = buffer.read(cx).snapshot();
+    let buffer_id = snapshot.remote_id();
+    lenses.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+
+    let fetch_id = post_inc(&mut editor.code_lens.next_fetch_id);
+    let mut block_ids = Vec::new();
+    if let Some(previous) = editor.code_lens.buffers.remove(&buffer_id) {
+        block_ids = previous.block_ids;
+        // Keep showing the commands of lenses that are still the same, instead
+        // of waiting for them to be resolved again.
+        for lens in lenses.iter_mut().filter(|lens| !lens.is_resolved()) {
+            if let Some(previous_lens) = previous.lenses.iter().find(|previous_lens| {
+                previous_lens.is_resolved()
+                    && previous_lens.server_id == lens.server_id
+                    && previous_lens.lsp_lens.range == lens.lsp_lens.range
+                    && previous_lens.lsp_lens.data == lens.lsp_lens.data
+            }) {
+                lens.lsp_lens.command = previous_lens.lsp_lens.command.clone();
+            }
+        }
+    }
+
+    editor.code_lens.buffers.insert(
+        buffer_id,
+        BufferCodeLens {
+            buffer,
+            fetch_id,
+            lenses,
+            resolving: HashSet::default(),
+            unresolvable: HashSet::default(),
+            block_ids,
+        },
+    );
+    update_code_lens_blocks(editor, buffer_id, cx);
+}
+
+/// Resolves the code lenses in the visible part of the editor, which
+/// language servers often report without a command to save work.
+pub(super) fn resolve_visible_code_lens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    if editor.code_lens.buffers.is_empty() {
+        return None;
+    }
+    let project = editor.project.clone()?;
+    let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let visible_start = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&multibuffer_snapshot);
+    let visible_end = multibuffer_snapshot.clip_point(
+        visible_start + Point::new(editor.visible_line_count()?.ceil() as u32, 0),
+        Bias::Left,
+    );
+
+    let mut requests = Vec::new();
+    for (buffer_snapshot, visible_range, _) in
+        multibuffer_snapshot.range_to_buffer_ranges(visible_start..visible_end)
+    {
+        let buffer_id = buffer_snapshot.remote_id();
+        let Some(buffer_lens) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+            continue;
+        };
+        for (ix, lens) in buffer_lens.lenses.iter().enumerate() {
+            if lens.is_resolved()
+                || buffer_lens.resolving.contains(&ix)
+                || buffer_lens.unresolvable.contains(&ix)
+                || !visible_range.contains(&lens.range.start.to_offset(buffer_snapshot))
+            {
+                continue;
+            }
+            buffer_lens.resolving.insert(ix);
+            let fetch_id = buffer_lens.fetch_id;
+            let request = project.update(cx, |project, cx| {
+                project.resolve_code_lens(buffer_lens.buffer.clone(), lens.clone(), cx)
+            });
+            requests.push(async move { (buffer_id, fetch_id, ix, request.await) });
+        }
+    }
+    if requests.is_empty() {
+        return None;
+    }
+
+    cx.spawn_in(window, |editor, mut cx| async move {
+        let responses = join_all(requests).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                let mut updated_buffers = HashSet::default();
+                for (buffer_id, fetch_id, ix, response) in responses {
+                    let Some(buffer_lens) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+                        continue;
+                    };
+                    if buffer_lens.fetch_id != fetch_id {
+                        continue;
+                    }
+                    buffer_lens.resolving.remove(&ix);
+                    match response.log_err() {
+                        Some(lens) if lens.is_resolved() => buffer_lens.lenses[ix] = lens,
+                        _ => {
+                            buffer_lens.unresolvable.insert(ix);
+                        }
+                    }
+                    updated_buffers.insert(buffer_id);
+                }
+                for buffer_id in updated_buffers {
+                    update_code_lens_blocks(editor, buffer_id, cx);
+                }
+            })
+            .ok()
+    })
+    .detach();
+    None
+}
+
+/// Replaces the blocks of a buffer's code lenses, with one block above
+/// each line that has code lenses, in every excerpt of the buffer.
+fn update_code_lens_blocks(
+    editor: &mut Editor,
+    buffer_id: BufferId,
+    cx: &mut Context<Editor>,
+) {
+    let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let editor_handle = cx.entity().downgrade();
+    let Some(buffer_lens) = editor.code_lens.buffers.get_mut(&buffer_id) else {
+        return;
+    };
+    let old_block_ids = mem::take(&mut buffer_lens.block_ids);
+
+    let mut blocks = Vec::new();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in multibuffer_snapshot.excerpts() {
+        if buffer_snapshot.remote_id() != buffer_id {
+            continue;
+        }
+        let context = excerpt_range.context.to_offset(buffer_snapshot);
+        let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::new();
+        for (ix, lens) in buffer_lens.lenses.iter().enumerate() {
+            if buffer_lens.unresolvable.contains(&ix) {
+                continue;
+            }
+            let start = lens.range.start.to_offset(buffer_snapshot);
+            if context.contains(&start) {
+                let row = buffer_snapshot.offset_to_point(start).row;
+                lenses_by_row.entry(row).or_default().push(lens.clone());
+            }
+        }
+
+        for (row, lenses) in lenses_by_row {
+            let indent = buffer_snapshot.indent_size_for_line(row).len;
+            let anchor = buffer_snapshot.anchor_after(Point::new(row, indent));
+            let Some(anchor) = multibuffer_snapshot.anchor_in_excerpt(excerpt_id, anchor) else {
+                continue;
+            };
+            blocks.push(BlockProperties {
+                placement: BlockPlacement::Above(anchor),
+                height: 1,
+                style: BlockStyle::Flex,
+                render: render_code_lens_line(
+                    editor_handle.clone(),
+                    buffer_lens.buffer.clone(),
+                    lenses,
+                ),
+                priority: 0,
+            });
+        }
+    }
+
+    editor.remove_blocks(old_block_ids.into_iter().collect(), None, cx);
+    let block_ids = editor.insert_blocks(blocks, None, cx);
+    if let Some(buffer_lens) = editor.code_lens.buffers.get_mut(&buffer_id) {
+        buffer_lens.block_ids = block_ids;
+    }
+}
+
+fn render_code_lens_line(
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    lenses: Vec<CodeLens>,
+) -> RenderBlock {
+    let lenses = Arc::new(lenses);
+    Arc::new(move |cx: &mut BlockContext| {
+        let resolved_lenses = lenses.iter().filter_map(|lens| {
+            let command = lens.lsp_lens.command.as_ref()?;
+            Some((lens, command.title.clone()))
+        });
+
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .block_mouse_down()
+            .children(resolved_lenses.enumerate().flat_map(|(ix, (lens, title))| {
+                let separator = (ix > 0).then(|| {
+                    Label::new("|")
+                        .size(LabelSize::XSmall)
+                        .color(Color::Disabled)
+                        .into_any_element()
+                });
+                let editor = editor.clone();
+                let buffer = buffer.clone();
+                let lens = lens.clone();
+                let button = ButtonLike::new(ix)
+                    .child(
+                        Label::new(title)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                    )
+                    .on_click(move |_, window, cx| {
+                        editor
+                            .update(cx, |editor, cx| {
+                                apply_code_lens(editor, buffer.clone(), lens.clone(), window, cx);
+                            })
+                            .ok();
+                    })
+                    .into_any_element();
+                separator.into_iter().chain([button])
+            }))
+            .into_any_element()
+    })
+}
+
+fn apply_code_lens(
+    editor: &mut Editor,
+    buffer: Entity<Buffer>,
+    lens: CodeLens,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    let project = editor.project.as_ref()?;
+    let workspace = editor.workspace()?.downgrade();
+    let title = lens.lsp_lens.command.as_ref()?.title.clone();
+    let apply_code_lens = project.update(cx, |project, cx| {
+        project.apply_code_lens(buffer, lens, true, cx)
+    });
+    cx.spawn_in(window, |editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    })
+    .detach_and_log_err(cx);
+    Some(())
+}
@@ -998,6 +998,9 @@ impl InlaySnapshot {
             buffer_range,
             language_aware,
             highlights.text_highlights,
+            highlights
+                .semantic_token_highlights
+                .map(|highlights| &highlights[..]),
             &self.buffer,
         );
 
@@ -16,12 +16,15 @@ pub mod actions;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod commit_tooltip;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
+mod hierarchy_view;
 mod highlight_matching_bracket;
 mod hover_links;
 mod hover_popover;
@@ -30,13 +33,16 @@ mod inlay_hint_cache;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
+mod selection_ranges;
 mod selections_collection;
+mod semantic_tokens;
 pub mod tasks;
 
 #[cfg(test)]
@@ -108,6 +114,7 @@ use language::{
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
+pub use merge_conflicts::has_conflict_markers;
 use mouse_context_menu::MouseContextMenu;
 use persistence::DB;
 pub use proposed_changes_editor::{
@@ -135,6 +142,7 @@ use multi_buffer::{
     MultiOrSingleBufferOffsetRange, ToOffsetUtf16,
 };
 use project::{
+    git::GitEvent,
     lsp_store::{CompletionDocumentation, FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
     CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
@@ -662,6 +670,7 @@ pub struct Editor {
     show_runnables: Option<bool>,
     show_wrap_guides: Option<bool>,
     show_indent_guides: Option<bool>,
+    show_minimap: Option<bool>,
     placeholder_text: Option<Arc<str>>,
     highlight_order: usize,
     highlighted_rows: HashMap<TypeId, Vec<RowHighlight>>,
@@ -683,6 +692,12 @@ pub struct Editor {
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    semantic_tokens_task: Option<Task<Option<()>>>,
+    semantic_tokens: HashMap<BufferId, Vec<project::BufferSemanticTokens>>,
+    code_lens: code_lens::CodeLensState,
+    folding_ranges: folding_ranges::FoldingRangeState,
+    merge_conflicts: merge_conflicts::ConflictState,
+    selection_ranges_task: Option<Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
     cursor_shape: CursorShape,
@@ -978,6 +993,15 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    transforms: Vec<SnippetTransform>,
+}
+
+/// A transformed mirror of a tabstop in an inserted snippet, which is updated whenever the
+/// tabstop's text changes.
+struct SnippetTransform {
+    source: Range<Anchor>,
+    target: Range<Anchor>,
+    transform: snippet::Transform,
 }
 
 #[doc(hidden)]
@@ -1259,6 +1283,10 @@ impl Editor {
                         if let project::Event::RefreshInlayHints = event {
                             editor
                                 .refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                        } else if let project::Event::RefreshSemanticTokens = event {
+                            semantic_tokens::refresh_semantic_tokens(editor, window, cx);
+                        } else if let project::Event::RefreshCodeLens = event {
+                            code_lens::refresh_code_lens(editor, window, cx);
                         } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                             if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                                 let focus_handle = editor.focus_handle(cx);
@@ -1281,6 +1309,14 @@ impl Editor {
                         }
                     },
                 ));
+                let git_store = project.read(cx).git_store().clone();
+                project_subscriptions.push(cx.subscribe_in(
+                    &git_store,
+                    window,
+                    |editor, _, _: &GitEvent, window, cx| {
+                        merge_conflicts::refresh_conflicts(editor, window, cx);
+                    },
+                ));
                 if let Some(task_inventory) = project
                     .read(cx)
                     .task_store()
@@ -1373,6 +1409,7 @@ impl Editor {
             show_runnables: None,
             show_wrap_guides: None,
             show_indent_guides,
+            show_minimap: None,
             placeholder_text: None,
             highlight_order: 0,
             highlighted_rows: HashMap::default(),
@@ -1474,6 +1511,12 @@ impl Editor {
             ],
             tasks_update_task: None,
             linked_edit_ranges: Default::default(),
+            semantic_tokens_task: None,
+            semantic_tokens: HashMap::default(),
+            code_lens: Default::default(),
+            folding_ranges: Default::default(),
+            merge_conflicts: Default::default(),
+            selection_ranges_task: None,
             in_project_search: false,
             previous_search_ranges: None,
             breadcrumb_header: None,
@@ -1490,6 +1533,10 @@ impl Editor {
         };
         this.tasks_update_task = Some(this.refresh_runnables(window, cx));
         this._subscriptions.extend(project_subscriptions);
+        semantic_tokens::refresh_semantic_tokens(&mut this, window, cx);
+        code_lens::refresh_code_lens(&mut this, window, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, window, cx);
+        merge_conflicts::refresh_conflicts(&mut this, window, cx);
 
         this.end_selection(window, cx);
         this.scroll_manager.show_scrollbar(window, cx);
@@ -4114,7 +4161,16 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let variables = self.snippet_variables(&buffer_handle, completion.old_range.start, cx);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, |name| {
+                    variables
+                        .get(name)
+                        .cloned()
+                        .or_else(|| snippet::builtin_variable(name))
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -6963,7 +7019,7 @@ impl Editor {
             choices: Option<Vec<String>>,
         }
 
-        let tabstops = self.buffer.update(cx, |buffer, cx| {
+        let (tabstops, transforms) = self.buffer.update(cx, |buffer, cx| {
             let snippet_text: Arc<str> = snippet.text.clone().into();
             buffer.edit(
                 insertion_ranges
@@ -7009,7 +7065,38 @@ impl Editor {
                         choices: tabstop.choices.clone(),
                     }
                 })
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+
+            let mut insertion_starts = Vec::with_capacity(insertion_ranges.len());
+            let mut delta = 0_isize;
+            for insertion_range in insertion_ranges {
+                insertion_starts.push(insertion_range.start as isize + delta);
+                delta += snippet.text.len() as isize - insertion_range.len() as isize;
+            }
+            let anchor_range = |insertion_start: isize, range: &Range<isize>| {
+                let start = ((insertion_start + range.start) as usize).min(snapshot.len());
+                let end = ((insertion_start + range.end) as usize).min(snapshot.len());
+                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+            };
+            let transforms = snippet
+                .transforms
+                .iter()
+                .filter_map(|mirror| {
+                    let source = snippet.tabstops.get(mirror.tabstop_index)?.ranges.first()?;
+                    Some(
+                        insertion_starts
+                            .iter()
+                            .map(move |insertion_start| SnippetTransform {
+                                source: anchor_range(*insertion_start, source),
+                                target: anchor_range(*insertion_start, &mirror.range),
+                                transform: mirror.transform.clone(),
+                            }),
+                    )
+                })
+                .flatten()
+                .collect::<Vec<_>>();
+
+            (tabstops, transforms)
         });
         if let Some(tabstop) = tabstops.first() {
             self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
@@ -7039,7 +7126,9 @@ impl Editor {
                     active_index: 0,
                     ranges,
                     choices,
+                    transforms,
                 });
+                self.update_snippet_transforms(cx);
             }
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
@@ -7081,6 +7170,117 @@ impl Editor {
         Ok(())
     }
 
+    /// Updates the transformed mirrors of tabstops in the active snippets to reflect the
+    /// tabstops' current text.
+    fn update_snippet_transforms(&mut self, cx: &mut Context<Self>) {
+        if self.snippet_stack.is_empty() {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut edits = Vec::new();
+        for snippet in self.snippet_stack.iter() {
+            for mirror in &snippet.transforms {
+                let source = snapshot
+                    .text_for_range(mirror.source.clone())
+                    .collect::<String>();
+                let target = snapshot
+                    .text_for_range(mirror.target.clone())
+                    .collect::<String>();
+                let transformed = mirror.transform.apply(&source);
+                if transformed != target {
+                    edits.push((mirror.target.clone(), transformed));
+                }
+            }
+        }
+
+        if !edits.is_empty() {
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        }
+    }
+
+    /// Resolves the snippet variables that depend on the editor, such as `$TM_FILENAME` and
+    /// `$TM_SELECTED_TEXT`, for a snippet inserted at the given position in the buffer.
+    fn snippet_variables(
+        &self,
+        buffer: &Entity<Buffer>,
+        position: text::Anchor,
+        cx: &App,
+    ) -> HashMap<&'static str, String> {
+        let mut variables = HashMap::default();
+        let buffer = buffer.read(cx).snapshot();
+        let point = buffer.summary_for_anchor::<Point>(&position);
+
+        let selection = self.selections.newest::<usize>(cx);
+        let multibuffer = self.buffer.read(cx).snapshot(cx);
+        variables.insert(
+            "TM_SELECTED_TEXT",
+            multibuffer.text_for_range(selection.range()).collect(),
+        );
+        variables.insert(
+            "TM_CURRENT_LINE",
+            buffer
+                .text_for_range(
+                    Point::new(point.row, 0)..Point::new(point.row, buffer.line_len(point.row)),
+                )
+                .collect(),
+        );
+        let (word_range, _) = buffer.surrounding_word(point);
+        variables.insert(
+            "TM_CURRENT_WORD",
+            buffer.text_for_range(word_range).collect(),
+        );
+        variables.insert("TM_LINE_INDEX", point.row.to_string());
+        variables.insert("TM_LINE_NUMBER", (point.row + 1).to_string());
+
+        if let Some(file) = buffer.file() {
+            let path = file.path();
+            variables.insert("RELATIVE_FILEPATH", path.to_string_lossy().into_owned());
+            if let Some(file_name) = path.file_name() {
+                variables.insert("TM_FILENAME", file_name.to_string_lossy().into_owned());
+            }
+            if let Some(file_stem) = path.file_stem() {
+                variables.insert("TM_FILENAME_BASE", file_stem.to_string_lossy().into_owned());
+            }
+            if let Some(abs_path) = file.as_local().map(|file| file.abs_path(cx)) {
+                if let Some(directory) = abs_path.parent() {
+                    variables.insert("TM_DIRECTORY", directory.to_string_lossy().into_owned());
+                }
+                variables.insert("TM_FILEPATH", abs_path.to_string_lossy().into_owned());
+            }
+
+            let worktree = self
+                .project
+                .as_ref()
+                .and_then(|project| project.read(cx).worktree_for_id(file.worktree_id(cx), cx));
+            if let Some(worktree) = worktree {
+                let worktree = worktree.read(cx);
+                variables.insert("WORKSPACE_NAME", worktree.root_name().to_string());
+                variables.insert(
+                    "WORKSPACE_FOLDER",
+                    worktree.abs_path().to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        if let Some(scope) = buffer.language_scope_at(point) {
+            if let Some(prefix) = scope.line_comment_prefixes().first() {
+                variables.insert("LINE_COMMENT", prefix.trim_end().to_string());
+            }
+            if let Some((start, end)) = scope.block_comment_delimiters() {
+                variables.insert("BLOCK_COMMENT_START", start.trim_end().to_string());
+                variables.insert("BLOCK_COMMENT_END", end.trim_start().to_string());
+            }
+        }
+
+        if let Some(clipboard) = cx.read_from_clipboard().and_then(|item| item.text()) {
+            variables.insert("CLIPBOARD", clipboard);
+        }
+
+        variables
+    }
+
     pub fn move_to_next_snippet_tabstop(
         &mut self,
         window: &mut Window,
@@ -10909,6 +11109,12 @@ impl Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if selection_ranges::select_larger_lsp_range(self, window, cx).is_none() {
+            self.select_larger_syntax_ancestor(window, cx);
+        }
+    }
+
+    fn select_larger_syntax_ancestor(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = self.buffer.read(cx).snapshot(cx);
         let old_selections = self.selections.all::<usize>(cx).into_boxed_slice();
@@ -14212,6 +14418,29 @@ impl Editor {
         self.show_indent_guides
     }
 
+    pub fn toggle_minimap(&mut self, _: &ToggleMinimap, _: &mut Window, cx: &mut Context<Self>) {
+        let currently_enabled = self.minimap_columns(cx).is_some();
+        self.show_minimap = Some(!currently_enabled);
+        cx.notify();
+    }
+
+    /// The number of columns to render in the minimap, or `None` if it is hidden.
+    pub(crate) fn minimap_columns(&self, cx: &App) -> Option<u32> {
+        if self.mode != EditorMode::Full {
+            return None;
+        }
+
+        let settings = &EditorSettings::get_global(cx).minimap;
+        let language = self
+            .buffer
+            .read(cx)
+            .language_at(self.selections.newest_anchor().head(), cx);
+        let language_name = language.as_ref().map(|language| language.name());
+        self.show_minimap
+            .unwrap_or_else(|| settings.enabled_for(language_name.as_ref()))
+            .then(|| settings.max_width_columns_for(language_name.as_ref()))
+    }
+
     pub fn toggle_line_numbers(
         &mut self,
         _: &ToggleLineNumbers,
@@ -15089,35 +15318,26 @@ impl Editor {
         let mut results = Vec::new();
         for (color_fetcher, ranges) in self.background_highlights.values() {
             let color = color_fetcher(theme);
-            let start_ix = match ranges.binary_search_by(|probe| {
-                let cmp = probe
-                    .end
-                    .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
-                if cmp.is_gt() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            }) {
-                Ok(i) | Err(i) => i,
-            };
-            for range in &ranges[start_ix..] {
-                if range
-                    .start
-                    .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
-                    .is_ge()
-                {
-                    break;
-                }
-
-                let start = range.start.to_display_point(display_snapshot);
-                let end = range.end.to_display_point(display_snapshot);
-                results.push((start..end, color))
-            }
+            results.extend(
+                display_ranges_in_range(ranges, &search_range, display_snapshot)
+                    .map(|range| (range, color)),
+            );
         }
         results
     }
 
+    /// Like [`Self::background_highlights_in_range`], but only for the highlights of type `T`.
+    pub fn background_highlights_of_type_in_range<T: 'static>(
+        &self,
+        search_range: Range<Anchor>,
+        display_snapshot: &DisplaySnapshot,
+    ) -> Vec<Range<DisplayPoint>> {
+        let Some((_, ranges)) = self.background_highlights.get(&TypeId::of::<T>()) else {
+            return Vec::new();
+        };
+        display_ranges_in_range(ranges, &search_range, display_snapshot).collect()
+    }
+
     pub fn background_highlight_row_ranges<T: 'static>(
         &self,
         search_range: Range<Anchor>,
@@ -15332,6 +15552,7 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(window, cx);
+                self.update_snippet_transforms(cx);
                 if self.has_active_inline_completion() {
                     self.update_visible_inline_completion(window, cx);
                 }
@@ -15386,6 +15607,10 @@ impl Editor {
                     (telemetry, is_via_ssh)
                 };
                 refresh_linked_ranges(self, window, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                code_lens::refresh_code_lens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
+                merge_conflicts::refresh_conflicts(self, window, cx);
                 telemetry.log_edit_event("editor", is_via_ssh);
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -15412,12 +15637,26 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                code_lens::refresh_code_lens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
+                merge_conflicts::refresh_conflicts(self, window, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
                     .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                self.semantic_tokens
+                    .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                let removed_code_lens_blocks = self
+                    .code_lens
+                    .retain_buffers(|buffer_id| buffer.buffer(buffer_id).is_some());
+                self.remove_blocks(removed_code_lens_blocks.into_iter().collect(), None, cx);
+                self.folding_ranges
+                    .retain_buffers(|buffer_id| buffer.buffer(buffer_id).is_some());
+                semantic_tokens::update_semantic_token_highlights(self, cx);
+                folding_ranges::update_folding_range_creases(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited {
@@ -15433,6 +15672,9 @@ impl Editor {
             }
             multi_buffer::Event::ExcerptsExpanded { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::update_semantic_token_highlights(self, cx);
+                code_lens::refresh_code_lens(self, window, cx);
+                folding_ranges::update_folding_range_creases(self, cx);
                 cx.emit(EditorEvent::ExcerptsExpanded { ids: ids.clone() })
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
@@ -15445,11 +15687,17 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, window, cx);
+                semantic_tokens::refresh_semantic_tokens(self, window, cx);
+                code_lens::refresh_code_lens(self, window, cx);
+                folding_ranges::refresh_folding_ranges(self, window, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
             multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
+            multi_buffer::Event::Saved => {
+                merge_conflicts::stage_saved_resolutions(self, cx);
+                cx.emit(EditorEvent::Saved);
+            }
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
                 cx.emit(EditorEvent::TitleChanged)
             }
@@ -15490,6 +15738,9 @@ impl Editor {
             )),
             cx,
         );
+        semantic_tokens::refresh_semantic_tokens(self, window, cx);
+        code_lens::refresh_code_lens(self, window, cx);
+        folding_ranges::refresh_folding_ranges(self, window, cx);
 
         let old_cursor_shape = self.cursor_shape;
 
@@ -17157,6 +17408,37 @@ impl SemanticsProvider for Entity<Project> {
     }
 }
 
+fn display_ranges_in_range<'a>(
+    ranges: &'a [Range<Anchor>],
+    search_range: &'a Range<Anchor>,
+    display_snapshot: &'a DisplaySnapshot,
+) -> impl 'a + Iterator<Item = Range<DisplayPoint>> {
+    let start_ix = match ranges.binary_search_by(|probe| {
+        let cmp = probe
+            .end
+            .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
+        if cmp.is_gt() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    };
+    ranges[start_ix..]
+        .iter()
+        .take_while(|range| {
+            range
+                .start
+                .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
+                .is_lt()
+        })
+        .map(|range| {
+            range.start.to_display_point(display_snapshot)
+                ..range.end.to_display_point(display_snapshot)
+        })
+}
+
 fn inlay_hint_settings(
     location: Anchor,
     snapshot: &MultiBufferSnapshot,
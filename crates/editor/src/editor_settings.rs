@@ -1,5 +1,6 @@
+use collections::HashMap;
 use gpui::App;
-use language::CursorShape;
+use language::{CursorShape, LanguageName};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -16,6 +17,7 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -112,6 +114,32 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Minimap {
+    pub enabled: bool,
+    pub max_width_columns: u32,
+    pub git_diff: bool,
+    pub search_results: bool,
+    pub diagnostics: ScrollbarDiagnostics,
+    pub languages: HashMap<LanguageName, MinimapLanguageContent>,
+}
+
+impl Minimap {
+    /// Whether the minimap is shown for buffers in the given language.
+    pub fn enabled_for(&self, language: Option<&LanguageName>) -> bool {
+        language
+            .and_then(|language| self.languages.get(language)?.enabled)
+            .unwrap_or(self.enabled)
+    }
+
+    /// The maximum number of columns the minimap renders for buffers in the given language.
+    pub fn max_width_columns_for(&self, language: Option<&LanguageName>) -> u32 {
+        language
+            .and_then(|language| self.languages.get(language)?.max_width_columns)
+            .unwrap_or(self.max_width_columns)
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -300,6 +328,8 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -449,6 +479,44 @@ pub struct ScrollbarAxesContent {
     vertical: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// Whether to show a scaled-down overview of the buffer next to the scrollbar.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of columns to render in the minimap.
+    ///
+    /// Default: 80
+    pub max_width_columns: Option<u32>,
+    /// Whether to show git diff indicators in the minimap.
+    ///
+    /// Default: true
+    pub git_diff: Option<bool>,
+    /// Whether to show buffer search results in the minimap.
+    ///
+    /// Default: true
+    pub search_results: Option<bool>,
+    /// Which diagnostic indicators to show in the minimap.
+    ///
+    /// Default: all
+    pub diagnostics: Option<ScrollbarDiagnostics>,
+    /// Overrides for specific languages.
+    ///
+    /// Default: {}
+    pub languages: Option<HashMap<LanguageName, MinimapLanguageContent>>,
+}
+
+/// Minimap settings for a specific language
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct MinimapLanguageContent {
+    /// Whether to show the minimap for buffers in this language.
+    pub enabled: Option<bool>,
+    /// The maximum number of columns to render in the minimap for buffers in this language.
+    pub max_width_columns: Option<u32>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
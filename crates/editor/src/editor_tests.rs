@@ -7337,6 +7337,49 @@ async fn test_snippets(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_transforms(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges(
+        indoc! {"
+            ˇ
+            ˇ
+        "},
+        false,
+    );
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| build_editor(buffer, window, cx));
+
+    editor.update_in(cx, |editor, window, cx| {
+        let snippet = Snippet::parse("let ${1:name} = ${1/(.*)/${1:/upcase}/};$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, window, cx)
+            .unwrap();
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let name = NAME;
+                let name = NAME;
+            "}
+        );
+
+        editor.handle_input("foo", window, cx);
+    });
+
+    // Mirrors update as the tabstops they transform are edited.
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let foo = FOO;
+                let foo = FOO;
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
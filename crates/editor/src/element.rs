@@ -328,6 +328,11 @@ impl EditorElement {
         register_action(editor, window, Editor::go_to_prev_diagnostic);
         register_action(editor, window, Editor::go_to_next_hunk);
         register_action(editor, window, Editor::go_to_prev_hunk);
+        register_action(editor, window, Editor::go_to_next_conflict);
+        register_action(editor, window, Editor::go_to_previous_conflict);
+        register_action(editor, window, Editor::accept_conflict_ours);
+        register_action(editor, window, Editor::accept_conflict_theirs);
+        register_action(editor, window, Editor::accept_conflict_both);
         register_action(editor, window, |editor, action, window, cx| {
             editor
                 .go_to_definition(action, window, cx)
@@ -368,6 +373,16 @@ impl EditorElement {
                 .go_to_type_definition_split(action, window, cx)
                 .detach_and_log_err(cx);
         });
+        register_action(editor, window, |editor, action, window, cx| {
+            editor
+                .show_call_hierarchy(action, window, cx)
+                .detach_and_log_err(cx);
+        });
+        register_action(editor, window, |editor, action, window, cx| {
+            editor
+                .show_type_hierarchy(action, window, cx)
+                .detach_and_log_err(cx);
+        });
         register_action(editor, window, Editor::open_url);
         register_action(editor, window, Editor::open_selected_filename);
         register_action(editor, window, Editor::fold);
@@ -395,6 +410,7 @@ impl EditorElement {
         register_action(editor, window, Editor::toggle_line_numbers);
         register_action(editor, window, Editor::toggle_relative_line_numbers);
         register_action(editor, window, Editor::toggle_indent_guides);
+        register_action(editor, window, Editor::toggle_minimap);
         register_action(editor, window, Editor::toggle_inlay_hints);
         register_action(editor, window, Editor::toggle_edit_predictions);
         register_action(editor, window, Editor::toggle_inline_diagnostics);
@@ -1479,6 +1495,263 @@ impl EditorElement {
         axis_pair(horizontal_scrollbar, vertical_scrollbar)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        columns: u32,
+        scroll_position: gpui::Point<f32>,
+        height_in_lines: f32,
+        max_scroll_top: f32,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<MinimapLayout> {
+        if snapshot.mode != EditorMode::Full {
+            return None;
+        }
+
+        // If a drag took place after we started dragging the minimap,
+        // cancel the minimap drag.
+        if cx.has_active_drag() {
+            self.editor.update(cx, |editor, cx| {
+                editor.scroll_manager.set_is_dragging_minimap(false, cx);
+            });
+        }
+
+        let line_height = MinimapLayout::LINE_HEIGHT;
+        let max_row = snapshot.max_point().row();
+        let total_rows = max_row.next_row().as_f32();
+        let minimap_rows = bounds.size.height / line_height;
+
+        // When the buffer doesn't fit in the minimap, scroll the minimap proportionally
+        // to the editor so that both reach their ends together.
+        let scrollable_rows = (total_rows - minimap_rows).max(0.);
+        let first_row = if max_scroll_top > 0. {
+            scrollable_rows * (scroll_position.y / max_scroll_top).min(1.)
+        } else {
+            0.
+        };
+        let thumb_speed = if max_scroll_top > 0. {
+            (1. - scrollable_rows / max_scroll_top).max(f32::EPSILON)
+        } else {
+            1.
+        };
+        let thumb_bounds = Bounds::new(
+            point(
+                bounds.left(),
+                bounds.top() + line_height * (scroll_position.y - first_row),
+            ),
+            size(
+                bounds.size.width,
+                (line_height * height_in_lines).min(bounds.size.height),
+            ),
+        );
+
+        let start_row = DisplayRow(first_row.floor() as u32);
+        let end_row = DisplayRow(cmp::min(
+            (first_row + minimap_rows).ceil() as u32 + 1,
+            max_row.next_row().0,
+        ));
+        let origin = point(
+            bounds.left() + MinimapLayout::PADDING,
+            bounds.top() - line_height * (first_row - start_row.as_f32()),
+        );
+        let row_top =
+            |row: DisplayRow| origin.y + line_height * (row.as_f32() - start_row.as_f32());
+        let column_left = |column: u32| origin.x + MinimapLayout::COLUMN_WIDTH * column as f32;
+
+        let settings = EditorSettings::get_global(cx).minimap.clone();
+        let theme = cx.theme().clone();
+        let mut quads = Vec::new();
+
+        let start_point = DisplayPoint::new(start_row, 0).to_point(snapshot);
+        let end_point = if end_row > max_row {
+            snapshot.buffer_snapshot.max_point()
+        } else {
+            DisplayPoint::new(end_row, 0).to_point(snapshot)
+        };
+
+        if settings.search_results {
+            let mut color = theme.status().info;
+            color.fade_out(0.5);
+            let search_ranges = self
+                .editor
+                .read(cx)
+                .background_highlights_of_type_in_range::<BufferSearchHighlights>(
+                    snapshot.buffer_snapshot.anchor_before(start_point)
+                        ..snapshot.buffer_snapshot.anchor_after(end_point),
+                    &snapshot.display_snapshot,
+                );
+            for range in search_ranges {
+                for row in range.start.row().0..=range.end.row().0 {
+                    let row = DisplayRow(row);
+                    let start_column = if row == range.start.row() {
+                        range.start.column()
+                    } else {
+                        0
+                    };
+                    let end_column = if row == range.end.row() {
+                        range.end.column().max(start_column + 1)
+                    } else {
+                        columns
+                    };
+                    if start_column >= columns {
+                        continue;
+                    }
+                    quads.push(fill(
+                        Bounds::from_corners(
+                            point(column_left(start_column), row_top(row)),
+                            point(
+                                column_left(end_column.min(columns)),
+                                row_top(row) + line_height,
+                            ),
+                        ),
+                        color,
+                    ));
+                }
+            }
+        }
+
+        let mut row = start_row;
+        let mut column = 0;
+        let default_color = self.style.text.color;
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(default_color)
+                .opacity(0.8);
+            for (ix, line) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    row.0 += 1;
+                    column = 0;
+                }
+
+                let mut run_start = None;
+                for character in line.chars().map(Some).chain([None]) {
+                    if character.map_or(true, char::is_whitespace) {
+                        if let Some(start_column) = run_start.take() {
+                            if start_column < columns {
+                                quads.push(fill(
+                                    Bounds::new(
+                                        point(column_left(start_column), row_top(row)),
+                                        size(
+                                            MinimapLayout::COLUMN_WIDTH
+                                                * (column.min(columns) - start_column) as f32,
+                                            MinimapLayout::GLYPH_HEIGHT,
+                                        ),
+                                    ),
+                                    color,
+                                ));
+                            }
+                        }
+                    } else if run_start.is_none() {
+                        run_start = Some(column);
+                    }
+                    if character.is_some() {
+                        column += 1;
+                    }
+                }
+            }
+        }
+
+        if settings.git_diff {
+            for hunk in snapshot
+                .buffer_snapshot
+                .diff_hunks_in_range(start_point..end_point)
+            {
+                let hunk_start_row = MultiBufferPoint::new(hunk.row_range.start.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let hunk_end_row = MultiBufferPoint::new(hunk.row_range.end.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let color = match &hunk.status().kind {
+                    DiffHunkStatusKind::Added => theme.colors().version_control_added,
+                    DiffHunkStatusKind::Modified => theme.colors().version_control_modified,
+                    DiffHunkStatusKind::Deleted => theme.colors().version_control_deleted,
+                };
+                quads.push(fill(
+                    Bounds::from_corners(
+                        point(bounds.left(), row_top(hunk_start_row)),
+                        point(
+                            bounds.left() + MinimapLayout::PADDING / 2.,
+                            row_top(hunk_end_row).max(row_top(hunk_start_row) + line_height),
+                        ),
+                    ),
+                    color,
+                ));
+            }
+        }
+
+        if settings.diagnostics != ScrollbarDiagnostics::None {
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<Point>(start_point..end_point)
+                .filter(|diagnostic| {
+                    should_show_diagnostic(settings.diagnostics, diagnostic.diagnostic.severity)
+                })
+                // Paint the most severe diagnostics last.
+                .sorted_by_key(|diagnostic| std::cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let start = diagnostic
+                    .range
+                    .start
+                    .to_display_point(&snapshot.display_snapshot);
+                let end = diagnostic
+                    .range
+                    .end
+                    .to_display_point(&snapshot.display_snapshot);
+                let color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => theme.status().error,
+                    DiagnosticSeverity::WARNING => theme.status().warning,
+                    DiagnosticSeverity::INFORMATION => theme.status().info,
+                    _ => theme.status().hint,
+                };
+                for row in start.row().0..=end.row().0 {
+                    let row = DisplayRow(row);
+                    let start_column = if row == start.row() {
+                        start.column()
+                    } else {
+                        0
+                    };
+                    let end_column = if row == end.row() {
+                        end.column().max(start_column + 1)
+                    } else {
+                        columns
+                    };
+                    if start_column >= columns {
+                        continue;
+                    }
+                    quads.push(fill(
+                        Bounds::from_corners(
+                            point(
+                                column_left(start_column),
+                                row_top(row) + MinimapLayout::GLYPH_HEIGHT,
+                            ),
+                            point(
+                                column_left(end_column.min(columns)),
+                                row_top(row) + line_height,
+                            ),
+                        ),
+                        color,
+                    ));
+                }
+            }
+        }
+
+        Some(MinimapLayout {
+            hitbox: window.insert_hitbox(bounds, false),
+            thumb_bounds,
+            first_row,
+            thumb_row_height: line_height * thumb_speed,
+            visible_rows: height_in_lines,
+            max_scroll_top,
+            quads,
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -5049,6 +5322,118 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+        let minimap = Rc::new(minimap);
+        let hitbox = minimap.hitbox.clone();
+
+        window.paint_layer(hitbox.bounds, |window| {
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: hitbox.bounds,
+                }),
+                |window| {
+                    window.paint_quad(quad(
+                        hitbox.bounds,
+                        Corners::default(),
+                        cx.theme().colors().editor_background,
+                        Edges {
+                            top: Pixels::ZERO,
+                            right: Pixels::ZERO,
+                            bottom: Pixels::ZERO,
+                            left: ScrollbarLayout::BORDER_WIDTH,
+                        },
+                        cx.theme().colors().scrollbar_track_border,
+                    ));
+                    for quad in &minimap.quads {
+                        window.paint_quad(quad.clone());
+                    }
+
+                    let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+                    let thumb_color = if is_dragging || hitbox.is_hovered(window) {
+                        cx.theme().colors().scrollbar_thumb_hover_background
+                    } else {
+                        cx.theme().colors().scrollbar_thumb_background
+                    };
+                    window.paint_quad(fill(minimap.thumb_bounds, thumb_color));
+                },
+            );
+        });
+
+        window.set_cursor_style(CursorStyle::Arrow, &hitbox);
+
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let minimap = minimap.clone();
+            let mut mouse_position = window.mouse_position();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if editor.scroll_manager.is_dragging_minimap() {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            let mut position = editor.scroll_position(cx);
+                            position.y = (position.y
+                                + (event.position.y - mouse_position.y) / minimap.thumb_row_height)
+                                .clamp(0., minimap.max_scroll_top);
+                            editor.set_scroll_position(position, window, cx);
+                            cx.stop_propagation();
+                        } else {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !minimap.hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the visible region jumps to the clicked row,
+                        // after which the region can be dragged from there.
+                        if !minimap.thumb_bounds.contains(&event.position) {
+                            let mut position = editor.scroll_position(cx);
+                            position.y = minimap.scroll_top_for_position(event.position);
+                            editor.set_scroll_position(position, window, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -5183,28 +5568,10 @@ impl EditorElement {
                                     .diagnostics_in_range::<Point>(Point::zero()..max_point)
                                     // Don't show diagnostics the user doesn't care about
                                     .filter(|diagnostic| {
-                                        match (
+                                        should_show_diagnostic(
                                             scrollbar_settings.diagnostics,
                                             diagnostic.diagnostic.severity,
-                                        ) {
-                                            (ScrollbarDiagnostics::All, _) => true,
-                                            (
-                                                ScrollbarDiagnostics::Error,
-                                                DiagnosticSeverity::ERROR,
-                                            ) => true,
-                                            (
-                                                ScrollbarDiagnostics::Warning,
-                                                DiagnosticSeverity::ERROR
-                                                | DiagnosticSeverity::WARNING,
-                                            ) => true,
-                                            (
-                                                ScrollbarDiagnostics::Information,
-                                                DiagnosticSeverity::ERROR
-                                                | DiagnosticSeverity::WARNING
-                                                | DiagnosticSeverity::INFORMATION,
-                                            ) => true,
-                                            (_, _) => false,
-                                        }
+                                        )
                                     })
                                     // We want to sort by severity, in order to paint the most severe diagnostics last.
                                     .sorted_by_key(|diagnostic| {
@@ -5573,6 +5940,14 @@ impl EditorElement {
         bounds.top_right().x - self.style.scrollbar_width
     }
 
+    /// The number of columns to render in the minimap, if it should be shown in an editor
+    /// whose text area has the given width.
+    fn minimap_columns(&self, text_width: Pixels, cx: &App) -> Option<u32> {
+        let columns = self.editor.read(cx).minimap_columns(cx)?;
+        // Leave most of the editor's width to the text itself.
+        (MinimapLayout::width(columns) * 4. <= text_width).then_some(columns)
+    }
+
     fn column_pixels(&self, column: usize, window: &mut Window, _: &mut App) -> Pixels {
         let style = &self.style;
         let font_size = style.text.font_size.to_pixels(window.rem_size());
@@ -6586,8 +6961,13 @@ impl Element for EditorElement {
                         .unwrap_or_default();
                     let text_width = bounds.size.width - gutter_dimensions.width;
 
-                    let editor_width =
-                        text_width - gutter_dimensions.margin - em_width - style.scrollbar_width;
+                    let minimap_columns = self.minimap_columns(text_width, cx);
+                    let minimap_width = minimap_columns.map_or(Pixels::ZERO, MinimapLayout::width);
+                    let editor_width = text_width
+                        - gutter_dimensions.margin
+                        - em_width
+                        - style.scrollbar_width
+                        - minimap_width;
 
                     snapshot = self.editor.update(cx, |editor, cx| {
                         editor.last_bounds = Some(bounds);
@@ -7216,6 +7596,23 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = minimap_columns.and_then(|columns| {
+                        let right = self.scrollbar_left(&bounds);
+                        self.layout_minimap(
+                            &snapshot,
+                            Bounds::from_corners(
+                                point(right - minimap_width, bounds.top()),
+                                point(right, bounds.bottom()),
+                            ),
+                            columns,
+                            scroll_position,
+                            height_in_lines,
+                            max_scroll_top,
+                            window,
+                            cx,
+                        )
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let rows_with_hunk_bounds = display_hunks
@@ -7452,6 +7849,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -7542,6 +7940,7 @@ impl Element for EditorElement {
                         }
                     });
 
+                    self.paint_minimap(layout, window, cx);
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
@@ -7561,6 +7960,24 @@ pub(super) fn gutter_bounds(
     }
 }
 
+fn should_show_diagnostic(setting: ScrollbarDiagnostics, severity: DiagnosticSeverity) -> bool {
+    match (setting, severity) {
+        (ScrollbarDiagnostics::All, _) => true,
+        (ScrollbarDiagnostics::Error, DiagnosticSeverity::ERROR) => true,
+        (
+            ScrollbarDiagnostics::Warning,
+            DiagnosticSeverity::ERROR | DiagnosticSeverity::WARNING,
+        ) => true,
+        (
+            ScrollbarDiagnostics::Information,
+            DiagnosticSeverity::ERROR
+            | DiagnosticSeverity::WARNING
+            | DiagnosticSeverity::INFORMATION,
+        ) => true,
+        (_, _) => false,
+    }
+}
+
 struct ScrollbarRangeData {
     scrollbar_bounds: Bounds<Pixels>,
     scroll_range: Bounds<Pixels>,
@@ -7628,6 +8045,7 @@ pub struct EditorLayout {
     gutter_hitbox: Hitbox,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -7800,6 +8218,35 @@ impl ScrollbarLayout {
     }
 }
 
+struct MinimapLayout {
+    hitbox: Hitbox,
+    thumb_bounds: Bounds<Pixels>,
+    /// The fractional display row shown at the top of the minimap.
+    first_row: f32,
+    /// How far the thumb moves when the editor scrolls by one row.
+    thumb_row_height: Pixels,
+    visible_rows: f32,
+    max_scroll_top: f32,
+    quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    const LINE_HEIGHT: Pixels = px(3.);
+    const GLYPH_HEIGHT: Pixels = px(2.);
+    const COLUMN_WIDTH: Pixels = px(1.);
+    const PADDING: Pixels = px(4.);
+
+    fn width(columns: u32) -> Pixels {
+        Self::COLUMN_WIDTH * columns as f32 + Self::PADDING * 2.
+    }
+
+    /// The scroll position that centers the editor on the row under the given position.
+    fn scroll_top_for_position(&self, position: gpui::Point<Pixels>) -> f32 {
+        let row = self.first_row + (position.y - self.hitbox.top()) / Self::LINE_HEIGHT;
+        (row - self.visible_rows / 2.).clamp(0., self.max_scroll_top)
+    }
+}
+
 struct CreaseTrailerLayout {
     element: AnyElement,
     bounds: Bounds<Pixels>,
@@ -8270,7 +8717,7 @@ mod tests {
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
         editor_tests::{init_test, update_test_language_settings},
-        Editor, MultiBuffer,
+        Editor, MultiBuffer, ToggleMinimap,
     };
     use gpui::{TestAppContext, VisualTestContext};
     use language::language_settings;
@@ -8579,6 +9026,55 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple("ab cd\n\tefg", cx);
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style.clone()),
+        );
+        assert!(state.minimap.is_none());
+
+        window
+            .update(cx, |editor, window, cx| {
+                editor.toggle_minimap(&ToggleMinimap, window, cx);
+            })
+            .unwrap();
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style),
+        );
+        let minimap = state.minimap.expect("minimap should be shown");
+        let left = minimap.hitbox.left() + MinimapLayout::PADDING;
+        let top = minimap.hitbox.top();
+        assert_eq!(
+            minimap
+                .quads
+                .iter()
+                .map(|quad| (
+                    (quad.bounds.left() - left) / MinimapLayout::COLUMN_WIDTH,
+                    (quad.bounds.top() - top) / MinimapLayout::LINE_HEIGHT,
+                    quad.bounds.size.width / MinimapLayout::COLUMN_WIDTH,
+                ))
+                .collect::<Vec<_>>(),
+            // The tab is expanded to 4 columns.
+            [(0., 0., 2.), (3., 0., 2.), (4., 1., 3.)]
+        );
+        assert_eq!(minimap.first_row, 0.);
+        assert_eq!(minimap.thumb_bounds.top(), top);
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -0,0 +1,144 @@
+use std::{cmp, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{Context, Task, Window};
+use language::language_settings::{language_settings, RangeSource};
+use multi_buffer::Anchor;
+use project::FoldingRange;
+use text::{BufferId, ToPoint as _};
+use util::ResultExt;
+
+use crate::{
+    display_map::{Crease, CreaseId},
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The folding ranges reported by language servers for the buffers in an
+/// editor, and the creases that make them foldable.
+#[derive(Default)]
+pub(super) struct FoldingRangeState {
+    buffers: HashMap<BufferId, Vec<FoldingRange>>,
+    crease_ids: Vec<CreaseId>,
+    fetch_task: Option<Task<Option<()>>>,
+}
+
+impl FoldingRangeState {
+    pub(super) fn retain_buffers(&mut self, mut f: impl FnMut(BufferId) -> bool) {
+        self.buffers.retain(|buffer_id, _| f(*buffer_id));
+    }
+}
+
+/// Requests folding ranges for every buffer in the editor whose folds come
+/// from the language server, and turns them into creases once they arrive.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.folding_ranges.fetch_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let requests = editor
+            .update(&mut cx, |editor, cx| {
+                let buffers = editor.buffer.read(cx).all_buffers();
+                project
+                    .update(cx, |project, cx| {
+                        buffers
+                            .into_iter()
+                            .filter(|buffer| {
+                                let buffer = buffer.read(cx);
+                                language_settings(
+                                    buffer.language().map(|language| language.name()),
+                                    buffer.file(),
+                                    cx,
+                                )
+                                .folding_ranges
+                                    == RangeSource::LanguageServer
+                            })
+                            .map(|buffer| {
+                                let buffer_id = buffer.read(cx).remote_id();
+                                let request = project.folding_ranges(&buffer, cx);
+                                async move { (buffer_id, request.await) }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()
+            })
+            .ok()??;
+        let responses = join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let mut buffers = HashMap::default();
+                for (buffer_id, response) in responses {
+                    let ranges = match response.log_err() {
+                        Some(ranges) => ranges,
+                        None => editor
+                            .folding_ranges
+                            .buffers
+                            .remove(&buffer_id)
+                            .unwrap_or_default(),
+                    };
+                    buffers.insert(buffer_id, ranges);
+                }
+                editor.folding_ranges.buffers = buffers;
+                update_folding_range_creases(editor, cx);
+            })
+            .ok()
+    }));
+    None
+}
+
+/// Replaces the creases of the stored folding ranges with ones that match
+/// the current excerpts.
+pub(super) fn update_folding_range_creases(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let placeholder = editor.display_map.read(cx).fold_placeholder.clone();
+    let mut creases = Vec::new();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in multibuffer_snapshot.excerpts() {
+        let buffer_id = buffer_snapshot.remote_id();
+        let Some(ranges) = editor.folding_ranges.buffers.get(&buffer_id) else {
+            continue;
+        };
+
+        let context = excerpt_range.context.start.to_point(buffer_snapshot)
+            ..excerpt_range.context.end.to_point(buffer_snapshot);
+        let mut last_start_row = None;
+        for folding_range in ranges {
+            let start = folding_range.range.start.to_point(buffer_snapshot);
+            let end = cmp::min(
+                folding_range.range.end.to_point(buffer_snapshot),
+                context.end,
+            );
+            // Only one crease can start on a row, and the ranges are sorted so
+            // that the outermost range starting on each row comes first.
+            if start < context.start || end.row <= start.row || last_start_row == Some(start.row) {
+                continue;
+            }
+            last_start_row = Some(start.row);
+            creases.push(Crease::simple(
+                Anchor::range_in_buffer(
+                    excerpt_id,
+                    buffer_id,
+                    buffer_snapshot.anchor_after(start)..buffer_snapshot.anchor_before(end),
+                ),
+                placeholder.clone(),
+            ));
+        }
+    }
+
+    let old_crease_ids = std::mem::take(&mut editor.folding_ranges.crease_ids);
+    editor.display_map.update(cx, |display_map, cx| {
+        display_map.remove_creases(old_crease_ids, cx);
+        editor.folding_ranges.crease_ids = display_map.insert_creases(creases, cx);
+    });
+    cx.notify();
+}
@@ -0,0 +1,689 @@
+use crate::{Editor, Navigated, ShowCallHierarchy, ShowTypeHierarchy};
+use anyhow::Result;
+use gpui::{
+    actions, uniform_list, ClickEvent, Entity, EventEmitter, FocusHandle, Focusable, KeyContext,
+    ScrollStrategy, Task, UniformListScrollHandle, WeakEntity,
+};
+use language::{Anchor, Buffer, Location, ToPoint as _};
+use project::{HierarchyItem, LspHierarchyItem, Project};
+use std::ops::Range;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt as _;
+use workspace::{item::Item, SplitDirection, Workspace};
+
+actions!(
+    hierarchy_view,
+    [CollapseSelectedEntry, ExpandSelectedEntry, ToggleDirection,]
+);
+
+/// A relation between symbols that can be browsed as a tree in a [`HierarchyView`].
+pub trait Hierarchy: 'static {
+    type Item: LspHierarchyItem;
+    type Direction: Copy + PartialEq + 'static;
+
+    const NAME: &'static str;
+    /// The two directions the hierarchy can be walked in, along with their labels.
+    /// The first one is used when the view is opened.
+    const DIRECTIONS: [(Self::Direction, &'static str); 2];
+
+    fn prepare(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyItem<Self::Item>>>>;
+
+    fn children(
+        project: &mut Project,
+        item: HierarchyItem<Self::Item>,
+        direction: Self::Direction,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<Self::Item>>>>;
+}
+
+pub struct HierarchyChild<T> {
+    pub item: HierarchyItem<T>,
+    /// Where the relation is established, e.g. the call expressions of a call.
+    pub sites: Vec<Location>,
+}
+
+pub struct CallHierarchy;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallHierarchyDirection {
+    Incoming,
+    Outgoing,
+}
+
+impl Hierarchy for CallHierarchy {
+    type Item = lsp::CallHierarchyItem;
+    type Direction = CallHierarchyDirection;
+
+    const NAME: &'static str = "Call Hierarchy";
+    const DIRECTIONS: [(CallHierarchyDirection, &'static str); 2] = [
+        (CallHierarchyDirection::Incoming, "Incoming"),
+        (CallHierarchyDirection::Outgoing, "Outgoing"),
+    ];
+
+    fn prepare(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyItem<Self::Item>>>> {
+        project.prepare_call_hierarchy(buffer, position, cx)
+    }
+
+    fn children(
+        project: &mut Project,
+        item: HierarchyItem<Self::Item>,
+        direction: CallHierarchyDirection,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<Self::Item>>>> {
+        let calls = match direction {
+            CallHierarchyDirection::Incoming => project.incoming_calls(item, cx),
+            CallHierarchyDirection::Outgoing => project.outgoing_calls(item, cx),
+        };
+        cx.spawn(|_, _| async move {
+            Ok(calls
+                .await?
+                .into_iter()
+                .map(|call| HierarchyChild {
+                    item: call.item,
+                    sites: call.call_sites,
+                })
+                .collect())
+        })
+    }
+}
+
+pub struct TypeHierarchy;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeHierarchyDirection {
+    Supertypes,
+    Subtypes,
+}
+
+impl Hierarchy for TypeHierarchy {
+    type Item = lsp::TypeHierarchyItem;
+    type Direction = TypeHierarchyDirection;
+
+    const NAME: &'static str = "Type Hierarchy";
+    const DIRECTIONS: [(TypeHierarchyDirection, &'static str); 2] = [
+        (TypeHierarchyDirection::Supertypes, "Supertypes"),
+        (TypeHierarchyDirection::Subtypes, "Subtypes"),
+    ];
+
+    fn prepare(
+        project: &mut Project,
+        buffer: &Entity<Buffer>,
+        position: Anchor,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyItem<Self::Item>>>> {
+        project.prepare_type_hierarchy(buffer, position, cx)
+    }
+
+    fn children(
+        project: &mut Project,
+        item: HierarchyItem<Self::Item>,
+        direction: TypeHierarchyDirection,
+        cx: &mut Context<Project>,
+    ) -> Task<Result<Vec<HierarchyChild<Self::Item>>>> {
+        let types = match direction {
+            TypeHierarchyDirection::Supertypes => project.supertypes(item, cx),
+            TypeHierarchyDirection::Subtypes => project.subtypes(item, cx),
+        };
+        cx.spawn(|_, _| async move {
+            Ok(types
+                .await?
+                .into_iter()
+                .map(|item| HierarchyChild {
+                    item,
+                    sites: Vec::new(),
+                })
+                .collect())
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EntryState {
+    Collapsed,
+    Loading,
+    Expanded,
+}
+
+struct HierarchyEntry<T> {
+    id: usize,
+    item: HierarchyItem<T>,
+    /// Where the relation to the parent entry is established. Empty for root entries.
+    sites: Vec<Location>,
+    depth: usize,
+    state: EntryState,
+    /// Whether the entry has been expanded and found to have no children.
+    is_leaf: bool,
+}
+
+/// A tree of the relations of a symbol, such as its callers or its supertypes,
+/// expanded lazily by querying the language server as entries are opened.
+pub struct HierarchyView<H: Hierarchy> {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    roots: Vec<HierarchyItem<H::Item>>,
+    direction: H::Direction,
+    entries: Vec<HierarchyEntry<H::Item>>,
+    next_entry_id: usize,
+    selected_entry_ix: Option<usize>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+}
+
+impl Editor {
+    pub fn show_call_hierarchy(
+        &mut self,
+        _: &ShowCallHierarchy,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.show_hierarchy::<CallHierarchy>(window, cx)
+    }
+
+    pub fn show_type_hierarchy(
+        &mut self,
+        _: &ShowTypeHierarchy,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.show_hierarchy::<TypeHierarchy>(window, cx)
+    }
+
+    fn show_hierarchy<H: Hierarchy>(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Navigated>> {
+        let Some(workspace) = self.workspace() else {
+            return Task::ready(Ok(Navigated::No));
+        };
+        let head = self.selections.newest::<usize>(cx).head();
+        let Some((buffer, head)) = self.buffer.read(cx).text_anchor_for_position(head, cx) else {
+            return Task::ready(Ok(Navigated::No));
+        };
+        let project = workspace.read(cx).project().clone();
+        let roots = project.update(cx, |project, cx| H::prepare(project, &buffer, head, cx));
+        cx.spawn_in(window, |_, mut cx| async move {
+            let roots = roots.await?;
+            if roots.is_empty() {
+                return Ok(Navigated::No);
+            }
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let workspace_handle = workspace.weak_handle();
+                let view = cx.new(|cx| {
+                    HierarchyView::<H>::new(
+                        workspace_handle,
+                        project,
+                        roots,
+                        H::DIRECTIONS[0].0,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.split_item(SplitDirection::Right, Box::new(view), window, cx);
+                Navigated::Yes
+            })
+        })
+    }
+}
+
+impl<H: Hierarchy> HierarchyView<H> {
+    pub fn new(
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        roots: Vec<HierarchyItem<H::Item>>,
+        direction: H::Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            workspace,
+            project,
+            roots,
+            direction,
+            entries: Vec::new(),
+            next_entry_id: 0,
+            selected_entry_ix: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+        };
+        this.reset_entries(window, cx);
+        this
+    }
+
+    fn reset_entries(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.entries.clear();
+        for item in self.roots.clone() {
+            let id = self.post_inc_entry_id();
+            self.entries.push(HierarchyEntry {
+                id,
+                item,
+                sites: Vec::new(),
+                depth: 0,
+                state: EntryState::Collapsed,
+                is_leaf: false,
+            });
+        }
+        self.selected_entry_ix = (!self.entries.is_empty()).then_some(0);
+        // Expand a single root right away, since there is nothing else to choose from.
+        if self.entries.len() == 1 {
+            self.toggle_entry(0, window, cx);
+        }
+        cx.notify();
+    }
+
+    fn post_inc_entry_id(&mut self) -> usize {
+        let id = self.next_entry_id;
+        self.next_entry_id += 1;
+        id
+    }
+
+    /// Returns the index one past the last descendant of the entry at `ix`.
+    fn subtree_end(&self, ix: usize) -> usize {
+        let depth = self.entries[ix].depth;
+        self.entries[ix + 1..]
+            .iter()
+            .position(|entry| entry.depth <= depth)
+            .map_or(self.entries.len(), |offset| ix + 1 + offset)
+    }
+
+    fn toggle_entry(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get_mut(ix) else {
+            return;
+        };
+        match entry.state {
+            EntryState::Loading => {}
+            EntryState::Expanded => {
+                entry.state = EntryState::Collapsed;
+                let end = self.subtree_end(ix);
+                self.entries.drain(ix + 1..end);
+                if let Some(selected_ix) = self.selected_entry_ix {
+                    if selected_ix > ix && selected_ix < end {
+                        self.selected_entry_ix = Some(ix);
+                    } else if selected_ix >= end {
+                        self.selected_entry_ix = Some(selected_ix - (end - ix - 1));
+                    }
+                }
+                cx.notify();
+            }
+            EntryState::Collapsed => {
+                if entry.is_leaf {
+                    return;
+                }
+                entry.state = EntryState::Loading;
+                let entry_id = entry.id;
+                let item = entry.item.clone();
+                let direction = self.direction;
+                let children = self
+                    .project
+                    .update(cx, |project, cx| H::children(project, item, direction, cx));
+                cx.spawn(|this, mut cx| async move {
+                    let children = children.await.log_err().unwrap_or_default();
+                    this.update(&mut cx, |this, cx| {
+                        this.insert_children(entry_id, children, cx)
+                    })
+                    .ok();
+                })
+                .detach();
+                cx.notify();
+            }
+        }
+    }
+
+    fn insert_children(
+        &mut self,
+        parent_id: usize,
+        children: Vec<HierarchyChild<H::Item>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(parent_ix) = self.entries.iter().position(|entry| entry.id == parent_id) else {
+            return;
+        };
+        let parent = &mut self.entries[parent_ix];
+        if parent.state != EntryState::Loading {
+            return;
+        }
+        parent.state = EntryState::Expanded;
+        parent.is_leaf = children.is_empty();
+        let depth = parent.depth + 1;
+
+        let children = children
+            .into_iter()
+            .map(|child| HierarchyEntry {
+                id: self.post_inc_entry_id(),
+                item: child.item,
+                sites: child.sites,
+                depth,
+                state: EntryState::Collapsed,
+                is_leaf: false,
+            })
+            .collect::<Vec<_>>();
+        let inserted = children.len();
+        self.entries.splice(parent_ix + 1..parent_ix + 1, children);
+        if let Some(selected_ix) = self.selected_entry_ix {
+            if selected_ix > parent_ix {
+                self.selected_entry_ix = Some(selected_ix + inserted);
+            }
+        }
+        cx.notify();
+    }
+
+    fn toggle_direction(
+        &mut self,
+        _: &ToggleDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let [(first, _), (second, _)] = H::DIRECTIONS;
+        let direction = if self.direction == first {
+            second
+        } else {
+            first
+        };
+        self.set_direction(direction, window, cx);
+    }
+
+    fn set_direction(
+        &mut self,
+        direction: H::Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.direction != direction {
+            self.direction = direction;
+            self.reset_entries(window, cx);
+        }
+    }
+
+    fn select_entry(&mut self, ix: usize, cx: &mut Context<Self>) {
+        self.selected_entry_ix = Some(ix);
+        self.scroll_handle
+            .scroll_to_item(ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = self
+            .selected_entry_ix
+            .map_or(0, |ix| (ix + 1).min(self.entries.len() - 1));
+        self.select_entry(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let ix = self.selected_entry_ix.map_or(0, |ix| ix.saturating_sub(1));
+        self.select_entry(ix, cx);
+    }
+
+    fn expand_selected_entry(
+        &mut self,
+        _: &ExpandSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.selected_entry_ix else {
+            return;
+        };
+        let state = self.entries[ix].state;
+        match state {
+            EntryState::Collapsed => self.toggle_entry(ix, window, cx),
+            EntryState::Expanded if self.subtree_end(ix) > ix + 1 => self.select_entry(ix + 1, cx),
+            _ => {}
+        }
+    }
+
+    fn collapse_selected_entry(
+        &mut self,
+        _: &CollapseSelectedEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.selected_entry_ix else {
+            return;
+        };
+        let entry = &self.entries[ix];
+        if entry.state == EntryState::Expanded && !entry.is_leaf {
+            self.toggle_entry(ix, window, cx);
+        } else if let Some(parent_ix) = self.entries[..ix]
+            .iter()
+            .rposition(|parent| parent.depth < entry.depth)
+        {
+            self.select_entry(parent_ix, cx);
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry_ix {
+            self.open_entry(ix, true, window, cx);
+        }
+    }
+
+    /// Navigates to the first site of the entry, or to the symbol itself when there is none.
+    fn open_entry(&mut self, ix: usize, focus: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let location = entry.sites.first().cloned().unwrap_or_else(|| Location {
+            buffer: entry.item.location.buffer.clone(),
+            range: entry.item.selection_range.clone(),
+        });
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let buffer = location.buffer.read(cx);
+        let range = location.range.start.to_point(buffer)..location.range.end.to_point(buffer);
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.adjacent_pane(window, cx);
+            let editor = workspace.open_project_item::<Editor>(
+                pane,
+                location.buffer.clone(),
+                true,
+                focus,
+                window,
+                cx,
+            );
+            editor.update(cx, |editor, cx| {
+                editor.go_to_singleton_buffer_range(range, window, cx);
+            });
+        });
+    }
+
+    fn render_entry(&self, ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let entry = &self.entries[ix];
+        let toggle_state = match entry.state {
+            _ if entry.is_leaf => None,
+            EntryState::Collapsed | EntryState::Loading => Some(false),
+            EntryState::Expanded => Some(true),
+        };
+        let buffer = entry.item.location.buffer.read(cx);
+        let file_name = buffer
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let row = entry.item.selection_range.start.to_point(buffer).row + 1;
+        let site_count = entry.sites.len();
+
+        ListItem::new(("hierarchy-entry", entry.id))
+            .spacing(ListItemSpacing::Sparse)
+            .indent_level(entry.depth)
+            .indent_step_size(px(12.))
+            .toggle_state(self.selected_entry_ix == Some(ix))
+            .toggle(toggle_state)
+            .on_toggle(cx.listener(move |this, _, window, cx| {
+                this.toggle_entry(ix, window, cx);
+            }))
+            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                this.select_entry(ix, cx);
+                if event.up.click_count > 1 {
+                    this.toggle_entry(ix, window, cx);
+                } else {
+                    this.open_entry(ix, false, window, cx);
+                }
+            }))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(entry.item.name().to_string()))
+                    .when_some(entry.item.detail(), |row, detail| {
+                        row.child(
+                            Label::new(detail.to_string())
+                                .color(Color::Muted)
+                                .size(LabelSize::Small)
+                                .single_line(),
+                        )
+                    })
+                    .child(
+                        Label::new(format!("{file_name}:{row}"))
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                    )
+                    .when(entry.state == EntryState::Loading, |row| {
+                        row.child(
+                            Label::new("Loading…")
+                                .color(Color::Muted)
+                                .size(LabelSize::Small),
+                        )
+                    }),
+            )
+            .end_slot::<Label>((site_count > 1).then(|| {
+                Label::new(format!("{site_count} sites"))
+                    .color(Color::Muted)
+                    .size(LabelSize::Small)
+            }))
+            .into_any_element()
+    }
+
+    fn render_header(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let title = match self.roots.as_slice() {
+            [root] => format!("{}: {}", H::NAME, root.name()),
+            _ => H::NAME.to_string(),
+        };
+        h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(title))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .children(H::DIRECTIONS.into_iter().enumerate().map(
+                        |(ix, (direction, label))| {
+                            Button::new(("hierarchy-direction", ix), label)
+                                .label_size(LabelSize::Small)
+                                .toggle_state(self.direction == direction)
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    this.set_direction(direction, window, cx)
+                                }))
+                        },
+                    )),
+            )
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyView");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+}
+
+impl<H: Hierarchy> Render for HierarchyView<H> {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .id("hierarchy-view")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(
+                uniform_list(
+                    cx.entity().clone(),
+                    "hierarchy-entries",
+                    self.entries.len(),
+                    |this, range: Range<usize>, _, cx| {
+                        range.map(|ix| this.render_entry(ix, cx)).collect()
+                    },
+                )
+                .flex_grow()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+    }
+}
+
+impl<H: Hierarchy> EventEmitter<()> for HierarchyView<H> {}
+
+impl<H: Hierarchy> Focusable for HierarchyView<H> {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl<H: Hierarchy> Item for HierarchyView<H> {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(workspace::item::ItemEvent)) {}
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(match self.roots.as_slice() {
+            [root] => format!("{}: {}", H::NAME, root.name()).into(),
+            _ => H::NAME.into(),
+        })
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::ListTree))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                self.project.clone(),
+                self.roots.clone(),
+                self.direction,
+                window,
+                cx,
+            )
+        }))
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     MultiBuffer, MultiBufferSnapshot, NavigationData, SearchWithinRange, ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use file_icons::FileIcons;
 use futures::future::try_join_all;
 use git::status::GitSummary;
@@ -1426,19 +1426,13 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let replacement = replacements_for_matches(&snapshot, [identifier], query)
+            .pop()
+            .flatten();
+        if let Some(replacement) = replacement {
             self.transact(window, cx, |this, _, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), Arc::from(replacement))], cx);
             });
         }
     }
@@ -1449,22 +1443,14 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let mut edits = vec![];
-        for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
-                edits.push((m.clone(), Arc::from(&*replacement)));
-            }
-        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let matches = matches.collect::<Vec<_>>();
+        let replacements = replacements_for_matches(&snapshot, matches.iter().copied(), query);
+        let edits = matches
+            .into_iter()
+            .zip(replacements)
+            .filter_map(|(m, replacement)| Some((m.clone(), Arc::<str>::from(replacement?))))
+            .collect::<Vec<_>>();
 
         if !edits.is_empty() {
             self.transact(window, cx, |this, _, cx| {
@@ -1605,6 +1591,36 @@ impl SearchableItem for Editor {
     }
 }
 
+/// Computes the replacement of each match, searching each buffer once for structural queries.
+fn replacements_for_matches<'a>(
+    snapshot: &MultiBufferSnapshot,
+    matches: impl IntoIterator<Item = &'a Range<Anchor>>,
+    query: &SearchQuery,
+) -> Vec<Option<String>> {
+    let mut replacements = Vec::new();
+    let mut ranges_by_buffer = HashMap::default();
+    for (ix, range) in matches.into_iter().enumerate() {
+        replacements.push(None);
+        if let [(buffer, buffer_range, _)] =
+            snapshot.range_to_buffer_ranges(range.clone()).as_slice()
+        {
+            ranges_by_buffer
+                .entry(buffer.remote_id())
+                .or_insert_with(|| (*buffer, Vec::new()))
+                .1
+                .push((ix, buffer_range.clone()));
+        }
+    }
+    for (buffer, ranges) in ranges_by_buffer.into_values() {
+        let (indices, buffer_ranges): (Vec<_>, Vec<_>) = ranges.into_iter().unzip();
+        let buffer_replacements = query.replacements_for_matches(buffer, &buffer_ranges);
+        for (ix, replacement) in indices.into_iter().zip(buffer_replacements) {
+            replacements[ix] = replacement;
+        }
+    }
+    replacements
+}
+
 pub fn active_match_index(
     ranges: &[Range<Anchor>],
     cursor: &Anchor,
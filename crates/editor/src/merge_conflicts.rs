@@ -0,0 +1,613 @@
+use std::{ops::Range, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{Context, Task, Window};
+use multi_buffer::{Anchor, ToPoint as _};
+use text::{BufferId, Point};
+
+use crate::{
+    actions::{
+        AcceptConflictBoth, AcceptConflictOurs, AcceptConflictTheirs, GoToNextConflict,
+        GoToPreviousConflict,
+    },
+    scroll::Autoscroll,
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+enum ConflictOurs {}
+enum ConflictBase {}
+enum ConflictTheirs {}
+
+/// The merge conflict regions found in the conflicted buffers of an editor.
+#[derive(Default)]
+pub(super) struct ConflictState {
+    regions: Vec<ConflictRegion>,
+    /// Buffers that contained conflict markers the last time they were scanned.
+    buffers_with_markers: HashSet<BufferId>,
+    /// Buffers whose conflict markers were all removed, to be staged once they are saved.
+    resolved_buffers: HashSet<BufferId>,
+    refresh_task: Option<Task<()>>,
+}
+
+/// A single `<<<<<<<` ... `>>>>>>>` region, with the ranges of each side's contents.
+struct ConflictRegion {
+    range: Range<Anchor>,
+    ours: Range<Anchor>,
+    theirs: Range<Anchor>,
+    /// Whether the region runs up to the end of its buffer without a trailing newline.
+    at_buffer_end: bool,
+}
+
+/// The rows of a conflict region within a buffer. Content ranges exclude the marker lines.
+#[derive(Debug, PartialEq, Eq)]
+struct ConflictRows {
+    start: u32,
+    ours: Range<u32>,
+    base: Option<Range<u32>>,
+    theirs: Range<u32>,
+    end: u32,
+}
+
+#[derive(Default)]
+struct ConflictHighlights {
+    ours: Vec<Range<Anchor>>,
+    base: Vec<Range<Anchor>>,
+    theirs: Vec<Range<Anchor>>,
+}
+
+#[derive(Clone, Copy)]
+enum ConflictSide {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// Rescans the editor's conflicted buffers for conflict markers, updating the highlights, and
+/// stages any buffer whose last conflict was resolved once it has been saved.
+pub(super) fn refresh_conflicts(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) {
+    if editor.mode != EditorMode::Full || editor.project.is_none() {
+        return;
+    }
+
+    editor.merge_conflicts.refresh_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+        editor
+            .update(&mut cx, |editor, cx| update_conflicts(editor, cx))
+            .ok();
+    }));
+}
+
+fn update_conflicts(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let mut conflicted_buffers = HashSet::default();
+    for buffer in editor.buffer.read(cx).all_buffers() {
+        let buffer_id = buffer.read(cx).remote_id();
+        if let Some((repository, path)) = project
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer_id, cx)
+        {
+            if repository.read(cx).has_conflict(&path) {
+                conflicted_buffers.insert(buffer_id);
+            }
+        }
+    }
+
+    let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut regions = Vec::new();
+    let mut highlights = ConflictHighlights::default();
+    let mut buffers_with_markers = HashSet::default();
+    let mut parsed_buffers = HashMap::default();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in multibuffer_snapshot.excerpts() {
+        let buffer_id = buffer_snapshot.remote_id();
+        if !conflicted_buffers.contains(&buffer_id) {
+            continue;
+        }
+
+        let conflicts = parsed_buffers.entry(buffer_id).or_insert_with(|| {
+            let conflicts = parse_conflicts(buffer_snapshot.text().lines());
+            if !conflicts.is_empty() {
+                buffers_with_markers.insert(buffer_id);
+            }
+            conflicts
+        });
+
+        let context = excerpt_range.context.start.to_point(buffer_snapshot)
+            ..excerpt_range.context.end.to_point(buffer_snapshot);
+        let max_point = buffer_snapshot.max_point();
+        for conflict in conflicts.iter() {
+            if conflict.start < context.start.row || conflict.end > context.end.row {
+                continue;
+            }
+            let at_buffer_end = conflict.end >= max_point.row;
+            let end = if at_buffer_end {
+                max_point
+            } else {
+                Point::new(conflict.end + 1, 0)
+            };
+            let anchor_range = |range: Range<Point>| {
+                Anchor::range_in_buffer(
+                    excerpt_id,
+                    buffer_id,
+                    buffer_snapshot.anchor_after(range.start)
+                        ..buffer_snapshot.anchor_before(range.end),
+                )
+            };
+            // The marker lines are highlighted along with the side they introduce.
+            highlights.ours.push(anchor_range(
+                Point::new(conflict.start, 0)..Point::new(conflict.ours.end, 0),
+            ));
+            if let Some(base) = &conflict.base {
+                highlights.base.push(anchor_range(
+                    Point::new(base.start - 1, 0)..Point::new(base.end, 0),
+                ));
+            }
+            highlights
+                .theirs
+                .push(anchor_range(Point::new(conflict.theirs.start - 1, 0)..end));
+            regions.push(ConflictRegion {
+                range: anchor_range(Point::new(conflict.start, 0)..end),
+                ours: anchor_range(
+                    Point::new(conflict.ours.start, 0)..Point::new(conflict.ours.end, 0),
+                ),
+                theirs: anchor_range(
+                    Point::new(conflict.theirs.start, 0)..Point::new(conflict.end, 0),
+                ),
+                at_buffer_end,
+            });
+        }
+    }
+
+    let state = &mut editor.merge_conflicts;
+    let newly_resolved = state
+        .buffers_with_markers
+        .iter()
+        .filter(|buffer_id| {
+            conflicted_buffers.contains(buffer_id) && !buffers_with_markers.contains(buffer_id)
+        })
+        .copied()
+        .collect::<Vec<_>>();
+    state.resolved_buffers.extend(newly_resolved);
+    state.resolved_buffers.retain(|buffer_id| {
+        conflicted_buffers.contains(buffer_id) && !buffers_with_markers.contains(buffer_id)
+    });
+    state.regions = regions;
+    state.buffers_with_markers = buffers_with_markers;
+
+    update_conflict_highlights(editor, highlights, cx);
+    stage_saved_resolutions(editor, cx);
+}
+
+fn update_conflict_highlights(
+    editor: &mut Editor,
+    highlights: ConflictHighlights,
+    cx: &mut Context<Editor>,
+) {
+    if highlights.ours.is_empty() {
+        editor.clear_background_highlights::<ConflictOurs>(cx);
+        editor.clear_background_highlights::<ConflictBase>(cx);
+        editor.clear_background_highlights::<ConflictTheirs>(cx);
+        return;
+    }
+    editor.highlight_background::<ConflictOurs>(
+        &highlights.ours,
+        |colors| colors.version_control_added_background,
+        cx,
+    );
+    editor.highlight_background::<ConflictBase>(
+        &highlights.base,
+        |colors| colors.version_control_deleted_background,
+        cx,
+    );
+    editor.highlight_background::<ConflictTheirs>(
+        &highlights.theirs,
+        |colors| colors.version_control_modified_background,
+        cx,
+    );
+}
+
+/// Marks the files whose conflicts were resolved as resolved, by staging them as `git add`
+/// would. Buffers with unsaved changes are staged after their next save.
+pub(super) fn stage_saved_resolutions(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let mut staged = Vec::new();
+    for &buffer_id in &editor.merge_conflicts.resolved_buffers {
+        let Some(buffer) = editor.buffer.read(cx).buffer(buffer_id) else {
+            continue;
+        };
+        let buffer = buffer.read(cx);
+        if buffer.is_dirty() || has_conflict_markers(&buffer.text()) {
+            continue;
+        }
+        let Some((repository, path)) = project
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer_id, cx)
+        else {
+            continue;
+        };
+        repository
+            .update(cx, |repository, cx| {
+                repository.stage_entries(vec![path], cx)
+            })
+            .detach_and_log_err(cx);
+        staged.push(buffer_id);
+    }
+    for buffer_id in staged {
+        editor.merge_conflicts.resolved_buffers.remove(&buffer_id);
+    }
+}
+
+/// Returns whether the text still contains a complete conflict region.
+pub fn has_conflict_markers(text: &str) -> bool {
+    !parse_conflicts(text.lines()).is_empty()
+}
+
+/// Finds the conflict regions in the given lines of a file.
+///
+/// Both the default conflict style and `diff3`, which includes the merge base between
+/// `|||||||` and `=======`, are recognized.
+fn parse_conflicts<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<ConflictRows> {
+    let mut conflicts = Vec::new();
+    let mut start = None;
+    let mut base_start = None;
+    let mut separator = None;
+    for (row, line) in (0u32..).zip(lines) {
+        if line.starts_with(OURS_MARKER) {
+            start = Some(row);
+            base_start = None;
+            separator = None;
+        } else if line.starts_with(BASE_MARKER) {
+            if start.is_some() && separator.is_none() {
+                base_start = Some(row);
+            }
+        } else if line.starts_with(SEPARATOR_MARKER) {
+            if start.is_some() && separator.is_none() {
+                separator = Some(row);
+            }
+        } else if line.starts_with(THEIRS_MARKER) {
+            if let Some((start, separator)) = start.zip(separator) {
+                let ours_end = base_start.unwrap_or(separator);
+                conflicts.push(ConflictRows {
+                    start,
+                    ours: start + 1..ours_end,
+                    base: base_start.map(|base_start| base_start + 1..separator),
+                    theirs: separator + 1..row,
+                    end: row,
+                });
+            }
+            start = None;
+            base_start = None;
+            separator = None;
+        }
+    }
+    conflicts
+}
+
+impl Editor {
+    pub fn go_to_next_conflict(
+        &mut self,
+        _: &GoToNextConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head_row = self.selections.newest::<Point>(cx).head().row;
+        let regions = &self.merge_conflicts.regions;
+        let target = regions
+            .iter()
+            .find(|region| region.range.start.to_point(&snapshot).row > head_row)
+            .or_else(|| regions.first())
+            .map(|region| region.range.start.to_point(&snapshot));
+        if let Some(target) = target {
+            self.go_to_conflict(target, window, cx);
+        }
+    }
+
+    pub fn go_to_previous_conflict(
+        &mut self,
+        _: &GoToPreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head_row = self.selections.newest::<Point>(cx).head().row;
+        let regions = &self.merge_conflicts.regions;
+        let target = regions
+            .iter()
+            .rev()
+            .find(|region| region.range.start.to_point(&snapshot).row < head_row)
+            .or_else(|| regions.last())
+            .map(|region| region.range.start.to_point(&snapshot));
+        if let Some(target) = target {
+            self.go_to_conflict(target, window, cx);
+        }
+    }
+
+    fn go_to_conflict(&mut self, destination: Point, window: &mut Window, cx: &mut Context<Self>) {
+        self.unfold_ranges(&[destination..destination], false, false, cx);
+        self.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+            s.select_ranges([destination..destination]);
+        });
+    }
+
+    pub fn accept_conflict_ours(
+        &mut self,
+        _: &AcceptConflictOurs,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.resolve_conflict_at_cursor(ConflictSide::Ours, window, cx);
+    }
+
+    pub fn accept_conflict_theirs(
+        &mut self,
+        _: &AcceptConflictTheirs,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.resolve_conflict_at_cursor(ConflictSide::Theirs, window, cx);
+    }
+
+    pub fn accept_conflict_both(
+        &mut self,
+        _: &AcceptConflictBoth,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.resolve_conflict_at_cursor(ConflictSide::Both, window, cx);
+    }
+
+    fn resolve_conflict_at_cursor(
+        &mut self,
+        side: ConflictSide,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let Some(region) = self.merge_conflicts.regions.iter().find(|region| {
+            region.range.start.cmp(&head, &snapshot).is_le()
+                && region.range.end.cmp(&head, &snapshot).is_ge()
+        }) else {
+            return;
+        };
+
+        let mut text = match side {
+            ConflictSide::Ours => snapshot
+                .text_for_range(region.ours.clone())
+                .collect::<String>(),
+            ConflictSide::Theirs => snapshot
+                .text_for_range(region.theirs.clone())
+                .collect::<String>(),
+            ConflictSide::Both => snapshot
+                .text_for_range(region.ours.clone())
+                .chain(snapshot.text_for_range(region.theirs.clone()))
+                .collect::<String>(),
+        };
+        if region.at_buffer_end && text.ends_with('\n') {
+            text.pop();
+        }
+        let range = region.range.clone();
+
+        self.transact(window, cx, |editor, _, cx| {
+            editor.buffer.update(cx, |buffer, cx| {
+                buffer.edit([(range, text)], None, cx);
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use git::status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode};
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use std::path::Path;
+    use util::path;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = "\
+one
+<<<<<<< HEAD
+two
+=======
+three
+>>>>>>> branch
+four
+<<<<<<< HEAD
+five
+||||||| base
+six
+=======
+>>>>>>> branch
+";
+        assert_eq!(
+            parse_conflicts(text.lines()),
+            vec![
+                ConflictRows {
+                    start: 1,
+                    ours: 2..3,
+                    base: None,
+                    theirs: 4..5,
+                    end: 5,
+                },
+                ConflictRows {
+                    start: 7,
+                    ours: 8..9,
+                    base: Some(10..11),
+                    theirs: 12..12,
+                    end: 12,
+                },
+            ]
+        );
+
+        // Unterminated regions and stray markers are ignored.
+        assert_eq!(
+            parse_conflicts("=======\n>>>>>>> a\n<<<<<<< b\none\n=======\n".lines()),
+            vec![]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_resolve_and_stage_conflicts(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = "\
+one
+<<<<<<< HEAD
+two
+=======
+TWO
+>>>>>>> branch
+three
+<<<<<<< HEAD
+four
+=======
+FOUR
+>>>>>>> branch
+five
+<<<<<<< HEAD
+six
+=======
+SIX
+>>>>>>> branch
+";
+        let unmerged = FileStatus::Unmerged(UnmergedStatus {
+            first_head: UnmergedStatusCode::Updated,
+            second_head: UnmergedStatusCode::Updated,
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".git": {},
+                "file.txt": text,
+            }),
+        )
+        .await;
+        fs.set_status_for_repo_via_git_operation(
+            Path::new(path!("/root/.git")),
+            &[(Path::new("file.txt"), unmerged)],
+        );
+
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/file.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let editor = cx.add_window(|window, cx| {
+            Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx)
+        });
+        let refresh = |cx: &mut TestAppContext| {
+            cx.executor().advance_clock(UPDATE_DEBOUNCE);
+            cx.run_until_parked();
+        };
+        let cursor_row = |cx: &mut TestAppContext| {
+            editor
+                .update(cx, |editor, _, cx| {
+                    editor.selections.newest::<Point>(cx).head().row
+                })
+                .unwrap()
+        };
+        let status = |fs: &FakeFs| {
+            let mut status = None;
+            fs.with_git_state(Path::new(path!("/root/.git")), false, |state| {
+                status = state.statuses.get(Path::new("file.txt")).copied();
+            });
+            status
+        };
+        refresh(cx);
+
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_next_conflict(&GoToNextConflict, window, cx)
+            })
+            .unwrap();
+        assert_eq!(cursor_row(cx), 1);
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_next_conflict(&GoToNextConflict, window, cx)
+            })
+            .unwrap();
+        assert_eq!(cursor_row(cx), 7);
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_previous_conflict(&GoToPreviousConflict, window, cx)
+            })
+            .unwrap();
+        assert_eq!(cursor_row(cx), 1);
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_previous_conflict(&GoToPreviousConflict, window, cx)
+            })
+            .unwrap();
+        assert_eq!(cursor_row(cx), 13);
+
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.accept_conflict_theirs(&AcceptConflictTheirs, window, cx)
+            })
+            .unwrap();
+        refresh(cx);
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_previous_conflict(&GoToPreviousConflict, window, cx);
+                editor.accept_conflict_ours(&AcceptConflictOurs, window, cx);
+            })
+            .unwrap();
+        refresh(cx);
+        assert_eq!(status(&fs), Some(unmerged));
+
+        editor
+            .update(cx, |editor, window, cx| {
+                editor.go_to_next_conflict(&GoToNextConflict, window, cx);
+                editor.accept_conflict_both(&AcceptConflictBoth, window, cx);
+            })
+            .unwrap();
+        refresh(cx);
+        let resolved_text = "one\ntwo\nTWO\nthree\nfour\nfive\nSIX\n";
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            resolved_text
+        );
+        assert_eq!(
+            status(&fs),
+            Some(unmerged),
+            "unsaved resolutions should not be staged"
+        );
+
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(
+            status(&fs),
+            Some(FileStatus::Tracked(TrackedStatus {
+                index_status: StatusCode::Modified,
+                worktree_status: StatusCode::Unmodified,
+            }))
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use gpui::{Context, Window};
+use language::{
+    language_settings::{language_settings, RangeSource},
+    Selection, SelectionGoal, ToOffset as _,
+};
+use util::ResultExt;
+
+use crate::{scroll::Autoscroll, Editor};
+
+/// Expands every selection to the smallest enclosing selection range reported
+/// by the language server. Returns `None` if the buffer's selection ranges
+/// come from its syntax tree instead, which is also used when the server
+/// doesn't report any ranges.
+pub(super) fn select_larger_lsp_range(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    let project = editor.project.clone()?;
+    let buffer = editor.buffer.read(cx).as_singleton()?;
+    {
+        let buffer = buffer.read(cx);
+        let settings = language_settings(
+            buffer.language().map(|language| language.name()),
+            buffer.file(),
+            cx,
+        );
+        if settings.selection_ranges != RangeSource::LanguageServer {
+            return None;
+        }
+    }
+
+    let old_selections = editor.selections.all::<usize>(cx).into_boxed_slice();
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let positions = old_selections
+        .iter()
+        .map(|selection| buffer_snapshot.anchor_before(selection.start))
+        .collect();
+    let request = project.update(cx, |project, cx| {
+        project.selection_ranges(&buffer, positions, cx)
+    });
+
+    editor.selection_ranges_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        let chains = request.await.log_err().unwrap_or_default();
+        editor
+            .update_in(&mut cx, |editor, window, cx| {
+                let current_snapshot = buffer.read(cx).snapshot();
+                let selections_changed = !editor
+                    .selections
+                    .all::<usize>(cx)
+                    .iter()
+                    .map(|selection| selection.range())
+                    .eq(old_selections.iter().map(|selection| selection.range()));
+                if selections_changed || current_snapshot.version() != buffer_snapshot.version() {
+                    return;
+                }
+                if chains.len() != old_selections.len() {
+                    editor.select_larger_syntax_ancestor(window, cx);
+                    return;
+                }
+
+                let mut selected_larger_range = false;
+                let new_selections = old_selections
+                    .iter()
+                    .zip(chains)
+                    .map(|(selection, chain)| {
+                        let old_range = selection.range();
+                        let new_range = chain
+                            .iter()
+                            .map(|range| {
+                                range.start.to_offset(&current_snapshot)
+                                    ..range.end.to_offset(&current_snapshot)
+                            })
+                            .find(|range| {
+                                range.start <= old_range.start
+                                    && range.end >= old_range.end
+                                    && range.len() > old_range.len()
+                            })
+                            .unwrap_or(old_range.clone());
+                        selected_larger_range |= new_range != old_range;
+                        Selection {
+                            id: selection.id,
+                            start: new_range.start,
+                            end: new_range.end,
+                            goal: SelectionGoal::None,
+                            reversed: selection.reversed,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                if selected_larger_range {
+                    let mut stack = std::mem::take(&mut editor.select_larger_syntax_node_stack);
+                    stack.push(old_selections);
+                    editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                        s.select(new_selections);
+                    });
+                    editor.select_larger_syntax_node_stack = stack;
+                }
+            })
+            .ok();
+    }));
+    Some(())
+}
@@ -0,0 +1,239 @@
+use std::{cmp, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{px, Context, HighlightStyle, StrikethroughStyle, UnderlineStyle, Window};
+use language::{language_settings::language_settings, HighlightId};
+use multi_buffer::Anchor;
+use project::{BufferSemanticTokens, SemanticToken};
+use text::BufferId;
+use theme::{ActiveTheme, SyntaxTheme};
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Requests semantic tokens for every buffer in the editor that has them
+/// enabled, and layers them over the syntax highlights once they arrive.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    window: &mut Window,
+    cx: &mut Context<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.as_ref()?.downgrade();
+
+    editor.semantic_tokens_task = Some(cx.spawn_in(window, |editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let requests = editor
+            .update(&mut cx, |editor, cx| {
+                let buffers = editor.buffer.read(cx).all_buffers();
+                project
+                    .update(cx, |project, cx| {
+                        buffers
+                            .into_iter()
+                            .filter(|buffer| {
+                                let buffer = buffer.read(cx);
+                                language_settings(
+                                    buffer.language().map(|language| language.name()),
+                                    buffer.file(),
+                                    cx,
+                                )
+                                .semantic_tokens
+                            })
+                            .map(|buffer| {
+                                let buffer_id = buffer.read(cx).remote_id();
+                                let request = project.semantic_tokens(&buffer, cx);
+                                async move { (buffer_id, request.await) }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()
+            })
+            .ok()??;
+        let responses = join_all(requests).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let mut semantic_tokens = HashMap::default();
+                for (buffer_id, response) in responses {
+                    let tokens = match response.log_err() {
+                        Some(tokens) => tokens,
+                        None => editor
+                            .semantic_tokens
+                            .remove(&buffer_id)
+                            .unwrap_or_default(),
+                    };
+                    semantic_tokens.insert(buffer_id, tokens);
+                }
+                editor.semantic_tokens = semantic_tokens;
+                update_semantic_token_highlights(editor, cx);
+            })
+            .ok()
+    }));
+    None
+}
+
+/// Resolves the stored semantic tokens against the current theme and
+/// excerpts, and hands them to the display map.
+pub(super) fn update_semantic_token_highlights(editor: &mut Editor, cx: &mut Context<Editor>) {
+    let syntax_theme = cx.theme().syntax().clone();
+    let multibuffer_snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut styles = HashMap::<(BufferId, usize, u32, u32), Option<HighlightStyle>>::default();
+    let mut highlights = Vec::new();
+    for (excerpt_id, buffer_snapshot, excerpt_range) in multibuffer_snapshot.excerpts() {
+        let buffer_id = buffer_snapshot.remote_id();
+        // Tokens from different servers may overlap, so only the first
+        // server that reported any tokens is used.
+        let Some((server_ix, buffer_tokens)) =
+            editor.semantic_tokens.get(&buffer_id).and_then(|tokens| {
+                tokens
+                    .iter()
+                    .enumerate()
+                    .find(|(_, tokens)| !tokens.tokens.is_empty())
+            })
+        else {
+            continue;
+        };
+
+        let context = excerpt_range.context;
+        let start_ix = buffer_tokens
+            .tokens
+            .partition_point(|token| token.range.end.cmp(&context.start, buffer_snapshot).is_le());
+        for token in &buffer_tokens.tokens[start_ix..] {
+            if token.range.start.cmp(&context.end, buffer_snapshot).is_ge() {
+                break;
+            }
+            let style = *styles
+                .entry((
+                    buffer_id,
+                    server_ix,
+                    token.token_type,
+                    token.token_modifiers,
+                ))
+                .or_insert_with(|| token_style(buffer_tokens, token, &syntax_theme));
+            let Some(style) = style else {
+                continue;
+            };
+
+            let start = cmp::max_by(token.range.start, context.start, |a, b| {
+                a.cmp(b, buffer_snapshot)
+            });
+            let end = cmp::min_by(token.range.end, context.end, |a, b| {
+                a.cmp(b, buffer_snapshot)
+            });
+            highlights.push((
+                Anchor::range_in_buffer(excerpt_id, buffer_id, start..end),
+                style,
+            ));
+        }
+    }
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_semantic_token_highlights(highlights)
+    });
+    cx.notify();
+}
+
+fn token_style(
+    tokens: &BufferSemanticTokens,
+    token: &SemanticToken,
+    theme: &SyntaxTheme,
+) -> Option<HighlightStyle> {
+    let token_type = tokens.token_type(token)?;
+    let modifiers = tokens.token_modifiers(token).collect::<Vec<_>>();
+    let capture_name = capture_name_for_token(token_type, &modifiers)?;
+    let mut style = HighlightId::for_capture_name(capture_name, theme)?.style(theme)?;
+    if modifiers.contains(&"deprecated") {
+        style.strikethrough = Some(StrikethroughStyle {
+            thickness: px(1.),
+            color: None,
+        });
+    }
+    // Names that couldn't be resolved get a wavy underline, and mutable
+    // bindings a straight one, as in other editors.
+    if token_type == "unresolvedReference" {
+        style.underline = Some(UnderlineStyle {
+            thickness: px(1.),
+            color: None,
+            wavy: true,
+        });
+    } else if modifiers.contains(&"mutable") {
+        style.underline = Some(UnderlineStyle {
+            thickness: px(1.),
+            color: None,
+            wavy: false,
+        });
+    }
+    Some(style)
+}
+
+/// Maps a semantic token to the name of a syntax theme style, using the
+/// same names as the tree-sitter highlight queries. Tokens that don't map to
+/// a style leave the syntax highlighting untouched.
+fn capture_name_for_token(token_type: &str, modifiers: &[&str]) -> Option<&'static str> {
+    let is_constant = modifiers
+        .iter()
+        .any(|modifier| matches!(*modifier, "readonly" | "static" | "constant"));
+    Some(match token_type {
+        "namespace" => "namespace",
+        "type" | "class" | "struct" | "interface" | "union" | "typeAlias" => "type",
+        "typeParameter" => "type",
+        "builtinType" => "type.builtin",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" if is_constant => "constant",
+        "variable" | "unresolvedReference" => "variable",
+        "constParameter" => "constant",
+        "property" | "event" => "property",
+        "function" => "function",
+        "method" => "function.method",
+        "macro" => "function.special",
+        "keyword" | "modifier" | "selfKeyword" | "selfTypeKeyword" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "escapeSequence" => "string.escape",
+        "formatSpecifier" => "string.special",
+        "regexp" => "string.regex",
+        "number" => "number",
+        "boolean" => "boolean",
+        "operator" => "operator",
+        "decorator" | "attribute" | "attributeBracket" => "attribute",
+        "lifetime" => "lifetime",
+        "label" => "label",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_name_for_token() {
+        assert_eq!(capture_name_for_token("struct", &[]), Some("type"));
+        assert_eq!(
+            capture_name_for_token("method", &["declaration"]),
+            Some("function.method")
+        );
+        assert_eq!(capture_name_for_token("variable", &[]), Some("variable"));
+        assert_eq!(
+            capture_name_for_token("variable", &["readonly"]),
+            Some("constant")
+        );
+        assert_eq!(
+            capture_name_for_token("variable", &["mutable"]),
+            Some("variable")
+        );
+        assert_eq!(
+            capture_name_for_token("unresolvedReference", &[]),
+            Some("variable")
+        );
+        assert_eq!(capture_name_for_token("unknownType", &[]), None);
+    }
+}
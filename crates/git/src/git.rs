@@ -56,12 +56,17 @@ actions!(
         // per-file
         StageFile,
         UnstageFile,
+        StashFile,
         // repo-wide
         StageAll,
         UnstageAll,
         RestoreTrackedFiles,
         TrashUntrackedFiles,
         Uncommit,
+        StashAll,
+        StashPop,
+        ContinueOperation,
+        AbortOperation,
         Pull,
         Fetch,
         Commit,
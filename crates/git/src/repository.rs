@@ -1,4 +1,4 @@
-use crate::status::FileStatus;
+use crate::status::{FileStatus, StatusCode, TrackedStatus};
 use crate::GitHostingProviderRegistry;
 use crate::{blame::Blame, status::GitStatus};
 use anyhow::{anyhow, Context, Result};
@@ -10,6 +10,7 @@ use rope::Rope;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use std::borrow::Borrow;
+use std::hash::{Hash as _, Hasher as _};
 use std::io::Write as _;
 #[cfg(not(windows))]
 use std::os::unix::fs::PermissionsExt;
@@ -17,6 +18,7 @@ use std::process::Stdio;
 use std::sync::LazyLock;
 use std::{
     cmp::Ordering,
+    ops::Range,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
@@ -122,6 +124,125 @@ pub struct Remote {
     pub name: SharedString,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LogEntry {
+    pub sha: SharedString,
+    pub subject: SharedString,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// This is a unix timestamp
+    pub commit_timestamp: i64,
+}
+
+/// Selects which commits are returned by [`GitRepository::log`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// The number of commits to skip, used for pagination.
+    pub skip: usize,
+    /// The maximum number of commits to return.
+    pub limit: usize,
+    /// Only return commits that touched this path.
+    pub path: Option<RepoPath>,
+    /// Only return commits that touched these rows (zero-based, end-exclusive) of `path`.
+    pub row_range: Option<Range<u32>>,
+    /// Keep listing the history of `path` across renames.
+    pub follow_renames: bool,
+}
+
+/// A file changed by a commit, with its contents before and after the commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFile {
+    pub path: RepoPath,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+    /// Whether either version of the file is binary, in which case its texts are left empty.
+    pub is_binary: bool,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of this entry in the stash, as in `stash@{index}`
+    pub index: usize,
+    pub sha: SharedString,
+    pub message: SharedString,
+    /// This is a unix timestamp
+    pub timestamp: i64,
+}
+
+impl StashEntry {
+    pub fn reference(&self) -> String {
+        format!("stash@{{{}}}", self.index)
+    }
+}
+
+/// What to do with a commit during an interactive rebase.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RebaseAction::Pick => "pick",
+            RebaseAction::Reword => "reword",
+            RebaseAction::Squash => "squash",
+            RebaseAction::Fixup => "fixup",
+            RebaseAction::Drop => "drop",
+        }
+    }
+
+    /// Parses a todo list command, accepting git's one-letter abbreviations.
+    pub fn from_command(command: &str) -> Option<Self> {
+        match command {
+            "pick" | "p" => Some(RebaseAction::Pick),
+            "reword" | "r" => Some(RebaseAction::Reword),
+            "squash" | "s" => Some(RebaseAction::Squash),
+            "fixup" | "f" => Some(RebaseAction::Fixup),
+            "drop" | "d" => Some(RebaseAction::Drop),
+            _ => None,
+        }
+    }
+}
+
+/// A line of the todo list that drives an interactive rebase.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RebaseTodoEntry {
+    pub action: RebaseAction,
+    pub sha: SharedString,
+    /// The subject of the commit. When the commit is reworded, this becomes its new message.
+    pub subject: SharedString,
+}
+
+/// A multi-step operation that stopped partway through, usually because of conflicts, and is
+/// waiting to be continued or aborted.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum OperationInProgress {
+    /// A rebase, stopped at the given step of the todo list (one-based).
+    Rebase {
+        step: usize,
+        total: usize,
+    },
+    CherryPick,
+}
+
+impl OperationInProgress {
+    /// A short description of the operation, such as "Rebasing 2/5".
+    pub fn description(&self) -> String {
+        match self {
+            OperationInProgress::Rebase { step, total } if *total > 0 => {
+                format!("Rebasing {step}/{total}")
+            }
+            OperationInProgress::Rebase { .. } => "Rebasing".to_string(),
+            OperationInProgress::CherryPick => "Cherry-picking".to_string(),
+        }
+    }
+}
+
 pub enum ResetMode {
     // reset the branch pointer, leave index and worktree unchanged
     // (this will make it look like things that were committed are now
@@ -169,6 +290,12 @@ pub trait GitRepository: Send + Sync {
 
     fn show(&self, commit: &str) -> Result<CommitDetails>;
 
+    /// Returns the commits reachable from HEAD, most recent first.
+    fn log(&self, options: LogOptions) -> Result<Vec<LogEntry>>;
+
+    /// Returns the files changed by the given commit, compared to its first parent.
+    fn commit_files(&self, commit: &str) -> Result<Vec<CommitFile>>;
+
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
 
     /// Returns the absolute path to the repository. For worktrees, this will be the path to the
@@ -203,6 +330,35 @@ pub trait GitRepository: Send + Sync {
     fn pull(&self, branch_name: &str, upstream_name: &str) -> Result<RemoteCommandOutput>;
     fn get_remotes(&self, branch_name: Option<&str>) -> Result<Vec<Remote>>;
     fn fetch(&self) -> Result<RemoteCommandOutput>;
+
+    /// Saves the changes to the given paths (or the whole working tree, if no paths are given)
+    /// into a new stash entry, and reverts them in the working tree.
+    fn stash_push(
+        &self,
+        paths: &[RepoPath],
+        message: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<()>;
+    /// Returns the stash entries, most recent first.
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+    /// The stash operations act on `stash@{index}`. When a `sha` is given, they fail if that
+    /// entry is no longer the given commit, e.g. because another stash was pushed since.
+    fn stash_apply(&self, index: usize, sha: Option<&str>) -> Result<()>;
+    fn stash_pop(&self, index: usize, sha: Option<&str>) -> Result<()>;
+    fn stash_drop(&self, index: usize, sha: Option<&str>) -> Result<()>;
+    /// Returns the patch stored in the given stash entry, including untracked files.
+    fn stash_diff(&self, index: usize, sha: Option<&str>) -> Result<String>;
+
+    /// Returns the rebase or cherry-pick that is waiting to be continued, if any.
+    fn operation_in_progress(&self) -> Option<OperationInProgress>;
+    /// Returns the todo list for rebasing the commits after `base`, oldest first, all picked.
+    fn rebase_todo(&self, base: &str) -> Result<Vec<RebaseTodoEntry>>;
+    /// Starts an interactive rebase onto `base` that follows the given todo list.
+    fn rebase_interactive(&self, base: &str, todo: &[RebaseTodoEntry]) -> Result<()>;
+    fn cherry_pick(&self, commits: &[String]) -> Result<()>;
+    /// Continues the operation in progress, after its conflicts have been resolved.
+    fn continue_operation(&self) -> Result<()>;
+    fn abort_operation(&self) -> Result<()>;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
@@ -286,6 +442,92 @@ impl GitRepository for RealGitRepository {
         Ok(details)
     }
 
+    fn log(&self, options: LogOptions) -> Result<Vec<LogEntry>> {
+        let working_directory = self.working_directory()?;
+
+        let mut cmd = new_std_command(&self.git_binary_path);
+        cmd.current_dir(&working_directory)
+            .args(["log", "--format=%x1e%H%x00%an%x00%ae%x00%ct%x00%s"])
+            .arg(format!("--skip={}", options.skip))
+            .arg(format!("--max-count={}", options.limit));
+        match (&options.path, &options.row_range) {
+            (Some(path), Some(row_range)) => {
+                // `-L` follows the lines across renames on its own.
+                cmd.arg(format!(
+                    "-L{},{}:{}",
+                    row_range.start + 1,
+                    row_range.end.max(row_range.start + 1),
+                    path.to_string_lossy()
+                ));
+            }
+            (Some(path), None) => {
+                if options.follow_renames {
+                    cmd.arg("--follow");
+                }
+                cmd.arg("--").arg(path.as_ref());
+            }
+            (None, _) => {}
+        }
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to load log:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        parse_log_output(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn commit_files(&self, commit: &str) -> Result<Vec<CommitFile>> {
+        let repo = self.repository.lock();
+        let Ok(commit) = repo.revparse_single(commit)?.into_commit() else {
+            anyhow::bail!("{} is not a commit", commit);
+        };
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        let find_blob = |id: git2::Oid| -> Result<Option<git2::Blob>> {
+            if id.is_zero() {
+                return Ok(None);
+            }
+            Ok(Some(repo.find_blob(id)?))
+        };
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            let old_blob = find_blob(delta.old_file().id())?;
+            let new_blob = find_blob(delta.new_file().id())?;
+            let is_binary = old_blob
+                .iter()
+                .chain(&new_blob)
+                .any(|blob| blob.is_binary());
+            let blob_text = |blob: Option<git2::Blob>| {
+                blob.map(|blob| {
+                    if is_binary {
+                        String::new()
+                    } else {
+                        String::from_utf8_lossy(blob.content()).into_owned()
+                    }
+                })
+            };
+            files.push(CommitFile {
+                path: RepoPath::from(path),
+                old_text: blob_text(old_blob),
+                new_text: blob_text(new_blob),
+                is_binary,
+            });
+        }
+        Ok(files)
+    }
+
     fn reset(&self, commit: &str, mode: ResetMode) -> Result<()> {
         let working_directory = self.working_directory()?;
 
@@ -770,6 +1012,338 @@ impl GitRepository for RealGitRepository {
             ));
         }
     }
+
+    fn stash_push(
+        &self,
+        paths: &[RepoPath],
+        message: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        let mut cmd = new_std_command(&self.git_binary_path);
+        cmd.current_dir(&working_directory)
+            .args(["stash", "push", "--quiet"]);
+        if include_untracked {
+            cmd.arg("--include-untracked");
+        }
+        if let Some(message) = message {
+            cmd.arg("--message").arg(message);
+        }
+        if !paths.is_empty() {
+            cmd.arg("--").args(paths.iter().map(|p| p.as_ref()));
+        }
+
+        let output = cmd.output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to stash changes:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", "list", "--format=%gd%x00%H%x00%ct%x00%gs"])
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to list stashes:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn stash_apply(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        self.run_stash_command("apply", index, sha)
+    }
+
+    fn stash_pop(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        self.run_stash_command("pop", index, sha)
+    }
+
+    fn stash_drop(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        self.run_stash_command("drop", index, sha)
+    }
+
+    fn stash_diff(&self, index: usize, sha: Option<&str>) -> Result<String> {
+        let working_directory = self.working_directory()?;
+        if let Some(sha) = sha {
+            self.check_stash_entry(&working_directory, index, sha)?;
+        }
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", "show", "--patch", "--include-untracked"])
+            .arg(format!("stash@{{{index}}}"))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to show stash:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn operation_in_progress(&self) -> Option<OperationInProgress> {
+        let repo = self.repository.lock();
+        match repo.state() {
+            git2::RepositoryState::Rebase
+            | git2::RepositoryState::RebaseInteractive
+            | git2::RepositoryState::RebaseMerge => {
+                let (step, total) = read_rebase_progress(repo.path());
+                Some(OperationInProgress::Rebase { step, total })
+            }
+            git2::RepositoryState::CherryPick | git2::RepositoryState::CherryPickSequence => {
+                Some(OperationInProgress::CherryPick)
+            }
+            _ => None,
+        }
+    }
+
+    fn rebase_todo(&self, base: &str) -> Result<Vec<RebaseTodoEntry>> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["log", "--reverse", "--format=%H%x00%s"])
+            .arg(format!("{base}..HEAD"))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to list commits to rebase:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let (sha, subject) = line.split_once('\x00').context("no commit subject")?;
+                Ok(RebaseTodoEntry {
+                    action: RebaseAction::Pick,
+                    sha: sha.to_string().into(),
+                    subject: subject.to_string().into(),
+                })
+            })
+            .collect()
+    }
+
+    fn rebase_interactive(&self, base: &str, todo: &[RebaseTodoEntry]) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        // Git copies the todo list into the repository before running it, so the file only
+        // needs to outlive the command that starts the rebase.
+        let todo_dir = tempfile::Builder::new()
+            .prefix("zed-git-rebase")
+            .tempdir()?;
+        let todo_path = todo_dir.path().join("git-rebase-todo");
+        std::fs::write(&todo_path, git_rebase_todo(todo))?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .env(
+                "GIT_SEQUENCE_EDITOR",
+                format!("cp {}", shell_quote(&todo_path.to_string_lossy())),
+            )
+            .env("GIT_EDITOR", "true")
+            .args(["rebase", "--interactive", base])
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to rebase:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    fn cherry_pick(&self, commits: &[String]) -> Result<()> {
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .arg("cherry-pick")
+            .args(commits)
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to cherry-pick:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    fn continue_operation(&self) -> Result<()> {
+        self.run_sequencer_command("--continue")
+    }
+
+    fn abort_operation(&self) -> Result<()> {
+        self.run_sequencer_command("--abort")
+    }
+}
+
+impl RealGitRepository {
+    fn run_stash_command(&self, subcommand: &str, index: usize, sha: Option<&str>) -> Result<()> {
+        let working_directory = self.working_directory()?;
+        if let Some(sha) = sha {
+            self.check_stash_entry(&working_directory, index, sha)?;
+        }
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            .args(["stash", subcommand, "--quiet"])
+            .arg(format!("stash@{{{index}}}"))
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to {subcommand} stash:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fails if `stash@{index}` no longer refers to the commit that was selected.
+    fn check_stash_entry(&self, working_directory: &Path, index: usize, sha: &str) -> Result<()> {
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(working_directory)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("stash@{{{index}}}"))
+            .output()?;
+        if !output.status.success() || String::from_utf8_lossy(&output.stdout).trim() != sha {
+            return Err(anyhow!(
+                "The stash list changed, stash@{{{index}}} is no longer the selected entry"
+            ));
+        }
+        Ok(())
+    }
+
+    fn run_sequencer_command(&self, option: &str) -> Result<()> {
+        let command = match self.operation_in_progress() {
+            Some(OperationInProgress::Rebase { .. }) => "rebase",
+            Some(OperationInProgress::CherryPick) => "cherry-pick",
+            None => anyhow::bail!("No rebase or cherry-pick in progress"),
+        };
+        let working_directory = self.working_directory()?;
+
+        let output = new_std_command(&self.git_binary_path)
+            .current_dir(&working_directory)
+            // Keep the prepared commit messages instead of waiting on an editor.
+            .env("GIT_EDITOR", "true")
+            .args([command, option])
+            .output()?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Failed to run {command} {option}:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Reads how far along the current rebase is from the state git keeps in the `.git` directory.
+fn read_rebase_progress(git_dir: &Path) -> (usize, usize) {
+    let read_number = |path: PathBuf| {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| contents.trim().parse::<usize>().ok())
+    };
+    for (dir, step_file, total_file) in [
+        ("rebase-merge", "msgnum", "end"),
+        ("rebase-apply", "next", "last"),
+    ] {
+        let dir = git_dir.join(dir);
+        if let Some((step, total)) =
+            read_number(dir.join(step_file)).zip(read_number(dir.join(total_file)))
+        {
+            return (step, total);
+        }
+    }
+    (0, 0)
+}
+
+/// Formats a todo list for the user to edit, followed by comments explaining the commands.
+pub fn format_rebase_todo(entries: &[RebaseTodoEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format!(
+            "{} {} {}\n",
+            entry.action.as_str(),
+            entry.sha,
+            entry.subject
+        ));
+    }
+    text.push_str(
+        "\n\
+         # Reorder the lines to reorder the commits, and change the first word of a line to:\n\
+         # pick = use the commit\n\
+         # reword = use the commit, with the rest of the line as its new message\n\
+         # squash = meld the commit into the previous one, keeping both messages\n\
+         # fixup = meld the commit into the previous one, discarding its message\n\
+         # drop = remove the commit\n\
+         #\n\
+         # Save to start the rebase, or close without saving to cancel it.\n",
+    );
+    text
+}
+
+/// Parses a todo list produced by [`format_rebase_todo`] after it has been edited.
+pub fn parse_rebase_todo(text: &str) -> Result<Vec<RebaseTodoEntry>> {
+    let mut entries = Vec::new();
+    for (row, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.splitn(3, char::is_whitespace);
+        let command = fields.next().unwrap_or_default();
+        let action = RebaseAction::from_command(command)
+            .with_context(|| format!("Unknown rebase command {command:?} on line {}", row + 1))?;
+        let sha = fields
+            .next()
+            .with_context(|| format!("No commit on line {}", row + 1))?;
+        let subject = fields.next().unwrap_or_default().trim();
+        entries.push(RebaseTodoEntry {
+            action,
+            sha: sha.to_string().into(),
+            subject: subject.to_string().into(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Converts a todo list into the one git runs. Rewording is done by amending the picked commit,
+/// since git would otherwise wait on an editor for the new message. Only the subject is replaced,
+/// the body and trailers of the original message are kept.
+fn git_rebase_todo(entries: &[RebaseTodoEntry]) -> String {
+    let mut todo = String::new();
+    for entry in entries {
+        match entry.action {
+            RebaseAction::Reword => {
+                todo.push_str(&format!("pick {}\n", entry.sha));
+                todo.push_str(&format!(
+                    "exec git commit --amend --only --quiet --message {} --message \"$(git log -1 --format=%b)\"\n",
+                    shell_quote(&entry.subject)
+                ));
+            }
+            action => todo.push_str(&format!("{} {}\n", action.as_str(), entry.sha)),
+        }
+    }
+    todo
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
 }
 
 #[cfg(not(windows))]
@@ -799,6 +1373,18 @@ pub struct FakeGitRepositoryState {
     pub statuses: HashMap<RepoPath, FileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    /// Stashed changes, most recent first.
+    pub stashes: Vec<FakeStash>,
+    /// The commits returned by `log`, most recent first.
+    pub commits: Vec<LogEntry>,
+    pub operation_in_progress: Option<OperationInProgress>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeStash {
+    pub sha: String,
+    pub message: String,
+    pub statuses: HashMap<RepoPath, FileStatus>,
 }
 
 impl FakeGitRepository {
@@ -818,6 +1404,9 @@ impl FakeGitRepositoryState {
             statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            stashes: Default::default(),
+            commits: Default::default(),
+            operation_in_progress: Default::default(),
         }
     }
 }
@@ -865,6 +1454,21 @@ impl GitRepository for FakeGitRepository {
         unimplemented!()
     }
 
+    fn log(&self, options: LogOptions) -> Result<Vec<LogEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .commits
+            .iter()
+            .skip(options.skip)
+            .take(options.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn commit_files(&self, _: &str) -> Result<Vec<CommitFile>> {
+        unimplemented!()
+    }
+
     fn reset(&self, _: &str, _: ResetMode) -> Result<()> {
         unimplemented!()
     }
@@ -955,8 +1559,27 @@ impl GitRepository for FakeGitRepository {
             .cloned()
     }
 
-    fn stage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
-        unimplemented!()
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            let index_status = match state.statuses.get(path) {
+                Some(FileStatus::Untracked) => StatusCode::Added,
+                _ => StatusCode::Modified,
+            };
+            state.statuses.insert(
+                path.clone(),
+                TrackedStatus {
+                    index_status,
+                    worktree_status: StatusCode::Unmodified,
+                }
+                .into(),
+            );
+        }
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
     }
 
     fn unstage_paths(&self, _paths: &[RepoPath]) -> Result<()> {
@@ -987,6 +1610,171 @@ impl GitRepository for FakeGitRepository {
     fn get_remotes(&self, _branch: Option<&str>) -> Result<Vec<Remote>> {
         unimplemented!()
     }
+
+    fn stash_push(
+        &self,
+        paths: &[RepoPath],
+        message: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<()> {
+        let mut state = self.state.lock();
+        let stashed_paths = state
+            .statuses
+            .iter()
+            .filter(|(repo_path, status)| {
+                (include_untracked || !status.is_untracked())
+                    && (paths.is_empty() || paths.iter().any(|path| repo_path.starts_with(path)))
+            })
+            .map(|(repo_path, _)| repo_path.clone())
+            .collect::<Vec<_>>();
+        if stashed_paths.is_empty() {
+            anyhow::bail!("No local changes to save");
+        }
+        let statuses = stashed_paths
+            .into_iter()
+            .filter_map(|path| Some((path.clone(), state.statuses.remove(&path)?)))
+            .collect::<HashMap<_, _>>();
+        let branch_name = state
+            .current_branch_name
+            .as_deref()
+            .unwrap_or("(no branch)");
+        let message = match message {
+            Some(message) => format!("On {branch_name}: {message}"),
+            None => format!("WIP on {branch_name}"),
+        };
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (state.stashes.first().map(|stash| &stash.sha), &message).hash(&mut hasher);
+        let mut paths = statuses.keys().collect::<Vec<_>>();
+        paths.sort();
+        paths.hash(&mut hasher);
+        let sha = format!("{:040x}", hasher.finish());
+        state.stashes.insert(
+            0,
+            FakeStash {
+                sha,
+                message,
+                statuses,
+            },
+        );
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(index, stash)| StashEntry {
+                index,
+                sha: stash.sha.clone().into(),
+                message: stash.message.clone().into(),
+                timestamp: 0,
+            })
+            .collect())
+    }
+
+    fn stash_apply(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        let stash = fake_stash_entry(&state, index, sha)?.clone();
+        state.statuses.extend(stash.statuses);
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        self.stash_apply(index, sha)?;
+        self.stash_drop(index, sha)
+    }
+
+    fn stash_drop(&self, index: usize, sha: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        fake_stash_entry(&state, index, sha)?;
+        state.stashes.remove(index);
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize, sha: Option<&str>) -> Result<String> {
+        let state = self.state.lock();
+        let stash = fake_stash_entry(&state, index, sha)?;
+        let mut paths = stash.statuses.keys().collect::<Vec<_>>();
+        paths.sort();
+        Ok(paths
+            .into_iter()
+            .map(|path| format!("diff --git a/{path} b/{path}\n"))
+            .collect())
+    }
+
+    fn operation_in_progress(&self) -> Option<OperationInProgress> {
+        self.state.lock().operation_in_progress
+    }
+
+    fn rebase_todo(&self, base: &str) -> Result<Vec<RebaseTodoEntry>> {
+        let state = self.state.lock();
+        let mut entries = state
+            .commits
+            .iter()
+            .take_while(|commit| commit.sha.as_ref() != base)
+            .map(|commit| RebaseTodoEntry {
+                action: RebaseAction::Pick,
+                sha: commit.sha.clone(),
+                subject: commit.subject.clone(),
+            })
+            .collect::<Vec<_>>();
+        entries.reverse();
+        Ok(entries)
+    }
+
+    fn rebase_interactive(&self, _: &str, _: &[RebaseTodoEntry]) -> Result<()> {
+        unimplemented!()
+    }
+
+    fn cherry_pick(&self, _: &[String]) -> Result<()> {
+        unimplemented!()
+    }
+
+    fn continue_operation(&self) -> Result<()> {
+        // There is nothing left to apply in the fake, so continuing just finishes the operation.
+        self.abort_operation()
+    }
+
+    fn abort_operation(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.operation_in_progress.take().is_none() {
+            anyhow::bail!("No rebase or cherry-pick in progress");
+        }
+        state
+            .event_emitter
+            .try_send(state.path.clone())
+            .expect("Dropped repo change event");
+        Ok(())
+    }
+}
+
+fn fake_stash_entry<'a>(
+    state: &'a FakeGitRepositoryState,
+    index: usize,
+    sha: Option<&str>,
+) -> Result<&'a FakeStash> {
+    let stash = state
+        .stashes
+        .get(index)
+        .with_context(|| format!("stash@{{{index}}} is not a valid reference"))?;
+    if sha.is_some_and(|sha| sha != stash.sha) {
+        anyhow::bail!("The stash list changed, stash@{{{index}}} is no longer the selected entry");
+    }
+    Ok(stash)
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -1155,6 +1943,62 @@ fn parse_branch_input(input: &str) -> Result<Vec<Branch>> {
     Ok(branches)
 }
 
+fn parse_log_output(input: &str) -> Result<Vec<LogEntry>> {
+    let mut entries = Vec::new();
+    for record in input.split('\x1e') {
+        // With `-L`, each record is followed by the patch for the selected lines.
+        let Some(line) = record.lines().next().filter(|line| !line.is_empty()) else {
+            continue;
+        };
+        let mut fields = line.split('\x00');
+        let sha: SharedString = fields.next().context("no commit hash")?.to_string().into();
+        let author_name: SharedString = fields.next().context("no author name")?.to_string().into();
+        let author_email: SharedString =
+            fields.next().context("no author email")?.to_string().into();
+        let commit_timestamp = fields.next().context("no committer date")?.parse::<i64>()?;
+        let subject: SharedString = fields.next().context("no subject")?.to_string().into();
+        entries.push(LogEntry {
+            sha,
+            subject,
+            author_name,
+            author_email,
+            commit_timestamp,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_stash_list(input: &str) -> Result<Vec<StashEntry>> {
+    let mut entries = Vec::new();
+    for line in input.split('\n') {
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.split('\x00');
+        let index = fields
+            .next()
+            .context("no reflog selector")?
+            .strip_prefix("stash@{")
+            .and_then(|selector| selector.strip_suffix('}'))
+            .context("unexpected format for reflog selector")?
+            .parse::<usize>()?;
+        let sha: SharedString = fields.next().context("no commit hash")?.to_string().into();
+        let timestamp = fields.next().context("no committer date")?.parse::<i64>()?;
+        let message: SharedString = fields
+            .next()
+            .context("no reflog subject")?
+            .to_string()
+            .into();
+        entries.push(StashEntry {
+            index,
+            sha,
+            message,
+            timestamp,
+        });
+    }
+    Ok(entries)
+}
+
 fn parse_upstream_track(upstream_track: &str) -> Result<UpstreamTracking> {
     if upstream_track == "" {
         return Ok(UpstreamTracking::Tracked(UpstreamTrackingStatus {
@@ -1213,3 +2057,265 @@ fn test_branches_parsing() {
         }]
     )
 }
+
+#[test]
+fn test_stash_list_parsing() {
+    let input = "stash@{0}\0aa6f2b5c0d84f2b0bbd1fe9b2d4c25d4a7d2e3f1\x001733187470\0On main: wip\nstash@{1}\x0012b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5\x001733180000\0WIP on main: 060964d generated protobuf\n";
+    assert_eq!(
+        parse_stash_list(input).unwrap(),
+        vec![
+            StashEntry {
+                index: 0,
+                sha: "aa6f2b5c0d84f2b0bbd1fe9b2d4c25d4a7d2e3f1".into(),
+                message: "On main: wip".into(),
+                timestamp: 1733187470,
+            },
+            StashEntry {
+                index: 1,
+                sha: "12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5".into(),
+                message: "WIP on main: 060964d generated protobuf".into(),
+                timestamp: 1733180000,
+            },
+        ]
+    )
+}
+
+#[test]
+fn test_fake_stash() {
+    let (event_tx, _event_rx) = smol::channel::unbounded();
+    let mut state = FakeGitRepositoryState::new(PathBuf::from("/repo/.git"), event_tx);
+    state.current_branch_name = Some("main".into());
+    let modified = FileStatus::Tracked(TrackedStatus {
+        index_status: StatusCode::Unmodified,
+        worktree_status: StatusCode::Modified,
+    });
+    state.statuses.insert(RepoPath::from_str("a.txt"), modified);
+    state
+        .statuses
+        .insert(RepoPath::from_str("b/c.txt"), modified);
+    state
+        .statuses
+        .insert(RepoPath::from_str("new.txt"), FileStatus::Untracked);
+    let state = Arc::new(Mutex::new(state));
+    let repo = FakeGitRepository::open(state.clone());
+    let changed_paths = || {
+        let mut paths = state
+            .lock()
+            .statuses
+            .keys()
+            .map(|path| path.to_string())
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    };
+    let messages = || {
+        repo.stash_list()
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.message.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    repo.stash_push(&[RepoPath::from_str("b")], Some("only b"), false)
+        .unwrap();
+    assert_eq!(changed_paths(), ["a.txt", "new.txt"]);
+    repo.stash_push(&[], None, false).unwrap();
+    assert_eq!(changed_paths(), ["new.txt"]);
+    assert_eq!(messages(), ["WIP on main", "On main: only b"]);
+
+    // Pushing another stash shifts the indices, so operations on the entry selected before fail.
+    let stashes = repo.stash_list().unwrap();
+    let (wip_sha, only_b_sha) = (stashes[0].sha.to_string(), stashes[1].sha.to_string());
+    repo.stash_push(&[], Some("untracked"), true).unwrap();
+    assert_eq!(changed_paths(), Vec::<String>::new());
+    assert!(repo.stash_apply(0, Some(&wip_sha)).is_err());
+    assert!(repo.stash_drop(0, Some(&wip_sha)).is_err());
+    assert!(repo.stash_pop(0, Some(&wip_sha)).is_err());
+    assert_eq!(changed_paths(), Vec::<String>::new());
+    assert_eq!(
+        messages(),
+        ["On main: untracked", "WIP on main", "On main: only b"]
+    );
+
+    // Applying keeps the entry, popping removes it.
+    repo.stash_apply(1, Some(&wip_sha)).unwrap();
+    assert_eq!(changed_paths(), ["a.txt"]);
+    assert_eq!(messages().len(), 3);
+    repo.stash_pop(2, Some(&only_b_sha)).unwrap();
+    assert_eq!(changed_paths(), ["a.txt", "b/c.txt"]);
+    assert_eq!(messages(), ["On main: untracked", "WIP on main"]);
+    repo.stash_pop(0, None).unwrap();
+    assert_eq!(changed_paths(), ["a.txt", "b/c.txt", "new.txt"]);
+    assert_eq!(messages(), ["WIP on main"]);
+}
+
+#[test]
+fn test_log_parsing() {
+    let input = "\x1e060964da10574cd9bf06463a53bf6e0769c5c45e\0Jane Doe\0jane@example.com\x001733187470\0generated protobuf\n\x1e12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5\0John Doe\0john@example.com\x001733180000\0Fix typo\n\ndiff --git a/README.md b/README.md\n";
+    assert_eq!(
+        parse_log_output(input).unwrap(),
+        vec![
+            LogEntry {
+                sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
+                subject: "generated protobuf".into(),
+                author_name: "Jane Doe".into(),
+                author_email: "jane@example.com".into(),
+                commit_timestamp: 1733187470,
+            },
+            LogEntry {
+                sha: "12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5".into(),
+                subject: "Fix typo".into(),
+                author_name: "John Doe".into(),
+                author_email: "john@example.com".into(),
+                commit_timestamp: 1733180000,
+            },
+        ]
+    )
+}
+
+#[test]
+fn test_rebase_todo_parsing() {
+    let entries = vec![
+        RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
+            subject: "generated protobuf".into(),
+        },
+        RebaseTodoEntry {
+            action: RebaseAction::Pick,
+            sha: "12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5".into(),
+            subject: "Fix typo".into(),
+        },
+    ];
+    let text = format_rebase_todo(&entries);
+    assert_eq!(parse_rebase_todo(&text).unwrap(), entries);
+
+    let edited = "f 12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5 Fix typo\n\
+                  reword 060964da10574cd9bf06463a53bf6e0769c5c45e Don't generate protobuf\n\
+                  # a comment\n";
+    let entries = parse_rebase_todo(edited).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            RebaseTodoEntry {
+                action: RebaseAction::Fixup,
+                sha: "12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5".into(),
+                subject: "Fix typo".into(),
+            },
+            RebaseTodoEntry {
+                action: RebaseAction::Reword,
+                sha: "060964da10574cd9bf06463a53bf6e0769c5c45e".into(),
+                subject: "Don't generate protobuf".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        git_rebase_todo(&entries),
+        "fixup 12b4d9f6e0a2c5b8d1f3e7a9c0b2d4f6e8a1c3e5\n\
+         pick 060964da10574cd9bf06463a53bf6e0769c5c45e\n\
+         exec git commit --amend --only --quiet --message 'Don'\\''t generate protobuf' --message \"$(git log -1 --format=%b)\"\n"
+    );
+
+    assert!(parse_rebase_todo("edit 060964da Fix typo\n").is_err());
+}
+
+#[test]
+fn test_rebase_reword_keeps_message_body() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let output = new_std_command("git")
+            .current_dir(dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+    git(&["init", "--quiet"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["config", "user.email", "test@example.com"]);
+    std::fs::write(dir.path().join("file"), "one").unwrap();
+    git(&["add", "file"]);
+    git(&["commit", "--quiet", "--message", "Initial commit"]);
+    std::fs::write(dir.path().join("file"), "two").unwrap();
+    git(&[
+        "commit",
+        "--quiet",
+        "--all",
+        "--message",
+        "Fix tpyo\n\nExplain the fix,\n# including this line.\n\nSigned-off-by: Test <test@example.com>",
+    ]);
+
+    let repository = RealGitRepository::new(
+        git2::Repository::open(dir.path()).unwrap(),
+        None,
+        Arc::new(GitHostingProviderRegistry::new()),
+    );
+    let mut todo = repository.rebase_todo("HEAD~1").unwrap();
+    assert_eq!(todo.len(), 1);
+    todo[0].action = RebaseAction::Reword;
+    todo[0].subject = "Fix typo".into();
+    repository.rebase_interactive("HEAD~1", &todo).unwrap();
+
+    assert_eq!(
+        git(&["log", "-1", "--format=%B"]).trim_end(),
+        "Fix typo\n\nExplain the fix,\n# including this line.\n\nSigned-off-by: Test <test@example.com>"
+    );
+}
+
+#[test]
+fn test_commit_files_omits_binary_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let output = new_std_command("git")
+            .current_dir(dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+    git(&["init", "--quiet"]);
+    git(&["config", "user.name", "Test"]);
+    git(&["config", "user.email", "test@example.com"]);
+    std::fs::write(
+        dir.path().join("image.png"),
+        b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("text.txt"), "text\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "--message", "Initial commit"]);
+
+    let repository = RealGitRepository::new(
+        git2::Repository::open(dir.path()).unwrap(),
+        None,
+        Arc::new(GitHostingProviderRegistry::new()),
+    );
+    let mut files = repository.commit_files("HEAD").unwrap();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    assert_eq!(
+        files,
+        [
+            CommitFile {
+                path: RepoPath::from_str("image.png"),
+                old_text: None,
+                new_text: Some(String::new()),
+                is_binary: true,
+            },
+            CommitFile {
+                path: RepoPath::from_str("text.txt"),
+                old_text: None,
+                new_text: Some("text\n".into()),
+                is_binary: false,
+            },
+        ]
+    );
+}
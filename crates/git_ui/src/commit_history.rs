@@ -0,0 +1,529 @@
+use crate::{commit_view::CommitView, rebase_todo::RebaseTodoEditor};
+use editor::Editor;
+use feature_flags::FeatureFlagViewExt;
+use git::repository::{LogEntry, LogOptions, RepoPath};
+use gpui::{
+    actions, anchored, deferred, uniform_list, App, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, ListSizingBehavior, MouseButton, MouseDownEvent, Pixels, Point,
+    Subscription, Task, UniformListScrollHandle, WeakEntity,
+};
+use language::Point as TextPoint;
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
+use project::{git::Repository, ProjectItem as _, ProjectPath};
+use std::ops::Range;
+use theme::ActiveTheme;
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{prelude::*, ContextMenu};
+use util::maybe;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+actions!(
+    git,
+    [ViewHistory, ViewFileHistory, InteractiveRebase, CherryPick]
+);
+
+/// The number of commits requested from the repository at a time.
+const PAGE_SIZE: usize = 100;
+
+/// Lists the commits of a repository, optionally limited to those touching a file or a range of
+/// its lines, and opens a [`CommitView`] for the selected commit.
+pub struct CommitHistory {
+    repository: Entity<Repository>,
+    workspace: WeakEntity<Workspace>,
+    path: Option<RepoPath>,
+    row_range: Option<Range<u32>>,
+    entries: Vec<LogEntry>,
+    selected_index: Option<usize>,
+    has_more: bool,
+    load_task: Option<Task<()>>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
+}
+
+pub enum Event {}
+
+impl CommitHistory {
+    pub(crate) fn register(
+        _: &mut Workspace,
+        window: Option<&mut Window>,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(window) = window else { return };
+        cx.when_flag_enabled::<feature_flags::GitUiFeatureFlag>(window, |workspace, _, _cx| {
+            workspace.register_action(Self::view_history);
+            workspace.register_action(Self::view_file_history);
+        });
+    }
+
+    fn view_history(
+        workspace: &mut Workspace,
+        _: &ViewHistory,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(repository) = workspace.project().read(cx).active_repository(cx) else {
+            return;
+        };
+        Self::deploy(workspace, repository, None, None, window, cx);
+    }
+
+    fn view_file_history(
+        workspace: &mut Workspace,
+        _: &ViewFileHistory,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let (project_path, row_range) = editor.update(cx, |editor, cx| {
+            let project_path = editor
+                .buffer()
+                .read(cx)
+                .as_singleton()
+                .and_then(|buffer| buffer.read(cx).project_path(cx));
+            let selection = editor.selections.newest::<TextPoint>(cx);
+            let row_range = (!selection.is_empty()).then(|| {
+                // Don't include the line after a selection that ends at a line start.
+                let end_row =
+                    if selection.end.column == 0 && selection.end.row > selection.start.row {
+                        selection.end.row
+                    } else {
+                        selection.end.row + 1
+                    };
+                selection.start.row..end_row
+            });
+            (project_path, row_range)
+        });
+        let Some(project_path) = project_path else {
+            return;
+        };
+        let Some((repository, repo_path)) = repository_for_path(workspace, &project_path, cx)
+        else {
+            return;
+        };
+        Self::deploy(
+            workspace,
+            repository,
+            Some(repo_path),
+            row_range,
+            window,
+            cx,
+        );
+    }
+
+    pub fn deploy(
+        workspace: &mut Workspace,
+        repository: Entity<Repository>,
+        path: Option<RepoPath>,
+        row_range: Option<Range<u32>>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace.items_of_type::<Self>(cx).find(|history| {
+            let history = history.read(cx);
+            history.repository == repository
+                && history.path == path
+                && history.row_range == row_range
+        });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            return;
+        }
+
+        let workspace_handle = cx.entity().downgrade();
+        let history =
+            cx.new(|cx| Self::new(repository, workspace_handle, path, row_range, window, cx));
+        workspace.add_item_to_active_pane(Box::new(history), None, true, window, cx);
+    }
+
+    fn new(
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        path: Option<RepoPath>,
+        row_range: Option<Range<u32>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let focus_handle = cx.focus_handle();
+        focus_handle.focus(window);
+        let mut this = Self {
+            repository,
+            workspace,
+            path,
+            row_range,
+            entries: Vec::new(),
+            selected_index: None,
+            has_more: true,
+            load_task: None,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle,
+            context_menu: None,
+        };
+        this.load_next_page(cx);
+        this
+    }
+
+    fn load_next_page(&mut self, cx: &mut Context<Self>) {
+        if self.load_task.is_some() || !self.has_more {
+            return;
+        }
+
+        let load_log = self.repository.read(cx).log(LogOptions {
+            skip: self.entries.len(),
+            limit: PAGE_SIZE,
+            path: self.path.clone(),
+            row_range: self.row_range.clone(),
+            follow_renames: true,
+        });
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let result = maybe!(async { load_log.await? }).await;
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match result {
+                    Ok(entries) => {
+                        this.has_more = entries.len() == PAGE_SIZE;
+                        this.entries.extend(entries);
+                        if this.selected_index.is_none() && !this.entries.is_empty() {
+                            this.selected_index = Some(0);
+                        }
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        log::error!("failed to load commit history: {error:?}");
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        if !self.entries.is_empty() {
+            self.select_index(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(last_index) = self.entries.len().checked_sub(1) {
+            self.select_index(last_index, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let next_index = self.selected_index.map_or(0, |index| index + 1);
+        if next_index < self.entries.len() {
+            self.select_index(next_index, cx);
+        }
+    }
+
+    fn select_previous(&mut self, _: &SelectPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(index) = self.selected_index.and_then(|index| index.checked_sub(1)) {
+            self.select_index(index, cx);
+        }
+    }
+
+    fn select_index(&mut self, index: usize, cx: &mut Context<Self>) {
+        self.selected_index = Some(index);
+        self.scroll_handle
+            .scroll_to_item(index, gpui::ScrollStrategy::Top);
+        if index + 1 == self.entries.len() {
+            self.load_next_page(cx);
+        }
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(index) = self.selected_index {
+            self.open_commit(index, window, cx);
+        }
+    }
+
+    fn open_commit(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(index) else {
+            return;
+        };
+        self.selected_index = Some(index);
+        CommitView::open(
+            entry.clone(),
+            self.repository.clone(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+        cx.notify();
+    }
+
+    /// Rebases the commits after the selected one, after letting the user edit the todo list.
+    fn interactive_rebase(
+        &mut self,
+        _: &InteractiveRebase,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(entry) = self
+            .selected_index
+            .and_then(|index| self.entries.get(index))
+        else {
+            return;
+        };
+        RebaseTodoEditor::open(
+            entry.sha.clone(),
+            self.repository.clone(),
+            self.workspace.clone(),
+            window,
+            cx,
+        );
+    }
+
+    fn cherry_pick(&mut self, _: &CherryPick, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self
+            .selected_index
+            .and_then(|index| self.entries.get(index))
+        else {
+            return;
+        };
+        let cherry_pick = self
+            .repository
+            .read(cx)
+            .cherry_pick(vec![entry.sha.to_string()]);
+        cx.spawn_in(window, |_, _| async move { cherry_pick.await? })
+            .detach_and_prompt_err("Failed to cherry-pick", window, cx, |e, _, _| {
+                Some(e.to_string())
+            });
+    }
+
+    fn deploy_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_index(index, cx);
+        let focus_handle = self.focus_handle.clone();
+        let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
+            context_menu
+                .context(focus_handle)
+                .action("Open Commit", Confirm.boxed_clone())
+                .separator()
+                .action(
+                    "Rebase Interactively From Here",
+                    InteractiveRebase.boxed_clone(),
+                )
+                .action("Cherry-Pick", CherryPick.boxed_clone())
+        });
+        let subscription = cx.subscribe_in(
+            &context_menu,
+            window,
+            |this, _, _: &DismissEvent, window, cx| {
+                if this.context_menu.as_ref().is_some_and(|context_menu| {
+                    context_menu.0.focus_handle(cx).contains_focused(window, cx)
+                }) {
+                    cx.focus_self(window);
+                }
+                this.context_menu.take();
+                cx.notify();
+            },
+        );
+        self.context_menu = Some((context_menu, position, subscription));
+        cx.notify();
+    }
+
+    fn title(&self) -> SharedString {
+        let Some(path) = &self.path else {
+            return "History".into();
+        };
+        let file_name = path
+            .file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy();
+        match &self.row_range {
+            Some(row_range) => format!(
+                "History: {file_name}:{}-{}",
+                row_range.start + 1,
+                row_range.end
+            )
+            .into(),
+            None => format!("History: {file_name}").into(),
+        }
+    }
+
+    fn render_entry(&self, index: usize, entry: &LogEntry, cx: &Context<Self>) -> AnyElement {
+        let short_sha = entry
+            .sha
+            .get(..git::SHORT_SHA_LENGTH)
+            .unwrap_or(&entry.sha)
+            .to_string();
+        let date = OffsetDateTime::from_unix_timestamp(entry.commit_timestamp)
+            .map(|timestamp| {
+                time_format::format_local_timestamp(
+                    timestamp,
+                    OffsetDateTime::now_utc(),
+                    TimestampFormat::Relative,
+                )
+            })
+            .unwrap_or_default();
+
+        h_flex()
+            .id(index)
+            .w_full()
+            .h(px(28.))
+            .px_2()
+            .gap_2()
+            .when(self.selected_index == Some(index), |this| {
+                this.bg(cx.theme().colors().ghost_element_selected)
+            })
+            .hover(|this| this.bg(cx.theme().colors().ghost_element_hover))
+            .child(
+                Label::new(short_sha)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .buffer_font(cx),
+            )
+            .child(
+                div()
+                    .flex_grow()
+                    .overflow_hidden()
+                    .child(Label::new(entry.subject.clone()).truncate()),
+            )
+            .child(
+                Label::new(entry.author_name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(Label::new(date).size(LabelSize::Small).color(Color::Muted))
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.open_commit(index, window, cx);
+            }))
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                    this.deploy_context_menu(event.position, index, window, cx);
+                    cx.stop_propagation();
+                }),
+            )
+            .into_any_element()
+    }
+}
+
+/// Finds the repository containing the given path, along with the path relative to it.
+fn repository_for_path(
+    workspace: &Workspace,
+    project_path: &ProjectPath,
+    cx: &App,
+) -> Option<(Entity<Repository>, RepoPath)> {
+    let git_store = workspace.project().read(cx).git_store().read(cx);
+    git_store
+        .all_repositories()
+        .into_iter()
+        .filter_map(|repository| {
+            let repo_path = repository
+                .read(cx)
+                .project_path_to_repo_path(project_path)?;
+            Some((repository, repo_path))
+        })
+        // Prefer the innermost repository when repositories are nested.
+        .min_by_key(|(_, repo_path)| repo_path.components().count())
+}
+
+impl EventEmitter<Event> for CommitHistory {}
+
+impl Focusable for CommitHistory {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for CommitHistory {
+    type Event = Event;
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
+        Label::new(self.title())
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(self.title())
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Commit History Opened")
+    }
+}
+
+impl Render for CommitHistory {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.entries.len();
+
+        v_flex()
+            .key_context("CommitHistory")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::interactive_rebase))
+            .on_action(cx.listener(Self::cherry_pick))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .map(|this| {
+                if entry_count == 0 {
+                    this.items_center().justify_center().child(
+                        Label::new(if self.load_task.is_some() {
+                            "Loading history…"
+                        } else {
+                            "No commits"
+                        })
+                        .color(Color::Muted),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(cx.entity().clone(), "commit-history", entry_count, {
+                            move |this, range, _window, cx| {
+                                if range.end >= this.entries.len() {
+                                    this.load_next_page(cx);
+                                }
+                                range
+                                    .filter_map(|index| {
+                                        let entry = this.entries.get(index)?;
+                                        Some(this.render_entry(index, entry, cx))
+                                    })
+                                    .collect()
+                            }
+                        })
+                        .size_full()
+                        .with_sizing_behavior(ListSizingBehavior::Auto)
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+            .children(self.context_menu.as_ref().map(|(menu, position, _)| {
+                deferred(
+                    anchored()
+                        .position(*position)
+                        .anchor(gpui::Corner::TopLeft)
+                        .child(menu.clone()),
+                )
+                .with_priority(1)
+            }))
+    }
+}
@@ -0,0 +1,354 @@
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use git::repository::{CommitFile, LogEntry};
+use gpui::{
+    AnyElement, AnyView, App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable,
+    Render, Task, WeakEntity,
+};
+use language::{
+    Anchor, Buffer, Capability, DiskState, LanguageRegistry, OffsetRangeExt as _, Point,
+};
+use multi_buffer::{MultiBuffer, PathKey};
+use project::{git::Repository, Project, WorktreeId};
+use std::{
+    any::{Any, TypeId},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use theme::ActiveTheme;
+use ui::prelude::*;
+use util::ResultExt as _;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, TabContentParams},
+    searchable::SearchableItemHandle,
+    ItemNavHistory, ToolbarItemLocation, Workspace,
+};
+
+const COMMIT_NAMESPACE: &'static str = "0";
+const BINARY_FILE_TEXT: &str = "Binary file not shown";
+
+/// Shows the changes made by a single commit in a multibuffer.
+pub struct CommitView {
+    commit: LogEntry,
+    editor: Entity<Editor>,
+    multibuffer: Entity<MultiBuffer>,
+    _load_files: Task<()>,
+}
+
+/// A file read out of a commit, which is shown in a [`CommitView`] but doesn't exist on disk.
+struct GitBlob {
+    path: Arc<Path>,
+    worktree_id: WorktreeId,
+    is_deleted: bool,
+}
+
+impl CommitView {
+    pub fn open(
+        commit: LogEntry,
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let commit_files = repository.read(cx).commit_files(&commit.sha);
+        let worktree_id = repository.read(cx).worktree_id;
+        window
+            .spawn(cx, |mut cx| async move {
+                let files = commit_files.await??;
+                workspace.update_in(&mut cx, |workspace, window, cx| {
+                    let project = workspace.project().clone();
+                    let commit_view = cx
+                        .new(|cx| CommitView::new(commit, files, worktree_id, project, window, cx));
+                    workspace.add_item_to_active_pane(
+                        Box::new(commit_view),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn new(
+        commit: LogEntry,
+        files: Vec<CommitFile>,
+        worktree_id: WorktreeId,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer.clone(), None, true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        let language_registry = project.read(cx).languages().clone();
+        let load_files = cx.spawn(|this, mut cx| async move {
+            for file in files {
+                let Some((buffer, diff)) =
+                    load_file(file, worktree_id, language_registry.clone(), &mut cx)
+                        .await
+                        .log_err()
+                else {
+                    continue;
+                };
+                if this
+                    .update(&mut cx, |this, cx| this.register_file(buffer, diff, cx))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            commit,
+            editor,
+            multibuffer,
+            _load_files: load_files,
+        }
+    }
+
+    fn register_file(
+        &mut self,
+        buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(path) = buffer.read(cx).file().map(|file| file.path().clone()) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let mut diff_hunk_ranges = diff
+            .read(cx)
+            .hunks_intersecting_range(Anchor::MIN..Anchor::MAX, &snapshot, cx)
+            .map(|diff_hunk| diff_hunk.buffer_range.to_point(&snapshot))
+            .collect::<Vec<_>>();
+        // Files without a textual diff, such as binary ones, are shown in full.
+        if diff_hunk_ranges.is_empty() {
+            diff_hunk_ranges.push(Point::zero()..snapshot.max_point());
+        }
+
+        self.multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.add_diff(diff, cx);
+            multibuffer.set_excerpts_for_path(
+                PathKey::namespaced(COMMIT_NAMESPACE, path),
+                buffer,
+                diff_hunk_ranges,
+                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                cx,
+            );
+        });
+    }
+}
+
+async fn load_file(
+    file: CommitFile,
+    worktree_id: WorktreeId,
+    language_registry: Arc<LanguageRegistry>,
+    cx: &mut gpui::AsyncApp,
+) -> Result<(Entity<Buffer>, Entity<BufferDiff>)> {
+    let language = language_registry
+        .language_for_file_path(&file.path)
+        .await
+        .ok();
+    let blob = Arc::new(GitBlob {
+        path: file.path.0.clone(),
+        worktree_id,
+        is_deleted: file.new_text.is_none(),
+    });
+    // Binary files can't be diffed, so both versions are replaced by a note.
+    let (old_text, new_text) = if file.is_binary {
+        (BINARY_FILE_TEXT.to_string(), BINARY_FILE_TEXT.to_string())
+    } else {
+        (
+            file.old_text.unwrap_or_default(),
+            file.new_text.unwrap_or_default(),
+        )
+    };
+
+    let buffer = cx.new(|cx| {
+        let mut buffer = Buffer::local(new_text, cx);
+        buffer.file_updated(blob, cx);
+        buffer.set_language(language.clone(), cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        buffer
+    })?;
+    let base_buffer = cx.new(|cx| {
+        let mut buffer = Buffer::local(old_text, cx);
+        buffer.set_language_registry(language_registry);
+        buffer.set_language(language, cx);
+        buffer
+    })?;
+
+    let snapshot = buffer.update(cx, |buffer, _| buffer.text_snapshot())?;
+    let diff = cx.new(|cx| BufferDiff::new(&snapshot, cx))?;
+    diff.update(cx, |diff, cx| diff.set_base_text(base_buffer, snapshot, cx))?
+        .await?;
+    Ok((buffer, diff))
+}
+
+impl language::File for GitBlob {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn disk_state(&self) -> DiskState {
+        if self.is_deleted {
+            DiskState::Deleted
+        } else {
+            DiskState::New
+        }
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &App) -> PathBuf {
+        self.path.to_path_buf()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a App) -> &'a OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    fn worktree_id(&self, _: &App) -> WorktreeId {
+        self.worktree_id
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &App) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.worktree_id.to_proto(),
+            entry_id: None,
+            path: self.path.to_string_lossy().to_string(),
+            mtime: None,
+            is_deleted: self.is_deleted,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitView {}
+
+impl Focusable for CommitView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for CommitView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("{} {}", self.commit.sha, self.commit.subject).into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
+        let short_sha = self
+            .commit
+            .sha
+            .get(..git::SHORT_SHA_LENGTH)
+            .unwrap_or(&self.commit.sha);
+        Label::new(format!("{short_sha} {}", self.commit.subject))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Commit View Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+}
+
+impl Render for CommitView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .key_context("CommitView")
+            .bg(cx.theme().colors().editor_background)
+            .size_full()
+            .child(self.editor.clone())
+    }
+}
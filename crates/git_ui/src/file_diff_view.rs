@@ -0,0 +1,411 @@
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyElement, AnyView, App, AppContext as _, AsyncApp, Entity, EventEmitter, FocusHandle,
+    Focusable, Render, Subscription, Task, WeakEntity,
+};
+use language::{Buffer, BufferEvent, Capability};
+use multi_buffer::MultiBuffer;
+use project::Project;
+use std::{
+    any::{Any, TypeId},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use theme::ActiveTheme;
+use ui::prelude::*;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, TabContentParams},
+    searchable::SearchableItemHandle,
+    ItemNavHistory, ToolbarItemLocation, Workspace,
+};
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Shows the differences between two arbitrary files, as when Zed is used as a difftool.
+///
+/// The old file is read-only, while the new file can be edited and saved, with the diff
+/// following along.
+pub struct FileDiffView {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    editor: Entity<Editor>,
+    old_buffer: Entity<Buffer>,
+    new_buffer: Entity<Buffer>,
+    diff: Entity<BufferDiff>,
+    recalculate_diff_task: Option<Task<Result<()>>>,
+    _subscription: Subscription,
+}
+
+impl FileDiffView {
+    pub fn open(
+        old_path: PathBuf,
+        new_path: PathBuf,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        window.spawn(cx, |mut cx| async move {
+            let (project, fs) = workspace.update(&mut cx, |workspace, _| {
+                (
+                    workspace.project().clone(),
+                    workspace.app_state().fs.clone(),
+                )
+            })?;
+            let old_text = fs.load(&old_path).await?;
+            let new_buffer = open_buffer(&project, new_path.clone(), &mut cx).await?;
+
+            let (language, language_registry) = new_buffer.update(&mut cx, |buffer, _| {
+                (buffer.language().cloned(), buffer.language_registry())
+            })?;
+            let old_buffer = cx.new(|cx| {
+                let mut buffer = Buffer::local(old_text, cx);
+                if let Some(language_registry) = language_registry {
+                    buffer.set_language_registry(language_registry);
+                }
+                buffer.set_language(language, cx);
+                buffer.set_capability(Capability::ReadOnly, cx);
+                buffer
+            })?;
+
+            let snapshot = new_buffer.update(&mut cx, |buffer, _| buffer.text_snapshot())?;
+            let diff = cx.new(|cx| BufferDiff::new(&snapshot, cx))?;
+            diff.update(&mut cx, |diff, cx| {
+                diff.set_base_text(old_buffer.clone(), snapshot, cx)
+            })?
+            .await?;
+
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let diff_view = cx.new(|cx| {
+                    FileDiffView::new(
+                        old_path, new_path, old_buffer, new_buffer, diff, project, window, cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(
+                    Box::new(diff_view.clone()),
+                    None,
+                    true,
+                    window,
+                    cx,
+                );
+                diff_view
+            })
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        old_path: PathBuf,
+        new_path: PathBuf,
+        old_buffer: Entity<Buffer>,
+        new_buffer: Entity<Buffer>,
+        diff: Entity<BufferDiff>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(new_buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let subscription = cx.subscribe(&new_buffer, |this, _, event: &BufferEvent, cx| {
+            if let BufferEvent::Edited | BufferEvent::Reloaded = event {
+                this.recalculate_diff(cx);
+            }
+        });
+
+        Self {
+            old_path,
+            new_path,
+            editor,
+            old_buffer,
+            new_buffer,
+            diff,
+            recalculate_diff_task: None,
+            _subscription: subscription,
+        }
+    }
+
+    fn recalculate_diff(&mut self, cx: &mut Context<Self>) {
+        let old_buffer = self.old_buffer.clone();
+        let new_buffer = self.new_buffer.clone();
+        let diff = self.diff.clone();
+        // Replacing the task drops any pending recalculation, so bursts of edits are debounced.
+        self.recalculate_diff_task = Some(cx.spawn(|_, mut cx| async move {
+            cx.background_executor()
+                .timer(RECALCULATE_DIFF_DEBOUNCE)
+                .await;
+            let snapshot = new_buffer.update(&mut cx, |buffer, _| buffer.text_snapshot())?;
+            diff.update(&mut cx, |diff, cx| {
+                diff.set_base_text(old_buffer, snapshot, cx)
+            })?
+            .await?;
+            Ok(())
+        }));
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+async fn open_buffer(
+    project: &Entity<Project>,
+    abs_path: PathBuf,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>> {
+    let (worktree, relative_path) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(&abs_path, false, cx)
+        })?
+        .await?;
+    let worktree_id = worktree.update(cx, |worktree, _| worktree.id())?;
+    project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, relative_path), cx)
+        })?
+        .await
+}
+
+impl EventEmitter<EditorEvent> for FileDiffView {}
+
+impl Focusable for FileDiffView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for FileDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff).color(Color::Muted))
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("{} ↔ {}", self.old_path.display(), self.new_path.display()).into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
+        let old_name = Self::file_name(&self.old_path);
+        let new_name = Self::file_name(&self.new_path);
+        let title = if old_name == new_name {
+            new_name
+        } else {
+            format!("{old_name} ↔ {new_name}")
+        };
+        Label::new(title)
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("File Diff View Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.new_buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.new_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+}
+
+impl Render for FileDiffView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .key_context("FileDiffView")
+            .bg(cx.theme().colors().editor_background)
+            .size_full()
+            .child(self.editor.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::test::editor_test_context::assert_state_with_diff;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use unindent::Unindent as _;
+    use util::path;
+    use workspace::Workspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_file_diff_view(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/test"),
+            json!({
+                "old.txt": "one\ntwo\nthree\n",
+                "new.txt": "one\nTWO\nthree\n",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), [path!("/test").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let diff_view = workspace
+            .update_in(cx, |workspace, window, cx| {
+                FileDiffView::open(
+                    PathBuf::from(path!("/test/old.txt")),
+                    PathBuf::from(path!("/test/new.txt")),
+                    workspace.weak_handle(),
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        let editor = diff_view.read_with(cx, |diff_view, _| diff_view.editor.clone());
+
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &"
+                ˇone
+              - two
+              + TWO
+                three
+            "
+            .unindent(),
+        );
+
+        // The diff follows edits to the new file, which can be saved.
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_text("one\nTWO\nthree\nfour\n", window, cx);
+        });
+        cx.executor().advance_clock(RECALCULATE_DIFF_DEBOUNCE);
+        cx.run_until_parked();
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &"
+                one
+              - two
+              + TWO
+                three
+              + fourˇ
+            "
+            .unindent(),
+        );
+
+        workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.save_active_item(workspace::SaveIntent::Save, window, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load(Path::new(path!("/test/new.txt"))).await.unwrap(),
+            "one\nTWO\nthree\nfour\n"
+        );
+    }
+}
@@ -2,25 +2,29 @@ use crate::branch_picker::{self, BranchList};
 use crate::git_panel_settings::StatusStyle;
 use crate::remote_output_toast::{RemoteAction, RemoteOutputToast};
 use crate::repository_selector::RepositorySelectorPopoverMenu;
+use crate::{commit_history, picker_prompt, project_diff, ProjectDiff};
 use crate::{
     git_panel_settings::GitPanelSettings, git_status_icon, repository_selector::RepositorySelector,
 };
-use crate::{picker_prompt, project_diff, ProjectDiff};
 use db::kvp::KEY_VALUE_STORE;
 use editor::commit_tooltip::CommitTooltip;
 use editor::{
     scroll::ScrollbarAutoHide, Editor, EditorElement, EditorMode, EditorSettings, MultiBuffer,
     ShowScrollbar,
 };
+use futures::channel::oneshot;
 use git::repository::{
     Branch, CommitDetails, CommitSummary, PushOptions, Remote, RemoteCommandOutput, ResetMode,
-    Upstream, UpstreamTracking, UpstreamTrackingStatus,
+    StashEntry, Upstream, UpstreamTracking, UpstreamTrackingStatus,
 };
 use git::{repository::RepoPath, status::FileStatus, Commit, ToggleStaged};
-use git::{RestoreTrackedFiles, StageAll, TrashUntrackedFiles, UnstageAll};
+use git::{
+    AbortOperation, ContinueOperation, RestoreTrackedFiles, StageAll, StashAll, StashFile,
+    StashPop, TrashUntrackedFiles, UnstageAll,
+};
 use gpui::*;
 use itertools::Itertools;
-use language::{Buffer, File};
+use language::{Buffer, Capability, File};
 use menu::{Confirm, SecondaryConfirm, SelectFirst, SelectLast, SelectNext, SelectPrevious};
 use multi_buffer::ExcerptInfo;
 use panel::{
@@ -84,7 +88,11 @@ fn git_panel_context_menu(window: &mut Window, cx: &mut App) -> Entity<ContextMe
             .action("Stage All", StageAll.boxed_clone())
             .action("Unstage All", UnstageAll.boxed_clone())
             .separator()
+            .action("Stash All", StashAll.boxed_clone())
+            .action("Pop Stash", StashPop.boxed_clone())
+            .separator()
             .action("Open Diff", project_diff::Diff.boxed_clone())
+            .action("View History", commit_history::ViewHistory.boxed_clone())
             .separator()
             .action("Discard Tracked Changes", RestoreTrackedFiles.boxed_clone())
             .action("Trash Untracked Files", TrashUntrackedFiles.boxed_clone())
@@ -196,6 +204,8 @@ pub struct GitPanel {
     current_modifiers: Modifiers,
     add_coauthors: bool,
     entries: Vec<GitListEntry>,
+    stash_entries: Vec<StashEntry>,
+    update_stash_entries_task: Task<()>,
     focus_handle: FocusHandle,
     fs: Arc<dyn Fs>,
     hide_scrollbar_task: Option<Task<()>>,
@@ -323,6 +333,8 @@ impl GitPanel {
                 current_modifiers: window.modifiers(),
                 add_coauthors: true,
                 entries: Vec::new(),
+                stash_entries: Vec::new(),
+                update_stash_entries_task: Task::ready(()),
                 focus_handle: cx.focus_handle(),
                 fs,
                 hide_scrollbar_task: None,
@@ -1391,6 +1403,166 @@ impl GitPanel {
         .detach_and_log_err(cx);
     }
 
+    fn stash_all(&mut self, _: &StashAll, _window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash_operation(|repo| repo.stash_push(Vec::new(), None, true), cx);
+    }
+
+    fn stash_file(&mut self, _: &StashFile, _window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.get_selected_entry().and_then(|e| e.status_entry()) else {
+            return;
+        };
+        let path = entry.repo_path.clone();
+        self.perform_stash_operation(|repo| repo.stash_push(vec![path], None, true), cx);
+    }
+
+    fn stash_pop(&mut self, _: &StashPop, _window: &mut Window, cx: &mut Context<Self>) {
+        self.perform_stash_operation(|repo| repo.stash_pop(0, None), cx);
+    }
+
+    fn continue_operation(
+        &mut self,
+        _: &ContinueOperation,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let continue_operation = repo.read(cx).continue_operation();
+        cx.spawn_in(window, |_, _| async move { continue_operation.await? })
+            .detach_and_prompt_err("Failed to continue", window, cx, |e, _, _| {
+                Some(e.to_string())
+            });
+    }
+
+    fn abort_operation(&mut self, _: &AbortOperation, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let abort_operation = repo.read(cx).abort_operation();
+        cx.spawn_in(window, |_, _| async move { abort_operation.await? })
+            .detach_and_prompt_err("Failed to abort", window, cx, |e, _, _| Some(e.to_string()));
+    }
+
+    fn perform_stash_operation(
+        &mut self,
+        operation: impl FnOnce(&Repository) -> oneshot::Receiver<Result<()>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let operation = operation(repo.read(cx));
+        cx.spawn(|this, mut cx| async move {
+            let result = maybe!(async { operation.await? }).await;
+            this.update(&mut cx, |this, cx| {
+                if let Err(e) = result {
+                    this.show_err_toast(e, cx);
+                }
+                this.update_stash_entries(cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn open_stash_diff(&mut self, entry: &StashEntry, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.clone() else {
+            return;
+        };
+        let title = format!("{}: {}", entry.reference(), entry.message);
+        let stash_diff = repo
+            .read(cx)
+            .stash_diff(entry.index, Some(entry.sha.to_string()));
+        let project = self.project.clone();
+        let workspace = self.workspace.clone();
+        let diff_language = project.read(cx).languages().language_for_name("Diff");
+        cx.spawn_in(window, |_, mut cx| async move {
+            let diff = stash_diff.await??;
+            let diff_language = diff_language.await.log_err();
+            let buffer = project
+                .update(&mut cx, |project, cx| project.create_buffer(cx))?
+                .await?;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.set_text(diff, cx);
+                    buffer.set_language(diff_language, cx);
+                    buffer.set_capability(Capability::ReadOnly, cx);
+                });
+                let multibuffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                workspace.add_item_to_active_pane(
+                    Box::new(cx.new(|cx| {
+                        let mut editor =
+                            Editor::for_multibuffer(multibuffer, None, false, window, cx);
+                        editor.set_read_only(true);
+                        editor
+                    })),
+                    None,
+                    true,
+                    window,
+                    cx,
+                );
+            })
+        })
+        .detach_and_prompt_err("Failed to show stash", window, cx, |e, _, _| {
+            Some(e.to_string())
+        });
+    }
+
+    fn deploy_stash_context_menu(
+        &mut self,
+        position: Point<Pixels>,
+        entry: StashEntry,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let git_panel = cx.entity().downgrade();
+        let context_menu = ContextMenu::build(window, cx, |context_menu, _, _| {
+            let open_diff = {
+                let git_panel = git_panel.clone();
+                let entry = entry.clone();
+                move |window: &mut Window, cx: &mut App| {
+                    git_panel
+                        .update(cx, |git_panel, cx| {
+                            git_panel.open_stash_diff(&entry, window, cx)
+                        })
+                        .ok();
+                }
+            };
+            // The entry's sha is sent along, so that the operation fails rather than acting on
+            // a different stash if the list changed since the menu was opened.
+            let stash_operation = move |operation: fn(
+                &Repository,
+                usize,
+                Option<String>,
+            )
+                -> oneshot::Receiver<Result<()>>| {
+                let git_panel = git_panel.clone();
+                let (index, sha) = (entry.index, entry.sha.to_string());
+                move |_: &mut Window, cx: &mut App| {
+                    git_panel
+                        .update(cx, |git_panel, cx| {
+                            git_panel.perform_stash_operation(
+                                |repo| operation(repo, index, Some(sha.clone())),
+                                cx,
+                            )
+                        })
+                        .ok();
+                }
+            };
+            context_menu
+                .entry("Open Diff", None, open_diff)
+                .separator()
+                .entry(
+                    "Apply Stash",
+                    None,
+                    stash_operation(Repository::stash_apply),
+                )
+                .entry("Pop Stash", None, stash_operation(Repository::stash_pop))
+                .entry("Drop Stash", None, stash_operation(Repository::stash_drop))
+        });
+        self.set_context_menu(context_menu, position, window, cx);
+    }
+
     fn pull(&mut self, _: &git::Pull, window: &mut Window, cx: &mut Context<Self>) {
         let Some(repo) = self.active_repository.clone() else {
             return;
@@ -1660,6 +1832,7 @@ impl GitPanel {
                             git_panel.clear_pending();
                         }
                         git_panel.update_visible_entries(cx);
+                        git_panel.update_stash_entries(cx);
                         git_panel.update_editor_placeholder(cx);
                     })
                     .ok();
@@ -1792,6 +1965,24 @@ impl GitPanel {
         cx.notify();
     }
 
+    fn update_stash_entries(&mut self, cx: &mut Context<Self>) {
+        let Some(repo) = self.active_repository.as_ref() else {
+            self.stash_entries.clear();
+            return;
+        };
+        let stash_list = repo.read(cx).stash_list();
+        self.update_stash_entries_task = cx.spawn(|this, mut cx| async move {
+            let Some(stash_entries) = maybe!(async { stash_list.await? }).await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.stash_entries = stash_entries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
     fn header_state(&self, header_type: Section) -> ToggleState {
         let (staged_count, count) = match header_type {
             Section::New => (self.new_staged_count, self.new_count),
@@ -2173,6 +2364,113 @@ impl GitPanel {
         )
     }
 
+    fn render_operation_in_progress(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let operation = self
+            .active_repository
+            .as_ref()?
+            .read(cx)
+            .operation_in_progress()?;
+
+        Some(
+            h_flex()
+                .flex_none()
+                .px(px(8.))
+                .py_1()
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border)
+                .child(
+                    Icon::new(IconName::Warning)
+                        .size(IconSize::Small)
+                        .color(Color::Warning),
+                )
+                .child(
+                    div()
+                        .flex_grow()
+                        .child(Label::new(operation.description()).size(LabelSize::Small)),
+                )
+                .child(
+                    Button::new("abort-operation", "Abort")
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.abort_operation(&AbortOperation, window, cx)
+                        })),
+                )
+                .child(
+                    Button::new("continue-operation", "Continue")
+                        .label_size(LabelSize::Small)
+                        .style(ButtonStyle::Filled)
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.continue_operation(&ContinueOperation, window, cx)
+                        })),
+                ),
+        )
+    }
+
+    fn render_stashes(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        if self.stash_entries.is_empty() {
+            return None;
+        }
+
+        Some(
+            v_flex()
+                .id("stash-list")
+                .flex_none()
+                .max_h(px(160.))
+                .overflow_y_scroll()
+                .border_t_1()
+                .border_color(cx.theme().colors().border)
+                .py_1()
+                .child(
+                    h_flex().px(px(8.)).h(px(24.)).child(
+                        Label::new(format!("Stashes ({})", self.stash_entries.len()))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+                )
+                .children(self.stash_entries.iter().map(|entry| {
+                    let index = entry.index;
+                    let entry = entry.clone();
+                    h_flex()
+                        .id(ElementId::Name(format!("stash_{}", index).into()))
+                        .px(px(8.))
+                        .h(px(24.))
+                        .gap_1p5()
+                        .hover(|this| this.bg(cx.theme().colors().ghost_element_hover))
+                        .child(
+                            Label::new(entry.reference())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                        .child(
+                            div().flex_grow().overflow_hidden().child(
+                                Label::new(entry.message.clone())
+                                    .size(LabelSize::Small)
+                                    .truncate(),
+                            ),
+                        )
+                        .on_click(cx.listener({
+                            let entry = entry.clone();
+                            move |this, _, window, cx| {
+                                this.open_stash_diff(&entry, window, cx);
+                            }
+                        }))
+                        .on_mouse_down(
+                            MouseButton::Right,
+                            cx.listener(move |this, event: &MouseDownEvent, window, cx| {
+                                this.deploy_stash_context_menu(
+                                    event.position,
+                                    entry.clone(),
+                                    window,
+                                    cx,
+                                );
+                                cx.stop_propagation();
+                            }),
+                        )
+                })),
+        )
+    }
+
     fn render_empty_state(&self, cx: &mut Context<Self>) -> impl IntoElement {
         h_flex()
             .h_full()
@@ -2421,6 +2719,7 @@ impl GitPanel {
             context_menu
                 .action(stage_title, ToggleStaged.boxed_clone())
                 .action(restore_title, git::RestoreFile.boxed_clone())
+                .action("Stash File", StashFile.boxed_clone())
                 .separator()
                 .action("Open Diff", Confirm.boxed_clone())
                 .action("Open File", SecondaryConfirm.boxed_clone())
@@ -2719,6 +3018,11 @@ impl Render for GitPanel {
             .on_action(cx.listener(Self::fetch))
             .on_action(cx.listener(Self::pull))
             .on_action(cx.listener(Self::push))
+            .on_action(cx.listener(Self::stash_all))
+            .on_action(cx.listener(Self::stash_file))
+            .on_action(cx.listener(Self::stash_pop))
+            .on_action(cx.listener(Self::continue_operation))
+            .on_action(cx.listener(Self::abort_operation))
             .when(has_write_access && has_co_authors, |git_panel| {
                 git_panel.on_action(cx.listener(Self::toggle_fill_co_authors))
             })
@@ -2745,6 +3049,8 @@ impl Render for GitPanel {
                             this.child(self.render_empty_state(cx).into_any_element())
                         }
                     })
+                    .children(self.render_operation_in_progress(cx))
+                    .children(self.render_stashes(cx))
                     .children(self.render_footer(window, cx))
                     .children(self.render_previous_commit(cx))
                     .into_any_element(),
@@ -1,4 +1,5 @@
 use ::settings::Settings;
+use commit_history::CommitHistory;
 use git::status::FileStatus;
 use git_panel_settings::GitPanelSettings;
 use gpui::App;
@@ -6,11 +7,15 @@ use project_diff::ProjectDiff;
 use ui::{ActiveTheme, Color, Icon, IconName, IntoElement};
 
 pub mod branch_picker;
+pub mod commit_history;
 mod commit_modal;
+pub mod commit_view;
+pub mod file_diff_view;
 pub mod git_panel;
 mod git_panel_settings;
 pub mod picker_prompt;
 pub mod project_diff;
+pub mod rebase_todo;
 mod remote_output_toast;
 pub mod repository_selector;
 
@@ -18,6 +23,7 @@ pub fn init(cx: &mut App) {
     GitPanelSettings::register(cx);
     branch_picker::init(cx);
     cx.observe_new(ProjectDiff::register).detach();
+    cx.observe_new(CommitHistory::register).detach();
     commit_modal::init(cx);
 }
 
@@ -0,0 +1,188 @@
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use git::repository::{format_rebase_todo, parse_rebase_todo, RebaseTodoEntry};
+use gpui::{
+    AnyElement, AnyView, App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable,
+    Render, Task, WeakEntity,
+};
+use language::Buffer;
+use project::{git::Repository, Project};
+use std::any::TypeId;
+use theme::ActiveTheme;
+use ui::prelude::*;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+/// An editable todo list for an interactive rebase. Saving it starts the rebase, and closing it
+/// without saving cancels it.
+pub struct RebaseTodoEditor {
+    base: SharedString,
+    repository: Entity<Repository>,
+    editor: Entity<Editor>,
+    started: bool,
+}
+
+pub enum Event {
+    Close,
+}
+
+impl RebaseTodoEditor {
+    /// Opens the todo list for rebasing the commits after `base` onto it.
+    pub fn open(
+        base: SharedString,
+        repository: Entity<Repository>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
+        let todo = repository.read(cx).rebase_todo(&base);
+        window
+            .spawn(cx, |mut cx| async move {
+                let entries = todo.await??;
+                if entries.is_empty() {
+                    return Err(anyhow!("There are no commits after {base} to rebase"));
+                }
+                workspace.update_in(&mut cx, |workspace, window, cx| {
+                    let todo_editor =
+                        cx.new(|cx| RebaseTodoEditor::new(base, &entries, repository, window, cx));
+                    workspace.add_item_to_active_pane(
+                        Box::new(todo_editor),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })
+            })
+            .detach_and_prompt_err("Failed to start rebase", window, cx, |e, _, _| {
+                Some(e.to_string())
+            });
+    }
+
+    fn new(
+        base: SharedString,
+        entries: &[RebaseTodoEntry],
+        repository: Entity<Repository>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let buffer = cx.new(|cx| Buffer::local(format_rebase_todo(entries), cx));
+        let editor = cx.new(|cx| Editor::for_buffer(buffer, None, window, cx));
+        editor.focus_handle(cx).focus(window);
+        Self {
+            base,
+            repository,
+            editor,
+            started: false,
+        }
+    }
+
+    fn short_base(&self) -> &str {
+        self.base.get(..git::SHORT_SHA_LENGTH).unwrap_or(&self.base)
+    }
+}
+
+impl EventEmitter<Event> for RebaseTodoEditor {}
+
+impl Focusable for RebaseTodoEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for RebaseTodoEditor {
+    type Event = Event;
+
+    fn to_item_events(event: &Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            Event::Close => f(ItemEvent::CloseItem),
+        }
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
+        Label::new(format!("Rebase onto {}", self.short_base()))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Rebase Todo Opened")
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        true
+    }
+
+    fn is_dirty(&self, _: &App) -> bool {
+        !self.started
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        !self.started
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        _project: Entity<Project>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let text = self.editor.read(cx).text(cx);
+        let entries = match parse_rebase_todo(&text) {
+            Ok(entries) => entries,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        if entries.is_empty() {
+            return Task::ready(Err(anyhow!("The rebase todo list is empty")));
+        }
+
+        self.started = true;
+        let rebase = self
+            .repository
+            .read(cx)
+            .rebase_interactive(self.base.to_string(), entries);
+        cx.spawn(|this, mut cx| async move {
+            // Once git has started, the rebase continues from the git panel even if it stopped
+            // on a conflict, so the todo list is no longer needed.
+            let result = rebase.await;
+            this.update(&mut cx, |_, cx| cx.emit(Event::Close))?;
+            result?
+        })
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for RebaseTodoEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .key_context("RebaseTodoEditor")
+            .bg(cx.theme().colors().editor_background)
+            .size_full()
+            .child(self.editor.clone())
+    }
+}
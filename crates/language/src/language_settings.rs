@@ -141,6 +141,14 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code with semantic tokens from language servers.
+    pub semantic_tokens: bool,
+    /// Whether to show code lenses from language servers above the code they apply to.
+    pub code_lens: bool,
+    /// Where the ranges that can be folded come from.
+    pub folding_ranges: RangeSource,
+    /// Where the ranges used to expand and shrink selections come from.
+    pub selection_ranges: RangeSource,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -455,6 +463,29 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code with semantic tokens provided by language servers,
+    /// on top of the tree-sitter based syntax highlighting.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Whether to show code lenses provided by language servers, such as reference
+    /// counts or "Run test" buttons, above the code they apply to.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
+    /// Where to get the ranges that can be folded from. When set to
+    /// `language_server`, folds are provided by the language server's
+    /// `textDocument/foldingRange` responses, including region markers
+    /// such as `#region`.
+    ///
+    /// Default: syntax
+    pub folding_ranges: Option<RangeSource>,
+    /// Where to get the ranges used by `editor::SelectLargerSyntaxNode` from.
+    /// When set to `language_server`, selections are expanded using the
+    /// language server's `textDocument/selectionRange` responses.
+    ///
+    /// Default: syntax
+    pub selection_ranges: Option<RangeSource>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -488,6 +519,17 @@ pub enum RewrapBehavior {
     Anywhere,
 }
 
+/// Where folding and selection ranges come from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RangeSource {
+    /// Use the indentation and the tree-sitter syntax tree.
+    #[default]
+    Syntax,
+    /// Use the language server, if it supports the request.
+    LanguageServer,
+}
+
 /// The contents of the edit prediction settings.
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct EditPredictionSettingsContent {
@@ -1327,6 +1369,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.code_lens, src.code_lens);
+    merge(&mut settings.folding_ranges, src.folding_ranges);
+    merge(&mut settings.selection_ranges, src.selection_ranges);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
@@ -0,0 +1,306 @@
+//! Keeps snapshots of files as they are saved or reloaded from disk, so that
+//! work lost between commits can be recovered.
+
+mod local_history_view;
+pub mod persistence;
+
+use anyhow::Result;
+use collections::HashMap;
+use editor::Editor;
+use gpui::{
+    actions, App, AppContext as _, Context, Entity, EntityId, EventEmitter, Global, Subscription,
+    Window,
+};
+use language::{Buffer, BufferEvent};
+use project::{buffer_store::BufferStoreEvent, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::path::PathBuf;
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::Workspace;
+
+pub use local_history_view::LocalHistoryView;
+use persistence::{SnapshotReason, DB};
+
+actions!(local_history, [ShowFileHistory]);
+
+pub fn init(cx: &mut App) {
+    LocalHistorySettings::register(cx);
+
+    let local_history = cx.new(|_| LocalHistory::default());
+    cx.set_global(GlobalLocalHistory(local_history.clone()));
+
+    cx.observe_new(move |workspace: &mut Workspace, _, cx| {
+        local_history.update(cx, |local_history, cx| {
+            local_history.add_project(workspace.project(), cx)
+        });
+        workspace.register_action(show_file_history);
+    })
+    .detach();
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LocalHistorySettings {
+    pub enabled: bool,
+    pub max_age_days: u32,
+    pub max_total_size_mb: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to keep snapshots of files when they're saved, or before they're
+    /// reloaded after changing on disk.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// How many days snapshots are kept for.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u32>,
+    /// The total size of all snapshots in megabytes, after which the oldest
+    /// snapshots are deleted.
+    ///
+    /// Default: 256
+    pub max_total_size_mb: Option<u64>,
+}
+
+impl Settings for LocalHistorySettings {
+    const KEY: Option<&'static str> = Some("local_history");
+
+    type FileContent = LocalHistorySettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub enum LocalHistoryEvent {
+    SnapshotSaved { abs_path: PathBuf },
+}
+
+struct GlobalLocalHistory(Entity<LocalHistory>);
+
+impl Global for GlobalLocalHistory {}
+
+/// Watches the buffers of local projects and records a snapshot of a buffer's
+/// file whenever it's saved, or is about to be reloaded from disk.
+#[derive(Default)]
+pub struct LocalHistory {
+    buffer_stores: HashMap<EntityId, [Subscription; 2]>,
+    buffers: HashMap<EntityId, [Subscription; 2]>,
+}
+
+impl EventEmitter<LocalHistoryEvent> for LocalHistory {}
+
+impl LocalHistory {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalLocalHistory>()
+            .map(|local_history| local_history.0.clone())
+    }
+
+    fn add_project(&mut self, project: &Entity<Project>, cx: &mut Context<Self>) {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return;
+        }
+        let buffer_store = project.buffer_store().clone();
+        let buffer_store_id = buffer_store.entity_id();
+        if self.buffer_stores.contains_key(&buffer_store_id) {
+            return;
+        }
+
+        for buffer in buffer_store.read(cx).buffers().collect::<Vec<_>>() {
+            self.add_buffer(&buffer, cx);
+        }
+        self.buffer_stores.insert(
+            buffer_store_id,
+            [
+                cx.subscribe(&buffer_store, |this, _, event, cx| {
+                    if let BufferStoreEvent::BufferAdded(buffer) = event {
+                        this.add_buffer(buffer, cx);
+                    }
+                }),
+                cx.observe_release(&buffer_store, move |this, _, _| {
+                    this.buffer_stores.remove(&buffer_store_id);
+                }),
+            ],
+        );
+    }
+
+    fn add_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let buffer_id = buffer.entity_id();
+        self.buffers.insert(
+            buffer_id,
+            [
+                cx.subscribe(buffer, |this, buffer, event, cx| match event {
+                    BufferEvent::Saved => this.snapshot_buffer(&buffer, SnapshotReason::Save, cx),
+                    // Emitted before the buffer's contents are replaced, so this is
+                    // the last chance to capture them.
+                    BufferEvent::ReloadNeeded => {
+                        this.snapshot_buffer(&buffer, SnapshotReason::Reload, cx)
+                    }
+                    _ => {}
+                }),
+                cx.observe_release(buffer, move |this, _, _| {
+                    this.buffers.remove(&buffer_id);
+                }),
+            ],
+        );
+    }
+
+    fn snapshot_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        reason: SnapshotReason,
+        cx: &mut Context<Self>,
+    ) {
+        let settings = LocalHistorySettings::get_global(cx);
+        if !settings.enabled {
+            return;
+        }
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        let max_total_size = settings.max_total_size_mb.saturating_mul(1024 * 1024);
+        if buffer.len() as u64 > max_total_size {
+            return;
+        }
+
+        let abs_path = file.abs_path(cx);
+        let contents = buffer.text();
+        let now = OffsetDateTime::now_utc();
+        let oldest = now - time::Duration::days(settings.max_age_days.into());
+        let save = cx.background_spawn(async move {
+            let saved = DB
+                .save_snapshot(abs_path.clone(), contents, reason, now)
+                .await?;
+            DB.prune(oldest, max_total_size).await?;
+            anyhow::Ok(saved.then_some(abs_path))
+        });
+        cx.spawn(|this, mut cx| async move {
+            if let Some(abs_path) = save.await.log_err().flatten() {
+                this.update(&mut cx, |_, cx| {
+                    cx.emit(LocalHistoryEvent::SnapshotSaved { abs_path })
+                })
+                .ok();
+            }
+        })
+        .detach();
+    }
+}
+
+fn show_file_history(
+    workspace: &mut Workspace,
+    _: &ShowFileHistory,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let Some(abs_path) = buffer
+        .read(cx)
+        .file()
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx))
+    else {
+        return;
+    };
+
+    let existing = workspace
+        .items_of_type::<LocalHistoryView>(cx)
+        .find(|view| view.read(cx).buffer() == &buffer);
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let project = workspace.project().clone();
+    let view = cx.new(|cx| LocalHistoryView::new(abs_path, buffer, project, window, cx));
+    workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_snapshots_on_save_and_reload(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/history"), json!({ "file.txt": "one\n" }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/history").as_ref()], cx).await;
+        let (_workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let abs_path = Path::new(path!("/history/file.txt"));
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer(abs_path, cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        // When the file changes on disk, its previous contents are kept.
+        fs.save(abs_path, &"two\n".into(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), "two\n");
+
+        buffer.update(cx, |buffer, cx| buffer.set_text("three\n", cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        // Saving contents identical to the latest snapshot doesn't add another one.
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let snapshots = DB.snapshots_for_path(abs_path).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|snapshot| (
+                    snapshot.reason,
+                    DB.snapshot_contents(snapshot.id).unwrap().unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (SnapshotReason::Save, "three\n".to_string()),
+                (SnapshotReason::Reload, "one\n".to_string()),
+            ]
+        );
+    }
+}
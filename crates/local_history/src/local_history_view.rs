@@ -0,0 +1,488 @@
+use crate::{
+    persistence::{Snapshot, DB},
+    LocalHistory, LocalHistoryEvent,
+};
+use anyhow::{anyhow, Result};
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent};
+use gpui::{
+    AnyElement, AnyView, App, AppContext as _, Entity, EventEmitter, FocusHandle, Focusable,
+    Render, Subscription, Task,
+};
+use language::{Buffer, BufferEvent, Capability};
+use multi_buffer::MultiBuffer;
+use project::Project;
+use std::{
+    any::{Any, TypeId},
+    path::PathBuf,
+    time::Duration,
+};
+use theme::ActiveTheme;
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt as _;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, TabContentParams},
+    searchable::SearchableItemHandle,
+    ItemNavHistory, ToolbarItemLocation,
+};
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Lists the snapshots kept for a file, and shows how the selected snapshot
+/// differs from the file's current contents.
+///
+/// The file's buffer stays editable, and any snapshot can be restored into it.
+pub struct LocalHistoryView {
+    abs_path: PathBuf,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+    diff: Entity<BufferDiff>,
+    /// Holds the contents of the selected snapshot, which the buffer is diffed against.
+    snapshot_buffer: Entity<Buffer>,
+    snapshots: Vec<Snapshot>,
+    selected_snapshot_id: Option<i64>,
+    local_timezone: UtcOffset,
+    load_snapshot_task: Task<Result<()>>,
+    recalculate_diff_task: Option<Task<Result<()>>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LocalHistoryView {
+    pub fn new(
+        abs_path: PathBuf,
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot_buffer = cx.new(|cx| {
+            let buffer = buffer.read(cx);
+            let language = buffer.language().cloned();
+            let language_registry = buffer.language_registry();
+            let mut snapshot_buffer = Buffer::local("", cx);
+            if let Some(language_registry) = language_registry {
+                snapshot_buffer.set_language_registry(language_registry);
+            }
+            snapshot_buffer.set_language(language, cx);
+            snapshot_buffer.set_capability(Capability::ReadOnly, cx);
+            snapshot_buffer
+        });
+        let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        let mut subscriptions = vec![cx.subscribe(&buffer, |this, _, event: &BufferEvent, cx| {
+            if let BufferEvent::Edited | BufferEvent::Reloaded = event {
+                this.recalculate_diff(cx);
+            }
+        })];
+        if let Some(local_history) = LocalHistory::global(cx) {
+            subscriptions.push(cx.subscribe(
+                &local_history,
+                |this, _, event: &LocalHistoryEvent, cx| match event {
+                    LocalHistoryEvent::SnapshotSaved { abs_path } => {
+                        if abs_path == &this.abs_path {
+                            this.load_snapshots(cx);
+                        }
+                    }
+                },
+            ));
+        }
+
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let mut this = Self {
+            abs_path,
+            buffer,
+            editor,
+            diff,
+            snapshot_buffer,
+            snapshots: Vec::new(),
+            selected_snapshot_id: None,
+            local_timezone: UtcOffset::from_whole_seconds(local_offset).unwrap(),
+            load_snapshot_task: Task::ready(Ok(())),
+            recalculate_diff_task: None,
+            _subscriptions: subscriptions,
+        };
+        this.load_snapshots(cx);
+        this
+    }
+
+    pub fn buffer(&self) -> &Entity<Buffer> {
+        &self.buffer
+    }
+
+    fn load_snapshots(&mut self, cx: &mut Context<Self>) {
+        self.snapshots = DB
+            .snapshots_for_path(&self.abs_path)
+            .log_err()
+            .unwrap_or_default();
+        let selected_snapshot_exists = self
+            .snapshots
+            .iter()
+            .any(|snapshot| Some(snapshot.id) == self.selected_snapshot_id);
+        if !selected_snapshot_exists {
+            if let Some(snapshot) = self.snapshots.first() {
+                self.select_snapshot(snapshot.id, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn select_snapshot(&mut self, id: i64, cx: &mut Context<Self>) {
+        self.selected_snapshot_id = Some(id);
+        let contents = cx.background_spawn(async move { DB.snapshot_contents(id) });
+        self.load_snapshot_task = cx.spawn(|this, mut cx| async move {
+            let contents = contents
+                .await?
+                .ok_or_else(|| anyhow!("snapshot {id} not found"))?;
+            this.update(&mut cx, |this, cx| {
+                this.snapshot_buffer
+                    .update(cx, |buffer, cx| buffer.set_text(contents, cx));
+                this.recalculate_diff(cx);
+            })
+        });
+        cx.notify();
+    }
+
+    fn restore_snapshot(&mut self, id: i64, window: &mut Window, cx: &mut Context<Self>) {
+        self.select_snapshot(id, cx);
+        let contents = cx.background_spawn(async move { DB.snapshot_contents(id) });
+        cx.spawn_in(window, |this, mut cx| async move {
+            let contents = contents
+                .await?
+                .ok_or_else(|| anyhow!("snapshot {id} not found"))?;
+            this.update_in(&mut cx, |this, window, cx| {
+                // Replace the text through the editor, so that restoring can be undone.
+                this.editor
+                    .update(cx, |editor, cx| editor.set_text(contents, window, cx));
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn recalculate_diff(&mut self, cx: &mut Context<Self>) {
+        if self.selected_snapshot_id.is_none() {
+            return;
+        }
+        let snapshot_buffer = self.snapshot_buffer.clone();
+        let buffer = self.buffer.clone();
+        let diff = self.diff.clone();
+        // Replacing the task drops any pending recalculation, so bursts of edits are debounced.
+        self.recalculate_diff_task = Some(cx.spawn(|_, mut cx| async move {
+            cx.background_executor()
+                .timer(RECALCULATE_DIFF_DEBOUNCE)
+                .await;
+            let snapshot = buffer.update(&mut cx, |buffer, _| buffer.text_snapshot())?;
+            diff.update(&mut cx, |diff, cx| {
+                diff.set_base_text(snapshot_buffer, snapshot, cx)
+            })?
+            .await?;
+            Ok(())
+        }));
+    }
+
+    fn file_name(&self) -> String {
+        self.abs_path
+            .file_name()
+            .unwrap_or(self.abs_path.as_os_str())
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn render_snapshot(&self, snapshot: &Snapshot, cx: &mut Context<Self>) -> impl IntoElement {
+        let id = snapshot.id;
+        let timestamp = time_format::format_localized_timestamp(
+            snapshot.timestamp,
+            OffsetDateTime::now_utc(),
+            self.local_timezone,
+            time_format::TimestampFormat::EnhancedAbsolute,
+        );
+
+        ListItem::new(("local-history-snapshot", id as u64))
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_snapshot_id == Some(id))
+            .child(
+                v_flex().child(Label::new(timestamp)).child(
+                    Label::new(snapshot.reason.label())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+            )
+            .end_hover_slot(
+                IconButton::new(("restore-snapshot", id as u64), IconName::RotateCcw)
+                    .icon_size(IconSize::Small)
+                    .tooltip(Tooltip::text("Restore This Version"))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.restore_snapshot(id, window, cx)
+                    })),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| this.select_snapshot(id, cx)))
+    }
+}
+
+impl EventEmitter<EditorEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Local history of {}", self.abs_path.display()).into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, _: &App) -> AnyElement {
+        Label::new(format!("History: {}", self.file_name()))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Local History Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let snapshots = self
+            .snapshots
+            .iter()
+            .map(|snapshot| self.render_snapshot(snapshot, cx))
+            .collect::<Vec<_>>();
+
+        h_flex()
+            .key_context("LocalHistoryView")
+            .bg(cx.theme().colors().editor_background)
+            .size_full()
+            .child(
+                v_flex()
+                    .id("local-history-snapshots")
+                    .flex_none()
+                    .w(rems(18.))
+                    .h_full()
+                    .p_1()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .overflow_y_scroll()
+                    .when(snapshots.is_empty(), |this| {
+                        this.child(
+                            div().px_2().py_1().child(
+                                Label::new("No history for this file yet").color(Color::Muted),
+                            ),
+                        )
+                    })
+                    .children(snapshots),
+            )
+            .child(div().flex_1().h_full().child(self.editor.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::SnapshotReason;
+    use editor::test::editor_test_context::assert_state_with_diff;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use unindent::Unindent as _;
+    use util::path;
+    use workspace::Workspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_local_history_view(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/history-view"),
+            json!({ "file.txt": "one\nTWO\nthree\n" }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/history-view").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let abs_path = PathBuf::from(path!("/history-view/file.txt"));
+        DB.save_snapshot(
+            abs_path.clone(),
+            "one\ntwo\nthree\n".into(),
+            SnapshotReason::Save,
+            OffsetDateTime::now_utc(),
+        )
+        .await
+        .unwrap();
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(Path::new(path!("/history-view/file.txt")), cx)
+            })
+            .await
+            .unwrap();
+        let view = workspace.update_in(cx, |workspace, window, cx| {
+            let view = cx.new(|cx| LocalHistoryView::new(abs_path, buffer, project, window, cx));
+            workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+            view
+        });
+        cx.executor().advance_clock(RECALCULATE_DIFF_DEBOUNCE);
+        cx.run_until_parked();
+
+        let (editor, snapshot_id) = view.read_with(cx, |view, _| {
+            (view.editor.clone(), view.selected_snapshot_id.unwrap())
+        });
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &"
+                ˇone
+              - two
+              + TWO
+                three
+            "
+            .unindent(),
+        );
+
+        view.update_in(cx, |view, window, cx| {
+            view.restore_snapshot(snapshot_id, window, cx)
+        });
+        cx.executor().advance_clock(RECALCULATE_DIFF_DEBOUNCE);
+        cx.run_until_parked();
+        assert_state_with_diff(
+            &editor,
+            cx,
+            &"
+                one
+                two
+                threeˇ
+            "
+            .unindent(),
+        );
+
+        // Restoring is an ordinary edit, which can be undone.
+        editor.update_in(cx, |editor, window, cx| {
+            editor.undo(&editor::actions::Undo, window, cx)
+        });
+        cx.executor().advance_clock(RECALCULATE_DIFF_DEBOUNCE);
+        cx.run_until_parked();
+        assert_eq!(
+            editor.read_with(cx, |editor, cx| editor.text(cx)),
+            "one\nTWO\nthree\n"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Result};
+use db::query;
+use db::sqlez::bindable::{Bind, Column, StaticColumnCount};
+use db::sqlez::domain::Domain;
+use db::sqlez::statement::Statement;
+use db::sqlez::thread_safe_connection::ThreadSafeConnection;
+use db::sqlez_macros::sql;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use time::OffsetDateTime;
+
+/// Why a snapshot of a file was taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapshotReason {
+    /// The buffer was saved.
+    Save,
+    /// The file changed on disk and the buffer was about to be reloaded.
+    Reload,
+}
+
+impl SnapshotReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SnapshotReason::Save => "Saved",
+            SnapshotReason::Reload => "Before reload",
+        }
+    }
+}
+
+impl StaticColumnCount for SnapshotReason {}
+
+impl Bind for SnapshotReason {
+    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
+        let reason = match self {
+            SnapshotReason::Save => "save",
+            SnapshotReason::Reload => "reload",
+        };
+        statement.bind(&reason, start_index)
+    }
+}
+
+impl Column for SnapshotReason {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (reason, next_index) = String::column(statement, start_index)?;
+        let reason = match reason.as_str() {
+            "save" => SnapshotReason::Save,
+            "reload" => SnapshotReason::Reload,
+            _ => return Err(anyhow!("invalid snapshot reason: {reason}")),
+        };
+        Ok((reason, next_index))
+    }
+}
+
+/// A stored version of a file, without its contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub id: i64,
+    pub timestamp: OffsetDateTime,
+    pub reason: SnapshotReason,
+    pub size: usize,
+}
+
+impl Column for Snapshot {
+    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
+        let (id, next_index) = i64::column(statement, start_index)?;
+        let (timestamp, next_index) = i64::column(statement, next_index)?;
+        let (reason, next_index) = SnapshotReason::column(statement, next_index)?;
+        let (size, next_index) = i64::column(statement, next_index)?;
+        Ok((
+            Snapshot {
+                id,
+                timestamp: OffsetDateTime::from_unix_timestamp(timestamp)?,
+                reason,
+                size: size as usize,
+            },
+            next_index,
+        ))
+    }
+}
+
+/// The name of the file that snapshots are stored in. It lives next to Zed's main database,
+/// in `<database dir>/0-<release channel>/`, e.g. `~/.local/share/zed/db/0-stable/` on Linux or
+/// `~/Library/Application Support/Zed/db/0-stable/` on macOS. Snapshots hold the full contents
+/// of files, so they're kept out of the main database to avoid bloating it and slowing down its
+/// writes.
+pub const LOCAL_HISTORY_DB_FILE_NAME: &str = "local_history.sqlite";
+
+pub struct LocalHistoryDb(ThreadSafeConnection<LocalHistoryDb>);
+
+impl std::ops::Deref for LocalHistoryDb {
+    type Target = ThreadSafeConnection<LocalHistoryDb>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Domain for LocalHistoryDb {
+    fn name() -> &'static str {
+        "LocalHistoryDb"
+    }
+
+    // Current schema shape using pseudo-rust syntax:
+    // local_history(
+    //   id: i64,
+    //   abs_path: PathBuf,
+    //   timestamp: i64, // Seconds since the unix epoch
+    //   reason: String, // "save" or "reload"
+    //   size: i64, // Length of the contents in bytes
+    //   contents: String,
+    // )
+    fn migrations() -> &'static [&'static str] {
+        &[sql!(
+            CREATE TABLE local_history(
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                abs_path BLOB NOT NULL,
+                timestamp INTEGER NOT NULL,
+                reason TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                contents TEXT NOT NULL
+            ) STRICT;
+
+            CREATE INDEX local_history_abs_path ON local_history(abs_path, id);
+        )]
+    }
+}
+
+#[cfg(test)]
+pub static DB: LazyLock<LocalHistoryDb> =
+    LazyLock::new(|| LocalHistoryDb(db::smol::block_on(db::open_test_db("DB"))));
+
+#[cfg(not(test))]
+pub static DB: LazyLock<LocalHistoryDb> = LazyLock::new(|| {
+    LocalHistoryDb(db::smol::block_on(db::open_db_file(
+        db::database_dir(),
+        db::RELEASE_CHANNEL.dev_name(),
+        LOCAL_HISTORY_DB_FILE_NAME,
+    )))
+});
+
+impl LocalHistoryDb {
+    query! {
+        pub fn snapshots_for_path(abs_path: &Path) -> Result<Vec<Snapshot>> {
+            SELECT id, timestamp, reason, size
+            FROM local_history
+            WHERE abs_path = ?
+            ORDER BY id DESC
+        }
+    }
+
+    query! {
+        pub fn snapshot_contents(id: i64) -> Result<Option<String>> {
+            SELECT contents FROM local_history WHERE id = ?
+        }
+    }
+
+    query! {
+        fn latest_contents_for_path(abs_path: &Path) -> Result<Option<String>> {
+            SELECT contents
+            FROM local_history
+            WHERE abs_path = ?
+            ORDER BY id DESC
+            LIMIT 1
+        }
+    }
+
+    query! {
+        async fn insert_snapshot(
+            abs_path: PathBuf,
+            timestamp: i64,
+            reason: SnapshotReason,
+            size: i64,
+            contents: String
+        ) -> Result<()> {
+            INSERT INTO local_history
+                (abs_path, timestamp, reason, size, contents)
+            VALUES
+                (?1, ?2, ?3, ?4, ?5)
+        }
+    }
+
+    query! {
+        async fn delete_snapshots_older_than(timestamp: i64) -> Result<()> {
+            DELETE FROM local_history WHERE timestamp < ?
+        }
+    }
+
+    // Keeps the newest snapshots whose combined size fits in the given budget.
+    query! {
+        async fn delete_snapshots_exceeding_size(max_total_size: i64) -> Result<()> {
+            DELETE FROM local_history
+            WHERE id IN (
+                SELECT id FROM (
+                    SELECT id, SUM(size) OVER (ORDER BY id DESC) AS total_size
+                    FROM local_history
+                )
+                WHERE total_size > ?
+            )
+        }
+    }
+
+    /// Stores the given contents as the newest snapshot of a file, unless they're
+    /// identical to the previous snapshot. Returns whether a snapshot was stored.
+    pub async fn save_snapshot(
+        &self,
+        abs_path: PathBuf,
+        contents: String,
+        reason: SnapshotReason,
+        timestamp: OffsetDateTime,
+    ) -> Result<bool> {
+        if self.latest_contents_for_path(&abs_path)?.as_ref() == Some(&contents) {
+            return Ok(false);
+        }
+        let size = contents.len() as i64;
+        self.insert_snapshot(abs_path, timestamp.unix_timestamp(), reason, size, contents)
+            .await?;
+        Ok(true)
+    }
+
+    /// Deletes snapshots taken before the given time, then the oldest snapshots
+    /// until the total size of all snapshots is within the given limit.
+    pub async fn prune(&self, oldest: OffsetDateTime, max_total_size: u64) -> Result<()> {
+        self.delete_snapshots_older_than(oldest.unix_timestamp())
+            .await?;
+        self.delete_snapshots_exceeding_size(max_total_size.min(i64::MAX as u64) as i64)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Duration;
+
+    #[gpui::test]
+    async fn test_save_and_prune_snapshots() {
+        let db = LocalHistoryDb(db::open_test_db("test_save_and_prune_snapshots").await);
+        let a = PathBuf::from("/a.txt");
+        let b = PathBuf::from("/b.txt");
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+
+        let saved = db
+            .save_snapshot(a.clone(), "one".into(), SnapshotReason::Save, now)
+            .await
+            .unwrap();
+        assert!(saved);
+        // Identical contents aren't stored twice.
+        let saved = db
+            .save_snapshot(a.clone(), "one".into(), SnapshotReason::Reload, now)
+            .await
+            .unwrap();
+        assert!(!saved);
+        db.save_snapshot(a.clone(), "two".into(), SnapshotReason::Reload, now)
+            .await
+            .unwrap();
+        db.save_snapshot(b.clone(), "three".into(), SnapshotReason::Save, now)
+            .await
+            .unwrap();
+
+        let snapshots = db.snapshots_for_path(&a).unwrap();
+        assert_eq!(
+            snapshots
+                .iter()
+                .map(|snapshot| (snapshot.reason, snapshot.size, snapshot.timestamp))
+                .collect::<Vec<_>>(),
+            [
+                (SnapshotReason::Reload, 3, now),
+                (SnapshotReason::Save, 3, now)
+            ]
+        );
+        assert_eq!(
+            db.snapshot_contents(snapshots[0].id).unwrap().as_deref(),
+            Some("two")
+        );
+
+        // The oldest snapshots are removed first once the size limit is exceeded.
+        db.prune(now - Duration::days(1), 8).await.unwrap();
+        assert_eq!(db.snapshots_for_path(&a).unwrap().len(), 1);
+        assert_eq!(db.snapshots_for_path(&b).unwrap().len(), 1);
+
+        db.prune(now + Duration::seconds(1), u64::MAX)
+            .await
+            .unwrap();
+        assert!(db.snapshots_for_path(&a).unwrap().is_empty());
+        assert!(db.snapshots_for_path(&b).unwrap().is_empty());
+    }
+}
@@ -15,6 +15,9 @@ pub enum ParsedMarkdownElement {
     /// A paragraph of text and other inline elements.
     Paragraph(MarkdownParagraph),
     HorizontalRule(Range<usize>),
+    FootnoteDefinition(ParsedMarkdownFootnoteDefinition),
+    /// An HTML `<details>` element, which can be expanded to show its children.
+    Details(ParsedMarkdownDetails),
 }
 
 impl ParsedMarkdownElement {
@@ -28,8 +31,11 @@ impl ParsedMarkdownElement {
             Self::Paragraph(text) => match text.get(0)? {
                 MarkdownParagraphChunk::Text(t) => t.source_range.clone(),
                 MarkdownParagraphChunk::Image(image) => image.source_range.clone(),
+                MarkdownParagraphChunk::Math(math) => math.source_range.clone(),
             },
             Self::HorizontalRule(range) => range.clone(),
+            Self::FootnoteDefinition(definition) => definition.source_range.clone(),
+            Self::Details(details) => details.source_range.clone(),
         })
     }
 
@@ -45,6 +51,8 @@ pub type MarkdownParagraph = Vec<MarkdownParagraphChunk>;
 pub enum MarkdownParagraphChunk {
     Text(ParsedMarkdownText),
     Image(Image),
+    /// A `$$…$$` display math expression, shown on its own line.
+    Math(ParsedMarkdownMath),
 }
 
 #[derive(Debug)]
@@ -144,9 +152,58 @@ impl ParsedMarkdownTableRow {
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ParsedMarkdownBlockQuote {
     pub source_range: Range<usize>,
+    /// The kind of GitHub alert this block quote represents, as in `> [!NOTE]`.
+    pub alert: Option<ParsedMarkdownAlertKind>,
     pub children: Vec<ParsedMarkdownElement>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParsedMarkdownAlertKind {
+    Note,
+    Tip,
+    Important,
+    Warning,
+    Caution,
+}
+
+impl ParsedMarkdownAlertKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Note => "Note",
+            Self::Tip => "Tip",
+            Self::Important => "Important",
+            Self::Warning => "Warning",
+            Self::Caution => "Caution",
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownFootnoteDefinition {
+    pub source_range: Range<usize>,
+    pub label: SharedString,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownDetails {
+    pub source_range: Range<usize>,
+    pub summary: MarkdownParagraph,
+    /// Whether the `open` attribute was given, making the details expanded by default.
+    pub open: bool,
+    pub children: Vec<ParsedMarkdownElement>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ParsedMarkdownMath {
+    pub source_range: Range<usize>,
+    /// The expression, with the TeX commands that have Unicode equivalents converted.
+    pub contents: SharedString,
+}
+
 #[derive(Debug, Clone)]
 pub struct ParsedMarkdownText {
     /// Where the text is located in the source Markdown document.
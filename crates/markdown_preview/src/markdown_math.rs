@@ -0,0 +1,417 @@
+//! The preview has no math layout engine, so TeX expressions are shown as text. Greek letters,
+//! common symbols, fractions, roots, and super- and subscripts are converted to their Unicode
+//! equivalents, and any other command is shown as its TeX source.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Converts a TeX math expression to Unicode text, e.g. `\alpha^2 \leq \frac{1}{n}` to
+/// `α² ≤ 1/n`.
+pub fn tex_to_unicode(tex: &str) -> String {
+    let mut chars = tex.chars().peekable();
+    let mut output = String::new();
+    convert(&mut chars, &mut output, false);
+    output.trim().to_string()
+}
+
+/// Converts the expression until the end of the input, or the end of the current group if
+/// `in_group` is set.
+fn convert(chars: &mut Peekable<Chars>, output: &mut String, in_group: bool) {
+    while let Some(char) = chars.next() {
+        match char {
+            '}' if in_group => return,
+            '{' => convert(chars, output, true),
+            '\\' => convert_command(chars, output),
+            '^' | '_' => {
+                let argument = read_argument(chars);
+                let to_script = if char == '^' { superscript } else { subscript };
+                match argument.chars().map(to_script).collect::<Option<String>>() {
+                    Some(script) => output.push_str(&script),
+                    None => {
+                        output.push(char);
+                        output.push_str(&parenthesize(argument));
+                    }
+                }
+            }
+            // Alignment points of environments like `aligned` have no equivalent.
+            '&' => {}
+            char if char.is_whitespace() => {
+                if !output.ends_with([' ', '\n']) {
+                    output.push(' ');
+                }
+            }
+            char => output.push(char),
+        }
+    }
+}
+
+/// Converts the command following a backslash.
+fn convert_command(chars: &mut Peekable<Chars>, output: &mut String) {
+    let mut name = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_ascii_alphabetic()) {
+        name.push(char);
+    }
+    if name.is_empty() {
+        if let Some(char) = chars.next() {
+            name.push(char);
+        }
+    }
+
+    if let Some(symbol) = symbol(&name) {
+        output.push_str(symbol);
+        return;
+    }
+
+    match name.as_str() {
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = read_argument(chars);
+            let denominator = read_argument(chars);
+            output.push_str(&parenthesize(numerator));
+            output.push('/');
+            output.push_str(&parenthesize(denominator));
+        }
+        "sqrt" => {
+            let mut index = String::new();
+            if chars.next_if_eq(&'[').is_some() {
+                while let Some(char) = chars.next_if(|char| *char != ']') {
+                    index.push(char);
+                }
+                chars.next();
+            }
+            let radicand = read_argument(chars);
+            match index.trim() {
+                "" => output.push('√'),
+                "3" => output.push('∛'),
+                "4" => output.push('∜'),
+                index => {
+                    match index.chars().map(superscript).collect::<Option<String>>() {
+                        Some(index) => output.push_str(&index),
+                        None => output.push_str(&format!("({index})")),
+                    }
+                    output.push('√');
+                }
+            }
+            output.push_str(&parenthesize(radicand));
+        }
+        "mathbb" => {
+            let argument = read_argument(chars);
+            output.extend(argument.chars().map(double_struck));
+        }
+        "text" | "textrm" | "textit" | "textbf" | "mathrm" | "mathit" | "mathbf" | "mathsf"
+        | "mathtt" | "mathcal" | "boldsymbol" | "operatorname" | "mbox" => {
+            output.push_str(&read_argument(chars));
+        }
+        "begin" | "end" => {
+            read_argument(chars);
+        }
+        "left" | "right" | "big" | "Big" | "bigg" | "Bigg" => {
+            // `\left.` and `\right.` are invisible delimiters.
+            chars.next_if_eq(&'.');
+        }
+        "displaystyle" | "textstyle" | "limits" | "nolimits" | "!" => {}
+        "," | ":" | ";" | " " | "quad" | "qquad" => {
+            if !output.ends_with(' ') {
+                output.push(' ');
+            }
+        }
+        "\\" | "newline" => {
+            let len = output.trim_end_matches(' ').len();
+            output.truncate(len);
+            output.push('\n');
+        }
+        "{" | "}" | "%" | "$" | "&" | "#" | "_" | "|" => output.push_str(&name),
+        "sin" | "cos" | "tan" | "sec" | "csc" | "cot" | "arcsin" | "arccos" | "arctan" | "sinh"
+        | "cosh" | "tanh" | "log" | "ln" | "lg" | "exp" | "lim" | "max" | "min" | "sup" | "inf"
+        | "det" | "dim" | "ker" | "gcd" | "deg" | "arg" | "Pr" | "mod" => {
+            output.push_str(&name);
+        }
+        _ => {
+            // Keep unsupported commands, along with their arguments, as written.
+            output.push('\\');
+            output.push_str(&name);
+            while chars.next_if_eq(&'{').is_some() {
+                output.push('{');
+                convert(chars, output, true);
+                output.push('}');
+            }
+        }
+    }
+}
+
+/// Reads the argument of a command or script: a group, a command, or a single character.
+fn read_argument(chars: &mut Peekable<Chars>) -> String {
+    while chars.next_if(|char| char.is_whitespace()).is_some() {}
+
+    let mut argument = String::new();
+    match chars.next() {
+        Some('{') => convert(chars, &mut argument, true),
+        Some('\\') => convert_command(chars, &mut argument),
+        Some(char) => argument.push(char),
+        None => {}
+    }
+    argument.trim().to_string()
+}
+
+/// Wraps text made of more than one character in parentheses, so that it can be used as an
+/// operand.
+fn parenthesize(text: String) -> String {
+    if text.chars().count() > 1 {
+        format!("({text})")
+    } else {
+        text
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "times" => "×",
+        "cdot" => "⋅",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "pm" => "±",
+        "mp" => "∓",
+        "div" => "÷",
+        "le" | "leq" => "≤",
+        "ge" | "geq" => "≥",
+        "ll" => "≪",
+        "gg" => "≫",
+        "ne" | "neq" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "to" | "rightarrow" => "→",
+        "gets" | "leftarrow" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" => "|",
+        "Vert" => "‖",
+        "prime" => "′",
+        "degree" => "°",
+        "angle" => "∠",
+        "triangle" => "△",
+        "therefore" => "∴",
+        "because" => "∵",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        _ => return None,
+    })
+}
+
+fn superscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        'T' => 'ᵀ',
+        '′' => '′',
+        '∗' | '*' => '*',
+        _ => return None,
+    })
+}
+
+fn subscript(char: char) -> Option<char> {
+    Some(match char {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn double_struck(char: char) -> char {
+    match char {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        char => char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::tex_to_unicode;
+
+    #[test]
+    fn test_tex_to_unicode() {
+        assert_eq!(tex_to_unicode("y = x^2"), "y = x²");
+        assert_eq!(tex_to_unicode("a_{i+1} = a_i^{n-1}"), "aᵢ₊₁ = aᵢⁿ⁻¹");
+        assert_eq!(tex_to_unicode(r"\alpha \leq \beta"), "α ≤ β");
+        assert_eq!(tex_to_unicode(r"\frac{1}{n+1}"), "1/(n+1)");
+        assert_eq!(tex_to_unicode(r"\sum_{i=1}^{n} i"), "∑ᵢ₌₁ⁿ i");
+        assert_eq!(tex_to_unicode(r"\sqrt{x^2 + 1}"), "√(x² + 1)");
+        assert_eq!(
+            tex_to_unicode(r"f: \mathbb{R}^n \to \mathbb{R}"),
+            "f: ℝⁿ → ℝ"
+        );
+        assert_eq!(tex_to_unicode(r"\text{if } x > 0"), "if x > 0");
+        // Scripts without Unicode equivalents are parenthesized.
+        assert_eq!(tex_to_unicode("e^{i q}"), "e^(i q)");
+        // Unsupported commands are shown as written.
+        assert_eq!(tex_to_unicode(r"\overline{z}"), r"\overline{z}");
+    }
+}
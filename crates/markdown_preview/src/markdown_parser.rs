@@ -1,9 +1,9 @@
-use crate::markdown_elements::*;
+use crate::{markdown_elements::*, markdown_math::tex_to_unicode};
 use async_recursion::async_recursion;
 use collections::FxHashMap;
-use gpui::FontWeight;
+use gpui::{FontWeight, SharedString};
 use language::LanguageRegistry;
-use pulldown_cmark::{Alignment, Event, Options, Parser, Tag, TagEnd};
+use pulldown_cmark::{Alignment, BlockQuoteKind, Event, Options, Parser, Tag, TagEnd};
 use std::{ops::Range, path::PathBuf, sync::Arc, vec};
 
 pub async fn parse_markdown(
@@ -100,6 +100,8 @@ impl<'a> MarkdownParser<'a> {
             // Represent an inline code block
             | Event::Code(_)
             | Event::Html(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
             | Event::FootnoteReference(_)
             | Event::Start(Tag::Link { .. })
             | Event::Start(Tag::Emphasis)
@@ -152,9 +154,10 @@ impl<'a> MarkdownParser<'a> {
                     let list = self.parse_list(order).await;
                     Some(list)
                 }
-                Tag::BlockQuote(_kind) => {
+                Tag::BlockQuote(kind) => {
+                    let alert = kind.map(Self::convert_alert_kind);
                     self.cursor += 1;
-                    let block_quote = self.parse_block_quote().await;
+                    let block_quote = self.parse_block_quote(alert).await;
                     Some(vec![ParsedMarkdownElement::BlockQuote(block_quote)])
                 }
                 Tag::CodeBlock(kind) => {
@@ -172,8 +175,28 @@ impl<'a> MarkdownParser<'a> {
                     self.cursor += 1;
 
                     let code_block = self.parse_code_block(language).await;
+                    // GitHub renders ```math blocks the same way as `$$` display math.
+                    if code_block.language.as_deref() == Some("math") {
+                        let math = ParsedMarkdownMath {
+                            source_range: code_block.source_range,
+                            contents: tex_to_unicode(&code_block.contents).into(),
+                        };
+                        return Some(vec![ParsedMarkdownElement::Paragraph(vec![
+                            MarkdownParagraphChunk::Math(math),
+                        ])]);
+                    }
                     Some(vec![ParsedMarkdownElement::CodeBlock(code_block)])
                 }
+                Tag::FootnoteDefinition(label) => {
+                    let label = SharedString::from(label.to_string());
+                    self.cursor += 1;
+                    let definition = self.parse_footnote_definition(label).await;
+                    Some(vec![ParsedMarkdownElement::FootnoteDefinition(definition)])
+                }
+                Tag::HtmlBlock => {
+                    self.cursor += 1;
+                    Some(self.parse_html_block().await)
+                }
                 _ => None,
             },
             Event::Rule => {
@@ -228,9 +251,62 @@ impl<'a> MarkdownParser<'a> {
                     text.push('\n');
                 }
 
-                // We want to ignore any inline HTML tags in the text but keep
+                // We want to ignore most inline HTML tags in the text but keep
                 // the text between them
-                Event::InlineHtml(_) => {}
+                Event::InlineHtml(html) => match html_tag_name(html).as_str() {
+                    "br" => text.push('\n'),
+                    "img" => {
+                        if let Some(image) = self.parse_html_image(html, source_range.clone()) {
+                            markdown_text_like.extend(take_text_chunk(
+                                &mut text,
+                                &mut highlights,
+                                &mut region_ranges,
+                                &mut regions,
+                                &source_range,
+                            ));
+                            markdown_text_like.push(MarkdownParagraphChunk::Image(image));
+                        }
+                    }
+                    _ => {}
+                },
+
+                Event::InlineMath(t) => {
+                    text.push_str(&tex_to_unicode(t.as_ref()));
+                    region_ranges.push(prev_len..text.len());
+                    regions.push(ParsedRegion {
+                        code: true,
+                        link: None,
+                    });
+                    highlights.push((
+                        prev_len..text.len(),
+                        MarkdownHighlight::Style(MarkdownHighlightStyle {
+                            italic: true,
+                            ..Default::default()
+                        }),
+                    ));
+                }
+
+                Event::DisplayMath(t) => {
+                    let (_, range) = self.current().unwrap();
+                    let math = ParsedMarkdownMath {
+                        source_range: range.clone(),
+                        contents: tex_to_unicode(t.as_ref()).into(),
+                    };
+                    markdown_text_like.extend(take_text_chunk(
+                        &mut text,
+                        &mut highlights,
+                        &mut region_ranges,
+                        &mut regions,
+                        &source_range,
+                    ));
+                    markdown_text_like.push(MarkdownParagraphChunk::Math(math));
+                }
+
+                Event::FootnoteReference(label) => {
+                    text.push('[');
+                    text.push_str(label.as_ref());
+                    text.push(']');
+                }
 
                 Event::Text(t) => {
                     text.push_str(t.as_ref());
@@ -317,20 +393,13 @@ impl<'a> MarkdownParser<'a> {
                     if let Some(image) = image.as_mut() {
                         text.truncate(text.len() - t.len());
                         image.set_alt_text(t.to_string().into());
-                        if !text.is_empty() {
-                            let parsed_regions = MarkdownParagraphChunk::Text(ParsedMarkdownText {
-                                source_range: source_range.clone(),
-                                contents: text.clone(),
-                                highlights: highlights.clone(),
-                                region_ranges: region_ranges.clone(),
-                                regions: regions.clone(),
-                            });
-                            text = String::new();
-                            highlights = vec![];
-                            region_ranges = vec![];
-                            regions = vec![];
-                            markdown_text_like.push(parsed_regions);
-                        }
+                        markdown_text_like.extend(take_text_chunk(
+                            &mut text,
+                            &mut highlights,
+                            &mut region_ranges,
+                            &mut regions,
+                            &source_range,
+                        ));
 
                         let parsed_image = MarkdownParagraphChunk::Image(image.clone());
                         markdown_text_like.push(parsed_image);
@@ -654,7 +723,10 @@ impl<'a> MarkdownParser<'a> {
     }
 
     #[async_recursion]
-    async fn parse_block_quote(&mut self) -> ParsedMarkdownBlockQuote {
+    async fn parse_block_quote(
+        &mut self,
+        alert: Option<ParsedMarkdownAlertKind>,
+    ) -> ParsedMarkdownBlockQuote {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
         let mut nested_depth = 1;
@@ -696,10 +768,243 @@ impl<'a> MarkdownParser<'a> {
 
         ParsedMarkdownBlockQuote {
             source_range,
+            alert,
             children,
         }
     }
 
+    fn convert_alert_kind(kind: BlockQuoteKind) -> ParsedMarkdownAlertKind {
+        match kind {
+            BlockQuoteKind::Note => ParsedMarkdownAlertKind::Note,
+            BlockQuoteKind::Tip => ParsedMarkdownAlertKind::Tip,
+            BlockQuoteKind::Important => ParsedMarkdownAlertKind::Important,
+            BlockQuoteKind::Warning => ParsedMarkdownAlertKind::Warning,
+            BlockQuoteKind::Caution => ParsedMarkdownAlertKind::Caution,
+        }
+    }
+
+    #[async_recursion]
+    async fn parse_footnote_definition(
+        &mut self,
+        label: SharedString,
+    ) -> ParsedMarkdownFootnoteDefinition {
+        let (_event, source_range) = self.previous().unwrap();
+        let source_range = source_range.clone();
+        let mut children = vec![];
+
+        while !self.eof() {
+            if let Some(Event::End(TagEnd::FootnoteDefinition)) = self.current_event() {
+                self.cursor += 1;
+                break;
+            }
+
+            if let Some(block) = self.parse_block().await {
+                children.extend(block);
+            } else {
+                self.cursor += 1;
+            }
+        }
+
+        ParsedMarkdownFootnoteDefinition {
+            source_range,
+            label,
+            children,
+        }
+    }
+
+    /// Parses an HTML block, of which only text, images and `<details>` elements are shown.
+    #[async_recursion]
+    async fn parse_html_block(&mut self) -> Vec<ParsedMarkdownElement> {
+        let (_event, source_range) = self.previous().unwrap();
+        let mut source_range = source_range.clone();
+        let html = self.parse_html_block_contents();
+
+        let Some(details_start) = find_ignore_ascii_case(&html, "<details") else {
+            let paragraph = self.parse_html(&html, source_range);
+            if paragraph.is_empty() {
+                return Vec::new();
+            }
+            return vec![ParsedMarkdownElement::Paragraph(paragraph)];
+        };
+
+        let mut elements = Vec::new();
+        let leading = self.parse_html(&html[..details_start], source_range.clone());
+        if !leading.is_empty() {
+            elements.push(ParsedMarkdownElement::Paragraph(leading));
+        }
+
+        let details = &html[details_start..];
+        let tag_end = details.find('>').map_or(details.len(), |end| end + 1);
+        let open = html_attribute(&details[..tag_end], "open").is_some();
+        let mut body = &details[tag_end..];
+
+        let mut summary = Vec::new();
+        if let Some(summary_start) = find_ignore_ascii_case(body, "<summary") {
+            let summary_html = &body[summary_start..];
+            let contents_start = summary_html
+                .find('>')
+                .map_or(summary_html.len(), |end| end + 1);
+            let contents_end = find_ignore_ascii_case(summary_html, "</summary>")
+                .unwrap_or(summary_html.len())
+                .max(contents_start);
+            summary = self.parse_html(
+                &summary_html[contents_start..contents_end],
+                source_range.clone(),
+            );
+            body = summary_html
+                .get(contents_end + "</summary>".len()..)
+                .unwrap_or("");
+        }
+        if summary.is_empty() {
+            summary = self.parse_html("Details", source_range.clone());
+        }
+
+        let mut children = Vec::new();
+        let closing_tag = find_ignore_ascii_case(body, "</details>");
+        let inline_body = self.parse_html(
+            &body[..closing_tag.unwrap_or(body.len())],
+            source_range.clone(),
+        );
+        if !inline_body.is_empty() {
+            children.push(ParsedMarkdownElement::Paragraph(inline_body));
+        }
+
+        // Markdown inside of `<details>` is only parsed when it's separated from the HTML by
+        // blank lines, making it a sequence of blocks up until the closing tag.
+        if closing_tag.is_none() {
+            while !self.eof() {
+                if let Some((Event::Start(Tag::HtmlBlock), range)) = self.current() {
+                    let range = range.clone();
+                    let html = self.peek_html_block_contents();
+                    if let Some(closing_tag) = find_ignore_ascii_case(&html, "</details>") {
+                        if find_ignore_ascii_case(&html, "<details").is_none() {
+                            self.cursor += 1;
+                            self.parse_html_block_contents();
+                            source_range.end = range.end;
+                            let trailing = self.parse_html(&html[..closing_tag], range);
+                            if !trailing.is_empty() {
+                                children.push(ParsedMarkdownElement::Paragraph(trailing));
+                            }
+                            break;
+                        }
+                    }
+                }
+
+                if let Some(block) = self.parse_block().await {
+                    if let Some(last) = block.last().and_then(|block| block.source_range()) {
+                        source_range.end = source_range.end.max(last.end);
+                    }
+                    children.extend(block);
+                } else {
+                    self.cursor += 1;
+                }
+            }
+        }
+
+        elements.push(ParsedMarkdownElement::Details(ParsedMarkdownDetails {
+            source_range,
+            summary,
+            open,
+            children,
+        }));
+        elements
+    }
+
+    /// Consumes the contents of an HTML block, along with its end tag.
+    fn parse_html_block_contents(&mut self) -> String {
+        let mut html = String::new();
+        while !self.eof() {
+            match self.current_event() {
+                Some(Event::Html(text)) => {
+                    html.push_str(text);
+                    self.cursor += 1;
+                }
+                Some(Event::End(TagEnd::HtmlBlock)) => {
+                    self.cursor += 1;
+                    break;
+                }
+                _ => break,
+            }
+        }
+        html
+    }
+
+    /// Returns the contents of the HTML block starting at the cursor, without consuming it.
+    fn peek_html_block_contents(&self) -> String {
+        self.tokens[self.cursor + 1..]
+            .iter()
+            .map_while(|(event, _)| match event {
+                Event::Html(text) => Some(text.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Reads the text and images out of a fragment of HTML, dropping all other markup.
+    fn parse_html(&self, html: &str, source_range: Range<usize>) -> MarkdownParagraph {
+        let mut chunks = Vec::new();
+        let mut text = String::new();
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            } else if rest.starts_with('<') {
+                let tag_end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                let tag = &rest[..tag_end];
+                match html_tag_name(tag).as_str() {
+                    "br" => text.push('\n'),
+                    "img" => {
+                        if let Some(image) = self.parse_html_image(tag, source_range.clone()) {
+                            chunks.extend(take_html_text_chunk(&mut text, &source_range));
+                            chunks.push(MarkdownParagraphChunk::Image(image));
+                        }
+                    }
+                    "p" | "div" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                        if !text.trim().is_empty() && !text.ends_with('\n') {
+                            text.push('\n');
+                        }
+                    }
+                    _ => {}
+                }
+                rest = &rest[tag_end..];
+            } else {
+                let text_end = rest.find('<').unwrap_or(rest.len());
+                let segment = decode_html_entities(&rest[..text_end]);
+                if segment.starts_with(char::is_whitespace)
+                    && !text.is_empty()
+                    && !text.ends_with(char::is_whitespace)
+                {
+                    text.push(' ');
+                }
+                let mut words = segment.split_whitespace().peekable();
+                let has_words = words.peek().is_some();
+                for (ix, word) in words.enumerate() {
+                    if ix > 0 {
+                        text.push(' ');
+                    }
+                    text.push_str(word);
+                }
+                if has_words && segment.ends_with(char::is_whitespace) {
+                    text.push(' ');
+                }
+                rest = &rest[text_end..];
+            }
+        }
+
+        chunks.extend(take_html_text_chunk(&mut text, &source_range));
+        chunks
+    }
+
+    fn parse_html_image(&self, tag: &str, source_range: Range<usize>) -> Option<Image> {
+        let src = html_attribute(tag, "src")?;
+        let mut image = Image::identify(src, source_range, self.file_location_directory.clone())?;
+        if let Some(alt_text) = html_attribute(tag, "alt").filter(|alt| !alt.is_empty()) {
+            image.set_alt_text(alt_text.into());
+        }
+        Some(image)
+    }
+
     async fn parse_code_block(&mut self, language: Option<String>) -> ParsedMarkdownCodeBlock {
         let (_event, source_range) = self.previous().unwrap();
         let source_range = source_range.clone();
@@ -745,6 +1050,116 @@ impl<'a> MarkdownParser<'a> {
     }
 }
 
+/// Moves the text parsed so far into a new chunk, so that a non-text chunk can follow it.
+fn take_text_chunk(
+    text: &mut String,
+    highlights: &mut Vec<(Range<usize>, MarkdownHighlight)>,
+    region_ranges: &mut Vec<Range<usize>>,
+    regions: &mut Vec<ParsedRegion>,
+    source_range: &Range<usize>,
+) -> Option<MarkdownParagraphChunk> {
+    if text.is_empty() {
+        return None;
+    }
+    Some(MarkdownParagraphChunk::Text(ParsedMarkdownText {
+        source_range: source_range.clone(),
+        contents: std::mem::take(text),
+        highlights: std::mem::take(highlights),
+        region_ranges: std::mem::take(region_ranges),
+        regions: std::mem::take(regions),
+    }))
+}
+
+fn take_html_text_chunk(
+    text: &mut String,
+    source_range: &Range<usize>,
+) -> Option<MarkdownParagraphChunk> {
+    let contents = text.trim().to_string();
+    text.clear();
+    if contents.is_empty() {
+        return None;
+    }
+    Some(MarkdownParagraphChunk::Text(ParsedMarkdownText {
+        source_range: source_range.clone(),
+        contents,
+        highlights: Vec::new(),
+        region_ranges: Vec::new(),
+        regions: Vec::new(),
+    }))
+}
+
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(needle)
+}
+
+/// Returns the lowercased name of an HTML tag such as `<img src="…">` or `</details>`.
+fn html_tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Returns the value of an attribute of an HTML tag, or an empty string for an attribute
+/// without a value, such as `open` in `<details open>`.
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    let inner = tag
+        .strip_prefix('<')?
+        .trim_end_matches('>')
+        .trim_end_matches('/');
+    let mut rest = inner.trim_start_matches(|c: char| !c.is_whitespace());
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+
+        let key_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = rest[key_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start();
+            match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let value_end = after_equals[1..]
+                        .find(quote)
+                        .map_or(after_equals.len(), |end| end + 1);
+                    value = &after_equals[1..value_end];
+                    rest = after_equals.get(value_end + 1..).unwrap_or("");
+                }
+                _ => {
+                    let value_end = after_equals
+                        .find(char::is_whitespace)
+                        .unwrap_or(after_equals.len());
+                    value = &after_equals[..value_end];
+                    rest = &after_equals[value_end..];
+                }
+            }
+        }
+
+        if key.eq_ignore_ascii_case(name) {
+            return Some(decode_html_entities(value));
+        }
+    }
+}
+
+fn decode_html_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&amp;", "&")
+}
+
 #[cfg(test)]
 mod tests {
     use core::panic;
@@ -1276,6 +1691,121 @@ More text
         );
     }
 
+    #[gpui::test]
+    async fn test_alert() {
+        let parsed = parse("> [!WARNING]\n> Mind the gap.\n").await;
+
+        let [ParsedMarkdownElement::BlockQuote(block_quote)] = parsed.children.as_slice() else {
+            panic!("expected a block quote, got {:?}", parsed.children);
+        };
+        assert_eq!(block_quote.alert, Some(ParsedMarkdownAlertKind::Warning));
+        assert_eq!(paragraph_texts(&block_quote.children), ["Mind the gap."]);
+    }
+
+    #[gpui::test]
+    async fn test_footnotes() {
+        let parsed = parse("Zed[^editor] is fast.\n\n[^editor]: A code editor.\n").await;
+
+        let [ParsedMarkdownElement::Paragraph(paragraph), ParsedMarkdownElement::FootnoteDefinition(definition)] =
+            parsed.children.as_slice()
+        else {
+            panic!(
+                "expected a paragraph and a footnote, got {:?}",
+                parsed.children
+            );
+        };
+        assert_eq!(chunk_texts(paragraph), ["Zed[editor] is fast."]);
+        assert_eq!(definition.label, "editor");
+        assert_eq!(paragraph_texts(&definition.children), ["A code editor."]);
+    }
+
+    #[gpui::test]
+    async fn test_math() {
+        let parsed = parse("Where $x^2$ is positive:\n\n$$\ny = x^2\n$$\n").await;
+
+        let [ParsedMarkdownElement::Paragraph(inline), ParsedMarkdownElement::Paragraph(display)] =
+            parsed.children.as_slice()
+        else {
+            panic!("expected two paragraphs, got {:?}", parsed.children);
+        };
+        assert_eq!(chunk_texts(inline), ["Where x² is positive:"]);
+        let [MarkdownParagraphChunk::Math(math)] = display.as_slice() else {
+            panic!("expected display math, got {:?}", display);
+        };
+        assert_eq!(math.contents, "y = x²");
+    }
+
+    #[gpui::test]
+    async fn test_html_details() {
+        let parsed = parse(
+            "\
+<details open>
+<summary>More <b>info</b></summary>
+
+Hidden *text*.
+
+</details>
+
+After
+",
+        )
+        .await;
+
+        let [ParsedMarkdownElement::Details(details), ParsedMarkdownElement::Paragraph(after)] =
+            parsed.children.as_slice()
+        else {
+            panic!(
+                "expected details and a paragraph, got {:?}",
+                parsed.children
+            );
+        };
+        assert!(details.open);
+        assert_eq!(chunk_texts(&details.summary), ["More info"]);
+        assert_eq!(paragraph_texts(&details.children), ["Hidden text."]);
+        assert_eq!(chunk_texts(after), ["After"]);
+    }
+
+    #[gpui::test]
+    async fn test_html_images() {
+        let parsed = parse(
+            "<p align=\"center\">\n  <img src=\"https://zed.dev/logo.png\" alt=\"Zed\">\n</p>\n\nInline <img src='https://zed.dev/icon.png'> image\n",
+        )
+        .await;
+
+        let [ParsedMarkdownElement::Paragraph(block), ParsedMarkdownElement::Paragraph(inline)] =
+            parsed.children.as_slice()
+        else {
+            panic!("expected two paragraphs, got {:?}", parsed.children);
+        };
+        let [MarkdownParagraphChunk::Image(image)] = block.as_slice() else {
+            panic!("expected an image, got {:?}", block);
+        };
+        assert_eq!(image.link.to_string(), "https://zed.dev/logo.png");
+        assert_eq!(image.alt_text, Some("Zed".into()));
+
+        let [MarkdownParagraphChunk::Text(before), MarkdownParagraphChunk::Image(image), MarkdownParagraphChunk::Text(after)] =
+            inline.as_slice()
+        else {
+            panic!("expected text around an image, got {:?}", inline);
+        };
+        assert_eq!(before.contents, "Inline ");
+        assert_eq!(image.link.to_string(), "https://zed.dev/icon.png");
+        assert_eq!(after.contents, " image");
+    }
+
+    #[test]
+    fn test_html_attribute() {
+        let tag = r#"<img width=100 src="a &amp; b.png" alt='x'>"#;
+        assert_eq!(html_attribute(tag, "src").as_deref(), Some("a & b.png"));
+        assert_eq!(html_attribute(tag, "ALT").as_deref(), Some("x"));
+        assert_eq!(html_attribute(tag, "width").as_deref(), Some("100"));
+        assert_eq!(html_attribute(tag, "height"), None);
+        assert_eq!(
+            html_attribute("<details open>", "open").as_deref(),
+            Some("")
+        );
+    }
+
     #[gpui::test]
     async fn test_code_block() {
         let parsed = parse(
@@ -1382,12 +1912,33 @@ fn main() {
         })]
     }
 
+    fn chunk_texts(paragraph: &MarkdownParagraph) -> Vec<&str> {
+        paragraph
+            .iter()
+            .filter_map(|chunk| match chunk {
+                MarkdownParagraphChunk::Text(text) => Some(text.contents.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn paragraph_texts(elements: &[ParsedMarkdownElement]) -> Vec<&str> {
+        elements
+            .iter()
+            .flat_map(|element| match element {
+                ParsedMarkdownElement::Paragraph(paragraph) => chunk_texts(paragraph),
+                _ => Vec::new(),
+            })
+            .collect()
+    }
+
     fn block_quote(
         children: Vec<ParsedMarkdownElement>,
         source_range: Range<usize>,
     ) -> ParsedMarkdownElement {
         ParsedMarkdownElement::BlockQuote(ParsedMarkdownBlockQuote {
             source_range,
+            alert: None,
             children,
         })
     }
@@ -2,6 +2,7 @@ use gpui::{actions, App};
 use workspace::Workspace;
 
 pub mod markdown_elements;
+mod markdown_math;
 pub mod markdown_parser;
 pub mod markdown_preview_view;
 pub mod markdown_renderer;
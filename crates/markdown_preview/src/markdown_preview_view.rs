@@ -3,6 +3,7 @@ use std::time::Duration;
 use std::{ops::Range, path::PathBuf};
 
 use anyhow::Result;
+use collections::HashSet;
 use editor::scroll::{Autoscroll, AutoscrollStrategy};
 use editor::{Editor, EditorEvent};
 use gpui::{
@@ -37,6 +38,7 @@ pub struct MarkdownPreviewView {
     fallback_tab_description: SharedString,
     language_registry: Arc<LanguageRegistry>,
     parsing_markdown_task: Option<Task<Result<()>>>,
+    toggled_details: HashSet<usize>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -184,6 +186,14 @@ impl MarkdownPreviewView {
                                                 }
                                             })
                                         }
+                                    })
+                                    .with_toggled_details(this.toggled_details.clone(), {
+                                        let view = view.clone();
+                                        move |details_start, _, cx| {
+                                            view.update(cx, |view, cx| {
+                                                view.toggle_details(details_start, cx)
+                                            })
+                                        }
                                     });
                             let block = contents.children.get(ix).unwrap();
                             let rendered_block = render_markdown_block(block, &mut render_cx);
@@ -258,6 +268,7 @@ impl MarkdownPreviewView {
                 fallback_tab_description: fallback_description
                     .unwrap_or_else(|| "Markdown Preview".into()),
                 parsing_markdown_task: None,
+                toggled_details: HashSet::default(),
             };
 
             this.set_editor(active_editor, window, cx);
@@ -311,6 +322,7 @@ impl MarkdownPreviewView {
                 return;
             }
         }
+        self.toggled_details.clear();
 
         let subscription = cx.subscribe_in(
             &editor,
@@ -464,6 +476,24 @@ impl MarkdownPreviewView {
         block_index.unwrap_or_default()
     }
 
+    fn toggle_details(&mut self, details_start: usize, cx: &mut Context<Self>) {
+        if !self.toggled_details.remove(&details_start) {
+            self.toggled_details.insert(details_start);
+        }
+
+        // Re-measure the block containing the details, whose height has changed.
+        if let Some(ix) = self.contents.as_ref().and_then(|contents| {
+            contents.children.iter().position(|block| {
+                block
+                    .source_range()
+                    .map_or(false, |range| range.contains(&details_start))
+            })
+        }) {
+            self.list_state.splice(ix..ix + 1, 1);
+        }
+        cx.notify();
+    }
+
     fn should_apply_padding_between(
         current_block: &ParsedMarkdownElement,
         next_block: Option<&ParsedMarkdownElement>,
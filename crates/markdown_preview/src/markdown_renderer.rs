@@ -1,9 +1,11 @@
 use crate::markdown_elements::{
     HeadingLevel, Link, MarkdownParagraph, MarkdownParagraphChunk, ParsedMarkdown,
-    ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock, ParsedMarkdownElement,
-    ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownTable,
-    ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
+    ParsedMarkdownAlertKind, ParsedMarkdownBlockQuote, ParsedMarkdownCodeBlock,
+    ParsedMarkdownDetails, ParsedMarkdownElement, ParsedMarkdownFootnoteDefinition,
+    ParsedMarkdownHeading, ParsedMarkdownListItem, ParsedMarkdownListItemType, ParsedMarkdownMath,
+    ParsedMarkdownTable, ParsedMarkdownTableAlignment, ParsedMarkdownTableRow,
 };
+use collections::HashSet;
 use gpui::{
     div, img, px, rems, AbsoluteLength, AnyElement, App, AppContext as _, ClipboardItem, Context,
     DefiniteLength, Div, Element, ElementId, Entity, HighlightStyle, Hsla, ImageSource,
@@ -19,13 +21,14 @@ use std::{
 use theme::{ActiveTheme, SyntaxTheme, ThemeSettings};
 use ui::{
     h_flex, relative, tooltip_container, v_flex, ButtonCommon, Checkbox, Clickable, Color,
-    FluentBuilder, IconButton, IconName, IconSize, InteractiveElement, Label, LabelCommon,
+    FluentBuilder, Icon, IconButton, IconName, IconSize, InteractiveElement, Label, LabelCommon,
     LabelSize, LinkPreview, StatefulInteractiveElement, StyledExt, StyledImage, ToggleState,
     Tooltip, VisibleOnHover,
 };
 use workspace::Workspace;
 
 type CheckboxClickedCallback = Arc<Box<dyn Fn(bool, Range<usize>, &mut Window, &mut App)>>;
+type DetailsToggledCallback = Arc<Box<dyn Fn(usize, &mut Window, &mut App)>>;
 
 #[derive(Clone)]
 pub struct RenderContext {
@@ -39,9 +42,18 @@ pub struct RenderContext {
     text_muted_color: Hsla,
     code_block_background_color: Hsla,
     code_span_background_color: Hsla,
+    info_color: Hsla,
+    success_color: Hsla,
+    hint_color: Hsla,
+    warning_color: Hsla,
+    error_color: Hsla,
     syntax_theme: Arc<SyntaxTheme>,
     indent: usize,
     checkbox_clicked_callback: Option<CheckboxClickedCallback>,
+    /// The `<details>` elements, identified by where they start in the source, whose
+    /// expansion has been toggled from how the document specifies it.
+    toggled_details: HashSet<usize>,
+    details_toggled_callback: Option<DetailsToggledCallback>,
 }
 
 impl RenderContext {
@@ -70,7 +82,14 @@ impl RenderContext {
             text_muted_color: theme.colors().text_muted,
             code_block_background_color: theme.colors().surface_background,
             code_span_background_color: theme.colors().editor_document_highlight_read_background,
+            info_color: theme.status().info,
+            success_color: theme.status().success,
+            hint_color: theme.status().hint,
+            warning_color: theme.status().warning,
+            error_color: theme.status().error,
             checkbox_clicked_callback: None,
+            toggled_details: HashSet::default(),
+            details_toggled_callback: None,
         }
     }
 
@@ -82,6 +101,16 @@ impl RenderContext {
         self
     }
 
+    pub fn with_toggled_details(
+        mut self,
+        toggled_details: HashSet<usize>,
+        callback: impl Fn(usize, &mut Window, &mut App) + 'static,
+    ) -> Self {
+        self.toggled_details = toggled_details;
+        self.details_toggled_callback = Some(Arc::new(Box::new(callback)));
+        self
+    }
+
     fn next_id(&mut self, span: &Range<usize>) -> ElementId {
         let id = format!("markdown-{}-{}-{}", self.next_id, span.start, span.end);
         self.next_id += 1;
@@ -136,6 +165,8 @@ pub fn render_markdown_block(block: &ParsedMarkdownElement, cx: &mut RenderConte
         BlockQuote(block_quote) => render_markdown_block_quote(block_quote, cx),
         CodeBlock(code_block) => render_markdown_code_block(code_block, cx),
         HorizontalRule(_) => render_markdown_rule(cx),
+        FootnoteDefinition(definition) => render_markdown_footnote_definition(definition, cx),
+        Details(details) => render_markdown_details(details, cx),
     }
 }
 
@@ -202,18 +233,14 @@ fn render_markdown_list_item(
                                     _ => return,
                                 };
 
-                                if window.modifiers().secondary() {
-                                    callback(checked, range.clone(), window, cx);
-                                }
+                                callback(checked, range.clone(), window, cx);
                             }
                         })
                     },
                 ),
             )
             .hover(|s| s.cursor_pointer())
-            .tooltip(|_, cx| {
-                InteractiveMarkdownElementTooltip::new(None, "toggle checkbox", cx).into()
-            })
+            .tooltip(Tooltip::text("Toggle task"))
             .into_any_element(),
     };
     let bullet = div().mr_2().child(bullet);
@@ -239,6 +266,7 @@ fn paragraph_len(paragraphs: &MarkdownParagraph) -> usize {
             MarkdownParagraphChunk::Text(text) => text.contents.len(),
             // TODO: Scale column width based on image size
             MarkdownParagraphChunk::Image(_) => 1,
+            MarkdownParagraphChunk::Math(math) => math.contents.len(),
         })
         .sum()
 }
@@ -354,17 +382,119 @@ fn render_markdown_block_quote(
 
     cx.indent -= 1;
 
+    let (border_color, alert_title) = match parsed.alert {
+        Some(kind) => {
+            let (color, icon) = match kind {
+                ParsedMarkdownAlertKind::Note => (cx.info_color, IconName::Info),
+                ParsedMarkdownAlertKind::Tip => (cx.success_color, IconName::Sparkle),
+                ParsedMarkdownAlertKind::Important => (cx.hint_color, IconName::MessageBubbles),
+                ParsedMarkdownAlertKind::Warning => (cx.warning_color, IconName::Warning),
+                ParsedMarkdownAlertKind::Caution => (cx.error_color, IconName::XCircle),
+            };
+            let title = h_flex()
+                .gap_1()
+                .pb_1()
+                .child(
+                    Icon::new(icon)
+                        .size(IconSize::Small)
+                        .color(Color::Custom(color)),
+                )
+                .child(Label::new(kind.label()).color(Color::Custom(color)));
+            (color, Some(title))
+        }
+        None => (cx.border_color, None),
+    };
+
     cx.with_common_p(div())
         .child(
             div()
                 .border_l_4()
-                .border_color(cx.border_color)
+                .border_color(border_color)
                 .pl_3()
+                .children(alert_title)
                 .children(children),
         )
         .into_any()
 }
 
+fn render_markdown_footnote_definition(
+    parsed: &ParsedMarkdownFootnoteDefinition,
+    cx: &mut RenderContext,
+) -> AnyElement {
+    let children: Vec<AnyElement> = parsed
+        .children
+        .iter()
+        .map(|child| render_markdown_block(child, cx))
+        .collect();
+
+    cx.with_common_p(h_flex())
+        .items_start()
+        .gap_2()
+        .text_sm()
+        .child(
+            div()
+                .text_color(cx.text_muted_color)
+                .child(format!("{}.", parsed.label)),
+        )
+        .child(div().w_full().children(children))
+        .into_any()
+}
+
+fn render_markdown_details(parsed: &ParsedMarkdownDetails, cx: &mut RenderContext) -> AnyElement {
+    let expanded = parsed.open != cx.toggled_details.contains(&parsed.source_range.start);
+
+    let summary = h_flex()
+        .id(cx.next_id(&parsed.source_range))
+        .gap_1()
+        .cursor_pointer()
+        .child(
+            Icon::new(if expanded {
+                IconName::ChevronDown
+            } else {
+                IconName::ChevronRight
+            })
+            .size(IconSize::Small)
+            .color(Color::Muted),
+        )
+        .children(render_markdown_text(&parsed.summary, cx))
+        .when_some(cx.details_toggled_callback.clone(), |this, callback| {
+            let start = parsed.source_range.start;
+            this.on_click(move |_, window, cx| callback(start, window, cx))
+        });
+
+    let children: Vec<AnyElement> = if expanded {
+        parsed
+            .children
+            .iter()
+            .map(|child| render_markdown_block(child, cx))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    cx.with_common_p(v_flex())
+        .gap_2()
+        .child(summary)
+        .when(expanded, |this| {
+            this.child(v_flex().gap_2().pl_5().children(children))
+        })
+        .into_any()
+}
+
+fn render_markdown_math(parsed: &ParsedMarkdownMath, cx: &RenderContext) -> AnyElement {
+    div()
+        .w_full()
+        .flex()
+        .justify_center()
+        .px_3()
+        .py_2()
+        .font_family(cx.buffer_font_family.clone())
+        .bg(cx.code_block_background_color)
+        .rounded_md()
+        .child(StyledText::new(parsed.contents.clone()))
+        .into_any()
+}
+
 fn render_markdown_code_block(
     parsed: &ParsedMarkdownCodeBlock,
     cx: &mut RenderContext,
@@ -557,6 +687,10 @@ fn render_markdown_text(parsed_new: &MarkdownParagraph, cx: &mut RenderContext)
                     .into_any();
                 any_element.push(image_element);
             }
+
+            MarkdownParagraphChunk::Math(math) => {
+                any_element.push(render_markdown_math(math, cx));
+            }
         }
     }
 
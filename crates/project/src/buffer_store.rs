@@ -1926,6 +1926,7 @@ impl BufferStore {
         query: &SearchQuery,
         mut limit: usize,
         fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
         cx: &mut Context<Self>,
     ) -> Receiver<Entity<Buffer>> {
         let (tx, rx) = smol::channel::unbounded();
@@ -1945,7 +1946,14 @@ impl BufferStore {
         let project_paths_rx = self
             .worktree_store
             .update(cx, |worktree_store, cx| {
-                worktree_store.find_search_candidates(query.clone(), limit, open_buffers, fs, cx)
+                worktree_store.find_search_candidates(
+                    query.clone(),
+                    limit,
+                    open_buffers,
+                    fs,
+                    languages,
+                    cx,
+                )
             })
             .chunks(MAX_CONCURRENT_BUFFER_OPENS);
 
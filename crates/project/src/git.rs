@@ -5,7 +5,10 @@ use anyhow::{Context as _, Result};
 use client::ProjectId;
 use futures::channel::{mpsc, oneshot};
 use futures::StreamExt as _;
-use git::repository::{Branch, CommitDetails, PushOptions, Remote, RemoteCommandOutput, ResetMode};
+use git::repository::{
+    Branch, CommitDetails, CommitFile, LogEntry, LogOptions, OperationInProgress, PushOptions,
+    RebaseAction, RebaseTodoEntry, Remote, RemoteCommandOutput, ResetMode, StashEntry,
+};
 use git::repository::{GitRepository, RepoPath};
 use gpui::{
     App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Subscription, Task,
@@ -104,7 +107,19 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_commit);
         client.add_entity_request_handler(Self::handle_reset);
         client.add_entity_request_handler(Self::handle_show);
+        client.add_entity_request_handler(Self::handle_log);
+        client.add_entity_request_handler(Self::handle_commit_files);
         client.add_entity_request_handler(Self::handle_checkout_files);
+        client.add_entity_request_handler(Self::handle_stash_push);
+        client.add_entity_request_handler(Self::handle_stash_list);
+        client.add_entity_request_handler(Self::handle_stash_apply);
+        client.add_entity_request_handler(Self::handle_stash_drop);
+        client.add_entity_request_handler(Self::handle_stash_diff);
+        client.add_entity_request_handler(Self::handle_rebase_todo);
+        client.add_entity_request_handler(Self::handle_rebase);
+        client.add_entity_request_handler(Self::handle_cherry_pick);
+        client.add_entity_request_handler(Self::handle_continue_operation);
+        client.add_entity_request_handler(Self::handle_abort_operation);
         client.add_entity_request_handler(Self::handle_open_commit_message_buffer);
         client.add_entity_request_handler(Self::handle_set_index_text);
     }
@@ -483,6 +498,74 @@ impl GitStore {
         })
     }
 
+    async fn handle_log(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitLog>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitLogResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let payload = envelope.payload;
+        let options = LogOptions {
+            skip: payload.skip as usize,
+            limit: payload.limit as usize,
+            path: payload.path.as_deref().map(RepoPath::from_str),
+            row_range: payload
+                .start_row
+                .zip(payload.end_row)
+                .map(|(start, end)| start..end),
+            follow_renames: payload.follow_renames,
+        };
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.log(options)
+            })?
+            .await??;
+        Ok(proto::GitLogResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::LogEntry {
+                    sha: entry.sha.into(),
+                    subject: entry.subject.into(),
+                    author_name: entry.author_name.into(),
+                    author_email: entry.author_email.into(),
+                    commit_timestamp: entry.commit_timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_commit_files(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCommitFiles>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitCommitFilesResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let files = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.commit_files(&envelope.payload.commit)
+            })?
+            .await??;
+        Ok(proto::GitCommitFilesResponse {
+            files: files
+                .into_iter()
+                .map(|file| proto::CommitFile {
+                    path: file.path.to_string_lossy().to_string(),
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                    is_binary: file.is_binary,
+                })
+                .collect(),
+        })
+    }
+
     async fn handle_reset(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::GitReset>,
@@ -530,6 +613,220 @@ impl GitStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_stash_push(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashPush>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let paths = envelope
+            .payload
+            .paths
+            .iter()
+            .map(|s| RepoPath::from_str(s))
+            .collect();
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_push(
+                    paths,
+                    envelope.payload.message,
+                    envelope.payload.include_untracked,
+                )
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_list(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashList>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashListResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_list()
+            })?
+            .await??;
+        Ok(proto::GitStashListResponse {
+            entries: entries
+                .into_iter()
+                .map(|entry| proto::StashEntry {
+                    index: entry.index as u64,
+                    sha: entry.sha.into(),
+                    message: entry.message.into(),
+                    timestamp: entry.timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    async fn handle_stash_apply(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashApply>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let index = envelope.payload.index as usize;
+        let sha = envelope.payload.sha;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                if envelope.payload.pop {
+                    repository_handle.stash_pop(index, sha)
+                } else {
+                    repository_handle.stash_apply(index, sha)
+                }
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_drop(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDrop>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_drop(envelope.payload.index as usize, envelope.payload.sha)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_stash_diff(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitStashDiff>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitStashDiffResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let diff = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.stash_diff(envelope.payload.index as usize, envelope.payload.sha)
+            })?
+            .await??;
+        Ok(proto::GitStashDiffResponse { diff })
+    }
+
+    async fn handle_rebase_todo(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebaseTodo>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GitRebaseTodoResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        let entries = repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_todo(&envelope.payload.base)
+            })?
+            .await??;
+        Ok(proto::GitRebaseTodoResponse {
+            entries: entries.iter().map(rebase_todo_entry_to_proto).collect(),
+        })
+    }
+
+    async fn handle_rebase(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitRebase>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+        let entries = envelope
+            .payload
+            .entries
+            .iter()
+            .map(proto_to_rebase_todo_entry)
+            .collect::<Result<Vec<_>>>()?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.rebase_interactive(envelope.payload.base, entries)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_cherry_pick(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitCherryPick>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.cherry_pick(envelope.payload.commits)
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_continue_operation(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitContinueOperation>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.continue_operation()
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_abort_operation(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GitAbortOperation>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let work_directory_id = ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+        let repository_handle =
+            Self::repository_for_request(&this, worktree_id, work_directory_id, &mut cx)?;
+
+        repository_handle
+            .update(&mut cx, |repository_handle, _| {
+                repository_handle.abort_operation()
+            })?
+            .await??;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_open_commit_message_buffer(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::OpenCommitMessageBuffer>,
@@ -902,6 +1199,390 @@ impl Repository {
         })
     }
 
+    pub fn log(&self, options: LogOptions) -> oneshot::Receiver<Result<Vec<LogEntry>>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.log(options),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitLog {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            skip: options.skip as u64,
+                            limit: options.limit as u64,
+                            path: options.path.map(|path| path.to_string_lossy().to_string()),
+                            start_row: options.row_range.as_ref().map(|range| range.start),
+                            end_row: options.row_range.as_ref().map(|range| range.end),
+                            follow_renames: options.follow_renames,
+                        })
+                        .await?;
+
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(|entry| LogEntry {
+                            sha: entry.sha.into(),
+                            subject: entry.subject.into(),
+                            author_name: entry.author_name.into(),
+                            author_email: entry.author_email.into(),
+                            commit_timestamp: entry.commit_timestamp,
+                        })
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn commit_files(&self, commit: &str) -> oneshot::Receiver<Result<Vec<CommitFile>>> {
+        let commit = commit.to_string();
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(git_repository) => git_repository.commit_files(&commit),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitCommitFiles {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            commit,
+                        })
+                        .await?;
+
+                    Ok(response
+                        .files
+                        .into_iter()
+                        .map(|file| CommitFile {
+                            path: RepoPath::from_str(&file.path),
+                            old_text: file.old_text,
+                            new_text: file.new_text,
+                            is_binary: file.is_binary,
+                        })
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn stash_push(
+        &self,
+        paths: Vec<RepoPath>,
+        message: Option<String>,
+        include_untracked: bool,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => {
+                    repo.stash_push(&paths, message.as_deref(), include_untracked)
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashPush {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            paths: paths
+                                .into_iter()
+                                .map(|p| p.to_string_lossy().to_string())
+                                .collect(),
+                            message,
+                            include_untracked,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn stash_list(&self) -> oneshot::Receiver<Result<Vec<StashEntry>>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.stash_list(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitStashList {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(response
+                        .entries
+                        .into_iter()
+                        .map(|entry| StashEntry {
+                            index: entry.index as usize,
+                            sha: entry.sha.into(),
+                            message: entry.message.into(),
+                            timestamp: entry.timestamp,
+                        })
+                        .collect())
+                }
+            }
+        })
+    }
+
+    pub fn stash_apply(&self, index: usize, sha: Option<String>) -> oneshot::Receiver<Result<()>> {
+        self.send_stash_apply(index, sha, false)
+    }
+
+    pub fn stash_pop(&self, index: usize, sha: Option<String>) -> oneshot::Receiver<Result<()>> {
+        self.send_stash_apply(index, sha, true)
+    }
+
+    fn send_stash_apply(
+        &self,
+        index: usize,
+        sha: Option<String>,
+        pop: bool,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => {
+                    if pop {
+                        repo.stash_pop(index, sha.as_deref())
+                    } else {
+                        repo.stash_apply(index, sha.as_deref())
+                    }
+                }
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashApply {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                            pop,
+                            sha,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn stash_drop(&self, index: usize, sha: Option<String>) -> oneshot::Receiver<Result<()>> {
+        self.send_job(move |git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.stash_drop(index, sha.as_deref()),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitStashDrop {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                            sha,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn stash_diff(
+        &self,
+        index: usize,
+        sha: Option<String>,
+    ) -> oneshot::Receiver<Result<String>> {
+        self.send_job(move |git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.stash_diff(index, sha.as_deref()),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitStashDiff {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            index: index as u64,
+                            sha,
+                        })
+                        .await?;
+
+                    Ok(response.diff)
+                }
+            }
+        })
+    }
+
+    pub fn operation_in_progress(&self) -> Option<OperationInProgress> {
+        self.repository_entry.operation_in_progress()
+    }
+
+    pub fn rebase_todo(&self, base: &str) -> oneshot::Receiver<Result<Vec<RebaseTodoEntry>>> {
+        let base = base.to_string();
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.rebase_todo(&base),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    let response = client
+                        .request(proto::GitRebaseTodo {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            base,
+                        })
+                        .await?;
+
+                    response
+                        .entries
+                        .iter()
+                        .map(proto_to_rebase_todo_entry)
+                        .collect()
+                }
+            }
+        })
+    }
+
+    pub fn rebase_interactive(
+        &self,
+        base: String,
+        entries: Vec<RebaseTodoEntry>,
+    ) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.rebase_interactive(&base, &entries),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitRebase {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            base,
+                            entries: entries.iter().map(rebase_todo_entry_to_proto).collect(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn cherry_pick(&self, commits: Vec<String>) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.cherry_pick(&commits),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitCherryPick {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                            commits,
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn continue_operation(&self) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.continue_operation(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitContinueOperation {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    pub fn abort_operation(&self) -> oneshot::Receiver<Result<()>> {
+        self.send_job(|git_repo| async move {
+            match git_repo {
+                GitRepo::Local(repo) => repo.abort_operation(),
+                GitRepo::Remote {
+                    project_id,
+                    client,
+                    worktree_id,
+                    work_directory_id,
+                } => {
+                    client
+                        .request(proto::GitAbortOperation {
+                            project_id: project_id.0,
+                            worktree_id: worktree_id.to_proto(),
+                            work_directory_id: work_directory_id.to_proto(),
+                        })
+                        .await?;
+
+                    Ok(())
+                }
+            }
+        })
+    }
+
     fn buffer_store(&self, cx: &App) -> Option<Entity<BufferStore>> {
         Some(self.git_store.upgrade()?.read(cx).buffer_store.clone())
     }
@@ -1280,3 +1961,20 @@ impl Repository {
         })
     }
 }
+
+fn rebase_todo_entry_to_proto(entry: &RebaseTodoEntry) -> proto::RebaseTodoEntry {
+    proto::RebaseTodoEntry {
+        action: entry.action.as_str().to_string(),
+        sha: entry.sha.to_string(),
+        subject: entry.subject.to_string(),
+    }
+}
+
+fn proto_to_rebase_todo_entry(entry: &proto::RebaseTodoEntry) -> Result<RebaseTodoEntry> {
+    Ok(RebaseTodoEntry {
+        action: RebaseAction::from_command(&entry.action)
+            .with_context(|| format!("unknown rebase action {:?}", entry.action))?,
+        sha: entry.sha.clone().into(),
+        subject: entry.subject.clone().into(),
+    })
+}
@@ -1,3 +1,6 @@
+mod pull_diagnostics;
+mod semantic_tokens;
+
 use crate::{
     buffer_store::{BufferStore, BufferStoreEvent},
     deserialize_code_actions,
@@ -11,8 +14,8 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    CodeAction, CodeLens, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _,
+    ProjectPath, ProjectTransaction, ResolveState, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -56,10 +59,18 @@ use parking_lot::Mutex;
 use postage::watch;
 use rand::prelude::*;
 
+use pull_diagnostics::{
+    diagnostic_options, document_diagnostic_reports, workspace_diagnostic_reports,
+    PulledDiagnostics,
+};
 use rpc::{
     proto::{FromProto, ToProto},
     AnyProtoClient,
 };
+use semantic_tokens::{
+    decode_semantic_tokens, deserialize_semantic_tokens, semantic_tokens_options,
+    serialize_semantic_tokens, CachedSemanticTokens,
+};
 use serde::Serialize;
 use settings::{Settings, SettingsLocation, SettingsStore};
 use sha2::{Digest, Sha256};
@@ -90,6 +101,7 @@ pub use fs::*;
 pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use semantic_tokens::{BufferSemanticTokens, SemanticToken};
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, UpdatedEntriesSet,
     UpdatedGitRepositoriesSet, Worktree, WorktreeId, WorktreeSettings, FS_WATCH_LATENCY,
@@ -163,9 +175,13 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, HashMap<LanguageServerId, CachedSemanticTokens>>,
+    pulled_diagnostics: PulledDiagnostics,
     _subscription: gpui::Subscription,
     lsp_tree: Entity<LanguageServerTree>,
     registered_buffers: HashMap<BufferId, usize>,
+    /// The ids reserved for diagnostics that are not reported by a language server.
+    diagnostic_sources: HashSet<LanguageServerId>,
 }
 
 impl LocalLspStore {
@@ -804,6 +820,64 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            this.pull_diagnostics_for_open_buffers(server_id, cx);
+                            this.pull_workspace_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -2053,6 +2127,11 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             let _ = self.buffer_snapshots.remove(&buffer.remote_id());
+            let _ = self.semantic_tokens.remove(&buffer.remote_id());
+            let _ = self
+                .pulled_diagnostics
+                .document_pulls
+                .remove(&buffer.remote_id());
 
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server.unregister_buffer(file_url.clone());
@@ -2911,6 +2990,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2966,6 +3047,10 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_resolve_inlay_hint);
         client.add_entity_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_entity_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_entity_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_entity_request_handler(Self::handle_semantic_tokens);
+        client.add_entity_request_handler(Self::handle_resolve_code_lens);
+        client.add_entity_request_handler(Self::handle_refresh_code_lens);
         client.add_entity_request_handler(Self::handle_on_type_formatting);
         client.add_entity_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_entity_request_handler(Self::handle_register_buffer_with_language_servers);
@@ -2982,6 +3067,14 @@ impl LspStore {
         client.add_entity_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_entity_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_entity_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_entity_request_handler(Self::handle_lsp_command::<GetSelectionRanges>);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -3068,6 +3161,8 @@ impl LspStore {
                 language_server_watcher_registrations: Default::default(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                pulled_diagnostics: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -3080,6 +3175,7 @@ impl LspStore {
                 }),
                 lsp_tree: LanguageServerTree::new(project_tree, languages.clone(), cx),
                 registered_buffers: Default::default(),
+                diagnostic_sources: Default::default(),
             }),
             last_formatting_failure: None,
             downstream_client: None,
@@ -3326,6 +3422,7 @@ impl LspStore {
 
             if !ignore_refcounts || *refcount == 1 {
                 local.register_buffer_with_language_servers(buffer, cx);
+                self.pull_diagnostics(buffer, cx);
             }
             if !ignore_refcounts {
                 cx.observe_release(&handle, move |this, buffer, cx| {
@@ -4299,6 +4396,134 @@ impl LspStore {
         }
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request_task = upstream_client.request(proto::MultiLspQuery {
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+                project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetCodeLens(
+                    GetCodeLens.to_proto(project_id, buffer_handle.read(cx)),
+                )),
+            });
+            let buffer = buffer_handle.clone();
+            cx.spawn(|weak_project, cx| async move {
+                let Some(project) = weak_project.upgrade() else {
+                    return Ok(Vec::new());
+                };
+                let responses = request_task.await?.responses;
+                let code_lens = join_all(
+                    responses
+                        .into_iter()
+                        .filter_map(|lsp_response| match lsp_response.response? {
+                            proto::lsp_response::Response::GetCodeLensResponse(response) => {
+                                Some(response)
+                            }
+                            unexpected => {
+                                debug_panic!("Unexpected response: {unexpected:?}");
+                                None
+                            }
+                        })
+                        .map(|code_lens_response| {
+                            GetCodeLens.response_from_proto(
+                                code_lens_response,
+                                project.clone(),
+                                buffer.clone(),
+                                cx.clone(),
+                            )
+                        }),
+                )
+                .await;
+
+                Ok(code_lens
+                    .into_iter()
+                    .collect::<Result<Vec<Vec<_>>>>()?
+                    .into_iter()
+                    .flatten()
+                    .collect())
+            })
+        } else {
+            let code_lens_task =
+                self.request_multiple_lsp_locally(buffer_handle, None::<usize>, GetCodeLens, cx);
+            cx.spawn(|_, _| async move { Ok(code_lens_task.await.into_iter().flatten().collect()) })
+        }
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.is_resolved() {
+            return Task::ready(Ok(lens));
+        }
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens {
+                    Some(resolved_lens) => Self::deserialize_code_lens(resolved_lens),
+                    None => Ok(lens),
+                }
+            })
+        } else {
+            let Some(lang_server) = buffer_handle.update(cx, |buffer, cx| {
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+                    .map(|(_, server)| server.clone())
+            }) else {
+                return Task::ready(Ok(lens));
+            };
+            if !GetCodeLens::can_resolve_lens(&lang_server.capabilities()) {
+                return Task::ready(Ok(lens));
+            }
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(CodeLens { lsp_lens, ..lens })
+            })
+        }
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(command) = lens.lsp_lens.command else {
+            return Task::ready(Err(anyhow!("code lens has no command")));
+        };
+        // A code lens command is executed the same way as the command of a code action.
+        let action = CodeAction {
+            server_id: lens.server_id,
+            range: lens.range,
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..Default::default()
+            },
+        };
+        self.apply_code_action(buffer_handle, action, push_to_history, cx)
+    }
+
     #[inline(never)]
     pub fn completions(
         &self,
@@ -4825,6 +5050,171 @@ impl LspStore {
         }
     }
 
+    /// Fetches the semantic tokens of every language server of the buffer that provides them,
+    /// requesting only the changes since the previous request when the server supports it.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<BufferSemanticTokens>>> {
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+            };
+            let buffer = buffer.clone();
+            return cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                let mut tokens = Vec::with_capacity(response.servers.len());
+                for server_tokens in response.servers {
+                    tokens
+                        .push(deserialize_semantic_tokens(server_tokens, &buffer, &mut cx).await?);
+                }
+                Ok(tokens)
+            });
+        }
+
+        let Some(local) = self.as_local() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let buffer_id = buffer.read(cx).remote_id();
+        let snapshot = buffer.read(cx).text_snapshot();
+        let Some(abs_path) = File::from_dyn(buffer.read(cx).file())
+            .and_then(File::as_local)
+            .map(|file| file.abs_path(cx))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some(uri) = lsp::Url::from_file_path(&abs_path).log_err() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let requests = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .filter_map(|(_, server)| {
+                    let capabilities = server.capabilities();
+                    let options = semantic_tokens_options(&capabilities)?;
+                    let supports_delta = match options.full.as_ref()? {
+                        lsp::SemanticTokensFullOptions::Bool(full) => {
+                            if !full {
+                                return None;
+                            }
+                            false
+                        }
+                        lsp::SemanticTokensFullOptions::Delta { delta } => delta.unwrap_or(false),
+                    };
+                    let legend = Arc::new(options.legend.clone());
+                    let previous_result_id = local
+                        .semantic_tokens
+                        .get(&buffer_id)
+                        .and_then(|tokens| tokens.get(&server.server_id()))
+                        .and_then(|tokens| tokens.result_id.clone())
+                        .filter(|_| supports_delta);
+                    let server = server.clone();
+                    let text_document = lsp::TextDocumentIdentifier::new(uri.clone());
+                    Some(async move {
+                        let result = if let Some(previous_result_id) = previous_result_id.clone() {
+                            server
+                                .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                                    lsp::SemanticTokensDeltaParams {
+                                        text_document,
+                                        previous_result_id,
+                                        work_done_progress_params: Default::default(),
+                                        partial_result_params: Default::default(),
+                                    },
+                                )
+                                .await?
+                        } else {
+                            server
+                                .request::<lsp::request::SemanticTokensFullRequest>(
+                                    lsp::SemanticTokensParams {
+                                        text_document,
+                                        work_done_progress_params: Default::default(),
+                                        partial_result_params: Default::default(),
+                                    },
+                                )
+                                .await?
+                                .map(|result| match result {
+                                    lsp::SemanticTokensResult::Tokens(tokens) => {
+                                        lsp::SemanticTokensFullDeltaResult::Tokens(tokens)
+                                    }
+                                    lsp::SemanticTokensResult::Partial(partial) => {
+                                        lsp::SemanticTokensFullDeltaResult::Tokens(
+                                            lsp::SemanticTokens {
+                                                result_id: None,
+                                                data: partial.data,
+                                            },
+                                        )
+                                    }
+                                })
+                        };
+                        anyhow::Ok((server.server_id(), legend, previous_result_id, result))
+                    })
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let buffer = buffer.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let responses = join_all(requests).await;
+            let (tokens, stale) = this.update(&mut cx, |this, _| {
+                let mut stale = false;
+                let Some(local) = this.as_local_mut() else {
+                    return (Vec::new(), stale);
+                };
+                let cached_tokens = local.semantic_tokens.entry(buffer_id).or_default();
+                let mut tokens = Vec::with_capacity(responses.len());
+                for response in responses {
+                    let (server_id, legend, previous_result_id, result) = match response {
+                        Ok((server_id, legend, previous_result_id, Some(result))) => {
+                            (server_id, legend, previous_result_id, result)
+                        }
+                        Ok((server_id, _, _, None)) => {
+                            cached_tokens.remove(&server_id);
+                            continue;
+                        }
+                        Err(error) => {
+                            log::warn!("semantic tokens request failed: {error:#}");
+                            continue;
+                        }
+                    };
+                    let cached = cached_tokens.entry(server_id).or_default();
+                    let is_delta = !matches!(result, lsp::SemanticTokensFullDeltaResult::Tokens(_));
+                    if is_delta && cached.result_id != previous_result_id {
+                        // Another response replaced the tokens this delta was computed against.
+                        cached_tokens.remove(&server_id);
+                        stale = true;
+                        continue;
+                    }
+                    if let Err(error) = cached.apply(result) {
+                        // Start over with a full request the next time.
+                        log::warn!("failed to apply semantic tokens delta: {error:#}");
+                        cached_tokens.remove(&server_id);
+                        continue;
+                    }
+                    tokens.push(BufferSemanticTokens {
+                        server_id,
+                        legend,
+                        tokens: decode_semantic_tokens(&cached.data, &snapshot).into(),
+                    });
+                }
+                (tokens, stale)
+            })?;
+            if stale {
+                // Request the full tokens of the servers whose deltas were dropped.
+                return this
+                    .update(&mut cx, |this, cx| this.semantic_tokens(&buffer, cx))?
+                    .await;
+            }
+            Ok(tokens)
+        })
+    }
+
     pub fn signature_help<T: ToPointUtf16>(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -5204,11 +5594,13 @@ impl LspStore {
             )
         })?;
 
+        let buffer_handle = buffer.clone();
         let buffer = buffer.read(cx);
         let file = File::from_dyn(buffer.file())?;
         let abs_path = file.as_local()?.abs_path(cx);
         let uri = lsp::Url::from_file_path(abs_path).unwrap();
         let next_snapshot = buffer.text_snapshot();
+        let mut inter_file_servers = Vec::new();
         for language_server in language_servers {
             let language_server = language_server.clone();
 
@@ -5289,6 +5681,17 @@ impl LspStore {
                     },
                 )
                 .log_err();
+
+            if diagnostic_options(&language_server.capabilities())
+                .map_or(false, |options| options.inter_file_dependencies)
+            {
+                inter_file_servers.push(language_server.server_id());
+            }
+        }
+
+        self.pull_diagnostics(&buffer_handle, cx);
+        for server_id in inter_file_servers {
+            self.pull_diagnostics_for_open_buffers(server_id, cx);
         }
 
         None
@@ -5330,6 +5733,7 @@ impl LspStore {
         });
         for language_server_id in language_servers {
             self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
+            self.pull_workspace_diagnostics(language_server_id, cx);
         }
 
         None
@@ -6087,6 +6491,43 @@ impl LspStore {
                         .collect(),
                 })
             }
+            Some(proto::multi_lsp_query::Request::GetCodeLens(get_code_lens)) => {
+                let get_code_lens = GetCodeLens::from_proto(
+                    get_code_lens,
+                    this.clone(),
+                    buffer.clone(),
+                    cx.clone(),
+                )
+                .await?;
+
+                let all_code_lens = this
+                    .update(&mut cx, |project, cx| {
+                        project.request_multiple_lsp_locally(
+                            &buffer,
+                            None::<usize>,
+                            get_code_lens,
+                            cx,
+                        )
+                    })?
+                    .await
+                    .into_iter();
+
+                this.update(&mut cx, |project, cx| proto::MultiLspQueryResponse {
+                    responses: all_code_lens
+                        .map(|code_lens| proto::LspResponse {
+                            response: Some(proto::lsp_response::Response::GetCodeLensResponse(
+                                GetCodeLens::response_to_proto(
+                                    code_lens,
+                                    project,
+                                    sender_id,
+                                    &buffer_version,
+                                    cx,
+                                ),
+                            )),
+                        })
+                        .collect(),
+                })
+            }
             None => anyhow::bail!("empty multi lsp query request"),
         }
     }
@@ -6788,6 +7229,8 @@ impl LspStore {
             if let Some(work) = status.pending_work.remove(&token) {
                 if !work.is_disk_based_diagnostics_progress {
                     cx.emit(LspStoreEvent::RefreshInlayHints);
+                    cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                    cx.emit(LspStoreEvent::RefreshCodeLens);
                 }
             }
             cx.notify();
@@ -6917,6 +7360,41 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Entity<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_semantic_tokens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+        let tokens = this
+            .update(&mut cx, |this, cx| this.semantic_tokens(&buffer, cx))?
+            .await
+            .context("semantic tokens fetch")?;
+        Ok(proto::GetSemanticTokensResponse {
+            servers: tokens.iter().map(serialize_semantic_tokens).collect(),
+        })
+    }
+
     async fn handle_inlay_hints(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::InlayHints>,
@@ -6993,6 +7471,40 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_lens = this
+            .update(&mut cx, |this, cx| this.resolve_code_lens(buffer, lens, cx))?
+            .await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&resolved_lens)),
+        })
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Entity<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Entity<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -7501,6 +8013,7 @@ impl LspStore {
             });
         }
         local.language_server_watched_paths.remove(&server_id);
+        local.pulled_diagnostics.remove_server(server_id);
         let server_state = local.language_servers.remove(&server_id);
         cx.notify();
         cx.emit(LspStoreEvent::LanguageServerRemoved(server_id));
@@ -7564,6 +8077,72 @@ impl LspStore {
         }
     }
 
+    /// Reserves a language server id for diagnostics that are not reported by a language server,
+    /// such as the problems found in a task's output. They are published with [`Self::update_diagnostics`].
+    pub fn reserve_diagnostic_source(&mut self) -> LanguageServerId {
+        let server_id = self.languages.next_language_server_id();
+        if let Some(local) = self.as_local_mut() {
+            local.diagnostic_sources.insert(server_id);
+        }
+        server_id
+    }
+
+    /// Removes all the diagnostics published under a reserved diagnostic source and releases its id.
+    pub fn release_diagnostic_source(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        if !local.diagnostic_sources.remove(&server_id) {
+            return;
+        }
+        let paths = local
+            .diagnostics
+            .iter()
+            .flat_map(|(worktree_id, diagnostics)| {
+                diagnostics
+                    .iter()
+                    .filter(|(_, diagnostics_by_server_id)| {
+                        diagnostics_by_server_id
+                            .iter()
+                            .any(|(id, _)| *id == server_id)
+                    })
+                    .map(|(path, _)| (*worktree_id, path.clone()))
+            })
+            .collect::<Vec<_>>();
+        for (worktree_id, path) in paths {
+            let Some(worktree) = self
+                .worktree_store
+                .read(cx)
+                .worktree_for_id(worktree_id, cx)
+            else {
+                continue;
+            };
+            let Some(uri) = worktree
+                .read(cx)
+                .absolutize(&path)
+                .log_err()
+                .and_then(|abs_path| lsp::Url::from_file_path(abs_path).ok())
+            else {
+                continue;
+            };
+            self.update_diagnostics(
+                server_id,
+                lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: Vec::new(),
+                    version: None,
+                },
+                &[],
+                cx,
+            )
+            .log_err();
+        }
+    }
+
     pub fn update_diagnostics(
         &mut self,
         language_server_id: LanguageServerId,
@@ -7685,6 +8264,202 @@ impl LspStore {
         Ok(())
     }
 
+    /// Pulls the diagnostics of a buffer from each of its language servers
+    /// that support `textDocument/diagnostic`, after a short debounce.
+    fn pull_diagnostics(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) -> Option<()> {
+        const DIAGNOSTIC_PULL_DEBOUNCE: Duration = Duration::from_millis(125);
+
+        let local = self.as_local()?;
+        let buffer_id = buffer.read(cx).remote_id();
+        let file = File::from_dyn(buffer.read(cx).file())?;
+        let uri = lsp::Url::from_file_path(file.as_local()?.abs_path(cx)).log_err()?;
+        let servers = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .filter(|(_, server)| diagnostic_options(&server.capabilities()).is_some())
+                .map(|(adapter, server)| (adapter.clone(), server.clone()))
+                .collect::<Vec<_>>()
+        });
+        if servers.is_empty() {
+            return None;
+        }
+
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(DIAGNOSTIC_PULL_DEBOUNCE)
+                .await;
+            let pulls = servers.into_iter().map(|(adapter, server)| {
+                Self::pull_document_diagnostics(
+                    this.clone(),
+                    buffer_id,
+                    uri.clone(),
+                    adapter,
+                    server,
+                    cx.clone(),
+                )
+                .log_err()
+            });
+            join_all(pulls).await;
+        });
+        self.as_local_mut()?
+            .pulled_diagnostics
+            .document_pulls
+            .insert(buffer_id, task);
+        None
+    }
+
+    async fn pull_document_diagnostics(
+        this: WeakEntity<Self>,
+        buffer_id: BufferId,
+        uri: lsp::Url,
+        adapter: Arc<CachedLspAdapter>,
+        server: Arc<LanguageServer>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let server_id = server.server_id();
+        let identifier = diagnostic_options(&server.capabilities())
+            .and_then(|options| options.identifier.clone());
+        let (previous_result_id, version) = this
+            .update(&mut cx, |this, _| {
+                let local = this.as_local()?;
+                let version = local
+                    .buffer_snapshots
+                    .get(&buffer_id)?
+                    .get(&server_id)?
+                    .last()?
+                    .version;
+                Some((
+                    local.pulled_diagnostics.previous_result_id(server_id, &uri),
+                    version,
+                ))
+            })?
+            .context("buffer is not open in the language server")?;
+
+        let result = server
+            .request::<lsp::request::DocumentDiagnosticRequest>(lsp::DocumentDiagnosticParams {
+                text_document: lsp::TextDocumentIdentifier { uri: uri.clone() },
+                identifier,
+                previous_result_id,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await?;
+
+        this.update(&mut cx, |this, cx| {
+            for (report_uri, report) in document_diagnostic_reports(uri.clone(), result) {
+                let version = (report_uri == uri).then_some(version);
+                this.apply_pulled_diagnostics(server_id, &adapter, report_uri, version, report, cx)
+                    .log_err();
+            }
+        })
+    }
+
+    /// Pulls the diagnostics of every open buffer the given language server
+    /// is responsible for.
+    fn pull_diagnostics_for_open_buffers(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut Context<Self>,
+    ) -> Option<()> {
+        let local = self.as_local()?;
+        let buffers = local
+            .buffer_snapshots
+            .iter()
+            .filter(|(_, snapshots)| snapshots.contains_key(&server_id))
+            .filter_map(|(buffer_id, _)| self.buffer_store.read(cx).get(*buffer_id))
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_diagnostics(&buffer, cx);
+        }
+        None
+    }
+
+    /// Pulls the diagnostics of the whole workspace from a language server
+    /// that supports `workspace/diagnostic`.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut Context<Self>,
+    ) -> Option<()> {
+        let local = self.as_local_mut()?;
+        let LanguageServerState::Running {
+            adapter, server, ..
+        } = local.language_servers.get(&server_id)?
+        else {
+            return None;
+        };
+        let options = diagnostic_options(&server.capabilities())?.clone();
+        if !options.workspace_diagnostics {
+            return None;
+        }
+
+        let adapter = adapter.clone();
+        let request = server.request::<lsp::request::WorkspaceDiagnosticRequest>(
+            lsp::WorkspaceDiagnosticParams {
+                identifier: options.identifier,
+                previous_result_ids: local.pulled_diagnostics.previous_result_ids(server_id),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        let task = cx.spawn(move |this, mut cx| async move {
+            let Some(result) = request.await.log_err() else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                for (uri, version, report) in workspace_diagnostic_reports(result) {
+                    this.apply_pulled_diagnostics(server_id, &adapter, uri, version, report, cx)
+                        .log_err();
+                }
+            })
+            .ok();
+        });
+        local
+            .pulled_diagnostics
+            .workspace_pulls
+            .insert(server_id, task);
+        None
+    }
+
+    fn apply_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .context("called apply_pulled_diagnostics on remote")?;
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                local
+                    .pulled_diagnostics
+                    .set_result_id(server_id, uri.clone(), report.result_id);
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                local
+                    .pulled_diagnostics
+                    .set_result_id(server_id, uri, Some(report.result_id));
+                Ok(())
+            }
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn insert_newly_running_language_server(
         &mut self,
@@ -7755,6 +8530,8 @@ impl LspStore {
             Some(key.0),
         ));
         cx.emit(LspStoreEvent::RefreshInlayHints);
+        cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        cx.emit(LspStoreEvent::RefreshCodeLens);
 
         if let Some((downstream_client, project_id)) = self.downstream_client.as_ref() {
             downstream_client
@@ -7848,6 +8625,8 @@ impl LspStore {
                 });
             }
         });
+        self.pull_diagnostics_for_open_buffers(server_id, cx);
+        self.pull_workspace_diagnostics(server_id, cx);
 
         cx.notify();
     }
@@ -8183,6 +8962,32 @@ impl LspStore {
         })
     }
 
+    pub(crate) fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.to_proto(),
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    pub(crate) fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId::from_proto(lens.server_id),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
     fn update_last_formatting_failure<T>(&mut self, formatting_result: &anyhow::Result<T>) {
         match &formatting_result {
             Ok(_) => self.last_formatting_failure = None,
@@ -0,0 +1,130 @@
+use collections::HashMap;
+use gpui::Task;
+use lsp::LanguageServerId;
+use text::BufferId;
+
+/// The state of the diagnostics pulled from language servers that support
+/// `textDocument/diagnostic` and `workspace/diagnostic`.
+#[derive(Default)]
+pub(crate) struct PulledDiagnostics {
+    /// The result ID of the last report for each document, which lets servers
+    /// respond that a document's diagnostics are unchanged.
+    pub result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    pub document_pulls: HashMap<BufferId, Task<()>>,
+    pub workspace_pulls: HashMap<LanguageServerId, Task<()>>,
+}
+
+impl PulledDiagnostics {
+    pub fn previous_result_id(
+        &self,
+        server_id: LanguageServerId,
+        uri: &lsp::Url,
+    ) -> Option<String> {
+        self.result_ids.get(&server_id)?.get(uri).cloned()
+    }
+
+    pub fn previous_result_ids(&self, server_id: LanguageServerId) -> Vec<lsp::PreviousResultId> {
+        self.result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, value)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+
+    pub fn set_result_id(
+        &mut self,
+        server_id: LanguageServerId,
+        uri: lsp::Url,
+        result_id: Option<String>,
+    ) {
+        let result_ids = self.result_ids.entry(server_id).or_default();
+        match result_id {
+            Some(result_id) => {
+                result_ids.insert(uri, result_id);
+            }
+            None => {
+                result_ids.remove(&uri);
+            }
+        }
+    }
+
+    pub fn remove_server(&mut self, server_id: LanguageServerId) {
+        self.result_ids.remove(&server_id);
+        self.workspace_pulls.remove(&server_id);
+    }
+}
+
+pub(crate) fn diagnostic_options(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<&lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(&options.diagnostic_options)
+        }
+    }
+}
+
+/// Flattens the response to a `textDocument/diagnostic` request into the
+/// reports for the requested document and its related documents.
+pub(crate) fn document_diagnostic_reports(
+    uri: lsp::Url,
+    result: lsp::DocumentDiagnosticReportResult,
+) -> Vec<(lsp::Url, lsp::DocumentDiagnosticReportKind)> {
+    let (report, related_documents) = match result {
+        lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Full(
+            report,
+        )) => (
+            Some(lsp::DocumentDiagnosticReportKind::Full(
+                report.full_document_diagnostic_report,
+            )),
+            report.related_documents,
+        ),
+        lsp::DocumentDiagnosticReportResult::Report(lsp::DocumentDiagnosticReport::Unchanged(
+            report,
+        )) => (
+            Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                report.unchanged_document_diagnostic_report,
+            )),
+            report.related_documents,
+        ),
+        lsp::DocumentDiagnosticReportResult::Partial(report) => (None, report.related_documents),
+    };
+    report
+        .map(|report| (uri, report))
+        .into_iter()
+        .chain(related_documents.into_iter().flatten())
+        .collect()
+}
+
+/// Flattens the response to a `workspace/diagnostic` request into the
+/// reports for each document, along with the document version they apply to.
+pub(crate) fn workspace_diagnostic_reports(
+    result: lsp::WorkspaceDiagnosticReportResult,
+) -> Vec<(lsp::Url, Option<i32>, lsp::DocumentDiagnosticReportKind)> {
+    let items = match result {
+        lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+        lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+    };
+    items
+        .into_iter()
+        .map(|item| match item {
+            lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                report.uri,
+                report.version.and_then(|version| version.try_into().ok()),
+                lsp::DocumentDiagnosticReportKind::Full(report.full_document_diagnostic_report),
+            ),
+            lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                report.uri,
+                report.version.and_then(|version| version.try_into().ok()),
+                lsp::DocumentDiagnosticReportKind::Unchanged(
+                    report.unchanged_document_diagnostic_report,
+                ),
+            ),
+        })
+        .collect()
+}
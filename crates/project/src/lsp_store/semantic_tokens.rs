@@ -0,0 +1,215 @@
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use client::proto;
+use gpui::{AsyncApp, Entity};
+use language::{
+    proto::{deserialize_anchor, serialize_anchor},
+    Bias, Buffer, PointUtf16, Unclipped,
+};
+use lsp::LanguageServerId;
+use rpc::proto::{FromProto, ToProto};
+use text::Anchor;
+
+/// The semantic tokens reported by a language server for a buffer.
+#[derive(Clone, Debug)]
+pub struct BufferSemanticTokens {
+    pub server_id: LanguageServerId,
+    /// The legend the token types and modifiers index into.
+    pub legend: Arc<lsp::SemanticTokensLegend>,
+    /// Non-overlapping tokens, sorted by their position in the buffer.
+    pub tokens: Arc<[SemanticToken]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    pub token_type: u32,
+    pub token_modifiers: u32,
+}
+
+impl BufferSemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.legend
+            .token_types
+            .get(token.token_type as usize)
+            .map(|token_type| token_type.as_str())
+    }
+
+    pub fn token_modifiers<'a>(
+        &'a self,
+        token: &SemanticToken,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        let modifiers = token.token_modifiers;
+        self.legend
+            .token_modifiers
+            .iter()
+            .take(u32::BITS as usize)
+            .enumerate()
+            .filter(move |(ix, _)| modifiers & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+/// The raw tokens last received from a language server for a buffer,
+/// which `textDocument/semanticTokens/full/delta` responses are applied to.
+#[derive(Debug, Default)]
+pub(crate) struct CachedSemanticTokens {
+    pub result_id: Option<String>,
+    pub data: Vec<lsp::SemanticToken>,
+}
+
+impl CachedSemanticTokens {
+    pub fn apply(&mut self, result: lsp::SemanticTokensFullDeltaResult) -> Result<()> {
+        match result {
+            lsp::SemanticTokensFullDeltaResult::Tokens(tokens) => {
+                self.result_id = tokens.result_id;
+                self.data = tokens.data;
+            }
+            lsp::SemanticTokensFullDeltaResult::TokensDelta(delta) => {
+                self.apply_edits(delta.edits)?;
+                self.result_id = delta.result_id;
+            }
+            lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits } => {
+                self.apply_edits(edits)?;
+                self.result_id = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_edits(&mut self, mut edits: Vec<lsp::SemanticTokensEdit>) -> Result<()> {
+        // Each token is encoded as 5 integers, and all edits refer to the
+        // previous data, so they're applied back to front.
+        const TOKEN_LEN: u32 = 5;
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+        for edit in edits {
+            if edit.start % TOKEN_LEN != 0 || edit.delete_count % TOKEN_LEN != 0 {
+                return Err(anyhow!("semantic tokens edit does not align with tokens"));
+            }
+            let start = (edit.start / TOKEN_LEN) as usize;
+            let end = start + (edit.delete_count / TOKEN_LEN) as usize;
+            if end > self.data.len() {
+                return Err(anyhow!("semantic tokens edit is out of bounds"));
+            }
+            self.data.splice(start..end, edit.data.unwrap_or_default());
+        }
+        Ok(())
+    }
+}
+
+/// Resolves the relative positions of LSP semantic tokens against the
+/// buffer snapshot they were requested for.
+pub(crate) fn decode_semantic_tokens(
+    data: &[lsp::SemanticToken],
+    snapshot: &text::BufferSnapshot,
+) -> Vec<SemanticToken> {
+    let mut line = 0;
+    let mut column = 0;
+    data.iter()
+        .filter_map(|token| {
+            if token.delta_line > 0 {
+                line += token.delta_line;
+                column = token.delta_start;
+            } else {
+                column += token.delta_start;
+            }
+            let start =
+                snapshot.clip_point_utf16(Unclipped(PointUtf16::new(line, column)), Bias::Left);
+            let end = snapshot.clip_point_utf16(
+                Unclipped(PointUtf16::new(line, column + token.length)),
+                Bias::Left,
+            );
+            (start < end).then(|| SemanticToken {
+                range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers_bitset,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn semantic_tokens_options(
+    capabilities: &lsp::ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+pub(crate) fn serialize_semantic_tokens(
+    tokens: &BufferSemanticTokens,
+) -> proto::BufferSemanticTokens {
+    proto::BufferSemanticTokens {
+        server_id: tokens.server_id.to_proto(),
+        token_types: tokens
+            .legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect(),
+        token_modifiers: tokens
+            .legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect(),
+        tokens: tokens
+            .tokens
+            .iter()
+            .map(|token| proto::SemanticToken {
+                start: Some(serialize_anchor(&token.range.start)),
+                end: Some(serialize_anchor(&token.range.end)),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers,
+            })
+            .collect(),
+    }
+}
+
+pub(crate) async fn deserialize_semantic_tokens(
+    message: proto::BufferSemanticTokens,
+    buffer: &Entity<Buffer>,
+    cx: &mut AsyncApp,
+) -> Result<BufferSemanticTokens> {
+    let tokens = message
+        .tokens
+        .into_iter()
+        .map(|token| {
+            let start = token.start.and_then(deserialize_anchor);
+            let end = token.end.and_then(deserialize_anchor);
+            Ok(SemanticToken {
+                range: start
+                    .zip(end)
+                    .map(|(start, end)| start..end)
+                    .ok_or_else(|| anyhow!("invalid semantic token range"))?,
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_anchors(
+                tokens
+                    .iter()
+                    .flat_map(|token| [token.range.start, token.range.end]),
+            )
+        })?
+        .await?;
+    Ok(BufferSemanticTokens {
+        server_id: LanguageServerId::from_proto(message.server_id),
+        legend: Arc::new(lsp::SemanticTokensLegend {
+            token_types: message.token_types.into_iter().map(Into::into).collect(),
+            token_modifiers: message
+                .token_modifiers
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }),
+        tokens: tokens.into(),
+    })
+}
@@ -58,8 +58,8 @@ use gpui::{
 use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent, Capability,
-    CodeLabel, File as _, Language, LanguageName, LanguageRegistry, PointUtf16, ToOffset,
-    ToPointUtf16, Toolchain, ToolchainList, Transaction, Unclipped,
+    CodeLabel, File as _, Language, LanguageName, LanguageRegistry, ParseStatus, PointUtf16,
+    ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServerId,
@@ -116,9 +116,9 @@ pub use worktree::{
 
 pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
-    DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
-    LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
-    SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    BufferSemanticTokens, DiagnosticSummary, LanguageServerLogType, LanguageServerProgress,
+    LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
+    LspStoreEvent, SemanticToken, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 pub use toolchain_store::ToolchainStore;
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -285,6 +285,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
     ExpandedAllForEntry(WorktreeId, ProjectEntryId),
@@ -418,6 +420,34 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+impl CodeLens {
+    /// Code lenses are often reported without a command, which is then
+    /// filled in by a `codeLens/resolve` request.
+    pub fn is_resolved(&self) -> bool {
+        self.lsp_lens.command.is_some()
+    }
+}
+
+/// A foldable range of a buffer, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// Spans from the end of the range's first line to the end of its last line,
+    /// so that the first line stays visible when the range is folded.
+    pub range: Range<Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -471,6 +501,90 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol taking part in a call or type hierarchy, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct HierarchyItem<T> {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The location of the whole symbol, e.g. a function including its body.
+    pub location: Location,
+    /// The range to reveal when navigating to the symbol, e.g. the function's name.
+    pub selection_range: Range<Anchor>,
+    /// The raw item provided by the language server, sent back when querying its relations.
+    pub lsp_item: T,
+}
+
+pub type CallHierarchyItem = HierarchyItem<lsp::CallHierarchyItem>;
+pub type TypeHierarchyItem = HierarchyItem<lsp::TypeHierarchyItem>;
+
+/// The fields shared by the LSP call and type hierarchy items.
+pub trait LspHierarchyItem:
+    Clone + std::fmt::Debug + serde::Serialize + serde::de::DeserializeOwned + Send + 'static
+{
+    fn name(&self) -> &str;
+    fn kind(&self) -> lsp::SymbolKind;
+    fn detail(&self) -> Option<&str>;
+    fn uri(&self) -> &lsp::Url;
+    fn range(&self) -> lsp::Range;
+    fn selection_range(&self) -> lsp::Range;
+}
+
+macro_rules! impl_lsp_hierarchy_item {
+    ($item:ty) => {
+        impl LspHierarchyItem for $item {
+            fn name(&self) -> &str {
+                &self.name
+            }
+
+            fn kind(&self) -> lsp::SymbolKind {
+                self.kind
+            }
+
+            fn detail(&self) -> Option<&str> {
+                self.detail.as_deref()
+            }
+
+            fn uri(&self) -> &lsp::Url {
+                &self.uri
+            }
+
+            fn range(&self) -> lsp::Range {
+                self.range
+            }
+
+            fn selection_range(&self) -> lsp::Range {
+                self.selection_range
+            }
+        }
+    };
+}
+
+impl_lsp_hierarchy_item!(lsp::CallHierarchyItem);
+impl_lsp_hierarchy_item!(lsp::TypeHierarchyItem);
+
+impl<T: LspHierarchyItem> HierarchyItem<T> {
+    pub fn name(&self) -> &str {
+        self.lsp_item.name()
+    }
+
+    pub fn kind(&self) -> lsp::SymbolKind {
+        self.lsp_item.kind()
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.lsp_item.detail()
+    }
+}
+
+/// A call made to or from a [`CallHierarchyItem`].
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The calling symbol for incoming calls, or the called symbol for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The locations of the call expressions, within the calling symbol.
+    pub call_sites: Vec<Location>,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -741,6 +855,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -885,6 +1000,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1105,6 +1221,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -2341,6 +2458,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2865,6 +2984,117 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls { item },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: CallHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls { item },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes { item },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: TypeHierarchyItem,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes { item },
+            cx,
+        )
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Returns, for each of the given positions, the ranges that enclose it,
+    /// ordered from the innermost to the outermost.
+    pub fn selection_ranges(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        positions: Vec<Anchor>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<Vec<Range<Anchor>>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::FirstCapable,
+            GetSelectionRanges { positions },
+            cx,
+        )
+    }
+
     pub fn references<T: ToPointUtf16>(
         &mut self,
         buffer: &Entity<Buffer>,
@@ -3039,6 +3269,39 @@ impl Project {
         })
     }
 
+    pub fn code_lens(
+        &mut self,
+        buffer_handle: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.code_lens(buffer_handle, cx))
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(buffer_handle, lens, cx)
+        })
+    }
+
+    /// Executes the command of a resolved code lens on the language server that provided it.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Entity<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer_handle, lens, push_to_history, cx)
+        })
+    }
+
     pub fn apply_code_action_kind(
         &self,
         buffers: HashSet<Entity<Buffer>>,
@@ -3121,6 +3384,15 @@ impl Project {
         })
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<BufferSemanticTokens>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(buffer, cx))
+    }
+
     pub fn resolve_inlay_hint(
         &self,
         hint: InlayHint,
@@ -3159,6 +3431,15 @@ impl Project {
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
+                    if query.is_structural() {
+                        // Structural queries match against the syntax tree, which may still be
+                        // parsing for buffers that were just opened.
+                        let mut parse_status =
+                            buffer.read_with(&cx, |buffer, _| buffer.parse_status())?;
+                        while *parse_status.borrow() != ParseStatus::Idle {
+                            parse_status.changed().await?;
+                        }
+                    }
                     let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                     chunk_results.push(cx.background_spawn(async move {
                         let ranges = query
@@ -3211,8 +3492,9 @@ impl Project {
     ) -> Receiver<Entity<Buffer>> {
         if self.is_local() {
             let fs = self.fs.clone();
+            let languages = self.languages.clone();
             self.buffer_store.update(cx, |buffer_store, cx| {
-                buffer_store.find_search_candidates(query, limit, fs, cx)
+                buffer_store.find_search_candidates(query, limit, fs, languages, cx)
             })
         } else {
             self.find_search_candidates_remote(query, limit, cx)
@@ -2765,6 +2765,774 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn a() {}",
+            "b.rs": "fn b() { a(); }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let lsp_item = |name: &str, path: &str, range: lsp::Range| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let a_item = lsp_item(
+        "a",
+        path!("/dir/a.rs"),
+        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+    );
+    let b_item = lsp_item(
+        "b",
+        path!("/dir/b.rs"),
+        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+    );
+
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 3)
+                );
+                Ok(Some(vec![a_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            let b_item = b_item.clone();
+            async move {
+                assert_eq!(params.item, a_item);
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: b_item,
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(0, 9),
+                        lsp::Position::new(0, 10),
+                    )],
+                }]))
+            }
+        }
+    });
+
+    let mut items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 3, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    assert_eq!(item.name(), "a");
+    cx.update(|cx| {
+        assert_eq!(item.location.buffer, buffer);
+        assert_eq!(item.selection_range.to_offset(buffer.read(cx)), 3..4);
+    });
+
+    let mut calls = project
+        .update(cx, |project, cx| project.incoming_calls(item, cx))
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    let call = calls.pop().unwrap();
+    assert_eq!(call.item.name(), "b");
+    cx.update(|cx| {
+        let caller_buffer = call.item.location.buffer.read(cx);
+        assert_eq!(
+            caller_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new(path!("/dir/b.rs")),
+        );
+        assert_eq!(call.call_sites.len(), 1);
+        assert_eq!(call.call_sites[0].buffer, call.item.location.buffer);
+        assert_eq!(call.call_sites[0].range.to_offset(caller_buffer), 9..10);
+    });
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "shape.rs": "trait Shape {}",
+            "circle.rs": "struct Circle; impl Shape for Circle {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: serde_json::from_value(json!({ "typeHierarchyProvider": true })).unwrap(),
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/circle.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let lsp_item = |name: &str, path: &str, range: lsp::Range| lsp::TypeHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::STRUCT,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let circle_item = lsp_item(
+        "Circle",
+        path!("/dir/circle.rs"),
+        lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 13)),
+    );
+    let shape_item = lsp_item(
+        "Shape",
+        path!("/dir/shape.rs"),
+        lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 11)),
+    );
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let circle_item = circle_item.clone();
+        move |params, _| {
+            let circle_item = circle_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 7)
+                );
+                Ok(Some(vec![circle_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let circle_item = circle_item.clone();
+        move |params, _| {
+            let circle_item = circle_item.clone();
+            let shape_item = shape_item.clone();
+            async move {
+                assert_eq!(params.item, circle_item);
+                Ok(Some(vec![shape_item]))
+            }
+        }
+    });
+
+    let mut items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, 7, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    let item = items.pop().unwrap();
+    assert_eq!(item.name(), "Circle");
+    cx.update(|cx| {
+        assert_eq!(item.location.buffer, buffer);
+        assert_eq!(item.selection_range.to_offset(buffer.read(cx)), 7..13);
+    });
+
+    let mut supertypes = project
+        .update(cx, |project, cx| project.supertypes(item, cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    let supertype = supertypes.pop().unwrap();
+    assert_eq!(supertype.name(), "Shape");
+    cx.update(|cx| {
+        let supertype_buffer = supertype.location.buffer.read(cx);
+        assert_eq!(
+            supertype_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new(path!("/dir/shape.rs")),
+        );
+        assert_eq!(supertype.selection_range.to_offset(supertype_buffer), 6..11);
+    });
+}
+
+#[gpui::test]
+async fn test_pulled_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn main() {\n    let unused = 1;\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    // The server's current diagnostics, along with the ID of that result.
+    let server_diagnostics = Arc::new(Mutex::new((
+        "1".to_string(),
+        vec![lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(1, 8), lsp::Position::new(1, 14)),
+            severity: Some(lsp::DiagnosticSeverity::WARNING),
+            message: "unused variable".to_string(),
+            ..Default::default()
+        }],
+    )));
+    let mut pulls = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let server_diagnostics = server_diagnostics.clone();
+        move |params, _| {
+            let (result_id, items) = server_diagnostics.lock().clone();
+            async move {
+                assert_eq!(
+                    params.text_document.uri,
+                    lsp::Url::from_file_path(path!("/dir/a.rs")).unwrap()
+                );
+                let report = if params.previous_result_id.as_ref() == Some(&result_id) {
+                    lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    )
+                } else {
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some(result_id),
+                            items,
+                        },
+                    })
+                };
+                Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+            }
+        }
+    });
+    let diagnostics = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    cx.executor().advance_clock(Duration::from_millis(200));
+    pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diagnostics(cx),
+        vec![(
+            Point::new(1, 8)..Point::new(1, 14),
+            "unused variable".to_string()
+        )]
+    );
+
+    // Editing the buffer pulls its diagnostics again, which the server reports
+    // as unchanged since the previous result.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "// main\n")], None, cx)
+    });
+    cx.executor().advance_clock(Duration::from_millis(200));
+    pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        diagnostics(cx),
+        vec![(
+            Point::new(2, 8)..Point::new(2, 14),
+            "unused variable".to_string()
+        )]
+    );
+
+    // A refresh request from the server pulls the diagnostics again.
+    *server_diagnostics.lock() = ("2".to_string(), Vec::new());
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(200));
+    pulls.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(diagnostics(cx), Vec::new());
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn main() { let x = 1; }",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensOptions {
+                        legend: lsp::SemanticTokensLegend {
+                            token_types: vec![
+                                lsp::SemanticTokenType::FUNCTION,
+                                lsp::SemanticTokenType::VARIABLE,
+                            ],
+                            token_modifiers: vec![
+                                lsp::SemanticTokenModifier::DECLARATION,
+                                lsp::SemanticTokenModifier::READONLY,
+                            ],
+                        },
+                        full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        ..Default::default()
+                    }
+                    .into(),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let token =
+        |delta_line, delta_start, length, token_type, token_modifiers_bitset| lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset,
+        };
+
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        move |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 3, 4, 0, 1), token(0, 13, 1, 1, 1)],
+                },
+            )))
+        },
+    );
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(tokens.len(), 1);
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        let tokens = &tokens[0];
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    tokens.token_type(token).unwrap(),
+                    tokens.token_modifiers(token).collect::<Vec<_>>(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (3..7, "function", vec!["declaration"]),
+                (16..17, "variable", vec!["declaration"]),
+            ]
+        );
+    });
+
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: Some(vec![token(0, 13, 1, 1, 3)]),
+                    }],
+                },
+            )))
+        },
+    );
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        let tokens = &tokens[0];
+        assert_eq!(
+            tokens
+                .tokens
+                .iter()
+                .map(|token| (
+                    token.range.to_offset(buffer),
+                    tokens.token_modifiers(token).collect::<Vec<_>>(),
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (3..7, vec!["declaration"]),
+                (16..17, vec!["declaration", "readonly"]),
+            ]
+        );
+    });
+
+    // When two deltas are computed against the same result, only the first one can be applied,
+    // and the full tokens are requested instead of the second one.
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>({
+        let requests = requests.clone();
+        move |params, _| {
+            let requests = requests.clone();
+            async move {
+                let mut requests = requests.lock();
+                requests.push(format!("delta from {}", params.previous_result_id));
+                Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                    lsp::SemanticTokensDelta {
+                        result_id: Some(format!("delta {}", requests.len())),
+                        edits: Vec::new(),
+                    },
+                )))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>({
+        let requests = requests.clone();
+        move |_, _| {
+            let requests = requests.clone();
+            async move {
+                requests.lock().push("full".to_string());
+                Ok(Some(lsp::SemanticTokensResult::Tokens(
+                    lsp::SemanticTokens {
+                        result_id: Some("full".into()),
+                        data: vec![token(0, 3, 4, 0, 1), token(0, 13, 1, 1, 1)],
+                    },
+                )))
+            }
+        }
+    });
+    let first = project.update(cx, |project, cx| project.semantic_tokens(&buffer, cx));
+    let second = project.update(cx, |project, cx| project.semantic_tokens(&buffer, cx));
+    let (first, second) = future::join(first, second).await;
+    assert_eq!(first.unwrap().len(), 1);
+    assert_eq!(second.unwrap().len(), 1);
+    project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        *requests.lock(),
+        ["delta from 2", "delta from 2", "full", "delta from full"]
+    );
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn main() {}\n\n#[test]\nfn it_works() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let run_test = lsp::Command {
+        title: "Run Test".into(),
+        command: "run-test".into(),
+        arguments: Some(vec![json!("it_works")]),
+    };
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(3, 3), lsp::Position::new(3, 11)),
+            command: None,
+            data: Some(json!({ "test": "it_works" })),
+        }]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>({
+        let run_test = run_test.clone();
+        move |lens, _| {
+            let run_test = run_test.clone();
+            async move {
+                assert_eq!(lens.data, Some(json!({ "test": "it_works" })));
+                Ok(lsp::CodeLens {
+                    command: Some(run_test),
+                    ..lens
+                })
+            }
+        }
+    });
+
+    let mut code_lens = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(code_lens.len(), 1);
+    let lens = code_lens.pop().unwrap();
+    assert!(!lens.is_resolved());
+    cx.update(|cx| assert_eq!(lens.range.to_offset(buffer.read(cx)), 25..33));
+
+    let lens = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(buffer.clone(), lens, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(lens.lsp_lens.command.as_ref(), Some(&run_test));
+
+    let mut execute_command =
+        fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "run-test");
+            assert_eq!(params.arguments, vec![json!("it_works")]);
+            Ok(None)
+        });
+    project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), lens, true, cx)
+        })
+        .await
+        .unwrap();
+    execute_command.next().await.unwrap();
+}
+
+#[gpui::test]
+async fn test_folding_and_selection_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "fn main() {\n    let a = 1;\n    // #region\n    let b = 2;\n    // #endregion\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        let folding_range = |start_line, end_line, kind| lsp::FoldingRange {
+            start_line,
+            end_line,
+            kind,
+            ..Default::default()
+        };
+        Ok(Some(vec![
+            folding_range(2, 3, None),
+            folding_range(0, 4, None),
+            // Ranges within a single line can't be folded.
+            folding_range(3, 3, None),
+            folding_range(2, 4, Some(lsp::FoldingRangeKind::Region)),
+        ]))
+    });
+
+    let folding_ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            folding_ranges
+                .iter()
+                .map(|range| (range.range.to_point(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Point::new(0, 11)..Point::new(4, 17), None),
+                (
+                    Point::new(2, 14)..Point::new(4, 17),
+                    Some(lsp::FoldingRangeKind::Region)
+                ),
+                (Point::new(2, 14)..Point::new(3, 14), None),
+            ]
+        );
+    });
+
+    fake_server.handle_request::<lsp::request::SelectionRangeRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.positions, vec![lsp::Position::new(1, 8)]);
+            Ok(Some(vec![lsp::SelectionRange {
+                range: lsp::Range::new(lsp::Position::new(1, 8), lsp::Position::new(1, 9)),
+                parent: Some(Box::new(lsp::SelectionRange {
+                    range: lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 14)),
+                    parent: Some(Box::new(lsp::SelectionRange {
+                        range: lsp::Range::new(lsp::Position::new(0, 10), lsp::Position::new(5, 1)),
+                        parent: None,
+                    })),
+                })),
+            }]))
+        },
+    );
+
+    let position = buffer.read_with(cx, |buffer, _| buffer.anchor_before(Point::new(1, 8)));
+    let selection_ranges = project
+        .update(cx, |project, cx| {
+            project.selection_ranges(&buffer, vec![position], cx)
+        })
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(
+            selection_ranges
+                .iter()
+                .map(|chain| {
+                    chain
+                        .iter()
+                        .map(|range| range.to_point(buffer))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>(),
+            vec![vec![
+                Point::new(1, 8)..Point::new(1, 9),
+                Point::new(1, 4)..Point::new(1, 14),
+                Point::new(0, 10)..Point::new(5, 1),
+            ]]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4880,6 +5648,86 @@ async fn test_search_multiple_worktrees_with_inclusions(cx: &mut gpui::TestAppCo
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn main() { let x = foo.unwrap(); bar.baz().unwrap(); }",
+            "two.rs": "fn two() { foo.expect(\"unwrap\"); }",
+            "three.txt": "foo.unwrap()",
+            "four.json": "{ \"call_expression\": \"foo.unwrap()\" }",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    language_registry.add(json_lang());
+
+    let query = SearchQuery::structural(
+        "$X.unwrap()",
+        false,
+        Default::default(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+    assert!(SearchQuery::from_proto(query.to_proto())
+        .unwrap()
+        .is_structural());
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/one.rs").to_string(), vec![20..32, 34..52]),
+            // Files containing every word of the pattern are parsed, but only match structurally.
+            // Files in a language without a grammar for the pattern aren't opened at all.
+            (separator!("dir/two.rs").to_string(), vec![]),
+            (separator!("dir/three.txt").to_string(), vec![]),
+        ])
+    );
+
+    let query = query.with_replacement("$X?".to_string());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/one.rs"), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(
+        query.replacements_for_matches(&snapshot, &[20..32, 34..52, 0..2]),
+        [
+            Some("foo?".to_string()),
+            Some("bar.baz()?".to_string()),
+            None
+        ]
+    );
+    // Without a buffer, replacements can't refer to the captures of a match.
+    assert_eq!(query.replacement_for("bar.baz().unwrap()"), None);
+
+    let query = SearchQuery::structural(
+        r#"(call_expression function: (field_expression field: (field_identifier) @method (#eq? @method "expect"))) @match"#,
+        false,
+        Default::default(),
+        Default::default(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/one.rs").to_string(), vec![]),
+            (separator!("dir/two.rs").to_string(), vec![11..31]),
+            (separator!("dir/three.txt").to_string(), vec![]),
+        ])
+    );
+}
+
 #[gpui::test]
 async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     init_test(cx);
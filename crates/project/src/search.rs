@@ -3,7 +3,7 @@ use anyhow::Result;
 use client::proto;
 use fancy_regex::{Captures, Regex, RegexBuilder};
 use gpui::Entity;
-use language::{Buffer, BufferSnapshot, CharKind};
+use language::{Buffer, BufferSnapshot, CharKind, Language};
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
@@ -15,6 +15,10 @@ use std::{
 use text::Anchor;
 use util::paths::PathMatcher;
 
+mod structural;
+
+pub use structural::{StructuralMatch, StructuralPattern};
+
 pub enum SearchResult {
     Buffer {
         buffer: Entity<Buffer>,
@@ -72,6 +76,13 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -158,8 +169,41 @@ impl SearchQuery {
         })
     }
 
+    /// Creates a query that matches syntax nodes, given either a tree-sitter query or a code
+    /// pattern with `$NAME` metavariables. See [`StructuralPattern`].
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(query.clone())?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -191,6 +235,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -202,6 +250,7 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -247,14 +296,19 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(pattern.may_match(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
@@ -281,6 +335,37 @@ impl SearchQuery {
                     None
                 }
             }
+            SearchQuery::Structural {
+                pattern,
+                replacement,
+                ..
+            } => replacement
+                .as_deref()
+                .and_then(|replacement| pattern.replacement_without_captures(replacement))
+                .map(Cow::from),
+        }
+    }
+
+    /// Returns the replacement for each of the given match ranges in the buffer. Unlike
+    /// [`Self::replacement_for`], this expands references to the captures of structural matches.
+    pub fn replacements_for_matches(
+        &self,
+        buffer: &BufferSnapshot,
+        ranges: &[Range<usize>],
+    ) -> Vec<Option<String>> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacements_for(replacement, buffer, ranges),
+            _ => ranges
+                .iter()
+                .map(|range| {
+                    let text = buffer.text_for_range(range.clone()).collect::<String>();
+                    self.replacement_for(&text).map(Cow::into_owned)
+                })
+                .collect(),
         }
     }
 
@@ -376,6 +461,16 @@ impl SearchQuery {
                     }
                 }
             }
+            Self::Structural { pattern, .. } => {
+                // Syntax nodes are matched against the whole buffer's syntax tree, so matches
+                // outside of the subrange are discarded afterwards.
+                let subrange = range_offset..range_offset + rope.len();
+                for mat in pattern.search(buffer) {
+                    if subrange.start <= mat.range.start && mat.range.end <= subrange.end {
+                        matches.push(mat.range.start - range_offset..mat.range.end - range_offset);
+                    }
+                }
+            }
         }
 
         matches
@@ -393,6 +488,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -400,6 +496,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -411,6 +508,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -418,6 +518,19 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
+    /// Returns whether files in the given language may contain matches. Only structural queries
+    /// depend on the language.
+    pub(crate) fn matches_language(&self, language: &Arc<Language>) -> bool {
+        match self {
+            Self::Structural { pattern, .. } => pattern.may_match_language(language),
+            Self::Text { .. } | Self::Regex { .. } => true,
+        }
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -455,7 +568,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
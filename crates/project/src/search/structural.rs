@@ -0,0 +1,401 @@
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{BufferSnapshot, Language, LanguageName, Node, Tree};
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use std::{
+    fmt,
+    ops::Range,
+    sync::{Arc, LazyLock},
+};
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Query, QueryCursor};
+
+/// The capture that determines the range of a tree-sitter query match. When a query has no such
+/// capture, the match spans all of its captures.
+const MATCH_CAPTURE: &str = "match";
+const METAVARIABLE_PLACEHOLDER_PREFIX: &str = "__zed_";
+
+static METAVARIABLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$([A-Za-z_][A-Za-z0-9_]*)").unwrap());
+static REPLACEMENT_VARIABLE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$(?:(\$)|\{([^}]+)\}|([A-Za-z_][A-Za-z0-9_]*))").unwrap());
+static WORD_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+/// A search pattern that matches syntax nodes rather than text.
+///
+/// The pattern is either a tree-sitter query, such as `(call_expression function: (_) @callee)`,
+/// or a snippet of code in which `$NAME` metavariables stand for any syntax node, such as
+/// `$X.unwrap()`. Patterns are compiled lazily for each language they are matched against,
+/// since the same source may be a valid query for one grammar and a code snippet for another.
+pub struct StructuralPattern {
+    source: String,
+    compiled: Mutex<HashMap<LanguageName, Option<Arc<CompiledPattern>>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    pub captures: Vec<(String, Range<usize>)>,
+}
+
+enum CompiledPattern {
+    Query(Query),
+    Code(CodePattern),
+}
+
+struct CodePattern {
+    tree: Tree,
+    text: String,
+    /// Maps each placeholder identifier in `text` to the name of its metavariable.
+    metavariables: HashMap<String, String>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: String) -> Result<Self> {
+        let trimmed = source.trim();
+        if trimmed.is_empty() {
+            return Err(anyhow!("structural search pattern is empty"));
+        }
+        if METAVARIABLE_REGEX
+            .find(trimmed)
+            .is_some_and(|metavariable| metavariable.as_str() == trimmed)
+        {
+            return Err(anyhow!(
+                "structural search pattern must contain more than a metavariable"
+            ));
+        }
+        Ok(Self {
+            source,
+            compiled: Default::default(),
+        })
+    }
+
+    fn may_be_query(&self) -> bool {
+        self.source.trim_start().starts_with(['(', '['])
+    }
+
+    /// Returns whether `text` could contain a match, without parsing it. Code patterns can only
+    /// match text that contains each of their words, while queries can match anything.
+    pub fn may_match(&self, text: &str) -> bool {
+        if self.may_be_query() {
+            return true;
+        }
+        let without_metavariables = METAVARIABLE_REGEX.replace_all(&self.source, " ");
+        WORD_REGEX
+            .find_iter(&without_metavariables)
+            .all(|word| text.contains(word.as_str()))
+    }
+
+    /// Returns whether a file in the given language could contain a match, as patterns are only
+    /// valid for some grammars. Languages injected into the file aren't considered.
+    pub fn may_match_language(&self, language: &Arc<Language>) -> bool {
+        self.compiled_for(language).is_some()
+    }
+
+    /// Finds the non-overlapping matches in every syntax layer of the buffer.
+    pub fn search(&self, buffer: &BufferSnapshot) -> Vec<StructuralMatch> {
+        let text = buffer.text();
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            if let Some(compiled) = self.compiled_for(layer.language) {
+                compiled.matches(layer.node(), &text, &mut matches);
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut last_end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= last_end && !mat.range.is_empty();
+            if keep {
+                last_end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Returns the replacement for each of the given match ranges in the buffer, with the
+    /// references in `replacement` expanded to the text captured by that match. The captures
+    /// are found by searching the buffer again, so that all ranges of a buffer share one search.
+    pub fn replacements_for(
+        &self,
+        replacement: &str,
+        buffer: &BufferSnapshot,
+        ranges: &[Range<usize>],
+    ) -> Vec<Option<String>> {
+        let matches = self.search(buffer);
+        ranges
+            .iter()
+            .map(|range| {
+                let captures = matches
+                    .binary_search_by_key(&range.start, |mat| mat.range.start)
+                    .ok()
+                    .map(|ix| &matches[ix])
+                    .filter(|mat| mat.range == *range)
+                    .map(|mat| {
+                        mat.captures
+                            .iter()
+                            .map(|(name, range)| {
+                                (
+                                    name.clone(),
+                                    buffer.text_for_range(range.clone()).collect::<String>(),
+                                )
+                            })
+                            .collect::<HashMap<_, _>>()
+                    });
+                expand_replacement(replacement, captures.as_ref())
+            })
+            .collect()
+    }
+
+    /// Returns `replacement` if it refers to no captures, as the captures of a match are not
+    /// known from its text alone.
+    pub fn replacement_without_captures(&self, replacement: &str) -> Option<String> {
+        expand_replacement(replacement, None)
+    }
+
+    fn compiled_for(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| self.compile(language).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, language: &Language) -> Option<CompiledPattern> {
+        let ts_language = &language.grammar()?.ts_language;
+        if self.may_be_query() {
+            if let Ok(query) = Query::new(ts_language, &self.source) {
+                if !query.capture_names().is_empty() {
+                    return Some(CompiledPattern::Query(query));
+                }
+                let query = Query::new(ts_language, &format!("{} @{MATCH_CAPTURE}", self.source));
+                return query.ok().map(CompiledPattern::Query);
+            }
+        }
+
+        let mut metavariables = HashMap::default();
+        let text = METAVARIABLE_REGEX
+            .replace_all(self.source.trim(), |c: &Captures| {
+                let placeholder = format!("{METAVARIABLE_PLACEHOLDER_PREFIX}{}", &c[1]);
+                metavariables.insert(placeholder.clone(), c[1].to_string());
+                placeholder
+            })
+            .into_owned();
+        let tree = language::with_parser(|parser| {
+            parser.set_language(ts_language).ok()?;
+            parser.parse(&text, None)
+        })?;
+        let pattern = CodePattern {
+            tree,
+            text,
+            metavariables,
+        };
+        if pattern.root().has_error() {
+            None
+        } else {
+            Some(CompiledPattern::Code(pattern))
+        }
+    }
+}
+
+/// Expands `$NAME` and `${NAME}` references to captures in `replacement`. `$$` expands to a
+/// literal `$`. Returns `None` if the replacement refers to captures but none are given.
+fn expand_replacement(
+    replacement: &str,
+    captures: Option<&HashMap<String, String>>,
+) -> Option<String> {
+    let mut missing_captures = false;
+    let expanded = REPLACEMENT_VARIABLE_REGEX.replace_all(replacement, |c: &Captures| {
+        if c.get(1).is_some() {
+            return "$".to_string();
+        }
+        let name = c.get(2).or_else(|| c.get(3)).unwrap().as_str();
+        match captures {
+            Some(captures) => captures
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| c[0].to_string()),
+            None => {
+                missing_captures = true;
+                String::new()
+            }
+        }
+    });
+    if missing_captures {
+        None
+    } else {
+        Some(expanded.into_owned())
+    }
+}
+
+impl CompiledPattern {
+    fn matches(&self, root: Node, text: &str, matches: &mut Vec<StructuralMatch>) {
+        match self {
+            CompiledPattern::Query(query) => {
+                let capture_names = query.capture_names();
+                let mut cursor = QueryCursor::new();
+                let mut query_matches = cursor.matches(query, root, text.as_bytes());
+                while let Some(mat) = query_matches.next() {
+                    let mut range: Option<Range<usize>> = None;
+                    let mut captures = Vec::new();
+                    for capture in mat.captures {
+                        let name = capture_names[capture.index as usize];
+                        let capture_range = capture.node.byte_range();
+                        if name == MATCH_CAPTURE {
+                            range = Some(capture_range.clone());
+                        }
+                        captures.push((name.to_string(), capture_range));
+                    }
+                    let range = range.or_else(|| {
+                        let start = captures.iter().map(|(_, range)| range.start).min()?;
+                        let end = captures.iter().map(|(_, range)| range.end).max()?;
+                        Some(start..end)
+                    });
+                    if let Some(range) = range {
+                        matches.push(StructuralMatch { range, captures });
+                    }
+                }
+            }
+            CompiledPattern::Code(pattern) => {
+                let pattern_root = pattern.root();
+                let mut cursor = root.walk();
+                'outer: loop {
+                    let node = cursor.node();
+                    let mut captures = Vec::new();
+                    if node.kind_id() == pattern_root.kind_id()
+                        && pattern.match_node(pattern_root, node, text, &mut captures)
+                    {
+                        matches.push(StructuralMatch {
+                            range: node.byte_range(),
+                            captures,
+                        });
+                    } else if cursor.goto_first_child() {
+                        continue;
+                    }
+
+                    loop {
+                        if cursor.goto_next_sibling() {
+                            continue 'outer;
+                        }
+                        if !cursor.goto_parent() {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CodePattern {
+    /// The outermost node spanning the whole pattern that isn't a mere wrapper around a single
+    /// child, so that `$X.unwrap()` matches call expressions rather than whole source files.
+    fn root(&self) -> Node<'_> {
+        let mut node = self.tree.root_node();
+        loop {
+            let mut cursor = node.walk();
+            let mut children = node
+                .children(&mut cursor)
+                .filter(|child| !child.is_extra() && !child.is_missing());
+            match (children.next(), children.next()) {
+                (Some(child), None) if child.byte_range() == node.byte_range() => node = child,
+                _ => return node,
+            }
+        }
+    }
+
+    fn metavariable(&self, node: Node) -> Option<&String> {
+        self.metavariables.get(&self.text[node.byte_range()])
+    }
+
+    fn match_node(
+        &self,
+        pattern: Node,
+        candidate: Node,
+        text: &str,
+        captures: &mut Vec<(String, Range<usize>)>,
+    ) -> bool {
+        if let Some(name) = self.metavariable(pattern) {
+            let range = candidate.byte_range();
+            if let Some((_, previous)) = captures.iter().find(|(existing, _)| existing == name) {
+                return text[previous.clone()] == text[range];
+            }
+            captures.push((name.clone(), range));
+            return true;
+        }
+
+        if pattern.kind_id() != candidate.kind_id() {
+            return false;
+        }
+
+        let mut pattern_cursor = pattern.walk();
+        let pattern_children = pattern
+            .children(&mut pattern_cursor)
+            .filter(|child| !child.is_extra())
+            .collect::<Vec<_>>();
+        let mut candidate_cursor = candidate.walk();
+        let candidate_children = candidate
+            .children(&mut candidate_cursor)
+            .filter(|child| !child.is_extra())
+            .collect::<Vec<_>>();
+
+        if pattern_children.is_empty() {
+            return candidate_children.is_empty()
+                && self.text[pattern.byte_range()] == text[candidate.byte_range()];
+        }
+
+        pattern_children.len() == candidate_children.len()
+            && pattern_children
+                .into_iter()
+                .zip(candidate_children)
+                .all(|(pattern, candidate)| self.match_node(pattern, candidate, text, captures))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacement_expansion() {
+        let captures = HashMap::from_iter([("X".to_string(), "foo".to_string())]);
+
+        assert_eq!(
+            expand_replacement("$X.expect(\"${X} is set\")", Some(&captures)),
+            Some("foo.expect(\"foo is set\")".to_string())
+        );
+        assert_eq!(
+            expand_replacement("$$X or $Y", Some(&captures)),
+            Some("$X or $Y".to_string())
+        );
+        assert_eq!(expand_replacement("$X?", None), None);
+        assert_eq!(
+            expand_replacement("todo!()", None),
+            Some("todo!()".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pattern_prefilter() {
+        let pattern = StructuralPattern::new("$X.unwrap()".to_string()).unwrap();
+        assert!(pattern.may_match("let a = b.unwrap();"));
+        assert!(!pattern.may_match("let a = b.expect(\"\");"));
+
+        let query = StructuralPattern::new("(call_expression) @call".to_string()).unwrap();
+        assert!(query.may_match(""));
+
+        assert!(StructuralPattern::new("  ".to_string()).is_err());
+        assert!(StructuralPattern::new("$X".to_string()).is_err());
+    }
+}
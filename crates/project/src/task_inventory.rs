@@ -15,7 +15,8 @@ use itertools::Itertools;
 use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    ResolvedTask, TaskContext, TaskGraph, TaskGraphNode, TaskId, TaskTemplate, TaskTemplates,
+    TaskVariables, VariableName,
 };
 use text::{Point, ToPoint};
 use util::{paths::PathExt as _, post_inc, NumericPrefixWithSuffix, ResultExt as _};
@@ -318,6 +319,37 @@ impl Inventory {
         self.last_scheduled_tasks.retain(|(_, task)| &task.id != id);
     }
 
+    /// Resolves the given task and all tasks it depends on, directly or transitively, into a [`TaskGraph`].
+    /// Dependencies are looked up by their labels among the tasks available in the task's worktree, preferring the
+    /// tasks from the same source, and are resolved with the same [`TaskContext`] as the task itself.
+    ///
+    /// Fails if any dependency cannot be found or resolved, or if the tasks depend on each other in a cycle.
+    pub fn resolve_task_graph(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        resolved_task: ResolvedTask,
+        cx: &App,
+    ) -> Result<TaskGraph> {
+        let worktree = match task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let templates = self
+            .list_tasks(None, None, worktree, cx)
+            .into_iter()
+            .sorted_by_key(|(kind, _)| kind != task_source_kind)
+            .collect::<Vec<_>>();
+        let mut builder = TaskGraphBuilder {
+            templates: &templates,
+            task_context: resolved_task.task_context().clone(),
+            nodes: Vec::new(),
+            resolved_labels: HashMap::default(),
+            labels_in_progress: Vec::new(),
+        };
+        builder.add_task(resolved_task)?;
+        TaskGraph::new(builder.nodes).context("building task graph")
+    }
+
     fn global_templates_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
@@ -404,6 +436,61 @@ impl Inventory {
     }
 }
 
+struct TaskGraphBuilder<'a> {
+    templates: &'a [(TaskSourceKind, TaskTemplate)],
+    task_context: TaskContext,
+    nodes: Vec<TaskGraphNode>,
+    resolved_labels: HashMap<String, usize>,
+    labels_in_progress: Vec<String>,
+}
+
+impl TaskGraphBuilder<'_> {
+    /// Adds the task after all of its dependencies, returning its index in the graph.
+    fn add_task(&mut self, task: ResolvedTask) -> Result<usize> {
+        let label = task.original_task().label.clone();
+        if let Some(cycle_start) = self
+            .labels_in_progress
+            .iter()
+            .position(|label_in_progress| label_in_progress == &label)
+        {
+            anyhow::bail!(
+                "task dependencies form a cycle: {} -> {label}",
+                self.labels_in_progress[cycle_start..].join(" -> ")
+            );
+        }
+
+        self.labels_in_progress.push(label.clone());
+        let mut depends_on = Vec::with_capacity(task.original_task().depends_on.len());
+        for dependency in &task.original_task().depends_on {
+            let index = match self.resolved_labels.get(dependency) {
+                Some(index) => *index,
+                None => {
+                    let (kind, template) = self
+                        .templates
+                        .iter()
+                        .find(|(_, template)| &template.label == dependency)
+                        .with_context(|| {
+                            format!("task {label:?} depends on unknown task {dependency:?}")
+                        })?;
+                    let dependency_task = template
+                        .resolve_task(&kind.to_id_base(), &self.task_context)
+                        .with_context(|| {
+                            format!("resolving task {dependency:?}, required by task {label:?}")
+                        })?;
+                    self.add_task(dependency_task)?
+                }
+            };
+            depends_on.push(index);
+        }
+        self.labels_in_progress.pop();
+
+        let index = self.nodes.len();
+        self.nodes.push(TaskGraphNode { task, depends_on });
+        self.resolved_labels.insert(label, index);
+        Ok(index)
+    }
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -927,6 +1014,80 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_resolving_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            { "label": "build", "command": "cargo build" },
+                            { "label": "lint", "command": "cargo clippy", "depends_on": ["build"] },
+                            { "label": "test", "command": "cargo test", "depends_on": ["build"] },
+                            {
+                                "label": "ci",
+                                "depends_on": ["lint", "test"],
+                                "depends_order": "sequence"
+                            },
+                            { "label": "missing", "command": "echo", "depends_on": ["unknown"] },
+                            { "label": "cycle_a", "command": "echo", "depends_on": ["cycle_b"] },
+                            { "label": "cycle_b", "command": "echo", "depends_on": ["cycle_a"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+
+        let resolve_graph = |label: &str, cx: &mut TestAppContext| {
+            inventory.update(cx, |inventory, cx| {
+                let (kind, template) = inventory
+                    .list_tasks(None, None, None, cx)
+                    .into_iter()
+                    .find(|(_, template)| template.label == label)
+                    .unwrap();
+                let resolved_task = template
+                    .resolve_task(&kind.to_id_base(), &TaskContext::default())
+                    .unwrap();
+                inventory.resolve_task_graph(&kind, resolved_task, cx)
+            })
+        };
+
+        let graph = resolve_graph("ci", cx).unwrap();
+        assert_eq!(
+            graph
+                .nodes()
+                .iter()
+                .map(|node| (node.task.resolved_label.as_str(), node.depends_on.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("build", vec![]),
+                ("lint", vec![0]),
+                ("test", vec![0]),
+                ("ci", vec![1, 2]),
+            ],
+            "Shared dependencies should be resolved once, before all of their dependents"
+        );
+        assert_eq!(graph.root().task.resolved_label, "ci");
+
+        let graph = resolve_graph("build", cx).unwrap();
+        assert_eq!(graph.nodes().len(), 1);
+
+        let error = resolve_graph("missing", cx).unwrap_err();
+        assert!(
+            error.to_string().contains("unknown task \"unknown\""),
+            "Unexpected error: {error:#}"
+        );
+        let error = resolve_graph("cycle_a", cx).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "task dependencies form a cycle: cycle_a -> cycle_b -> cycle_a"
+        );
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
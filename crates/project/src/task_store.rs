@@ -11,13 +11,13 @@ use language::{
 };
 use rpc::{proto, AnyProtoClient, TypedEnvelope};
 use settings::{watch_config_file, SettingsLocation};
-use task::{TaskContext, TaskVariables, VariableName};
+use task::{ResolvedTask, TaskContext, TaskGraph, TaskVariables, VariableName};
 use text::{BufferId, OffsetRangeExt};
 use util::ResultExt;
 
 use crate::{
     buffer_store::BufferStore, worktree_store::WorktreeStore, BasicContextProvider, Inventory,
-    ProjectEnvironment,
+    ProjectEnvironment, TaskSourceKind,
 };
 
 #[allow(clippy::large_enum_variant)] // platform-dependent warning
@@ -234,6 +234,21 @@ impl TaskStore {
         }
     }
 
+    /// Resolves the task and everything it depends on into a [`TaskGraph`], see [`Inventory::resolve_task_graph`].
+    pub fn resolve_task_graph(
+        &self,
+        task_source_kind: &TaskSourceKind,
+        resolved_task: ResolvedTask,
+        cx: &App,
+    ) -> anyhow::Result<TaskGraph> {
+        let inventory = self
+            .task_inventory()
+            .context("no task inventory to resolve task dependencies with")?;
+        inventory
+            .read(cx)
+            .resolve_task_graph(task_source_kind, resolved_task, cx)
+    }
+
     pub fn shared(&mut self, remote_id: u64, new_downstream_client: AnyProtoClient, _cx: &mut App) {
         if let Self::Functional(StoreState {
             mode: StoreMode::Local {
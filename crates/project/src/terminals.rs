@@ -4,6 +4,7 @@ use collections::HashMap;
 use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, Task, WeakEntity};
 use itertools::Itertools;
 use language::LanguageName;
+use lsp::LanguageServerId;
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::{
@@ -13,7 +14,9 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, SpawnInTerminal};
+use task::{
+    Problem, ProblemScanner, ProblemSeverity, Shell, ShellBuilder, SpawnInTerminal, TaskId,
+};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
@@ -25,6 +28,13 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    pub(crate) task_diagnostics: HashMap<TaskId, TaskDiagnostics>,
+}
+
+/// Diagnostics, found by the problem matchers in the output of the last run of a task.
+pub(crate) struct TaskDiagnostics {
+    server_id: LanguageServerId,
+    diagnostics: HashMap<PathBuf, Vec<lsp::Diagnostic>>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -40,6 +50,7 @@ pub enum TerminalKind {
 /// SshCommand describes how to connect to a remote server
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshCommand {
+    program: String,
     arguments: Vec<String>,
 }
 
@@ -67,10 +78,10 @@ impl Project {
     pub fn ssh_details(&self, cx: &App) -> Option<(String, SshCommand)> {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
-            if let Some(args) = ssh_client.ssh_args() {
+            if let Some((program, arguments)) = ssh_client.shell_command() {
                 return Some((
                     ssh_client.connection_options().host.clone(),
-                    SshCommand { arguments: args },
+                    SshCommand { program, arguments },
                 ));
             }
         }
@@ -229,6 +240,7 @@ impl Project {
         };
 
         let mut python_venv_activate_command = None;
+        let mut task_problems_source = None;
 
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
@@ -264,6 +276,22 @@ impl Project {
                 }
             }
             TerminalKind::Task(spawn_task) => {
+                this.clear_task_diagnostics(&spawn_task.id, cx);
+                let problem_scanner = if spawn_task.problem_matcher.is_empty()
+                    || this.lsp_store.read(cx).as_local().is_none()
+                {
+                    None
+                } else {
+                    ProblemScanner::new(&spawn_task.problem_matcher).log_err()
+                };
+                if problem_scanner.is_some() {
+                    task_problems_source = Some((
+                        spawn_task.id.clone(),
+                        spawn_task.label.clone(),
+                        path.clone(),
+                    ));
+                }
+
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -274,6 +302,7 @@ impl Project {
                     show_summary: spawn_task.show_summary,
                     show_command: spawn_task.show_command,
                     completion_rx,
+                    problem_scanner,
                 });
 
                 env.extend(spawn_task.env);
@@ -359,6 +388,21 @@ impl Project {
             })
             .detach();
 
+            if let Some((task_id, task_label, cwd)) = task_problems_source {
+                cx.subscribe(&terminal_handle, move |project, _, event, cx| {
+                    if let terminal::Event::TaskProblems(problems) = event {
+                        project.publish_task_problems(
+                            &task_id,
+                            &task_label,
+                            cwd.as_deref(),
+                            problems,
+                            cx,
+                        );
+                    }
+                })
+                .detach();
+            }
+
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
@@ -366,6 +410,73 @@ impl Project {
         })
     }
 
+    /// Removes the diagnostics, published for the previous run of the task.
+    fn clear_task_diagnostics(&mut self, task_id: &TaskId, cx: &mut Context<Self>) {
+        let Some(task_diagnostics) = self.terminals.task_diagnostics.remove(task_id) else {
+            return;
+        };
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.release_diagnostic_source(task_diagnostics.server_id, cx)
+        });
+    }
+
+    fn publish_task_problems(
+        &mut self,
+        task_id: &TaskId,
+        task_label: &str,
+        cwd: Option<&Path>,
+        problems: &[Problem],
+        cx: &mut Context<Self>,
+    ) {
+        let lsp_store = self.lsp_store.clone();
+        let task_diagnostics = self
+            .terminals
+            .task_diagnostics
+            .entry(task_id.clone())
+            .or_insert_with(|| TaskDiagnostics {
+                server_id: lsp_store
+                    .update(cx, |lsp_store, _| lsp_store.reserve_diagnostic_source()),
+                diagnostics: HashMap::default(),
+            });
+
+        let mut updated_paths = Vec::new();
+        for problem in problems {
+            let abs_path = match cwd {
+                Some(cwd) if problem.path.is_relative() => cwd.join(&problem.path),
+                _ => problem.path.clone(),
+            };
+            let diagnostics = task_diagnostics
+                .diagnostics
+                .entry(abs_path.clone())
+                .or_default();
+            diagnostics.push(problem_to_diagnostic(problem, task_label));
+            if !updated_paths.contains(&abs_path) {
+                updated_paths.push(abs_path);
+            }
+        }
+
+        let server_id = task_diagnostics.server_id;
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            for abs_path in updated_paths {
+                let Ok(uri) = lsp::Url::from_file_path(&abs_path) else {
+                    continue;
+                };
+                lsp_store
+                    .update_diagnostics(
+                        server_id,
+                        lsp::PublishDiagnosticsParams {
+                            uri,
+                            diagnostics: task_diagnostics.diagnostics[&abs_path].clone(),
+                            version: None,
+                        },
+                        &[],
+                        cx,
+                    )
+                    .log_err();
+            }
+        });
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
@@ -561,10 +672,9 @@ fn wrap_for_ssh(
     };
     let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
 
-    let program = "ssh".to_string();
+    let program = ssh_command.program.clone();
     let mut args = ssh_command.arguments.clone();
 
-    args.push("-t".to_string());
     args.push(shell_invocation);
     (program, args)
 }
@@ -622,3 +732,33 @@ mod tests {
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
 }
+
+fn problem_to_diagnostic(problem: &Problem, source: &str) -> lsp::Diagnostic {
+    let start = lsp::Position::new(
+        problem.line.saturating_sub(1),
+        problem.column.map_or(0, |column| column.saturating_sub(1)),
+    );
+    let end = match (problem.end_line, problem.end_column) {
+        (Some(end_line), Some(end_column)) => {
+            lsp::Position::new(end_line.saturating_sub(1), end_column.saturating_sub(1))
+        }
+        (Some(end_line), None) => lsp::Position::new(end_line.saturating_sub(1), u32::MAX),
+        (None, Some(end_column)) => lsp::Position::new(start.line, end_column.saturating_sub(1)),
+        // Without a column, the problem is about the whole line.
+        (None, None) if problem.column.is_none() => lsp::Position::new(start.line, u32::MAX),
+        (None, None) => start,
+    };
+    lsp::Diagnostic {
+        range: lsp::Range::new(start, end),
+        severity: Some(match problem.severity {
+            ProblemSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+            ProblemSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+            ProblemSeverity::Info => lsp::DiagnosticSeverity::INFORMATION,
+            ProblemSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+        }),
+        code: problem.code.clone().map(lsp::NumberOrString::String),
+        source: Some(source.to_string()),
+        message: problem.message.clone(),
+        ..lsp::Diagnostic::default()
+    }
+}
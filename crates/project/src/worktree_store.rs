@@ -16,6 +16,7 @@ use git::repository::Branch;
 use gpui::{
     App, AppContext as _, AsyncApp, Context, Entity, EntityId, EventEmitter, Task, WeakEntity,
 };
+use language::LanguageRegistry;
 use postage::oneshot;
 use rpc::{
     proto::{self, FromProto, ToProto, SSH_PROJECT_ID},
@@ -658,6 +659,7 @@ impl WorktreeStore {
         limit: usize,
         open_entries: HashSet<ProjectEntryId>,
         fs: Arc<dyn Fs>,
+        languages: Arc<LanguageRegistry>,
         cx: &Context<Self>,
     ) -> Receiver<ProjectPath> {
         let snapshots = self
@@ -700,12 +702,13 @@ impl WorktreeStore {
         let filters = cx.background_spawn(async move {
             let fs = &fs;
             let query = &query;
+            let languages = &languages;
             executor
                 .scoped(move |scope| {
                     for _ in 0..MAX_CONCURRENT_FILE_SCANS {
                         let filter_rx = filter_rx.clone();
                         scope.spawn(async move {
-                            Self::filter_paths(fs, filter_rx, query)
+                            Self::filter_paths(fs, filter_rx, query, languages)
                                 .await
                                 .log_with_level(log::Level::Debug);
                         })
@@ -1038,9 +1041,21 @@ impl WorktreeStore {
         fs: &Arc<dyn Fs>,
         mut input: Receiver<MatchingEntry>,
         query: &SearchQuery,
+        languages: &Arc<LanguageRegistry>,
     ) -> Result<()> {
         let mut input = pin!(input);
         while let Some(mut entry) = input.next().await {
+            // Structural queries can only match files whose language they're valid for, so other
+            // files are skipped without reading them. Files whose language can't be told from
+            // their path are checked by their contents.
+            if query.is_structural() {
+                if let Ok(language) = languages.language_for_file_path(&entry.path.path).await {
+                    if !query.matches_language(&language) {
+                        continue;
+                    }
+                }
+            }
+
             let abs_path = entry.worktree_path.join(&entry.path.path);
             let Some(file) = fs.open_sync(&abs_path).await.log_err() else {
                 continue;
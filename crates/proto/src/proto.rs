@@ -452,6 +452,47 @@ messages!(
     (GetRemotesResponse, Background),
     (Pull, Background),
     (RemoteMessageResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (RefreshCodeLens, Foreground),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSelectionRanges, Background),
+    (GetSelectionRangesResponse, Background),
+    (GitStashPush, Background),
+    (GitStashList, Background),
+    (GitStashListResponse, Background),
+    (GitStashApply, Background),
+    (GitStashDrop, Background),
+    (GitStashDiff, Background),
+    (GitStashDiffResponse, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitCommitFiles, Background),
+    (GitCommitFilesResponse, Background),
+    (GitRebaseTodo, Background),
+    (GitRebaseTodoResponse, Background),
+    (GitRebase, Background),
+    (GitCherryPick, Background),
+    (GitContinueOperation, Background),
+    (GitAbortOperation, Background),
 );
 
 request_messages!(
@@ -594,6 +635,31 @@ request_messages!(
     (Fetch, RemoteMessageResponse),
     (GetRemotes, GetRemotesResponse),
     (Pull, RemoteMessageResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetSupertypesResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (RefreshSemanticTokens, Ack),
+    (GetCodeLens, GetCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (RefreshCodeLens, Ack),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSelectionRanges, GetSelectionRangesResponse),
+    (GitStashPush, Ack),
+    (GitStashList, GitStashListResponse),
+    (GitStashApply, Ack),
+    (GitStashDrop, Ack),
+    (GitStashDiff, GitStashDiffResponse),
+    (GitLog, GitLogResponse),
+    (GitCommitFiles, GitCommitFilesResponse),
+    (GitRebaseTodo, GitRebaseTodoResponse),
+    (GitRebase, Ack),
+    (GitCherryPick, Ack),
+    (GitContinueOperation, Ack),
+    (GitAbortOperation, Ack),
 );
 
 entity_messages!(
@@ -695,6 +761,31 @@ entity_messages!(
     Fetch,
     GetRemotes,
     Pull,
+    PrepareCallHierarchy,
+    GetIncomingCalls,
+    GetOutgoingCalls,
+    PrepareTypeHierarchy,
+    GetSupertypes,
+    GetSubtypes,
+    GetSemanticTokens,
+    RefreshSemanticTokens,
+    GetCodeLens,
+    ResolveCodeLens,
+    RefreshCodeLens,
+    GetFoldingRanges,
+    GetSelectionRanges,
+    GitStashPush,
+    GitStashList,
+    GitStashApply,
+    GitStashDrop,
+    GitStashDiff,
+    GitLog,
+    GitCommitFiles,
+    GitRebaseTodo,
+    GitRebase,
+    GitCherryPick,
+    GitContinueOperation,
+    GitAbortOperation,
 );
 
 entity_messages!(
@@ -794,6 +885,7 @@ pub fn split_worktree_update(mut message: UpdateWorktree) -> impl Iterator<Item
                     .drain(..removed_statuses_limit)
                     .collect(),
                 current_merge_conflicts: repo.current_merge_conflicts.clone(),
+                operation_in_progress: repo.operation_in_progress.clone(),
             });
             if repo.removed_statuses.is_empty() && repo.updated_statuses.is_empty() {
                 message.updated_repositories.remove(0);
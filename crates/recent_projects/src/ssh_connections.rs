@@ -54,6 +54,8 @@ impl SshSettings {
                     username,
                     port_forwards: conn.port_forwards,
                     password: None,
+                    command: conn.command,
+                    terminal_command: conn.terminal_command,
                 };
             }
         }
@@ -90,6 +92,16 @@ pub struct SshConnection {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port_forwards: Option<Vec<SshPortForwardOption>>,
+
+    /// A command to connect with instead of SSH, such as `["docker", "exec", "-i", "dev"]`.
+    /// The remote server is launched and talked to over the command's stdio.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// The command to open terminals with, when `command` doesn't attach a TTY,
+    /// such as `["docker", "exec", "-it", "dev"]`. Defaults to `command`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_command: Option<Vec<String>>,
 }
 
 impl From<SshConnection> for SshConnectionOptions {
@@ -103,6 +115,8 @@ impl From<SshConnection> for SshConnectionOptions {
             nickname: val.nickname,
             upload_binary_over_ssh: val.upload_binary_over_ssh.unwrap_or_default(),
             port_forwards: val.port_forwards,
+            command: val.command,
+            terminal_command: val.terminal_command,
         }
     }
 }
@@ -0,0 +1,387 @@
+use crate::{
+    shell_script,
+    ssh_session::{multiplex, start_proxy_command, RemoteConnection, RemoteShell},
+    SshClientDelegate, SshConnectionOptions,
+};
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use futures::{
+    channel::{
+        mpsc::{Sender, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    AsyncWriteExt as _,
+};
+use gpui::{App, AppContext as _, AsyncApp, Task};
+use itertools::Itertools;
+use parking_lot::Mutex;
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use rpc::proto::Envelope;
+use smol::{
+    fs,
+    process::{self, Stdio},
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+};
+
+/// A connection to a host that is reached by running a user-specified command, such as
+/// `docker exec -i <container>` or `kubectl exec -i <pod> --`, rather than over SSH.
+///
+/// Each remote invocation spawns the command with `sh -c <script>` appended, and talks
+/// to the script over the command's stdio. Unlike SSH, there's no long-lived master
+/// process: every invocation is independent.
+pub(crate) struct CommandRemoteConnection {
+    connection_options: SshConnectionOptions,
+    command: Vec<String>,
+    remote_binary_path: Option<PathBuf>,
+    killed: AtomicBool,
+    /// Kills the proxy processes started over this connection when fired or dropped.
+    kill_proxy_txs: Mutex<Vec<oneshot::Sender<()>>>,
+}
+
+impl CommandRemoteConnection {
+    pub(crate) async fn new(
+        connection_options: SshConnectionOptions,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        let command = connection_options
+            .command
+            .clone()
+            .filter(|command| !command.is_empty())
+            .ok_or_else(|| anyhow!("no connection command specified"))?;
+
+        delegate.set_status(Some("Connecting"), cx);
+        let mut this = Self {
+            connection_options,
+            command,
+            remote_binary_path: None,
+            killed: AtomicBool::new(false),
+            kill_proxy_txs: Mutex::default(),
+        };
+        this.run_command("true", &[])
+            .await
+            .context("failed to connect to host")?;
+
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        })?;
+        this.remote_binary_path = Some(
+            this.ensure_server_binary(&delegate, release_channel, version, commit, cx)
+                .await?,
+        );
+
+        Ok(this)
+    }
+
+    /// Builds a process that runs the given shell script on the host, starting from the
+    /// home directory.
+    fn shell_script_command(&self, script: &str) -> process::Command {
+        let mut command = util::command::new_smol_command(&self.command[0]);
+        command
+            .args(&self.command[1..])
+            .args(["sh", "-c", &format!("cd; {script}")])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        log::debug!("{} {:?}", self.command.join(" "), script);
+        command
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteShell for CommandRemoteConnection {
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        let script = std::iter::once(&program)
+            .chain(args.iter())
+            .map(|token| shlex::try_quote(token).unwrap())
+            .join(" ");
+        let output = self.shell_script_command(&script).output().await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(anyhow!(
+                "failed to run command: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
+        let script = shell_script!(
+            "cat > {dest_path}",
+            dest_path = &dest_path.to_string_lossy()
+        );
+        let mut child = self.shell_script_command(&script).spawn()?;
+        let mut stdin = child.stdin.take().unwrap();
+        let file = fs::File::open(src_path).await?;
+        let copied = futures::io::copy(file, &mut stdin).await;
+        stdin.close().await.ok();
+        drop(stdin);
+
+        let output = child.output().await?;
+        copied?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    fn connection_options(&self) -> &SshConnectionOptions {
+        &self.connection_options
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for CommandRemoteConnection {
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_path) = self.remote_binary_path.clone() else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let start_proxy_command =
+            start_proxy_command(&remote_binary_path, &unique_identifier, reconnect);
+        let proxy_process = match self
+            .shell_script_command(&start_proxy_command)
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(process) => process,
+            Err(error) => {
+                return Task::ready(Err(anyhow!("failed to spawn remote server: {}", error)))
+            }
+        };
+
+        let (kill_proxy_tx, kill_proxy_rx) = oneshot::channel();
+        self.kill_proxy_txs.lock().push(kill_proxy_tx);
+        multiplex(
+            proxy_process,
+            Some(kill_proxy_rx),
+            incoming_tx,
+            outgoing_rx,
+            connection_activity_tx,
+            &cx,
+        )
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: PathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let archive = util::command::new_smol_command("tar")
+            .arg("-C")
+            .arg(&src_path)
+            .args(["-czf", "-", "."])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let script = shell_script!(
+            "mkdir -p {dest_path} && tar -C {dest_path} -xzf -",
+            dest_path = &dest_path.to_string_lossy(),
+        );
+        let extract = self.shell_script_command(&script).spawn();
+
+        cx.background_spawn(async move {
+            let mut archive = archive?;
+            let mut extract = extract?;
+            let mut stdin = extract.stdin.take().unwrap();
+            let copied = futures::io::copy(archive.stdout.take().unwrap(), &mut stdin).await;
+            stdin.close().await.ok();
+            drop(stdin);
+
+            let archive_output = archive.output().await?;
+            let extract_output = extract.output().await?;
+            copied?;
+            for output in [archive_output, extract_output] {
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "failed to upload directory {} -> {}: {}",
+                        src_path.display(),
+                        dest_path.display(),
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    async fn kill(&self) -> Result<()> {
+        self.killed.store(true, SeqCst);
+        for kill_proxy_tx in self.kill_proxy_txs.lock().drain(..) {
+            kill_proxy_tx.send(()).ok();
+        }
+        Ok(())
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.killed.load(SeqCst)
+    }
+
+    fn shell_command(&self) -> (String, Vec<String>) {
+        let command = self
+            .connection_options
+            .terminal_command
+            .as_ref()
+            .filter(|command| !command.is_empty())
+            .unwrap_or(&self.command);
+        (command[0].clone(), command[1..].to_vec())
+    }
+
+    fn connection_options(&self) -> SshConnectionOptions {
+        self.connection_options.clone()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::ssh_session::fake::Delegate;
+    use futures::{channel::mpsc, StreamExt as _};
+    use gpui::TestAppContext;
+    use std::os::unix::fs::PermissionsExt as _;
+
+    /// A connection that runs the remote scripts locally, with `home` as the home directory.
+    fn local_connection(home: &Path) -> CommandRemoteConnection {
+        let command = vec!["env".to_string(), format!("HOME={}", home.display())];
+        CommandRemoteConnection {
+            connection_options: SshConnectionOptions {
+                host: "local".to_string(),
+                command: Some(command.clone()),
+                ..Default::default()
+            },
+            command,
+            remote_binary_path: Some(home.join("server")),
+            killed: AtomicBool::new(false),
+            kill_proxy_txs: Mutex::default(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_run_command_and_upload(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let home = tempfile::tempdir().unwrap();
+        let local = tempfile::tempdir().unwrap();
+        let connection = local_connection(home.path());
+
+        assert_eq!(
+            connection
+                .run_command("echo", &["one two", "$HOME"])
+                .await
+                .unwrap(),
+            "one two $HOME\n"
+        );
+        assert_eq!(
+            connection.run_command("pwd", &[]).await.unwrap().trim_end(),
+            home.path().to_string_lossy()
+        );
+        assert!(connection.run_command("false", &[]).await.is_err());
+
+        std::fs::write(local.path().join("file"), "contents").unwrap();
+        connection
+            .upload_file(&local.path().join("file"), Path::new("uploaded"))
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(home.path().join("uploaded")).unwrap(),
+            "contents"
+        );
+
+        std::fs::create_dir_all(local.path().join("dir/nested")).unwrap();
+        std::fs::write(local.path().join("dir/top.txt"), "top").unwrap();
+        std::fs::write(local.path().join("dir/nested/inner.txt"), "inner").unwrap();
+        cx.update(|cx| {
+            connection.upload_directory(local.path().join("dir"), PathBuf::from("a/b"), cx)
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(home.path().join("a/b/top.txt")).unwrap(),
+            "top"
+        );
+        assert_eq!(
+            std::fs::read_to_string(home.path().join("a/b/nested/inner.txt")).unwrap(),
+            "inner"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_start_and_kill_proxy(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let home = tempfile::tempdir().unwrap();
+        let server_path = home.path().join("server");
+        // The fake server echoes the messages back to the client.
+        std::fs::write(
+            &server_path,
+            "#!/bin/sh\necho \"$@\" >> \"$HOME/proxy_args\"\nexec cat\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&server_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let connection = local_connection(home.path());
+
+        let mut proxies = Vec::new();
+        for (id, reconnect) in [(1, false), (2, true)] {
+            let (incoming_tx, mut incoming_rx) = mpsc::unbounded();
+            let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
+            let (connection_activity_tx, _connection_activity_rx) = mpsc::channel(1);
+            let proxy = connection.start_proxy(
+                "the-id".to_string(),
+                reconnect,
+                incoming_tx,
+                outgoing_rx,
+                connection_activity_tx,
+                Arc::new(Delegate),
+                &mut cx.to_async(),
+            );
+            outgoing_tx
+                .unbounded_send(Envelope {
+                    id,
+                    ..Default::default()
+                })
+                .unwrap();
+            assert_eq!(incoming_rx.next().await.unwrap().id, id);
+            proxies.push((proxy, outgoing_tx));
+        }
+        assert_eq!(
+            std::fs::read_to_string(home.path().join("proxy_args")).unwrap(),
+            "proxy --identifier the-id\nproxy --identifier the-id --reconnect\n"
+        );
+
+        assert!(!connection.has_been_killed());
+        connection.kill().await.unwrap();
+        assert!(connection.has_been_killed());
+        for (proxy, _outgoing_tx) in proxies {
+            assert_eq!(proxy.await.unwrap(), 1);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    command_connection::CommandRemoteConnection,
     json_log::LogRecord,
     protocol::{
         message_len_from_buffer, read_message_with_len, write_message, MessageId, MESSAGE_LEN_SIZE,
@@ -82,6 +83,14 @@ pub struct SshConnectionOptions {
 
     pub nickname: Option<String>,
     pub upload_binary_over_ssh: bool,
+    /// A command to reach the host with instead of SSH, such as `docker exec -i <container>`.
+    ///
+    /// The command is run locally with the remote command appended as its trailing arguments,
+    /// and must connect its stdio to that command.
+    pub command: Option<Vec<String>>,
+    /// The command to open terminals on the host with, when `command` doesn't allocate a TTY,
+    /// such as `docker exec -it <container>`. Defaults to `command`.
+    pub terminal_command: Option<Vec<String>>,
 }
 
 #[macro_export]
@@ -232,6 +241,8 @@ impl SshConnectionOptions {
             password: None,
             nickname: None,
             upload_binary_over_ssh: false,
+            command: None,
+            terminal_command: None,
         })
     }
 
@@ -1067,12 +1078,14 @@ impl SshRemoteClient {
         self.client.subscribe_to_entity(remote_id, entity);
     }
 
-    pub fn ssh_args(&self) -> Option<Vec<String>> {
+    /// The program and arguments that run a shell invocation, appended as the final
+    /// argument, on the remote host.
+    pub fn shell_command(&self) -> Option<(String, Vec<String>)> {
         self.state
             .lock()
             .as_ref()
             .and_then(|state| state.ssh_connection())
-            .map(|ssh_connection| ssh_connection.ssh_args())
+            .map(|ssh_connection| ssh_connection.shell_command())
     }
 
     pub fn upload_directory(
@@ -1239,9 +1252,15 @@ impl ConnectionPool {
                 let opts = opts.clone();
                 let delegate = delegate.clone();
                 |mut cx| async move {
-                    let connection = SshRemoteConnection::new(opts.clone(), delegate, &mut cx)
-                        .await
-                        .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>);
+                    let connection = if opts.command.is_some() {
+                        CommandRemoteConnection::new(opts.clone(), delegate, &mut cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    } else {
+                        SshRemoteConnection::new(opts.clone(), delegate, &mut cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    };
 
                     cx.update_global(|pool: &mut Self, _| {
                         debug_assert!(matches!(
@@ -1279,7 +1298,7 @@ impl From<SshRemoteClient> for AnyProtoClient {
 }
 
 #[async_trait(?Send)]
-trait RemoteConnection: Send + Sync {
+pub(crate) trait RemoteConnection: Send + Sync {
     #[allow(clippy::too_many_arguments)]
     fn start_proxy(
         &self,
@@ -1295,7 +1314,9 @@ trait RemoteConnection: Send + Sync {
         -> Task<Result<()>>;
     async fn kill(&self) -> Result<()>;
     fn has_been_killed(&self) -> bool;
-    fn ssh_args(&self) -> Vec<String>;
+    /// The program and arguments that run a shell invocation, appended as the final
+    /// argument, on the remote host with a terminal attached, if the transport can attach one.
+    fn shell_command(&self) -> (String, Vec<String>);
     fn connection_options(&self) -> SshConnectionOptions;
 
     #[cfg(any(test, feature = "test-support"))]
@@ -1324,8 +1345,10 @@ impl RemoteConnection for SshRemoteConnection {
         self.master_process.lock().is_none()
     }
 
-    fn ssh_args(&self) -> Vec<String> {
-        self.socket.ssh_args()
+    fn shell_command(&self) -> (String, Vec<String>) {
+        let mut args = self.socket.ssh_args();
+        args.push("-t".to_string());
+        ("ssh".to_string(), args)
     }
 
     fn connection_options(&self) -> SshConnectionOptions {
@@ -1391,30 +1414,8 @@ impl RemoteConnection for SshRemoteConnection {
             return Task::ready(Err(anyhow!("Remote binary path not set")));
         };
 
-        let mut start_proxy_command = shell_script!(
-            "exec {binary_path} proxy --identifier {identifier}",
-            binary_path = &remote_binary_path.to_string_lossy(),
-            identifier = &unique_identifier,
-        );
-
-        if let Some(rust_log) = std::env::var("RUST_LOG").ok() {
-            start_proxy_command = format!(
-                "RUST_LOG={} {}",
-                shlex::try_quote(&rust_log).unwrap(),
-                start_proxy_command
-            )
-        }
-        if let Some(rust_backtrace) = std::env::var("RUST_BACKTRACE").ok() {
-            start_proxy_command = format!(
-                "RUST_BACKTRACE={} {}",
-                shlex::try_quote(&rust_backtrace).unwrap(),
-                start_proxy_command
-            )
-        }
-        if reconnect {
-            start_proxy_command.push_str(" --reconnect");
-        }
-
+        let start_proxy_command =
+            start_proxy_command(&remote_binary_path, &unique_identifier, reconnect);
         let ssh_proxy_process = match self
             .socket
             .ssh_command("sh", &["-c", &start_proxy_command])
@@ -1428,8 +1429,9 @@ impl RemoteConnection for SshRemoteConnection {
             }
         };
 
-        Self::multiplex(
+        multiplex(
             ssh_proxy_process,
+            None,
             incoming_tx,
             outgoing_rx,
             connection_activity_tx,
@@ -1438,6 +1440,39 @@ impl RemoteConnection for SshRemoteConnection {
     }
 }
 
+/// The shell command that launches the remote server's proxy, which relays messages
+/// between its stdio and the server.
+pub(crate) fn start_proxy_command(
+    remote_binary_path: &Path,
+    unique_identifier: &str,
+    reconnect: bool,
+) -> String {
+    let mut start_proxy_command = shell_script!(
+        "exec {binary_path} proxy --identifier {identifier}",
+        binary_path = &remote_binary_path.to_string_lossy(),
+        identifier = unique_identifier,
+    );
+
+    if let Some(rust_log) = std::env::var("RUST_LOG").ok() {
+        start_proxy_command = format!(
+            "RUST_LOG={} {}",
+            shlex::try_quote(&rust_log).unwrap(),
+            start_proxy_command
+        )
+    }
+    if let Some(rust_backtrace) = std::env::var("RUST_BACKTRACE").ok() {
+        start_proxy_command = format!(
+            "RUST_BACKTRACE={} {}",
+            shlex::try_quote(&rust_backtrace).unwrap(),
+            start_proxy_command
+        )
+    }
+    if reconnect {
+        start_proxy_command.push_str(" --reconnect");
+    }
+    start_proxy_command
+}
+
 impl SshRemoteConnection {
     #[cfg(not(unix))]
     async fn new(
@@ -1632,9 +1667,63 @@ impl SshRemoteConnection {
 
         Ok(this)
     }
+}
+
+#[async_trait(?Send)]
+impl RemoteShell for SshRemoteConnection {
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        self.socket.run_command(program, args).await
+    }
+
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
+        let mut command = util::command::new_smol_command("scp");
+        let output = self
+            .socket
+            .ssh_options(&mut command)
+            .args(
+                self.socket
+                    .connection_options
+                    .port
+                    .map(|port| vec!["-P".to_string(), port.to_string()])
+                    .unwrap_or_default(),
+            )
+            .arg(src_path)
+            .arg(format!(
+                "{}:{}",
+                self.socket.connection_options.scp_url(),
+                dest_path.display()
+            ))
+            .output()
+            .await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    fn connection_options(&self) -> &SshConnectionOptions {
+        &self.socket.connection_options
+    }
+}
+
+/// Runs commands on the remote host and copies files to it, which is all that's
+/// needed to install the remote server there.
+#[async_trait(?Send)]
+pub(crate) trait RemoteShell {
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String>;
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()>;
+    fn connection_options(&self) -> &SshConnectionOptions;
 
     async fn platform(&self) -> Result<SshPlatform> {
-        let uname = self.socket.run_command("uname", &["-sm"]).await?;
+        let uname = self.run_command("uname", &["-sm"]).await?;
         let Some((os, arch)) = uname.split_once(" ") else {
             Err(anyhow!("unknown uname: {uname:?}"))?
         };
@@ -1664,108 +1753,6 @@ impl SshRemoteConnection {
         Ok(SshPlatform { os, arch })
     }
 
-    fn multiplex(
-        mut ssh_proxy_process: Child,
-        incoming_tx: UnboundedSender<Envelope>,
-        mut outgoing_rx: UnboundedReceiver<Envelope>,
-        mut connection_activity_tx: Sender<()>,
-        cx: &AsyncApp,
-    ) -> Task<Result<i32>> {
-        let mut child_stderr = ssh_proxy_process.stderr.take().unwrap();
-        let mut child_stdout = ssh_proxy_process.stdout.take().unwrap();
-        let mut child_stdin = ssh_proxy_process.stdin.take().unwrap();
-
-        let mut stdin_buffer = Vec::new();
-        let mut stdout_buffer = Vec::new();
-        let mut stderr_buffer = Vec::new();
-        let mut stderr_offset = 0;
-
-        let stdin_task = cx.background_spawn(async move {
-            while let Some(outgoing) = outgoing_rx.next().await {
-                write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
-            }
-            anyhow::Ok(())
-        });
-
-        let stdout_task = cx.background_spawn({
-            let mut connection_activity_tx = connection_activity_tx.clone();
-            async move {
-                loop {
-                    stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
-                    let len = child_stdout.read(&mut stdout_buffer).await?;
-
-                    if len == 0 {
-                        return anyhow::Ok(());
-                    }
-
-                    if len < MESSAGE_LEN_SIZE {
-                        child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
-                    }
-
-                    let message_len = message_len_from_buffer(&stdout_buffer);
-                    let envelope =
-                        read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len)
-                            .await?;
-                    connection_activity_tx.try_send(()).ok();
-                    incoming_tx.unbounded_send(envelope).ok();
-                }
-            }
-        });
-
-        let stderr_task: Task<anyhow::Result<()>> = cx.background_spawn(async move {
-            loop {
-                stderr_buffer.resize(stderr_offset + 1024, 0);
-
-                let len = child_stderr
-                    .read(&mut stderr_buffer[stderr_offset..])
-                    .await?;
-                if len == 0 {
-                    return anyhow::Ok(());
-                }
-
-                stderr_offset += len;
-                let mut start_ix = 0;
-                while let Some(ix) = stderr_buffer[start_ix..stderr_offset]
-                    .iter()
-                    .position(|b| b == &b'\n')
-                {
-                    let line_ix = start_ix + ix;
-                    let content = &stderr_buffer[start_ix..line_ix];
-                    start_ix = line_ix + 1;
-                    if let Ok(record) = serde_json::from_slice::<LogRecord>(content) {
-                        record.log(log::logger())
-                    } else {
-                        eprintln!("(remote) {}", String::from_utf8_lossy(content));
-                    }
-                }
-                stderr_buffer.drain(0..start_ix);
-                stderr_offset -= start_ix;
-
-                connection_activity_tx.try_send(()).ok();
-            }
-        });
-
-        cx.spawn(|_| async move {
-            let result = futures::select! {
-                result = stdin_task.fuse() => {
-                    result.context("stdin")
-                }
-                result = stdout_task.fuse() => {
-                    result.context("stdout")
-                }
-                result = stderr_task.fuse() => {
-                    result.context("stderr")
-                }
-            };
-
-            let status = ssh_proxy_process.status().await?.code().unwrap_or(1);
-            match result {
-                Ok(_) => Ok(status),
-                Err(error) => Err(error),
-            }
-        })
-    }
-
     #[allow(unused)]
     async fn ensure_server_binary(
         &self,
@@ -1809,7 +1796,6 @@ impl SshRemoteConnection {
         }
 
         if self
-            .socket
             .run_command(&dst_path.to_string_lossy(), &["version"])
             .await
             .is_ok()
@@ -1830,7 +1816,7 @@ impl SshRemoteConnection {
 
         let platform = self.platform().await?;
 
-        if !self.socket.connection_options.upload_binary_over_ssh {
+        if !self.connection_options().upload_binary_over_ssh {
             if let Some((url, body)) = delegate
                 .get_download_params(platform, release_channel, wanted_version, cx)
                 .await?
@@ -1873,15 +1859,13 @@ impl SshRemoteConnection {
         cx: &mut AsyncApp,
     ) -> Result<()> {
         if let Some(parent) = tmp_path_gz.parent() {
-            self.socket
-                .run_command("mkdir", &["-p", &parent.to_string_lossy()])
+            self.run_command("mkdir", &["-p", &parent.to_string_lossy()])
                 .await?;
         }
 
         delegate.set_status(Some("Downloading remote development server on host"), cx);
 
         match self
-            .socket
             .run_command(
                 "curl",
                 &[
@@ -1902,12 +1886,11 @@ impl SshRemoteConnection {
         {
             Ok(_) => {}
             Err(e) => {
-                if self.socket.run_command("which", &["curl"]).await.is_ok() {
+                if self.run_command("which", &["curl"]).await.is_ok() {
                     return Err(e);
                 }
 
                 match self
-                    .socket
                     .run_command(
                         "wget",
                         &[
@@ -1924,7 +1907,7 @@ impl SshRemoteConnection {
                 {
                     Ok(_) => {}
                     Err(e) => {
-                        if self.socket.run_command("which", &["wget"]).await.is_ok() {
+                        if self.run_command("which", &["wget"]).await.is_ok() {
                             return Err(e);
                         } else {
                             anyhow::bail!("Neither curl nor wget is available");
@@ -1945,8 +1928,7 @@ impl SshRemoteConnection {
         cx: &mut AsyncApp,
     ) -> Result<()> {
         if let Some(parent) = tmp_path_gz.parent() {
-            self.socket
-                .run_command("mkdir", &["-p", &parent.to_string_lossy()])
+            self.run_command("mkdir", &["-p", &parent.to_string_lossy()])
                 .await?;
         }
 
@@ -1984,44 +1966,10 @@ impl SshRemoteConnection {
             server_mode = &format!("{:o}", server_mode),
             dst_path = &dst_path.to_string_lossy()
         );
-        self.socket.run_command("sh", &["-c", &script]).await?;
+        self.run_command("sh", &["-c", &script]).await?;
         Ok(())
     }
 
-    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
-        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
-        let mut command = util::command::new_smol_command("scp");
-        let output = self
-            .socket
-            .ssh_options(&mut command)
-            .args(
-                self.socket
-                    .connection_options
-                    .port
-                    .map(|port| vec!["-P".to_string(), port.to_string()])
-                    .unwrap_or_default(),
-            )
-            .arg(src_path)
-            .arg(format!(
-                "{}:{}",
-                self.socket.connection_options.scp_url(),
-                dest_path.display()
-            ))
-            .output()
-            .await?;
-
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "failed to upload file {} -> {}: {}",
-                src_path.display(),
-                dest_path.display(),
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
     #[cfg(debug_assertions)]
     async fn build_local(
         &self,
@@ -2130,6 +2078,123 @@ impl SshRemoteConnection {
     }
 }
 
+/// Relays messages between the channels and the proxy process' stdio, until either side closes.
+///
+/// The proxy process is killed when `kill_rx` fires or its sender is dropped.
+pub(crate) fn multiplex(
+    mut proxy_process: Child,
+    kill_rx: Option<oneshot::Receiver<()>>,
+    incoming_tx: UnboundedSender<Envelope>,
+    mut outgoing_rx: UnboundedReceiver<Envelope>,
+    mut connection_activity_tx: Sender<()>,
+    cx: &AsyncApp,
+) -> Task<Result<i32>> {
+    let mut child_stderr = proxy_process.stderr.take().unwrap();
+    let mut child_stdout = proxy_process.stdout.take().unwrap();
+    let mut child_stdin = proxy_process.stdin.take().unwrap();
+
+    let mut stdin_buffer = Vec::new();
+    let mut stdout_buffer = Vec::new();
+    let mut stderr_buffer = Vec::new();
+    let mut stderr_offset = 0;
+
+    let stdin_task = cx.background_spawn(async move {
+        while let Some(outgoing) = outgoing_rx.next().await {
+            write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
+        }
+        anyhow::Ok(())
+    });
+
+    let stdout_task = cx.background_spawn({
+        let mut connection_activity_tx = connection_activity_tx.clone();
+        async move {
+            loop {
+                stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
+                let len = child_stdout.read(&mut stdout_buffer).await?;
+
+                if len == 0 {
+                    return anyhow::Ok(());
+                }
+
+                if len < MESSAGE_LEN_SIZE {
+                    child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
+                }
+
+                let message_len = message_len_from_buffer(&stdout_buffer);
+                let envelope =
+                    read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len)
+                        .await?;
+                connection_activity_tx.try_send(()).ok();
+                incoming_tx.unbounded_send(envelope).ok();
+            }
+        }
+    });
+
+    let stderr_task: Task<anyhow::Result<()>> = cx.background_spawn(async move {
+        loop {
+            stderr_buffer.resize(stderr_offset + 1024, 0);
+
+            let len = child_stderr
+                .read(&mut stderr_buffer[stderr_offset..])
+                .await?;
+            if len == 0 {
+                return anyhow::Ok(());
+            }
+
+            stderr_offset += len;
+            let mut start_ix = 0;
+            while let Some(ix) = stderr_buffer[start_ix..stderr_offset]
+                .iter()
+                .position(|b| b == &b'\n')
+            {
+                let line_ix = start_ix + ix;
+                let content = &stderr_buffer[start_ix..line_ix];
+                start_ix = line_ix + 1;
+                if let Ok(record) = serde_json::from_slice::<LogRecord>(content) {
+                    record.log(log::logger())
+                } else {
+                    eprintln!("(remote) {}", String::from_utf8_lossy(content));
+                }
+            }
+            stderr_buffer.drain(0..start_ix);
+            stderr_offset -= start_ix;
+
+            connection_activity_tx.try_send(()).ok();
+        }
+    });
+
+    let killed = async move {
+        match kill_rx {
+            Some(kill_rx) => kill_rx.await.ok(),
+            None => futures::future::pending().await,
+        }
+    };
+
+    cx.spawn(|_| async move {
+        let result = futures::select! {
+            result = stdin_task.fuse() => {
+                result.context("stdin")
+            }
+            result = stdout_task.fuse() => {
+                result.context("stdout")
+            }
+            result = stderr_task.fuse() => {
+                result.context("stderr")
+            }
+            _ = killed.fuse() => {
+                proxy_process.kill().ok();
+                Ok(())
+            }
+        };
+
+        let status = proxy_process.status().await?.code().unwrap_or(1);
+        match result {
+            Ok(_) => Ok(status),
+            Err(error) => Err(error),
+        }
+    })
+}
+
 type ResponseChannels = Mutex<HashMap<MessageId, oneshot::Sender<(Envelope, oneshot::Sender<()>)>>>;
 
 pub struct ChannelClient {
@@ -2437,7 +2502,7 @@ impl ProtoClient for ChannelClient {
 }
 
 #[cfg(any(test, feature = "test-support"))]
-mod fake {
+pub(crate) mod fake {
     use std::{path::PathBuf, sync::Arc};
 
     use anyhow::Result;
@@ -2490,8 +2555,8 @@ mod fake {
             false
         }
 
-        fn ssh_args(&self) -> Vec<String> {
-            Vec::new()
+        fn shell_command(&self) -> (String, Vec<String>) {
+            ("ssh".to_string(), Vec::new())
         }
         fn upload_directory(
             &self,
@@ -2555,7 +2620,7 @@ mod fake {
         }
     }
 
-    pub(super) struct Delegate;
+    pub(crate) struct Delegate;
 
     impl SshClientDelegate for Delegate {
         fn ask_password(&self, _: String, _: &mut AsyncApp) -> oneshot::Receiver<Result<String>> {
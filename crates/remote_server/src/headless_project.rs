@@ -524,7 +524,13 @@ impl HeadlessProject {
         )?;
         let results = this.update(&mut cx, |this, cx| {
             this.buffer_store.update(cx, |buffer_store, cx| {
-                buffer_store.find_search_candidates(&query, message.limit as _, this.fs.clone(), cx)
+                buffer_store.find_search_candidates(
+                    &query,
+                    message.limit as _,
+                    this.fs.clone(),
+                    this.languages.clone(),
+                    cx,
+                )
             })
         })?;
 
@@ -84,7 +84,7 @@ pub enum Cell {
 }
 
 fn convert_outputs(
-    outputs: &Vec<nbformat::v4::Output>,
+    outputs: &[nbformat::v4::Output],
     window: &mut Window,
     cx: &mut App,
 ) -> Vec<Output> {
@@ -192,7 +192,6 @@ impl Cell {
                         ..Default::default()
                     };
 
-                    editor.set_text(text, window, cx);
                     editor.set_show_gutter(false, cx);
                     editor.set_text_style_refinement(refinement);
 
@@ -216,6 +215,8 @@ impl Cell {
                     source: source.join(""),
                     editor: editor_view,
                     outputs: convert_outputs(outputs, window, cx),
+                    nbformat_outputs: outputs.clone(),
+                    outputs_changed: false,
                     selected: false,
                     language_task,
                     cell_position: None,
@@ -234,6 +235,49 @@ impl Cell {
             })),
         }
     }
+
+    /// Writes the current contents of the cell into the cell it was loaded from,
+    /// keeping the rest of it (metadata, attachments, etc.) as is.
+    ///
+    /// Markdown and raw cells can't be edited in the notebook view yet, so they're
+    /// written back as they were loaded.
+    pub fn to_nbformat(&self, mut cell: nbformat::v4::Cell, cx: &App) -> nbformat::v4::Cell {
+        match (self, &mut cell) {
+            (
+                Cell::Code(code_cell),
+                nbformat::v4::Cell::Code {
+                    source,
+                    outputs,
+                    execution_count,
+                    ..
+                },
+            ) => {
+                let code_cell = code_cell.read(cx);
+                *source = source_lines(&code_cell.editor.read(cx).text(cx));
+                *outputs = code_cell.nbformat_outputs.clone();
+                *execution_count = code_cell.execution_count;
+            }
+            (Cell::Markdown(markdown_cell), nbformat::v4::Cell::Markdown { source, .. }) => {
+                *source = source_lines(markdown_cell.read(cx).source());
+            }
+            (Cell::Raw(raw_cell), nbformat::v4::Cell::Raw { source, .. }) => {
+                *source = source_lines(raw_cell.read(cx).source());
+            }
+            _ => {}
+        }
+        cell
+    }
+
+    pub fn did_save(&self, cx: &mut App) {
+        if let Cell::Code(code_cell) = self {
+            code_cell.update(cx, |code_cell, cx| code_cell.did_save(cx));
+        }
+    }
+}
+
+/// Splits the source of a cell into lines, the way nbformat stores it.
+fn source_lines(source: &str) -> Vec<String> {
+    source.split_inclusive('\n').map(str::to_string).collect()
 }
 
 pub trait RenderableCell: Render {
@@ -429,21 +473,41 @@ pub struct CodeCell {
     source: String,
     editor: Entity<editor::Editor>,
     outputs: Vec<Output>,
+    /// The outputs in the form they're stored in the notebook, kept alongside the
+    /// rendered `outputs` so that they can be written back when saving.
+    nbformat_outputs: Vec<nbformat::v4::Output>,
+    outputs_changed: bool,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
 }
 
 impl CodeCell {
+    pub fn editor(&self) -> &Entity<Editor> {
+        &self.editor
+    }
+
     pub fn is_dirty(&self, cx: &App) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
+        self.outputs_changed || self.editor.read(cx).buffer().read(cx).is_dirty(cx)
     }
+
+    pub fn did_save(&mut self, cx: &mut Context<Self>) {
+        self.source = self.editor.read(cx).text(cx);
+        self.outputs_changed = false;
+        if let Some(buffer) = self.editor.read(cx).buffer().read(cx).as_singleton() {
+            buffer.update(cx, |buffer, cx| buffer.did_save(buffer.version(), None, cx));
+        }
+    }
+
     pub fn has_outputs(&self) -> bool {
         !self.outputs.is_empty()
     }
 
     pub fn clear_outputs(&mut self) {
+        self.outputs_changed |= self.has_outputs() || self.execution_count.is_some();
         self.outputs.clear();
+        self.nbformat_outputs.clear();
+        self.execution_count = None;
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -14,6 +14,7 @@ use gpui::{
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
+use serde::Serialize as _;
 use ui::{prelude::*, Tooltip};
 use workspace::item::{ItemEvent, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
@@ -172,6 +173,7 @@ impl NotebookEditor {
                 });
             }
         }
+        cx.notify();
     }
 
     fn run_cells(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -183,11 +185,57 @@ impl NotebookEditor {
     }
 
     fn move_cell_up(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_index();
+        if index > 0 && index < self.cell_count() {
+            self.swap_cells(index - 1, window, cx);
+            self.set_selected_index(index - 1, true, window, cx);
+        }
     }
 
     fn move_cell_down(&mut self, window: &mut Window, cx: &mut Context<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_index();
+        if index + 1 < self.cell_count() {
+            self.swap_cells(index, window, cx);
+            self.set_selected_index(index + 1, true, window, cx);
+        }
+    }
+
+    /// Swaps the cell at `index` with the one after it.
+    fn swap_cells(&mut self, index: usize, window: &mut Window, cx: &mut Context<Self>) {
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        cx.notify();
+    }
+
+    fn has_reordered_cells(&self, cx: &App) -> bool {
+        !self.cell_order.iter().eq(self
+            .notebook_item
+            .read(cx)
+            .notebook
+            .cells
+            .iter()
+            .map(|cell| cell.id()))
+    }
+
+    /// The notebook as it is shown in the editor, with everything that can't be edited
+    /// kept as it was loaded.
+    fn to_notebook(&self, cx: &App) -> nbformat::v4::Notebook {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        let mut loaded_cells = notebook
+            .cells
+            .drain(..)
+            .map(|cell| (cell.id().clone(), cell))
+            .collect::<HashMap<_, _>>();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| {
+                let cell = self.cell_map.get(cell_id)?;
+                let loaded_cell = loaded_cells.remove(cell_id)?;
+                Some(cell.to_nbformat(loaded_cell, cx))
+            })
+            .collect();
+        notebook
     }
 
     fn add_markdown_block(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -769,19 +817,35 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
     fn can_save(&self, _cx: &App) -> bool {
-        false
+        true
     }
-    // TODO
+
     fn save(
         &mut self,
         _format: bool,
-        _project: Entity<Project>,
+        project: Entity<Project>,
         _window: &mut Window,
-        _cx: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let notebook = self.to_notebook(cx);
+        let path = self.notebook_item.read(cx).path.clone();
+        let fs = project.read(cx).fs().clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let content = serialize_notebook(&notebook)?;
+            fs.atomic_write(path, content).await?;
+
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.notebook = notebook;
+                });
+                for cell in this.cell_map.values() {
+                    cell.did_save(cx);
+                }
+                cx.notify();
+            })
+        })
     }
 
     // TODO
@@ -805,14 +869,44 @@ impl Item for NotebookEditor {
     }
 
     fn is_dirty(&self, cx: &App) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
+        self.has_reordered_cells(cx)
+            || self.cell_map.values().any(|cell| {
+                if let Cell::Code(code_cell) = cell {
+                    code_cell.read(cx).is_dirty(cx)
+                } else {
+                    false
+                }
+            })
+    }
+}
+
+/// Serializes the notebook the way Jupyter does: with sorted keys, indented by a single space
+/// and ending with a newline, to keep the diffs of saved notebooks small.
+fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    fn sort_keys(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries = std::mem::take(map).into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (_, value) in &mut entries {
+                    sort_keys(value);
+                }
+                map.extend(entries);
             }
-        })
+            serde_json::Value::Array(values) => values.iter_mut().for_each(sort_keys),
+            _ => {}
+        }
     }
+
+    let mut value = serde_json::to_value(notebook)?;
+    sort_keys(&mut value);
+
+    let mut content = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut content, formatter);
+    value.serialize(&mut serializer)?;
+    content.push(b'\n');
+    Ok(String::from_utf8(content)?)
 }
 
 // TODO: Implement this to allow us to persist to the database, etc:
@@ -833,3 +927,192 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, window, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_save_edited_notebook(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let content = json!({
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "id": "intro",
+                    "metadata": {},
+                    "source": ["# Greeting\n"]
+                },
+                {
+                    "cell_type": "code",
+                    "execution_count": 1,
+                    "id": "greet",
+                    "metadata": { "custom": { "keep": true } },
+                    "outputs": [
+                        { "name": "stdout", "output_type": "stream", "text": ["hello\n"] }
+                    ],
+                    "source": ["print('hello')"]
+                }
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/notebooks"),
+            json!({ "greeting.ipynb": content.to_string() }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), [path!("/notebooks").as_ref()], cx).await;
+        let project_path = project.read_with(cx, |project, cx| ProjectPath {
+            worktree_id: project.worktrees(cx).next().unwrap().read(cx).id(),
+            path: Path::new("greeting.ipynb").into(),
+        });
+        let notebook_item = cx
+            .update(|cx| {
+                <NotebookItem as project::ProjectItem>::try_open(&project, &project_path, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        let (notebook, cx) = cx.add_window_view(|window, cx| {
+            NotebookEditor::new(project.clone(), notebook_item, window, cx)
+        });
+        cx.run_until_parked();
+
+        // Edit the code cell, clear its outputs, and move it above the markdown cell.
+        let code_cell = notebook.read_with(cx, |notebook, _| {
+            match notebook.cell_map.get(&notebook.cell_order[1]) {
+                Some(Cell::Code(code_cell)) => code_cell.clone(),
+                _ => panic!("expected a code cell"),
+            }
+        });
+        code_cell.update_in(cx, |code_cell, window, cx| {
+            code_cell.editor().update(cx, |editor, cx| {
+                editor.set_text("print('bye')\nprint('again')", window, cx)
+            });
+            code_cell.clear_outputs();
+        });
+        notebook.update_in(cx, |notebook, window, cx| {
+            notebook.set_selected_index(1, false, window, cx);
+            notebook.move_cell_up(window, cx);
+            assert!(notebook.is_dirty(cx));
+        });
+
+        notebook
+            .update_in(cx, |notebook, window, cx| {
+                notebook.save(false, project.clone(), window, cx)
+            })
+            .await
+            .unwrap();
+        notebook.read_with(cx, |notebook, cx| assert!(!notebook.is_dirty(cx)));
+
+        let saved = fs
+            .load(path!("/notebooks/greeting.ipynb").as_ref())
+            .await
+            .unwrap();
+        let Ok(nbformat::Notebook::V4(saved)) = nbformat::parse_notebook(&saved) else {
+            panic!("failed to parse the saved notebook");
+        };
+        let saved = serde_json::to_value(&saved).unwrap();
+        assert_eq!(
+            saved["cells"],
+            json!([
+                {
+                    "cell_type": "code",
+                    "execution_count": null,
+                    "id": "greet",
+                    "metadata": { "custom": { "keep": true } },
+                    "outputs": [],
+                    "source": ["print('bye')\n", "print('again')"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "id": "intro",
+                    "metadata": {},
+                    "source": ["# Greeting\n"]
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_serialize_notebook_preserves_unknown_metadata() {
+        let content = r#"{
+ "cells": [
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "id": "5f2a3c9e",
+   "metadata": {
+    "custom": {
+     "keep": true
+    }
+   },
+   "outputs": [
+    {
+     "name": "stdout",
+     "output_type": "stream",
+     "text": [
+      "hello\n"
+     ]
+    }
+   ],
+   "source": [
+    "print('hello')"
+   ]
+  }
+ ],
+ "metadata": {
+  "custom_extension": {
+   "setting": 1
+  }
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"#;
+        let Ok(nbformat::Notebook::V4(notebook)) = nbformat::parse_notebook(content) else {
+            panic!("failed to parse a v4 notebook");
+        };
+
+        let serialized = serialize_notebook(&notebook).unwrap();
+        assert!(serialized.starts_with("{\n \"cells\": [\n  {\n   \"cell_type\": \"code\",\n"));
+        assert!(serialized.ends_with("}\n"));
+
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            value["metadata"]["custom_extension"],
+            json!({ "setting": 1 })
+        );
+        assert_eq!(
+            value["cells"][0]["metadata"]["custom"],
+            json!({ "keep": true })
+        );
+        assert_eq!(
+            value["cells"][0]["outputs"][0]["output_type"],
+            json!("stream")
+        );
+        assert_eq!(value["cells"][0]["source"], json!(["print('hello')"]));
+    }
+}
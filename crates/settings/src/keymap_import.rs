@@ -0,0 +1,1241 @@
+use anyhow::{anyhow, Result};
+use collections::IndexMap;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt::{self, Write as _};
+
+use crate::{
+    keymap_file::{KeymapAction, KeymapFile, KeymapSection},
+    settings_store::parse_json_with_comments,
+};
+
+/// Editors whose keymap files can be translated into a Zed keymap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeymapImportSource {
+    /// A VS Code `keybindings.json` file.
+    VsCode,
+    /// A JetBrains keymap XML file, as exported from any of their IDEs.
+    JetBrains,
+}
+
+impl KeymapImportSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeymapImportSource::VsCode => "VS Code",
+            KeymapImportSource::JetBrains => "JetBrains",
+        }
+    }
+}
+
+/// The result of translating another editor's keymap, along with the bindings that could not be
+/// translated.
+#[derive(Debug)]
+pub struct ImportedKeymap {
+    pub keymap: KeymapFile,
+    pub skipped: Vec<SkippedKeyBinding>,
+}
+
+/// A binding from another editor's keymap which has no equivalent in the imported keymap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedKeyBinding {
+    /// The keystrokes as written in the original keymap. Empty when the binding only removed
+    /// keystrokes from a command.
+    pub keystrokes: String,
+    pub command: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// There is no known Zed action for the command.
+    UnknownCommand,
+    /// The binding passes arguments to the command, which aren't translated.
+    UnsupportedArguments,
+    /// The keystrokes use a key or modifier that Zed can't bind.
+    UnsupportedKeystroke(String),
+    /// The `when` clause uses a condition that has no equivalent keymap context.
+    UnsupportedWhen(String),
+    /// The binding removes a default binding of the other editor. Zed's defaults differ, so
+    /// there's nothing to remove.
+    Removal,
+    /// The binding is a mouse or gesture shortcut.
+    NotAKeystroke,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::UnknownCommand => write!(f, "no matching Zed action"),
+            SkipReason::UnsupportedArguments => write!(f, "command arguments aren't supported"),
+            SkipReason::UnsupportedKeystroke(key) => write!(f, "unsupported key `{key}`"),
+            SkipReason::UnsupportedWhen(condition) => {
+                write!(f, "unsupported `when` condition `{condition}`")
+            }
+            SkipReason::Removal => write!(f, "removes a default binding"),
+            SkipReason::NotAKeystroke => write!(f, "not a keyboard shortcut"),
+        }
+    }
+}
+
+impl KeymapFile {
+    /// Translates the keymap of another editor into keymap sections, mapping known commands to
+    /// Zed actions and `when` clauses to keymap contexts.
+    pub fn import(source: KeymapImportSource, content: &str) -> Result<ImportedKeymap> {
+        let mut builder = ImportBuilder::default();
+        match source {
+            KeymapImportSource::VsCode => import_vscode(content, &mut builder)?,
+            KeymapImportSource::JetBrains => import_jetbrains(content, &mut builder)?,
+        }
+        Ok(builder.finish())
+    }
+}
+
+impl ImportedKeymap {
+    /// Renders the imported sections as keymap JSON, preceded by comments listing the bindings
+    /// that were skipped.
+    pub fn to_json_text(&self, source: KeymapImportSource) -> Result<String> {
+        let mut text = String::new();
+        writeln!(text, "// Imported from a {} keymap.", source.name())?;
+        if !self.skipped.is_empty() {
+            writeln!(text, "//")?;
+            writeln!(text, "// The following bindings could not be translated:")?;
+            for skipped in &self.skipped {
+                if skipped.keystrokes.is_empty() {
+                    writeln!(text, "// - {}: {}", skipped.command, skipped.reason)?;
+                } else {
+                    writeln!(
+                        text,
+                        "// - {} ({}): {}",
+                        skipped.keystrokes, skipped.command, skipped.reason
+                    )?;
+                }
+            }
+        }
+        text.push_str(&serde_json::to_string_pretty(&self.keymap)?);
+        text.push('\n');
+        Ok(text)
+    }
+}
+
+#[derive(Default)]
+struct ImportBuilder {
+    sections: IndexMap<String, IndexMap<String, KeymapAction>>,
+    skipped: Vec<SkippedKeyBinding>,
+}
+
+impl ImportBuilder {
+    fn bind(&mut self, context: String, keystrokes: String, action: Value) {
+        let bindings = self.sections.entry(context).or_default();
+        // Later bindings take precedence in both VS Code and Zed, so move them to the end.
+        bindings.shift_remove(&keystrokes);
+        bindings.insert(keystrokes, KeymapAction(action));
+    }
+
+    fn skip(&mut self, keystrokes: &str, command: &str, reason: SkipReason) {
+        self.skipped.push(SkippedKeyBinding {
+            keystrokes: keystrokes.to_string(),
+            command: command.to_string(),
+            reason,
+        });
+    }
+
+    fn finish(self) -> ImportedKeymap {
+        let sections = self
+            .sections
+            .into_iter()
+            .map(|(context, bindings)| KeymapSection {
+                context,
+                bindings: Some(bindings),
+                ..Default::default()
+            })
+            .collect();
+        ImportedKeymap {
+            keymap: KeymapFile(sections),
+            skipped: self.skipped,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VsCodeKeyBinding {
+    key: String,
+    #[serde(default)]
+    command: String,
+    #[serde(default)]
+    when: Option<String>,
+    #[serde(default)]
+    args: Option<Value>,
+}
+
+fn import_vscode(content: &str, builder: &mut ImportBuilder) -> Result<()> {
+    let bindings = parse_json_with_comments::<Vec<VsCodeKeyBinding>>(content)?;
+    for binding in bindings {
+        let VsCodeKeyBinding {
+            key,
+            command,
+            when,
+            args,
+        } = binding;
+        if let Some(command) = command.strip_prefix('-') {
+            builder.skip(&key, command, SkipReason::Removal);
+            continue;
+        }
+        let Some((default_context, action)) = vscode_command(&command) else {
+            builder.skip(&key, &command, SkipReason::UnknownCommand);
+            continue;
+        };
+        if args.is_some() {
+            builder.skip(&key, &command, SkipReason::UnsupportedArguments);
+            continue;
+        }
+        let keystrokes = match translate_vscode_keystrokes(&key) {
+            Ok(keystrokes) => keystrokes,
+            Err(reason) => {
+                builder.skip(&key, &command, reason);
+                continue;
+            }
+        };
+        let context = match when.as_deref().map(str::trim) {
+            None | Some("") => default_context.to_string(),
+            // The `when` clause narrows the command's default context, unless it names the view itself,
+            // so that e.g. `isMac` does not bind the command everywhere.
+            Some(when) => match translate_when(when) {
+                // A clause like `!editorTextFocus` on an editor command could never match.
+                Ok(expression) if expression.excludes(default_context) => {
+                    builder.skip(
+                        &key,
+                        &command,
+                        SkipReason::UnsupportedWhen(when.to_string()),
+                    );
+                    continue;
+                }
+                Ok(expression) if expression.narrows_to_view() => expression.to_context(),
+                Ok(expression) => WhenExpression::And(
+                    Box::new(WhenExpression::Context(default_context.to_string())),
+                    Box::new(expression),
+                )
+                .to_context(),
+                Err(reason) => {
+                    builder.skip(&key, &command, reason);
+                    continue;
+                }
+            },
+        };
+        builder.bind(context, keystrokes, action);
+    }
+    Ok(())
+}
+
+/// Translates VS Code keystrokes like `ctrl+k ctrl+c` into Zed's `ctrl-k ctrl-c`.
+fn translate_vscode_keystrokes(key: &str) -> Result<String, SkipReason> {
+    let mut keystrokes = Vec::new();
+    for chord in key.split_whitespace() {
+        let (modifiers, key) = match chord.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => chord.rsplit_once('+').unwrap_or(("", chord)),
+        };
+        let mut keystroke = String::new();
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            let modifier = match modifier.to_lowercase().as_str() {
+                "ctrl" => "ctrl",
+                "shift" => "shift",
+                "alt" => "alt",
+                "cmd" => "cmd",
+                "meta" => "super",
+                "win" => "win",
+                _ => return Err(SkipReason::UnsupportedKeystroke(modifier.to_string())),
+            };
+            keystroke.push_str(modifier);
+            keystroke.push('-');
+        }
+        keystroke.push_str(&translate_vscode_key(key)?);
+        keystrokes.push(keystroke);
+    }
+    if keystrokes.is_empty() {
+        return Err(SkipReason::UnsupportedKeystroke(key.to_string()));
+    }
+    Ok(keystrokes.join(" "))
+}
+
+fn translate_vscode_key(key: &str) -> Result<String, SkipReason> {
+    let lowercase = key.to_lowercase();
+    let supported = match lowercase.as_str() {
+        "escape" | "enter" | "tab" | "space" | "backspace" | "delete" | "insert" | "home"
+        | "end" | "pageup" | "pagedown" | "up" | "down" | "left" | "right" => true,
+        key if key.len() == 1 => key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "`-=[]\\;',./+".contains(c)),
+        key => key
+            .strip_prefix('f')
+            .and_then(|number| number.parse::<u8>().ok())
+            .is_some_and(|number| (1..=24).contains(&number)),
+    };
+    if supported {
+        Ok(lowercase)
+    } else {
+        Err(SkipReason::UnsupportedKeystroke(key.to_string()))
+    }
+}
+
+/// Translates a VS Code `when` clause into a keymap context predicate.
+fn translate_when(when: &str) -> Result<WhenExpression, SkipReason> {
+    let tokens = tokenize_when(when);
+    let mut parser = WhenParser {
+        when,
+        tokens: &tokens,
+        position: 0,
+    };
+    let expression = parser.parse_or()?;
+    if parser.position < tokens.len() {
+        return Err(SkipReason::UnsupportedWhen(when.to_string()));
+    }
+    Ok(expression)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum WhenToken {
+    Word(String),
+    Not,
+    And,
+    Or,
+    Equal,
+    NotEqual,
+    OpenParen,
+    CloseParen,
+    Other(String),
+}
+
+fn tokenize_when(when: &str) -> Vec<WhenToken> {
+    let mut tokens = Vec::new();
+    let mut chars = when.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => WhenToken::OpenParen,
+            ')' => WhenToken::CloseParen,
+            '&' if chars.next_if_eq(&'&').is_some() => WhenToken::And,
+            '|' if chars.next_if_eq(&'|').is_some() => WhenToken::Or,
+            '=' if chars.next_if_eq(&'=').is_some() => {
+                chars.next_if_eq(&'=');
+                WhenToken::Equal
+            }
+            '!' if chars.next_if_eq(&'=').is_some() => {
+                chars.next_if_eq(&'=');
+                WhenToken::NotEqual
+            }
+            '!' => WhenToken::Not,
+            '\'' | '"' => {
+                let value = chars.by_ref().take_while(|next| *next != c).collect();
+                WhenToken::Word(value)
+            }
+            c if c.is_alphanumeric() || "._-:/".contains(c) => {
+                let mut word = c.to_string();
+                while let Some(next) =
+                    chars.next_if(|next| next.is_alphanumeric() || "._-:/".contains(*next))
+                {
+                    word.push(next);
+                }
+                WhenToken::Word(word)
+            }
+            c => {
+                let mut operator = c.to_string();
+                operator.extend(chars.next_if(|next| "=~".contains(*next)));
+                WhenToken::Other(operator)
+            }
+        };
+        tokens.push(token);
+    }
+    tokens
+}
+
+enum WhenExpression {
+    Context(String),
+    Not(Box<WhenExpression>),
+    And(Box<WhenExpression>, Box<WhenExpression>),
+    Or(Box<WhenExpression>, Box<WhenExpression>),
+}
+
+impl WhenExpression {
+    fn to_context(&self) -> String {
+        match self {
+            WhenExpression::Context(context) => context.clone(),
+            WhenExpression::Not(operand) => match operand.as_ref() {
+                WhenExpression::Context(context) if !context.contains(' ') => {
+                    format!("!{context}")
+                }
+                operand => format!("!({})", operand.to_context()),
+            },
+            WhenExpression::And(left, right) => {
+                format!("{} && {}", left.to_and_operand(), right.to_and_operand())
+            }
+            WhenExpression::Or(left, right) => {
+                format!("{} || {}", left.to_context(), right.to_context())
+            }
+        }
+    }
+
+    /// Whether the expression only matches within a view context, like `Editor`, rather than
+    /// constraining attributes like `os == macos` alone.
+    fn narrows_to_view(&self) -> bool {
+        match self {
+            WhenExpression::Context(context) => context.starts_with(|c: char| c.is_uppercase()),
+            WhenExpression::Not(_) => false,
+            WhenExpression::And(left, right) => left.narrows_to_view() || right.narrows_to_view(),
+            WhenExpression::Or(left, right) => left.narrows_to_view() && right.narrows_to_view(),
+        }
+    }
+
+    /// Whether the expression can only match outside of the given context.
+    fn excludes(&self, context: &str) -> bool {
+        match self {
+            WhenExpression::Context(_) => false,
+            WhenExpression::Not(operand) => {
+                matches!(operand.as_ref(), WhenExpression::Context(operand) if operand == context)
+            }
+            WhenExpression::And(left, right) => left.excludes(context) || right.excludes(context),
+            WhenExpression::Or(left, right) => left.excludes(context) && right.excludes(context),
+        }
+    }
+
+    fn to_and_operand(&self) -> String {
+        match self {
+            WhenExpression::Or(..) => format!("({})", self.to_context()),
+            _ => self.to_context(),
+        }
+    }
+}
+
+struct WhenParser<'a> {
+    when: &'a str,
+    tokens: &'a [WhenToken],
+    position: usize,
+}
+
+impl WhenParser<'_> {
+    fn next(&mut self) -> Option<&WhenToken> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn next_if(&mut self, expected: &WhenToken) -> bool {
+        if self.tokens.get(self.position) == Some(expected) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<WhenExpression, SkipReason> {
+        let mut expression = self.parse_and()?;
+        while self.next_if(&WhenToken::Or) {
+            expression = WhenExpression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<WhenExpression, SkipReason> {
+        let mut expression = self.parse_unary()?;
+        while self.next_if(&WhenToken::And) {
+            expression = WhenExpression::And(Box::new(expression), Box::new(self.parse_unary()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<WhenExpression, SkipReason> {
+        match self.next().cloned() {
+            Some(WhenToken::Not) => Ok(WhenExpression::Not(Box::new(self.parse_unary()?))),
+            Some(WhenToken::OpenParen) => {
+                let expression = self.parse_or()?;
+                if self.next_if(&WhenToken::CloseParen) {
+                    Ok(expression)
+                } else {
+                    Err(SkipReason::UnsupportedWhen(self.when.to_string()))
+                }
+            }
+            Some(WhenToken::Word(key)) => {
+                let operator = match self.tokens.get(self.position) {
+                    Some(WhenToken::Equal) => "==",
+                    Some(WhenToken::NotEqual) => "!=",
+                    Some(WhenToken::Other(operator)) => {
+                        return Err(SkipReason::UnsupportedWhen(format!("{key} {operator}")))
+                    }
+                    _ => {
+                        return vscode_context(&key)
+                            .map(|context| WhenExpression::Context(context.to_string()))
+                            .ok_or(SkipReason::UnsupportedWhen(key))
+                    }
+                };
+                self.position += 1;
+                let Some(WhenToken::Word(value)) = self.next().cloned() else {
+                    return Err(SkipReason::UnsupportedWhen(format!("{key} {operator}")));
+                };
+                match key.as_str() {
+                    "resourceExtname" => {
+                        let extension = value.trim_start_matches('.');
+                        Ok(WhenExpression::Context(format!(
+                            "extension {operator} {extension}"
+                        )))
+                    }
+                    _ => Err(SkipReason::UnsupportedWhen(format!(
+                        "{key} {operator} {value}"
+                    ))),
+                }
+            }
+            _ => Err(SkipReason::UnsupportedWhen(self.when.to_string())),
+        }
+    }
+}
+
+fn vscode_context(key: &str) -> Option<&'static str> {
+    Some(match key {
+        "editorFocus" | "editorTextFocus" | "textInputFocus" => "Editor",
+        "suggestWidgetVisible" => "showing_completions",
+        "codeActionMenuVisible" => "showing_code_actions",
+        "renameInputVisible" => "renaming",
+        "terminalFocus" | "terminalTextSelected" => "Terminal",
+        "inQuickOpen" => "Picker",
+        "filesExplorerFocus" | "explorerViewletFocus" => "ProjectPanel",
+        "findWidgetVisible" | "findInputFocussed" => "BufferSearchBar",
+        "replaceInputFocussed" => "BufferSearchBar && in_replace",
+        "searchViewletFocus" | "searchInputBoxFocus" => "ProjectSearchBar",
+        "isMac" => "os == macos",
+        "isLinux" => "os == linux",
+        "isWindows" => "os == windows",
+        _ => return None,
+    })
+}
+
+/// Returns the default keymap context and the action for a VS Code command.
+fn vscode_command(command: &str) -> Option<(&'static str, Value)> {
+    let (context, action) = match command {
+        "workbench.action.showCommands" => ("Workspace", "command_palette::Toggle"),
+        "workbench.action.quickOpen" => ("Workspace", "file_finder::Toggle"),
+        "workbench.action.showAllSymbols" => ("Workspace", "project_symbols::Toggle"),
+        "workbench.action.openSettings" => ("Workspace", "zed::OpenSettings"),
+        "workbench.action.openGlobalKeybindings" => ("Workspace", "zed::OpenKeymap"),
+        "workbench.action.files.save" => ("Workspace", "workspace::Save"),
+        "workbench.action.files.saveAll" => ("Workspace", "workspace::SaveAll"),
+        "workbench.action.files.saveAs" => ("Workspace", "workspace::SaveAs"),
+        "workbench.action.files.newUntitledFile" => ("Workspace", "workspace::NewFile"),
+        "workbench.action.newWindow" => ("Workspace", "workspace::NewWindow"),
+        "workbench.action.closeWindow" => ("Workspace", "workspace::CloseWindow"),
+        "workbench.action.openRecent" => ("Workspace", "projects::OpenRecent"),
+        "workbench.action.quit" => ("Workspace", "zed::Quit"),
+        "workbench.action.toggleSidebarVisibility" => ("Workspace", "workspace::ToggleLeftDock"),
+        "workbench.action.toggleAuxiliaryBar" => ("Workspace", "workspace::ToggleRightDock"),
+        "workbench.action.togglePanel" => ("Workspace", "workspace::ToggleBottomDock"),
+        "workbench.action.toggleMaximizedPanel" => ("Workspace", "workspace::ToggleZoom"),
+        "workbench.action.terminal.toggleTerminal" => ("Workspace", "terminal_panel::ToggleFocus"),
+        "workbench.action.terminal.new" => ("Workspace", "workspace::NewTerminal"),
+        "workbench.view.explorer" => ("Workspace", "project_panel::ToggleFocus"),
+        "workbench.view.scm" => ("Workspace", "git_panel::ToggleFocus"),
+        "workbench.view.search" | "workbench.action.findInFiles" => {
+            ("Workspace", "pane::DeploySearch")
+        }
+        "workbench.actions.view.problems" => ("Workspace", "diagnostics::Deploy"),
+        "workbench.action.selectTheme" => ("Workspace", "theme_selector::Toggle"),
+        "workbench.action.zoomIn" => ("Workspace", "zed::IncreaseBufferFontSize"),
+        "workbench.action.zoomOut" => ("Workspace", "zed::DecreaseBufferFontSize"),
+        "workbench.action.zoomReset" => ("Workspace", "zed::ResetBufferFontSize"),
+        "workbench.action.tasks.runTask" => ("Workspace", "task::Spawn"),
+        "workbench.action.tasks.reRunTask" => ("Workspace", "task::Rerun"),
+        "workbench.action.focusNextGroup" => ("Workspace", "workspace::ActivateNextPane"),
+        "workbench.action.focusPreviousGroup" => ("Workspace", "workspace::ActivatePreviousPane"),
+        "workbench.action.closeActiveEditor" => ("Pane", "pane::CloseActiveItem"),
+        "workbench.action.closeAllEditors" => ("Pane", "pane::CloseAllItems"),
+        "workbench.action.reopenClosedEditor" => ("Pane", "pane::ReopenClosedItem"),
+        "workbench.action.nextEditor" => ("Pane", "pane::ActivateNextItem"),
+        "workbench.action.previousEditor" => ("Pane", "pane::ActivatePreviousItem"),
+        "workbench.action.splitEditorRight" => ("Pane", "pane::SplitRight"),
+        "workbench.action.splitEditorDown" => ("Pane", "pane::SplitDown"),
+        "workbench.action.navigateBack" => ("Pane", "pane::GoBack"),
+        "workbench.action.navigateForward" => ("Pane", "pane::GoForward"),
+        "workbench.action.openPreviousRecentlyUsedEditorInGroup" => {
+            ("Workspace", "tab_switcher::Toggle")
+        }
+        "workbench.action.gotoLine" => ("Editor", "go_to_line::Toggle"),
+        "workbench.action.gotoSymbol" => ("Editor", "outline::Toggle"),
+        "actions.find" => ("Editor", "buffer_search::Deploy"),
+        "editor.action.startFindReplaceAction" => ("Editor", "buffer_search::DeployReplace"),
+        "editor.action.commentLine" => ("Editor", "editor::ToggleComments"),
+        "editor.action.formatDocument" => ("Editor", "editor::Format"),
+        "editor.action.organizeImports" => ("Editor", "editor::OrganizeImports"),
+        "editor.action.rename" => ("Editor", "editor::Rename"),
+        "editor.action.revealDefinition" => ("Editor", "editor::GoToDefinition"),
+        "editor.action.revealDefinitionAside" => ("Editor", "editor::GoToDefinitionSplit"),
+        "editor.action.revealDeclaration" => ("Editor", "editor::GoToDeclaration"),
+        "editor.action.goToTypeDefinition" => ("Editor", "editor::GoToTypeDefinition"),
+        "editor.action.goToImplementation" => ("Editor", "editor::GoToImplementation"),
+        "editor.action.goToReferences" | "references-view.findReferences" => {
+            ("Editor", "editor::FindAllReferences")
+        }
+        "editor.action.quickFix" => ("Editor", "editor::ToggleCodeActions"),
+        "editor.action.triggerSuggest" => ("Editor", "editor::ShowCompletions"),
+        "editor.action.triggerParameterHints" => ("Editor", "editor::ShowSignatureHelp"),
+        "editor.action.inlineSuggest.trigger" => ("Editor", "editor::ShowEditPrediction"),
+        "editor.action.inlineSuggest.commit" => ("Editor", "editor::AcceptEditPrediction"),
+        "editor.action.showHover" => ("Editor", "editor::Hover"),
+        "editor.action.marker.next" | "editor.action.marker.nextInFiles" => {
+            ("Editor", "editor::GoToDiagnostic")
+        }
+        "editor.action.marker.prev" | "editor.action.marker.prevInFiles" => {
+            ("Editor", "editor::GoToPreviousDiagnostic")
+        }
+        "workbench.action.editor.nextChange" => ("Editor", "editor::GoToHunk"),
+        "workbench.action.editor.previousChange" => ("Editor", "editor::GoToPreviousHunk"),
+        "editor.action.moveLinesUpAction" => ("Editor", "editor::MoveLineUp"),
+        "editor.action.moveLinesDownAction" => ("Editor", "editor::MoveLineDown"),
+        "editor.action.copyLinesUpAction" => ("Editor", "editor::DuplicateLineUp"),
+        "editor.action.copyLinesDownAction" => ("Editor", "editor::DuplicateLineDown"),
+        "editor.action.deleteLines" => ("Editor", "editor::DeleteLine"),
+        "editor.action.insertLineAfter" => ("Editor", "editor::NewlineBelow"),
+        "editor.action.insertLineBefore" => ("Editor", "editor::NewlineAbove"),
+        "editor.action.indentLines" => ("Editor", "editor::Indent"),
+        "editor.action.outdentLines" => ("Editor", "editor::Outdent"),
+        "editor.action.joinLines" => ("Editor", "editor::JoinLines"),
+        "editor.action.transformToUppercase" => ("Editor", "editor::ConvertToUpperCase"),
+        "editor.action.transformToLowercase" => ("Editor", "editor::ConvertToLowerCase"),
+        "editor.action.sortLinesAscending" => ("Editor", "editor::SortLinesCaseSensitive"),
+        "editor.action.addSelectionToNextFindMatch" => {
+            return Some((
+                "Editor",
+                json!(["editor::SelectNext", { "replace_newest": false }]),
+            ))
+        }
+        "editor.action.moveSelectionToNextFindMatch" => {
+            return Some((
+                "Editor",
+                json!(["editor::SelectNext", { "replace_newest": true }]),
+            ))
+        }
+        "editor.action.selectHighlights" => ("Editor", "editor::SelectAllMatches"),
+        "editor.action.insertCursorAbove" => ("Editor", "editor::AddSelectionAbove"),
+        "editor.action.insertCursorBelow" => ("Editor", "editor::AddSelectionBelow"),
+        "editor.action.insertCursorAtEndOfEachLineSelected" => {
+            ("Editor", "editor::SplitSelectionIntoLines")
+        }
+        "expandLineSelection" => ("Editor", "editor::SelectLine"),
+        "cursorUndo" => ("Editor", "editor::UndoSelection"),
+        "editor.action.smartSelect.expand" => ("Editor", "editor::SelectLargerSyntaxNode"),
+        "editor.action.smartSelect.shrink" => ("Editor", "editor::SelectSmallerSyntaxNode"),
+        "editor.action.jumpToBracket" => ("Editor", "editor::MoveToEnclosingBracket"),
+        "editor.fold" => ("Editor", "editor::Fold"),
+        "editor.unfold" => ("Editor", "editor::UnfoldLines"),
+        "editor.toggleFold" => ("Editor", "editor::ToggleFold"),
+        "editor.foldRecursively" => ("Editor", "editor::FoldRecursive"),
+        "editor.foldAll" => ("Editor", "editor::FoldAll"),
+        "editor.unfoldAll" => ("Editor", "editor::UnfoldAll"),
+        "editor.action.clipboardCutAction" => ("Editor", "editor::Cut"),
+        "editor.action.clipboardCopyAction" => ("Editor", "editor::Copy"),
+        "editor.action.clipboardPasteAction" => ("Editor", "editor::Paste"),
+        "editor.action.selectAll" => ("Editor", "editor::SelectAll"),
+        "editor.action.toggleWordWrap" => ("Editor", "editor::ToggleSoftWrap"),
+        "undo" => ("Editor", "editor::Undo"),
+        "redo" => ("Editor", "editor::Redo"),
+        "cursorTop" => ("Editor", "editor::MoveToBeginning"),
+        "cursorBottom" => ("Editor", "editor::MoveToEnd"),
+        "cursorWordLeft" => ("Editor", "editor::MoveToPreviousWordStart"),
+        "cursorWordRight" | "cursorWordEndRight" => ("Editor", "editor::MoveToNextWordEnd"),
+        "deleteWordLeft" => ("Editor", "editor::DeleteToPreviousWordStart"),
+        "deleteWordRight" => ("Editor", "editor::DeleteToNextWordEnd"),
+        "git.revertSelectedRanges" => ("Editor", "git::Restore"),
+        "workbench.action.terminal.clear" => ("Terminal", "terminal::Clear"),
+        "workbench.action.terminal.copySelection" => ("Terminal", "terminal::Copy"),
+        "workbench.action.terminal.paste" => ("Terminal", "terminal::Paste"),
+        _ => return None,
+    };
+    Some((context, Value::String(action.to_string())))
+}
+
+fn import_jetbrains(content: &str, builder: &mut ImportBuilder) -> Result<()> {
+    let mut current_action: Option<(String, bool)> = None;
+    for tag in (XmlTags { content }) {
+        let tag = tag?;
+        match tag.name.as_str() {
+            "action" if !tag.closing => {
+                let id = tag
+                    .attribute("id")
+                    .ok_or_else(|| anyhow!("keymap action without an id"))?;
+                if tag.self_closing {
+                    builder.skip("", &id, SkipReason::Removal);
+                } else {
+                    current_action = Some((id, false));
+                }
+            }
+            "action" => {
+                if let Some((id, false)) = current_action.take() {
+                    builder.skip("", &id, SkipReason::Removal);
+                }
+            }
+            "keyboard-shortcut" => {
+                let Some((id, has_shortcuts)) = current_action.as_mut() else {
+                    continue;
+                };
+                *has_shortcuts = true;
+                let Some(first) = tag.attribute("first-keystroke") else {
+                    continue;
+                };
+                let original = match tag.attribute("second-keystroke") {
+                    Some(second) => format!("{first}, {second}"),
+                    None => first.clone(),
+                };
+                let Some((context, action)) = jetbrains_action(id) else {
+                    builder.skip(&original, id, SkipReason::UnknownCommand);
+                    continue;
+                };
+                let keystrokes = std::iter::once(first)
+                    .chain(tag.attribute("second-keystroke"))
+                    .map(|keystroke| translate_jetbrains_keystroke(&keystroke))
+                    .collect::<Result<Vec<_>, _>>();
+                match keystrokes {
+                    Ok(keystrokes) => {
+                        builder.bind(context.to_string(), keystrokes.join(" "), action)
+                    }
+                    Err(reason) => builder.skip(&original, id, reason),
+                }
+            }
+            "mouse-shortcut" | "keyboard-gesture-shortcut" => {
+                if let Some((id, has_shortcuts)) = current_action.as_mut() {
+                    *has_shortcuts = true;
+                    let keystroke = tag
+                        .attribute("keystroke")
+                        .or_else(|| tag.attribute("modifier"))
+                        .unwrap_or_default();
+                    builder.skip(&keystroke, id, SkipReason::NotAKeystroke);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Translates JetBrains keystrokes like `shift ctrl F12` into Zed's `shift-ctrl-f12`.
+fn translate_jetbrains_keystroke(keystroke: &str) -> Result<String, SkipReason> {
+    let mut parts = keystroke.split_whitespace().collect::<Vec<_>>();
+    let Some(key) = parts.pop() else {
+        return Err(SkipReason::UnsupportedKeystroke(keystroke.to_string()));
+    };
+    let mut translated = String::new();
+    for modifier in parts {
+        let modifier = match modifier {
+            "ctrl" | "control" => "ctrl",
+            "shift" => "shift",
+            "alt" => "alt",
+            "meta" => "cmd",
+            _ => return Err(SkipReason::UnsupportedKeystroke(modifier.to_string())),
+        };
+        translated.push_str(modifier);
+        translated.push('-');
+    }
+    let key = match key {
+        "ENTER" => "enter",
+        "ESCAPE" => "escape",
+        "TAB" => "tab",
+        "SPACE" => "space",
+        "BACK_SPACE" => "backspace",
+        "DELETE" => "delete",
+        "INSERT" => "insert",
+        "HOME" => "home",
+        "END" => "end",
+        "PAGE_UP" => "pageup",
+        "PAGE_DOWN" => "pagedown",
+        "UP" => "up",
+        "DOWN" => "down",
+        "LEFT" => "left",
+        "RIGHT" => "right",
+        "BACK_QUOTE" => "`",
+        "MINUS" => "-",
+        "EQUALS" => "=",
+        "OPEN_BRACKET" => "[",
+        "CLOSE_BRACKET" => "]",
+        "BACK_SLASH" => "\\",
+        "SEMICOLON" => ";",
+        "QUOTE" => "'",
+        "COMMA" => ",",
+        "PERIOD" => ".",
+        "SLASH" => "/",
+        key if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphanumeric()) => key,
+        key if key
+            .strip_prefix('F')
+            .and_then(|number| number.parse::<u8>().ok())
+            .is_some_and(|number| (1..=24).contains(&number)) =>
+        {
+            key
+        }
+        key => return Err(SkipReason::UnsupportedKeystroke(key.to_string())),
+    };
+    translated.push_str(&key.to_lowercase());
+    Ok(translated)
+}
+
+/// Returns the default keymap context and the action for a JetBrains action id.
+fn jetbrains_action(id: &str) -> Option<(&'static str, Value)> {
+    let (context, action) = match id {
+        "GotoAction" => ("Workspace", "command_palette::Toggle"),
+        "GotoFile" => ("Workspace", "file_finder::Toggle"),
+        "GotoClass" | "GotoSymbol" => ("Workspace", "project_symbols::Toggle"),
+        "ShowSettings" => ("Workspace", "zed::OpenSettings"),
+        "SaveAll" => ("Workspace", "workspace::SaveAll"),
+        "Exit" => ("Workspace", "zed::Quit"),
+        "ManageRecentProjects" | "$LRU" => ("Workspace", "projects::OpenRecent"),
+        "RecentFiles" | "Switcher" => ("Workspace", "tab_switcher::Toggle"),
+        "FindInPath" => ("Workspace", "pane::DeploySearch"),
+        "ActivateProjectToolWindow" => ("Workspace", "project_panel::ToggleFocus"),
+        "ActivateTerminalToolWindow" => ("Workspace", "terminal_panel::ToggleFocus"),
+        "ActivateProblemsViewToolWindow" => ("Workspace", "diagnostics::Deploy"),
+        "ActivateCommitToolWindow" => ("Workspace", "git_panel::ToggleFocus"),
+        "HideAllWindows" => ("Workspace", "workspace::CloseAllDocks"),
+        "ToggleDistractionFreeMode" | "HideActiveWindow" => ("Workspace", "workspace::ToggleZoom"),
+        "NextSplitter" => ("Workspace", "workspace::ActivateNextPane"),
+        "PrevSplitter" => ("Workspace", "workspace::ActivatePreviousPane"),
+        "Rerun" => ("Workspace", "task::Rerun"),
+        "CloseContent" => ("Pane", "pane::CloseActiveItem"),
+        "CloseAllEditors" => ("Pane", "pane::CloseAllItems"),
+        "ReopenClosedTab" => ("Pane", "pane::ReopenClosedItem"),
+        "NextTab" => ("Pane", "pane::ActivateNextItem"),
+        "PreviousTab" => ("Pane", "pane::ActivatePreviousItem"),
+        "SplitVertically" => ("Pane", "pane::SplitRight"),
+        "SplitHorizontally" => ("Pane", "pane::SplitDown"),
+        "Back" => ("Pane", "pane::GoBack"),
+        "Forward" => ("Pane", "pane::GoForward"),
+        "GotoLine" => ("Editor", "go_to_line::Toggle"),
+        "FileStructurePopup" => ("Editor", "outline::Toggle"),
+        "Find" => ("Editor", "buffer_search::Deploy"),
+        "Replace" => ("Editor", "buffer_search::DeployReplace"),
+        "ReformatCode" => ("Editor", "editor::Format"),
+        "OptimizeImports" => ("Editor", "editor::OrganizeImports"),
+        "RenameElement" => ("Editor", "editor::Rename"),
+        "GotoDeclaration" => ("Editor", "editor::GoToDefinition"),
+        "GotoTypeDeclaration" => ("Editor", "editor::GoToTypeDefinition"),
+        "GotoImplementation" => ("Editor", "editor::GoToImplementation"),
+        "FindUsages" | "ShowUsages" => ("Editor", "editor::FindAllReferences"),
+        "ShowIntentionActions" => ("Editor", "editor::ToggleCodeActions"),
+        "CodeCompletion" => ("Editor", "editor::ShowCompletions"),
+        "ParameterInfo" => ("Editor", "editor::ShowSignatureHelp"),
+        "QuickJavaDoc" | "ShowHoverInfo" => ("Editor", "editor::Hover"),
+        "GotoNextError" => ("Editor", "editor::GoToDiagnostic"),
+        "GotoPreviousError" => ("Editor", "editor::GoToPreviousDiagnostic"),
+        "VcsShowNextChangeMarker" => ("Editor", "editor::GoToHunk"),
+        "VcsShowPrevChangeMarker" => ("Editor", "editor::GoToPreviousHunk"),
+        "MoveLineUp" | "MoveStatementUp" => ("Editor", "editor::MoveLineUp"),
+        "MoveLineDown" | "MoveStatementDown" => ("Editor", "editor::MoveLineDown"),
+        "EditorDuplicate" => ("Editor", "editor::DuplicateSelection"),
+        "EditorDeleteLine" => ("Editor", "editor::DeleteLine"),
+        "EditorStartNewLine" => ("Editor", "editor::NewlineBelow"),
+        "EditorStartNewLineBefore" => ("Editor", "editor::NewlineAbove"),
+        "EditorIndentSelection" => ("Editor", "editor::Indent"),
+        "EditorUnindentSelection" => ("Editor", "editor::Outdent"),
+        "EditorJoinLines" => ("Editor", "editor::JoinLines"),
+        "CommentByLineComment" => ("Editor", "editor::ToggleComments"),
+        "EditorSelectWord" => ("Editor", "editor::SelectLargerSyntaxNode"),
+        "EditorUnSelectWord" => ("Editor", "editor::SelectSmallerSyntaxNode"),
+        "EditorSelectLine" => ("Editor", "editor::SelectLine"),
+        "EditorMatchBrace" => ("Editor", "editor::MoveToEnclosingBracket"),
+        "EditorCloneCaretAbove" => ("Editor", "editor::AddSelectionAbove"),
+        "EditorCloneCaretBelow" => ("Editor", "editor::AddSelectionBelow"),
+        "EditorAddCaretPerSelectedLine" => ("Editor", "editor::SplitSelectionIntoLines"),
+        "EditorTextStart" => ("Editor", "editor::MoveToBeginning"),
+        "EditorTextEnd" => ("Editor", "editor::MoveToEnd"),
+        "EditorPreviousWord" => ("Editor", "editor::MoveToPreviousWordStart"),
+        "EditorNextWord" => ("Editor", "editor::MoveToNextWordEnd"),
+        "EditorDeleteToWordStart" => ("Editor", "editor::DeleteToPreviousWordStart"),
+        "EditorDeleteToWordEnd" => ("Editor", "editor::DeleteToNextWordEnd"),
+        "EditorToggleUseSoftWraps" => ("Editor", "editor::ToggleSoftWrap"),
+        "CollapseRegion" => ("Editor", "editor::Fold"),
+        "ExpandRegion" => ("Editor", "editor::UnfoldLines"),
+        "CollapseRegionRecursively" => ("Editor", "editor::FoldRecursive"),
+        "CollapseAllRegions" => ("Editor", "editor::FoldAll"),
+        "ExpandAllRegions" => ("Editor", "editor::UnfoldAll"),
+        "ChangesView.Revert" | "Vcs.RollbackChangedLines" => ("Editor", "git::Restore"),
+        "SelectNextOccurrence" => {
+            return Some((
+                "Editor",
+                json!(["editor::SelectNext", { "replace_newest": false }]),
+            ))
+        }
+        "SelectAllOccurrences" => ("Editor", "editor::SelectAllMatches"),
+        "$Undo" => ("Editor", "editor::Undo"),
+        "$Redo" => ("Editor", "editor::Redo"),
+        "$Cut" => ("Editor", "editor::Cut"),
+        "$Copy" => ("Editor", "editor::Copy"),
+        "$Paste" => ("Editor", "editor::Paste"),
+        "$SelectAll" => ("Editor", "editor::SelectAll"),
+        _ => return None,
+    };
+    Some((context, Value::String(action.to_string())))
+}
+
+/// Iterates over the tags of an XML document. This only supports what's needed to read keymap
+/// files: element names and attributes, skipping text, comments and processing instructions.
+struct XmlTags<'a> {
+    content: &'a str,
+}
+
+struct XmlTag {
+    name: String,
+    attributes: Vec<(String, String)>,
+    closing: bool,
+    self_closing: bool,
+}
+
+impl XmlTag {
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.clone())
+    }
+}
+
+impl Iterator for XmlTags<'_> {
+    type Item = Result<XmlTag>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.content.find('<')?;
+            self.content = &self.content[start..];
+            let skipped = [("<!--", "-->"), ("<?", "?>"), ("<!", ">")]
+                .into_iter()
+                .find(|(open, _)| self.content.starts_with(open));
+            let Some((open, close)) = skipped else {
+                break;
+            };
+            let Some(end) = self.content[open.len()..].find(close) else {
+                return Some(Err(anyhow!("unterminated `{open}` in keymap XML")));
+            };
+            self.content = &self.content[open.len() + end + close.len()..];
+        }
+
+        let Some(end) = find_tag_end(self.content) else {
+            return Some(Err(anyhow!("unterminated tag in keymap XML")));
+        };
+        let mut tag = &self.content[1..end];
+        self.content = &self.content[end + 1..];
+
+        let closing = tag.starts_with('/');
+        if closing {
+            tag = &tag[1..];
+        }
+        let self_closing = tag.ends_with('/');
+        if self_closing {
+            tag = &tag[..tag.len() - 1];
+        }
+        let (name, mut rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+
+        let mut attributes = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let Some((attribute, value)) = rest.split_once('=') else {
+                return Some(Err(anyhow!("invalid attribute in tag `{name}`")));
+            };
+            let value = value.trim_start();
+            let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                return Some(Err(anyhow!("unquoted attribute in tag `{name}`")));
+            };
+            let Some(value_end) = value[1..].find(quote) else {
+                return Some(Err(anyhow!("unterminated attribute in tag `{name}`")));
+            };
+            let value_text = match unescape_xml(&value[1..value_end + 1]) {
+                Ok(value_text) => value_text,
+                Err(error) => return Some(Err(error)),
+            };
+            attributes.push((attribute.trim().to_string(), value_text));
+            rest = &value[value_end + 2..];
+        }
+
+        Some(Ok(XmlTag {
+            name: name.to_string(),
+            attributes,
+            closing,
+            self_closing,
+        }))
+    }
+}
+
+/// Finds the `>` that ends the tag at the start of the given text, ignoring any in attribute
+/// values.
+fn find_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (ix, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('>', None) => return Some(ix),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Decodes the predefined entities and character references in an attribute value.
+fn unescape_xml(text: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find(';') else {
+            return Err(anyhow!("unterminated reference in keymap XML"));
+        };
+        let reference = &rest[..end];
+        let c = match reference {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "amp" => Some('&'),
+            _ => reference
+                .strip_prefix('#')
+                .and_then(|code| match code.strip_prefix('x') {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => code.parse().ok(),
+                })
+                .and_then(char::from_u32),
+        };
+        let Some(c) = c else {
+            return Err(anyhow!(
+                "unsupported reference `&{reference};` in keymap XML"
+            ));
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sections(keymap: &KeymapFile) -> Vec<(&str, Vec<(&str, String)>)> {
+        keymap
+            .sections()
+            .map(|section| {
+                (
+                    section.context.as_str(),
+                    section
+                        .bindings()
+                        .map(|(keystrokes, action)| (keystrokes.as_str(), action.to_string()))
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_import_vscode_keybindings() {
+        let content = r#"
+            // Place your key bindings in this file to override the defaults
+            [
+                { "key": "ctrl+shift+d", "command": "editor.action.copyLinesDownAction" },
+                {
+                    "key": "ctrl+k ctrl+c",
+                    "command": "editor.action.commentLine",
+                    "when": "editorTextFocus && !editorReadonly"
+                },
+                { "key": "ctrl+k ctrl+/", "command": "editor.action.commentLine", "when": "editorTextFocus" },
+                { "key": "cmd+p", "command": "workbench.action.quickOpen" },
+                { "key": "ctrl+tab", "command": "workbench.action.nextEditor" },
+                { "key": "ctrl+d", "command": "editor.action.addSelectionToNextFindMatch", "when": "editorFocus" },
+                { "key": "ctrl+l", "command": "workbench.action.terminal.clear", "when": "terminalFocus && isMac" },
+                {
+                    "key": "alt+f",
+                    "command": "editor.action.formatDocument",
+                    "when": "editorTextFocus && (resourceExtname == .rs || resourceExtname == '.toml')"
+                },
+                { "key": "ctrl+shift+k", "command": "-editor.action.deleteLines" },
+                { "key": "ctrl+alt+x", "command": "extension.doSomething" },
+                { "key": "numpad_add", "command": "workbench.action.zoomIn" },
+                { "key": "ctrl+t", "command": "workbench.action.tasks.runTask", "args": "build" },
+                { "key": "ctrl+shift+d", "command": "editor.action.deleteLines" },
+                { "key": "cmd+shift+o", "command": "workbench.action.quickOpen", "when": "isMac" },
+                { "key": "ctrl+/", "command": "editor.action.commentLine", "when": "!suggestWidgetVisible" },
+                { "key": "ctrl+shift+/", "command": "editor.action.commentLine", "when": "!editorTextFocus" },
+            ]
+        "#;
+        let imported = KeymapFile::import(KeymapImportSource::VsCode, content).unwrap();
+        assert_eq!(
+            sections(&imported.keymap),
+            [
+                (
+                    "Editor",
+                    vec![
+                        ("ctrl-k ctrl-/", "editor::ToggleComments".to_string()),
+                        (
+                            "ctrl-d",
+                            r#""editor::SelectNext", {"replace_newest":false}"#.to_string()
+                        ),
+                        ("ctrl-shift-d", "editor::DeleteLine".to_string()),
+                    ]
+                ),
+                (
+                    "Workspace",
+                    vec![("cmd-p", "file_finder::Toggle".to_string())]
+                ),
+                (
+                    "Pane",
+                    vec![("ctrl-tab", "pane::ActivateNextItem".to_string())]
+                ),
+                (
+                    "Terminal && os == macos",
+                    vec![("ctrl-l", "terminal::Clear".to_string())]
+                ),
+                (
+                    "Editor && (extension == rs || extension == toml)",
+                    vec![("alt-f", "editor::Format".to_string())]
+                ),
+                (
+                    "Workspace && os == macos",
+                    vec![("cmd-shift-o", "file_finder::Toggle".to_string())]
+                ),
+                (
+                    "Editor && !showing_completions",
+                    vec![("ctrl-/", "editor::ToggleComments".to_string())]
+                ),
+            ]
+        );
+        assert_eq!(
+            imported.skipped,
+            [
+                SkippedKeyBinding {
+                    keystrokes: "ctrl+k ctrl+c".into(),
+                    command: "editor.action.commentLine".into(),
+                    reason: SkipReason::UnsupportedWhen("editorReadonly".into()),
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl+shift+k".into(),
+                    command: "editor.action.deleteLines".into(),
+                    reason: SkipReason::Removal,
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl+alt+x".into(),
+                    command: "extension.doSomething".into(),
+                    reason: SkipReason::UnknownCommand,
+                },
+                SkippedKeyBinding {
+                    keystrokes: "numpad_add".into(),
+                    command: "workbench.action.zoomIn".into(),
+                    reason: SkipReason::UnsupportedKeystroke("numpad_add".into()),
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl+t".into(),
+                    command: "workbench.action.tasks.runTask".into(),
+                    reason: SkipReason::UnsupportedArguments,
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl+shift+/".into(),
+                    command: "editor.action.commentLine".into(),
+                    reason: SkipReason::UnsupportedWhen("!editorTextFocus".into()),
+                },
+            ]
+        );
+
+        // The generated JSON can be loaded as a keymap.
+        let json = imported.to_json_text(KeymapImportSource::VsCode).unwrap();
+        assert!(json.contains("// - ctrl+alt+x (extension.doSomething): no matching Zed action"));
+        let reparsed = KeymapFile::parse(&json).unwrap();
+        assert_eq!(sections(&reparsed), sections(&imported.keymap));
+    }
+
+    #[test]
+    fn test_import_jetbrains_keymap() {
+        let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <keymap version="1" name="Team" parent="$default">
+              <!-- Reformatting -->
+              <action id="ReformatCode">
+                <keyboard-shortcut first-keystroke="ctrl alt L" />
+                <keyboard-shortcut first-keystroke="ctrl K" second-keystroke="ctrl D" />
+              </action>
+              <action id="EditorDuplicate">
+                <keyboard-shortcut first-keystroke="meta D" />
+              </action>
+              <action id="SelectNextOccurrence">
+                <keyboard-shortcut first-keystroke="alt J" />
+              </action>
+              <action id="CloseContent">
+                <keyboard-shortcut first-keystroke="ctrl F4" />
+                <mouse-shortcut keystroke="button2" />
+              </action>
+              <action id="GotoDeclaration">
+                <keyboard-shortcut first-keystroke="ctrl NUMPAD1" />
+              </action>
+              <action id="ToggleLineBreakpoint">
+                <keyboard-shortcut first-keystroke="ctrl F8" />
+              </action>
+              <action id="EditorDeleteLine" />
+              <action id="Find"></action>
+            </keymap>
+        "#;
+        let imported = KeymapFile::import(KeymapImportSource::JetBrains, content).unwrap();
+        assert_eq!(
+            sections(&imported.keymap),
+            [
+                (
+                    "Editor",
+                    vec![
+                        ("ctrl-alt-l", "editor::Format".to_string()),
+                        ("ctrl-k ctrl-d", "editor::Format".to_string()),
+                        ("cmd-d", "editor::DuplicateSelection".to_string()),
+                        (
+                            "alt-j",
+                            r#""editor::SelectNext", {"replace_newest":false}"#.to_string()
+                        ),
+                    ]
+                ),
+                (
+                    "Pane",
+                    vec![("ctrl-f4", "pane::CloseActiveItem".to_string())]
+                ),
+            ]
+        );
+        assert_eq!(
+            imported.skipped,
+            [
+                SkippedKeyBinding {
+                    keystrokes: "button2".into(),
+                    command: "CloseContent".into(),
+                    reason: SkipReason::NotAKeystroke,
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl NUMPAD1".into(),
+                    command: "GotoDeclaration".into(),
+                    reason: SkipReason::UnsupportedKeystroke("NUMPAD1".into()),
+                },
+                SkippedKeyBinding {
+                    keystrokes: "ctrl F8".into(),
+                    command: "ToggleLineBreakpoint".into(),
+                    reason: SkipReason::UnknownCommand,
+                },
+                SkippedKeyBinding {
+                    keystrokes: String::new(),
+                    command: "EditorDeleteLine".into(),
+                    reason: SkipReason::Removal,
+                },
+                SkippedKeyBinding {
+                    keystrokes: String::new(),
+                    command: "Find".into(),
+                    reason: SkipReason::Removal,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unescape_xml() {
+        assert_eq!(
+            unescape_xml("&lt;a&gt; &amp;lt; &#65;&#x42;&#x1F600;").unwrap(),
+            "<a> &lt; AB\u{1F600}"
+        );
+        assert!(unescape_xml("&nbsp;").is_err());
+        assert!(unescape_xml("&#xD800;").is_err());
+        assert!(unescape_xml("a & b").is_err());
+    }
+}
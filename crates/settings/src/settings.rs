@@ -2,6 +2,7 @@ mod editable_setting_control;
 mod json_schema;
 mod key_equivalents;
 mod keymap_file;
+mod keymap_import;
 mod settings_file;
 mod settings_store;
 
@@ -16,6 +17,7 @@ pub use key_equivalents::*;
 pub use keymap_file::{
     KeyBindingValidator, KeyBindingValidatorRegistration, KeymapFile, KeymapFileLoadResult,
 };
+pub use keymap_import::{ImportedKeymap, KeymapImportSource, SkipReason, SkippedKeyBinding};
 pub use settings_file::*;
 pub use settings_store::{
     parse_json_with_comments, InvalidSettingsError, LocalSettingsKind, Settings, SettingsLocation,
@@ -1,11 +1,18 @@
+mod transform;
+mod variables;
+
 use anyhow::{anyhow, Context as _, Result};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
+pub use transform::Transform;
+pub use variables::builtin_variable;
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    pub transforms: Vec<TabStopTransform>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -14,19 +21,51 @@ pub struct TabStop {
     pub choices: Option<Vec<String>>,
 }
 
+/// A mirror of a tabstop whose text is derived from the tabstop's text, as in
+/// `${1/(.*)/${1:/upcase}/}`, and which updates as the tabstop is edited.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TabStopTransform {
+    /// The index in [`Snippet::tabstops`] of the mirrored tabstop.
+    pub tabstop_index: usize,
+    pub range: Range<isize>,
+    pub transform: Transform,
+}
+
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        Self::parse_with_variables(source, |_| None)
+    }
+
+    /// Parses a snippet, expanding variables such as `$TM_FILENAME` with the given resolver.
+    /// Unknown variables expand to their default, or to their name if they have none.
+    pub fn parse_with_variables(
+        source: &str,
+        variables: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut parser = Parser {
+            text: String::with_capacity(source.len()),
+            tabstops: BTreeMap::new(),
+            transforms: Vec::new(),
+            variables: &variables,
+        };
+        parser
+            .parse_snippet(source, false)
             .context("failed to parse snippet")?;
+        let Parser {
+            text,
+            mut tabstops,
+            transforms,
+            ..
+        } = parser;
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
+        let mut tabstop_indices = tabstops.keys().copied().collect::<Vec<_>>();
         let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
 
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
+            tabstop_indices.push(0);
         } else {
             let end_tabstop = TabStop {
                 ranges: [len..len].into_iter().collect(),
@@ -38,99 +77,205 @@ impl Snippet {
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        let transforms = transforms
+            .into_iter()
+            .filter_map(|(index, range, transform)| {
+                Some(TabStopTransform {
+                    tabstop_index: tabstop_indices.iter().position(|ix| *ix == index)?,
+                    range,
+                    transform,
+                })
+            })
+            .collect();
+
+        Ok(Snippet {
+            text,
+            tabstops,
+            transforms,
+        })
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    loop {
-        match source.chars().next() {
-            None => return Ok(""),
-            Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
-            }
-            Some('\\') => {
-                // As specified in the LSP spec (`Grammar` section),
-                // backslashes can escape some characters:
-                // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
-                source = &source[1..];
-                if let Some(c) = source.chars().next() {
-                    if c == '$' || c == '\\' || c == '}' {
-                        text.push(c);
-                        // All escapable characters are 1 byte long:
-                        source = &source[1..];
+struct Parser<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    /// Mirrors of tabstops, by tabstop number rather than by index.
+    transforms: Vec<(usize, Range<isize>, Transform)>,
+    variables: &'a dyn Fn(&str) -> Option<String>,
+}
+
+impl Parser<'_> {
+    fn parse_snippet<'a>(&mut self, mut source: &'a str, nested: bool) -> Result<&'a str> {
+        loop {
+            match source.chars().next() {
+                None => return Ok(""),
+                Some('$') => {
+                    source = self.parse_dollar(&source[1..])?;
+                }
+                Some('\\') => {
+                    // As specified in the LSP spec (`Grammar` section),
+                    // backslashes can escape some characters:
+                    // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
+                    source = &source[1..];
+                    if let Some(c) = source.chars().next() {
+                        if c == '$' || c == '\\' || c == '}' {
+                            self.text.push(c);
+                            // All escapable characters are 1 byte long:
+                            source = &source[1..];
+                        } else {
+                            self.text.push('\\');
+                        }
                     } else {
-                        text.push('\\');
+                        self.text.push('\\');
                     }
-                } else {
-                    text.push('\\');
                 }
-            }
-            Some('}') => {
-                if nested {
-                    return Ok(source);
-                } else {
-                    text.push('}');
-                    source = &source[1..];
+                Some('}') => {
+                    if nested {
+                        return Ok(source);
+                    } else {
+                        self.text.push('}');
+                        source = &source[1..];
+                    }
+                }
+                Some(_) => {
+                    let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
+                    let (chunk, rest) = source.split_at(chunk_end);
+                    self.text.push_str(chunk);
+                    source = rest;
                 }
             }
-            Some(_) => {
-                let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
-                let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
+        }
+    }
+
+    fn parse_dollar<'a>(&mut self, source: &'a str) -> Result<&'a str> {
+        let body = source.strip_prefix('{').unwrap_or(source);
+        match body.chars().next() {
+            Some(c) if c.is_ascii_digit() => self.parse_tabstop(source),
+            Some(c) if c == '_' || c.is_ascii_alphabetic() => self.parse_variable(source),
+            _ => {
+                self.text.push('$');
+                Ok(source)
+            }
+        }
+    }
+
+    fn parse_tabstop<'a>(&mut self, mut source: &'a str) -> Result<&'a str> {
+        let tabstop_start = self.text.len();
+        let tabstop_index;
+        let mut choices = None;
+
+        if source.starts_with('{') {
+            let (index, rest) = parse_int(&source[1..])?;
+            tabstop_index = index;
+            source = rest;
+
+            if source.starts_with('/') {
+                let (transform, rest) = Transform::parse(&source[1..])?;
+                self.push_transform(tabstop_index, transform);
+                return Ok(&rest[1..]);
+            }
+
+            if source.starts_with("|") {
+                let (rest, parsed_choices) = parse_choices(&source[1..], &mut self.text)?;
                 source = rest;
+                choices = parsed_choices;
             }
+
+            if source.starts_with(':') {
+                source = self.parse_snippet(&source[1..], true)?;
+            }
+
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        } else {
+            let (index, rest) = parse_int(source)?;
+            tabstop_index = index;
+            source = rest;
         }
+
+        self.tabstops
+            .entry(tabstop_index)
+            .or_insert_with(|| TabStop {
+                ranges: Default::default(),
+                choices,
+            })
+            .ranges
+            .push(tabstop_start as isize..self.text.len() as isize);
+        Ok(source)
     }
-}
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    let mut choices = None;
-
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
-
-        if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
+    /// Inserts a mirror of a tabstop, initially showing the transformed text of the tabstop if
+    /// it has already been parsed.
+    fn push_transform(&mut self, tabstop_index: usize, transform: Transform) {
+        let start = self.text.len();
+        let source_text = self
+            .tabstops
+            .get(&tabstop_index)
+            .and_then(|tabstop| tabstop.ranges.first())
+            .map(|range| self.text[range.start as usize..range.end as usize].to_string());
+        if let Some(source_text) = source_text {
+            self.text.push_str(&transform.apply(&source_text));
+        }
+        self.transforms.push((
+            tabstop_index,
+            start as isize..self.text.len() as isize,
+            transform,
+        ));
+    }
+
+    fn parse_variable<'a>(&mut self, source: &'a str) -> Result<&'a str> {
+        let Some(source) = source.strip_prefix('{') else {
+            let (name, rest) = parse_variable_name(source);
+            let value = (self.variables)(name).unwrap_or_else(|| name.to_string());
+            self.text.push_str(&value);
+            return Ok(rest);
+        };
+
+        let (name, mut source) = parse_variable_name(source);
+        let value = (self.variables)(name);
+        if source.starts_with('/') {
+            let (transform, rest) = Transform::parse(&source[1..])?;
+            match value {
+                Some(value) => self.text.push_str(&transform.apply(&value)),
+                None => self.text.push_str(name),
+            }
+            return Ok(&rest[1..]);
         }
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            // The default may contain tabstops, which are discarded along with it when the
+            // variable has a value.
+            let start = self.text.len();
+            let tabstops = self.tabstops.clone();
+            let transforms_len = self.transforms.len();
+            source = self.parse_snippet(&source[1..], true)?;
+            if let Some(value) = value.filter(|value| !value.is_empty()) {
+                self.text.truncate(start);
+                self.tabstops = tabstops;
+                self.transforms.truncate(transforms_len);
+                self.text.push_str(&value);
+            }
+        } else {
+            let value = value.unwrap_or_else(|| name.to_string());
+            self.text.push_str(&value);
         }
 
         if source.starts_with('}') {
-            source = &source[1..];
+            Ok(&source[1..])
         } else {
-            return Err(anyhow!("expected a closing brace"));
+            Err(anyhow!("expected a closing brace"))
         }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
-        source = rest;
     }
+}
 
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+fn parse_variable_name(source: &str) -> (&str, &str) {
+    let len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    source.split_at(len)
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -327,6 +472,71 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |selected_text: &'static str| {
+            move |name: &str| match name {
+                "TM_FILENAME" => Some("main.rs".to_string()),
+                "TM_SELECTED_TEXT" => Some(selected_text.to_string()),
+                _ => None,
+            }
+        };
+
+        let source = "// $TM_FILENAME: ${TM_SELECTED_TEXT:${1:todo}}$0";
+        let snippet = Snippet::parse_with_variables(source, variables("")).unwrap();
+        assert_eq!(snippet.text, "// main.rs: todo");
+        assert_eq!(tabstops(&snippet), &[vec![12..16], vec![16..16]]);
+
+        // A variable with a value replaces its default, including any tabstops within it.
+        let snippet = Snippet::parse_with_variables(source, variables("x")).unwrap();
+        assert_eq!(snippet.text, "// main.rs: x");
+        assert_eq!(tabstops(&snippet), &[vec![13..13]]);
+
+        let snippet = Snippet::parse("${UNKNOWN} ${UNKNOWN:fallback} $UNKNOWN $ $$1").unwrap();
+        assert_eq!(snippet.text, "UNKNOWN fallback UNKNOWN $ $");
+        assert_eq!(tabstops(&snippet), &[vec![28..28]]);
+
+        let snippet =
+            Snippet::parse_with_variables(r"${TM_FILENAME/(.*)\..+$/$1/}", variables("")).unwrap();
+        assert_eq!(snippet.text, "main");
+    }
+
+    #[test]
+    fn test_snippet_with_transforms() {
+        let snippet =
+            Snippet::parse(r"${1:foo_bar} ${1/(.*)/${1:/upcase}/} ${1/_(.)/${1:/upcase}/g}")
+                .unwrap();
+        assert_eq!(snippet.text, "foo_bar FOO_BAR fooBar");
+        assert_eq!(tabstops(&snippet), &[vec![0..7], vec![22..22]]);
+        assert_eq!(
+            snippet
+                .transforms
+                .iter()
+                .map(|transform| (transform.tabstop_index, transform.range.clone()))
+                .collect::<Vec<_>>(),
+            &[(0, 8..15), (0, 16..22)]
+        );
+        assert_eq!(snippet.transforms[1].transform.apply("a_b_c"), "aBC");
+
+        // Mirrors preceding their tabstop are filled in once the tabstop is edited.
+        let snippet = Snippet::parse(r"${2/(.*)/[$1]/} $2").unwrap();
+        assert_eq!(snippet.text, " ");
+        assert_eq!(snippet.transforms[0].tabstop_index, 0);
+        assert_eq!(snippet.transforms[0].transform.apply("x"), "[x]");
+
+        let snippet = Snippet::parse(
+            r"$1 ${1/(foo)?.*/${1:?yes:no}/} ${1/(.*)/${1:/camelcase}/} ${1/(.*)/${1:/pascalcase}/}",
+        )
+        .unwrap();
+        let apply = |ix: usize, input: &str| snippet.transforms[ix].transform.apply(input);
+        assert_eq!(apply(0, "foobar"), "yes");
+        assert_eq!(apply(0, "bar"), "no");
+        assert_eq!(apply(1, "hello world-again"), "helloWorldAgain");
+        assert_eq!(apply(2, "hello world-again"), "HelloWorldAgain");
+
+        assert!(Snippet::parse(r"${1/(.*)/${1:/shout}/}").is_err());
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
@@ -0,0 +1,326 @@
+use anyhow::{anyhow, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// A regular expression substitution applied to a variable or a mirrored tabstop, as in
+/// `${TM_FILENAME/(.*)\\..+$/$1/}` or `${1/(.*)/${1:/upcase}/}`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, CaseModifier),
+    Conditional {
+        group: usize,
+        if_text: String,
+        else_text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CaseModifier {
+    Upcase,
+    Downcase,
+    Capitalize,
+    CamelCase,
+    PascalCase,
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl Transform {
+    /// Parses a transform following the variable name or tabstop index, starting just after the
+    /// first `/`. Returns the source following the options, which should be the closing brace.
+    pub(crate) fn parse(source: &str) -> Result<(Self, &str)> {
+        let (pattern, source) = parse_pattern(source)?;
+        let (format, source) = parse_format(source)?;
+        let options_end = source
+            .find('}')
+            .ok_or_else(|| anyhow!("expected a closing brace"))?;
+        let (options, source) = source.split_at(options_end);
+
+        let mut builder = RegexBuilder::new(&pattern);
+        let mut global = false;
+        for option in options.chars() {
+            match option {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                'm' => {
+                    builder.multi_line(true);
+                }
+                's' => {
+                    builder.dot_matches_new_line(true);
+                }
+                _ => return Err(anyhow!("unknown transform option '{option}'")),
+            }
+        }
+
+        let transform = Self {
+            regex: builder.build()?,
+            format,
+            global,
+        };
+        Ok((transform, source))
+    }
+
+    /// Replaces the first match of the regular expression in `input` (or every match, with the
+    /// `g` option) with the format string, leaving the rest of the input untouched.
+    pub fn apply(&self, input: &str) -> String {
+        let mut result = String::with_capacity(input.len());
+        let mut last_end = 0;
+        for captures in self.regex.captures_iter(input) {
+            let mat = captures.get(0).unwrap();
+            result.push_str(&input[last_end..mat.start()]);
+            self.expand(&captures, &mut result);
+            last_end = mat.end();
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&input[last_end..]);
+        result
+    }
+
+    fn expand(&self, captures: &Captures, result: &mut String) {
+        let group = |index: usize| captures.get(index).map_or("", |group| group.as_str());
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Group(index) => result.push_str(group(*index)),
+                FormatItem::Case(index, modifier) => {
+                    result.push_str(&modifier.apply(group(*index)));
+                }
+                FormatItem::Conditional {
+                    group: index,
+                    if_text,
+                    else_text,
+                } => {
+                    if group(*index).is_empty() {
+                        result.push_str(else_text);
+                    } else {
+                        result.push_str(if_text);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl CaseModifier {
+    fn parse(name: &str) -> Result<Self> {
+        match name {
+            "upcase" => Ok(Self::Upcase),
+            "downcase" => Ok(Self::Downcase),
+            "capitalize" => Ok(Self::Capitalize),
+            "camelcase" => Ok(Self::CamelCase),
+            "pascalcase" => Ok(Self::PascalCase),
+            _ => Err(anyhow!("unknown case modifier '{name}'")),
+        }
+    }
+
+    fn apply(self, text: &str) -> String {
+        match self {
+            Self::Upcase => text.to_uppercase(),
+            Self::Downcase => text.to_lowercase(),
+            Self::Capitalize => capitalize(text),
+            Self::CamelCase | Self::PascalCase => {
+                let mut result = String::with_capacity(text.len());
+                for (ix, word) in text
+                    .split(|c: char| !c.is_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .enumerate()
+                {
+                    if ix == 0 && self == Self::CamelCase {
+                        let mut chars = word.chars();
+                        result.extend(chars.next().into_iter().flat_map(char::to_lowercase));
+                        result.push_str(chars.as_str());
+                    } else {
+                        result.push_str(&capitalize(word));
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .chain(chars)
+        .collect()
+}
+
+fn parse_pattern(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected '/' after transform pattern")),
+            Some('/') => return Ok((pattern, &source[1..])),
+            Some('\\') => {
+                // Only slashes need escaping here; other escapes belong to the regex.
+                match chars.next() {
+                    Some('/') => pattern.push('/'),
+                    Some(c) => {
+                        pattern.push('\\');
+                        pattern.push(c);
+                    }
+                    None => pattern.push('\\'),
+                }
+                source = chars.as_str();
+            }
+            Some(c) => {
+                pattern.push(c);
+                source = chars.as_str();
+            }
+        }
+    }
+}
+
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected '/' after transform format")),
+            Some('/') => {
+                if !text.is_empty() {
+                    items.push(FormatItem::Text(text));
+                }
+                return Ok((items, &source[1..]));
+            }
+            Some('\\') => {
+                match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c @ ('$' | '\\' | '/' | '}')) => text.push(c),
+                    Some(c) => {
+                        text.push('\\');
+                        text.push(c);
+                    }
+                    None => text.push('\\'),
+                }
+                source = chars.as_str();
+            }
+            Some('$') => {
+                let (item, rest) = parse_format_item(&source[1..])?;
+                match item {
+                    Some(item) => {
+                        if !text.is_empty() {
+                            items.push(FormatItem::Text(std::mem::take(&mut text)));
+                        }
+                        items.push(item);
+                    }
+                    None => text.push('$'),
+                }
+                source = rest;
+            }
+            Some(c) => {
+                text.push(c);
+                source = chars.as_str();
+            }
+        }
+    }
+}
+
+/// Parses `n`, `{n}`, `{n:/modifier}`, `{n:+if}`, `{n:-else}`, `{n:else}` or `{n:?if:else}`
+/// following a `$` in a format string.
+fn parse_format_item(source: &str) -> Result<(Option<FormatItem>, &str)> {
+    if let Ok((group, rest)) = parse_group(source) {
+        return Ok((Some(FormatItem::Group(group)), rest));
+    }
+    let Some(source) = source.strip_prefix('{') else {
+        return Ok((None, source));
+    };
+
+    let (group, source) = parse_group(source)?;
+    if let Some(rest) = source.strip_prefix('}') {
+        return Ok((Some(FormatItem::Group(group)), rest));
+    }
+    let source = source
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected ':' or '}}' in format group"))?;
+
+    let (item, source) = if let Some(source) = source.strip_prefix('/') {
+        let (name, source) = take_until(source, &['}'])?;
+        (FormatItem::Case(group, CaseModifier::parse(&name)?), source)
+    } else if let Some(source) = source.strip_prefix('+') {
+        let (if_text, source) = take_until(source, &['}'])?;
+        let else_text = String::new();
+        let item = FormatItem::Conditional {
+            group,
+            if_text,
+            else_text,
+        };
+        (item, source)
+    } else if let Some(source) = source.strip_prefix('?') {
+        let (if_text, source) = take_until(source, &[':'])?;
+        let (else_text, source) = take_until(&source[1..], &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_text,
+            else_text,
+        };
+        (item, source)
+    } else {
+        let source = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, source) = take_until(source, &['}'])?;
+        let if_text = String::new();
+        let item = FormatItem::Conditional {
+            group,
+            if_text,
+            else_text,
+        };
+        (item, source)
+    };
+    Ok((Some(item), &source[1..]))
+}
+
+fn parse_group(source: &str) -> Result<(usize, &str)> {
+    let len = source
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(source.len());
+    if len == 0 {
+        return Err(anyhow!("expected a group index"));
+    }
+    let (prefix, suffix) = source.split_at(len);
+    Ok((prefix.parse()?, suffix))
+}
+
+/// Takes text up to (but not including) one of the terminators, unescaping backslashes.
+fn take_until<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("expected one of {terminators:?} in format group")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                text.extend(chars.next());
+                source = chars.as_str();
+            }
+            Some(c) => {
+                text.push(c);
+                source = chars.as_str();
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use chrono::Local;
+use rand::Rng as _;
+
+/// Resolves the snippet variables that don't depend on the editor, such as `$CURRENT_YEAR`
+/// and `$UUID`, following the names used by TextMate and VS Code.
+pub fn builtin_variable(name: &str) -> Option<String> {
+    let date_format = match name {
+        "CURRENT_YEAR" => "%Y",
+        "CURRENT_YEAR_SHORT" => "%y",
+        "CURRENT_MONTH" => "%m",
+        "CURRENT_MONTH_NAME" => "%B",
+        "CURRENT_MONTH_NAME_SHORT" => "%b",
+        "CURRENT_DATE" => "%d",
+        "CURRENT_DAY_NAME" => "%A",
+        "CURRENT_DAY_NAME_SHORT" => "%a",
+        "CURRENT_HOUR" => "%H",
+        "CURRENT_MINUTE" => "%M",
+        "CURRENT_SECOND" => "%S",
+        "CURRENT_SECONDS_UNIX" => "%s",
+        "CURRENT_TIMEZONE_OFFSET" => "%:z",
+        "RANDOM" => return Some(format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))),
+        "RANDOM_HEX" => return Some(format!("{:06x}", rand::thread_rng().gen_range(0..1 << 24))),
+        "UUID" => return Some(uuid::Uuid::new_v4().to_string()),
+        _ => return None,
+    };
+    Some(Local::now().format(date_format).to_string())
+}
@@ -87,6 +87,25 @@ impl Connection {
         self.backup_main(&destination)
     }
 
+    /// Runs SQLite's quick integrity check, returning the problems it found. A file that isn't
+    /// a database, or is too damaged to be checked, is reported as a problem too.
+    pub fn integrity_problems(&self) -> Result<Vec<String>> {
+        let check = self
+            .select::<String>("PRAGMA quick_check")
+            .and_then(|mut check| check());
+        match check {
+            Ok(rows) => Ok(rows.into_iter().filter(|row| row != "ok").collect()),
+            Err(error) => {
+                let code = unsafe { sqlite3_errcode(self.sqlite3) } & 0xff;
+                if code == SQLITE_CORRUPT || code == SQLITE_NOTADB {
+                    Ok(vec![error.to_string()])
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+
     pub fn sql_has_syntax_error(&self, sql: &str) -> Option<(String, usize)> {
         let sql = CString::new(sql).unwrap();
         let mut remaining_sql = sql.as_c_str();
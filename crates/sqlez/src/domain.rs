@@ -3,6 +3,13 @@ use crate::connection::Connection;
 pub trait Domain: 'static {
     fn name() -> &'static str;
     fn migrations() -> &'static [&'static str];
+
+    /// Migrations that revert the steps returned by [`Domain::migrations`], each paired
+    /// with the index of the step it reverts. They're stored alongside the steps, so that
+    /// builds that don't know about a step can still revert it.
+    fn down_migrations() -> &'static [(usize, &'static str)] {
+        &[]
+    }
 }
 
 pub trait Migrator: 'static {
@@ -17,7 +24,11 @@ impl Migrator for () {
 
 impl<D: Domain> Migrator for D {
     fn migrate(connection: &Connection) -> anyhow::Result<()> {
-        connection.migrate(Self::name(), Self::migrations())
+        connection.migrate_with_down_migrations(
+            Self::name(),
+            Self::migrations(),
+            Self::down_migrations(),
+        )
     }
 }
 
@@ -1,17 +1,59 @@
 // Migrations are constructed by domain, and stored in a table in the connection db with domain name,
-// effected tables, actual query text, and order.
-// If a migration is run and any of the query texts don't match, the app panics on startup (maybe fallback
-// to creating a new db?)
+// step, actual query text, and optionally the query text that reverts the step.
+// If a migration is run and one of the query texts doesn't match, the stored steps from that point on
+// are reverted so that the new ones can run instead. If they can't be reverted, a `MigrationMismatch`
+// error is returned, which thread safe connections can recover from by recreating the domain's tables.
 // Otherwise any missing migrations are run on the connection
 
-use std::ffi::CString;
+use std::{
+    ffi::CString,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use anyhow::{anyhow, Context as _, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashSet;
 use indoc::{formatdoc, indoc};
 use libsqlite3_sys::sqlite3_exec;
 
 use crate::connection::Connection;
 
+/// How many backups of a domain's table are kept when the domain is reset.
+const MAX_BACKUPS_PER_TABLE: usize = 3;
+
+/// Returned when a domain's stored migration differs from the one proposed at the same step,
+/// and the stored steps can't be reverted.
+#[derive(Debug)]
+pub struct MigrationMismatch {
+    pub domain: &'static str,
+    pub step: usize,
+    pub stored: String,
+    pub proposed: String,
+}
+
+impl fmt::Display for MigrationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            formatdoc! {"
+                Migration changed for {} at step {}
+
+                Stored migration:
+                {}
+
+                Proposed migration:
+                {}", self.domain, self.step, self.stored, self.proposed}
+        )
+    }
+}
+
+impl std::error::Error for MigrationMismatch {}
+
+fn format_migration(migration: &str) -> String {
+    sqlformat::format(migration, &sqlformat::QueryParams::None, Default::default())
+}
+
 impl Connection {
     fn eager_exec(&self, sql: &str) -> anyhow::Result<()> {
         let sql_str = CString::new(sql).context("Error creating cstr")?;
@@ -30,63 +72,259 @@ impl Connection {
         Ok(())
     }
 
+    fn create_migrations_table(&self) -> Result<()> {
+        self.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS migrations (
+                domain TEXT,
+                step INTEGER,
+                migration TEXT
+            )"})?()?;
+
+        // Down migrations were added after the table was first created.
+        let has_down_migrations = self.select_row::<bool>(indoc! {"
+            SELECT COUNT(*) > 0 FROM pragma_table_info('migrations')
+            WHERE name = 'down_migration'
+            "})?()?
+        .unwrap_or(false);
+        if !has_down_migrations {
+            self.exec("ALTER TABLE migrations ADD COLUMN down_migration TEXT")?()?;
+        }
+        Ok(())
+    }
+
+    fn completed_migrations(&self, domain: &str) -> Result<Vec<(usize, String, Option<String>)>> {
+        self.select_bound::<&str, (usize, String, Option<String>)>(indoc! {"
+            SELECT step, migration, down_migration FROM migrations
+            WHERE domain = ?
+            ORDER BY step
+            "})?(domain)
+    }
+
     /// Migrate the database, for the given domain.
     /// Note: Unlike everything else in SQLez, migrations are run eagerly, without first
     /// preparing the SQL statements. This makes it possible to do multi-statement schema
     /// updates in a single string without running into prepare errors.
     pub fn migrate(&self, domain: &'static str, migrations: &[&'static str]) -> Result<()> {
+        self.migrate_with_down_migrations(domain, migrations, &[])
+    }
+
+    /// Migrate the database for the given domain, storing the given down migrations alongside
+    /// the steps they revert. Each down migration is paired with the index of its step.
+    ///
+    /// When a stored step differs from the proposed one, as happens when switching between
+    /// builds whose migrations diverged, the stored steps from that point on are reverted
+    /// using their stored down migrations before the proposed ones are run.
+    pub fn migrate_with_down_migrations(
+        &self,
+        domain: &'static str,
+        migrations: &[&'static str],
+        down_migrations: &[(usize, &'static str)],
+    ) -> Result<()> {
         self.with_savepoint("migrating", || {
             // Setup the migrations table unconditionally
-            self.exec(indoc! {"
-                CREATE TABLE IF NOT EXISTS migrations (
-                    domain TEXT,
-                    step INTEGER,
-                    migration TEXT
-                )"})?()?;
-
-            let completed_migrations =
-                self.select_bound::<&str, (String, usize, String)>(indoc! {"
-                    SELECT domain, step, migration FROM migrations
-                    WHERE domain = ?
-                    ORDER BY step
-                    "})?(domain)?;
+            self.create_migrations_table()?;
 
-            let mut store_completed_migration = self
-                .exec_bound("INSERT INTO migrations (domain, step, migration) VALUES (?, ?, ?)")?;
+            let completed_migrations = self.completed_migrations(domain)?;
+            let down_migration = |step: usize| {
+                down_migrations
+                    .iter()
+                    .find(|(down_step, _)| *down_step == step)
+                    .map(|(_, down_migration)| format_migration(down_migration))
+            };
 
+            let mut next_step = 0;
             for (index, migration) in migrations.iter().enumerate() {
-                let migration =
-                    sqlformat::format(migration, &sqlformat::QueryParams::None, Default::default());
-                if let Some((_, _, completed_migration)) = completed_migrations.get(index) {
-                    // Reformat completed migrations with the current `sqlformat` version, so that past migrations stored
-                    // conform to the new formatting rules.
-                    let completed_migration = sqlformat::format(
-                        completed_migration,
-                        &sqlformat::QueryParams::None,
-                        Default::default(),
-                    );
-                    if completed_migration == migration {
-                        // Migration already run. Continue
-                        continue;
-                    } else {
-                        return Err(anyhow!(formatdoc! {"
-                            Migration changed for {} at step {}
-
-                            Stored migration:
-                            {}
-
-                            Proposed migration:
-                            {}", domain, index, completed_migration, migration}));
+                let migration = format_migration(migration);
+                let Some((_, completed_migration, completed_down_migration)) =
+                    completed_migrations.get(index)
+                else {
+                    break;
+                };
+
+                // Reformat completed migrations with the current `sqlformat` version, so that past migrations stored
+                // conform to the new formatting rules.
+                let completed_migration = format_migration(completed_migration);
+                if completed_migration == migration {
+                    // Migration already run. Remember how to revert it, if it was run before
+                    // a down migration was added for it.
+                    if completed_down_migration.is_none() {
+                        if let Some(down_migration) = down_migration(index) {
+                            self.exec_bound::<(Option<String>, &str, usize)>(
+                                "UPDATE migrations SET down_migration = ? WHERE domain = ? AND step = ?",
+                            )?((Some(down_migration), domain, index))?;
+                        }
+                    }
+                    next_step = index + 1;
+                    continue;
+                }
+
+                if self.revert_migrations(domain, index).is_err() {
+                    return Err(MigrationMismatch {
+                        domain,
+                        step: index,
+                        stored: completed_migration,
+                        proposed: migration,
                     }
+                    .into());
                 }
+                break;
+            }
 
+            let mut store_completed_migration = self.exec_bound(indoc! {"
+                INSERT INTO migrations (domain, step, migration, down_migration)
+                VALUES (?, ?, ?, ?)"})?;
+            for (index, migration) in migrations.iter().enumerate().skip(next_step) {
+                let migration = format_migration(migration);
                 self.eager_exec(&migration)?;
-                store_completed_migration((domain, index, migration))?;
+                store_completed_migration((domain, index, migration, down_migration(index)))?;
             }
 
             Ok(())
         })
     }
+
+    /// Returns how many migration steps have been run for the given domain.
+    pub fn migration_version(&self, domain: &str) -> Result<usize> {
+        let has_migrations_table = self.select_row::<bool>(indoc! {"
+            SELECT COUNT(*) > 0 FROM sqlite_master
+            WHERE type = 'table' AND name = 'migrations'
+            "})?()?
+        .unwrap_or(false);
+        if !has_migrations_table {
+            return Ok(0);
+        }
+        let version = self
+            .select_row_bound::<&str, usize>("SELECT COUNT(*) FROM migrations WHERE domain = ?")?(
+            domain,
+        )?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// Reverts the domain's completed migration steps until only `version` steps remain,
+    /// running their stored down migrations from the newest step to the oldest.
+    ///
+    /// Fails without changing anything if one of the steps has no down migration.
+    pub fn revert_migrations(&self, domain: &str, version: usize) -> Result<()> {
+        self.with_savepoint("reverting_migrations", || {
+            self.create_migrations_table()?;
+            let mut delete_completed_migration = self.exec_bound::<(&str, usize)>(
+                "DELETE FROM migrations WHERE domain = ? AND step = ?",
+            )?;
+            for (step, _, down_migration) in self.completed_migrations(domain)?.into_iter().rev() {
+                if step < version {
+                    break;
+                }
+                let down_migration = down_migration.ok_or_else(|| {
+                    anyhow!("Migration for {domain} at step {step} can't be reverted")
+                })?;
+                self.eager_exec(&down_migration)?;
+                delete_completed_migration((domain, step))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Copies each table created by the domain's completed migrations into a backup table,
+    /// then drops it and forgets the migrations, so that they run again from scratch.
+    /// Only the most recent backups of each table are kept. Returns the names of the backup
+    /// tables.
+    ///
+    /// Must not be called inside a transaction, as foreign keys have to be disabled while the
+    /// tables are dropped.
+    pub fn back_up_and_reset_domain(&self, domain: &str) -> Result<Vec<String>> {
+        // Dropping a table with foreign keys enabled deletes its rows first, which would cascade
+        // into the rows of other domains' tables that reference it. The pragma is a no-op inside
+        // a transaction, so it's changed outside of the savepoint.
+        let foreign_keys = self.select_row::<bool>("PRAGMA foreign_keys")?()?.unwrap_or(false);
+        if foreign_keys {
+            self.exec("PRAGMA foreign_keys = OFF")?()?;
+            if self.select_row::<bool>("PRAGMA foreign_keys")?()? == Some(true) {
+                bail!("can't reset domain {domain} inside a transaction with foreign keys enabled");
+            }
+        }
+        let result = self.reset_domain_tables(domain);
+        if foreign_keys {
+            self.exec("PRAGMA foreign_keys = ON")?()?;
+        }
+        result
+    }
+
+    fn reset_domain_tables(&self, domain: &str) -> Result<Vec<String>> {
+        self.with_savepoint("resetting_domain", || {
+            self.create_migrations_table()?;
+            let completed_migrations = self.completed_migrations(domain)?;
+
+            // Find the domain's tables by replaying its migrations on an empty database. Steps
+            // that depend on other domains' tables fail there, and are skipped.
+            let scratch = Connection::open_memory(None);
+            for (_, migration, _) in &completed_migrations {
+                scratch.eager_exec(migration).ok();
+            }
+            let domain_tables = scratch.select::<String>(indoc! {"
+                SELECT name FROM sqlite_master
+                WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+                "})?()?;
+            let mut existing_tables =
+                self.select::<String>("SELECT name FROM sqlite_master WHERE type = 'table'")?()?
+                    .into_iter()
+                    .collect::<HashSet<_>>();
+
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let mut backup_tables = Vec::new();
+            for table in domain_tables {
+                if !existing_tables.contains(&table) {
+                    continue;
+                }
+                // A domain can be reset more than once a second, e.g. by builds of different
+                // versions opening the database at the same time.
+                let mut backup_table = format!("{table}_backup_{timestamp}");
+                let mut attempt = 1;
+                while existing_tables.contains(&backup_table) {
+                    attempt += 1;
+                    backup_table = format!("{table}_backup_{timestamp}_{attempt}");
+                }
+                existing_tables.insert(backup_table.clone());
+                self.eager_exec(&formatdoc! {"
+                    CREATE TABLE \"{backup_table}\" AS SELECT * FROM \"{table}\";
+                    DROP TABLE \"{table}\";"})?;
+                backup_tables.push(backup_table);
+                self.prune_backup_tables(&table, &mut existing_tables)?;
+            }
+
+            self.exec_bound::<&str>("DELETE FROM migrations WHERE domain = ?")?(domain)?;
+            Ok(backup_tables)
+        })
+    }
+
+    /// Drops all but the `MAX_BACKUPS_PER_TABLE` most recent backups of the given table.
+    fn prune_backup_tables(
+        &self,
+        table: &str,
+        existing_tables: &mut HashSet<String>,
+    ) -> Result<()> {
+        let prefix = format!("{table}_backup_");
+        let mut backups = existing_tables
+            .iter()
+            .filter_map(|name| {
+                let mut suffix = name.strip_prefix(&prefix)?.splitn(2, '_');
+                let timestamp = suffix.next()?.parse::<u64>().ok()?;
+                let attempt = match suffix.next() {
+                    Some(attempt) => attempt.parse::<u32>().ok()?,
+                    None => 1,
+                };
+                Some(((timestamp, attempt), name.clone()))
+            })
+            .collect::<Vec<_>>();
+        if backups.len() <= MAX_BACKUPS_PER_TABLE {
+            return Ok(());
+        }
+        backups.sort_unstable();
+        for (_, backup_table) in &backups[..backups.len() - MAX_BACKUPS_PER_TABLE] {
+            self.exec(&format!("DROP TABLE \"{backup_table}\""))?()?;
+            existing_tables.remove(backup_table);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -95,6 +333,8 @@ mod test {
 
     use crate::connection::Connection;
 
+    use super::{MigrationMismatch, MAX_BACKUPS_PER_TABLE};
+
     #[test]
     fn test_migrations_are_added_to_table() {
         let connection = Connection::open_memory(Some("migrations_are_added_to_table"));
@@ -312,4 +552,212 @@ mod test {
 
         assert_eq!(res, "test text");
     }
+
+    #[test]
+    fn changed_migration_is_reverted_with_down_migration() {
+        let connection = Connection::open_memory(Some("changed_migration_is_reverted"));
+
+        // A build adds a column in its second step, which it knows how to revert.
+        connection
+            .migrate_with_down_migrations(
+                "test",
+                &[
+                    "CREATE TABLE test(a TEXT) STRICT;",
+                    "ALTER TABLE test ADD COLUMN b TEXT;",
+                ],
+                &[(1, "ALTER TABLE test DROP COLUMN b;")],
+            )
+            .unwrap();
+        connection
+            .exec("INSERT INTO test(a, b) VALUES ('a', 'b')")
+            .unwrap()()
+        .unwrap();
+        assert_eq!(connection.migration_version("test").unwrap(), 2);
+
+        // Another build, whose second step differs, reverts it before running its own.
+        connection
+            .migrate(
+                "test",
+                &[
+                    "CREATE TABLE test(a TEXT) STRICT;",
+                    "ALTER TABLE test ADD COLUMN c TEXT;",
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            connection
+                .select::<(String, Option<String>)>("SELECT a, c FROM test")
+                .unwrap()()
+            .unwrap(),
+            &[("a".to_string(), None)]
+        );
+
+        // Its own step can't be reverted, so switching back fails.
+        let result = connection.migrate_with_down_migrations(
+            "test",
+            &[
+                "CREATE TABLE test(a TEXT) STRICT;",
+                "ALTER TABLE test ADD COLUMN b TEXT;",
+            ],
+            &[(1, "ALTER TABLE test DROP COLUMN b;")],
+        );
+        let error = result.unwrap_err();
+        let mismatch = error.downcast_ref::<MigrationMismatch>().unwrap();
+        assert_eq!((mismatch.domain, mismatch.step), ("test", 1));
+    }
+
+    #[test]
+    fn test_revert_migrations() {
+        let connection = Connection::open_memory(Some("test_revert_migrations"));
+        let migrations = [
+            "CREATE TABLE test1(a TEXT) STRICT;",
+            "CREATE TABLE test2(b TEXT) STRICT;",
+            "CREATE TABLE test3(c TEXT) STRICT;",
+        ];
+
+        connection
+            .migrate_with_down_migrations(
+                "test",
+                &migrations,
+                &[(1, "DROP TABLE test2;"), (2, "DROP TABLE test3;")],
+            )
+            .unwrap();
+        connection.revert_migrations("test", 1).unwrap();
+        assert_eq!(connection.migration_version("test").unwrap(), 1);
+        assert!(connection.select::<String>("SELECT c FROM test3").is_err());
+
+        // The first step has no down migration.
+        assert!(connection.revert_migrations("test", 0).is_err());
+        assert_eq!(connection.migration_version("test").unwrap(), 1);
+
+        // Down migrations added later are stored for steps that have already run.
+        connection
+            .migrate_with_down_migrations(
+                "test",
+                &migrations,
+                &[
+                    (0, "DROP TABLE test1;"),
+                    (1, "DROP TABLE test2;"),
+                    (2, "DROP TABLE test3;"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(connection.migration_version("test").unwrap(), 3);
+        connection.revert_migrations("test", 0).unwrap();
+        assert_eq!(connection.migration_version("test").unwrap(), 0);
+        assert!(connection.select::<String>("SELECT a FROM test1").is_err());
+    }
+
+    #[test]
+    fn test_back_up_and_reset_domain() {
+        let connection = Connection::open_memory(Some("test_back_up_and_reset_domain"));
+
+        connection
+            .migrate("other", &["CREATE TABLE other(a TEXT) STRICT;"])
+            .unwrap();
+        connection
+            .migrate(
+                "test",
+                &[
+                    "CREATE TABLE test_tmp(a TEXT) STRICT;",
+                    "ALTER TABLE test_tmp RENAME TO test;",
+                    // Fails when replayed on its own, as it depends on another domain's table.
+                    "INSERT INTO test(a) SELECT a FROM other;",
+                ],
+            )
+            .unwrap();
+        connection.exec("INSERT INTO test(a) VALUES ('a')").unwrap()().unwrap();
+
+        let backup_tables = connection.back_up_and_reset_domain("test").unwrap();
+        assert_eq!(backup_tables.len(), 1);
+        assert!(backup_tables[0].starts_with("test_backup_"));
+        assert_eq!(
+            connection
+                .select::<String>(&format!("SELECT a FROM {}", backup_tables[0]))
+                .unwrap()()
+            .unwrap(),
+            &["a"]
+        );
+        assert!(connection.select::<String>("SELECT a FROM test").is_err());
+        assert_eq!(connection.migration_version("test").unwrap(), 0);
+        assert_eq!(connection.migration_version("other").unwrap(), 1);
+
+        // The domain's migrations can run again from scratch.
+        connection
+            .migrate("test", &["CREATE TABLE test(b TEXT) STRICT;"])
+            .unwrap();
+        assert_eq!(
+            connection.select::<String>("SELECT b FROM test").unwrap()().unwrap(),
+            Vec::<String>::new()
+        );
+
+        // Resetting again right away doesn't reuse the name of the first backup.
+        let second_backup_tables = connection.back_up_and_reset_domain("test").unwrap();
+        assert_eq!(second_backup_tables.len(), 1);
+        assert_ne!(second_backup_tables[0], backup_tables[0]);
+
+        // Only the most recent backups are kept.
+        for _ in 0..MAX_BACKUPS_PER_TABLE {
+            connection
+                .migrate("test", &["CREATE TABLE test(b TEXT) STRICT;"])
+                .unwrap();
+            connection.back_up_and_reset_domain("test").unwrap();
+        }
+        let remaining_backups = connection
+            .select::<String>(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'test_backup_%'",
+            )
+            .unwrap()()
+        .unwrap();
+        assert_eq!(remaining_backups.len(), MAX_BACKUPS_PER_TABLE);
+        assert!(!remaining_backups.contains(&backup_tables[0]));
+        assert!(!remaining_backups.contains(&second_backup_tables[0]));
+    }
+
+    #[test]
+    fn test_back_up_and_reset_domain_keeps_referencing_rows() {
+        let connection =
+            Connection::open_memory(Some("test_back_up_and_reset_domain_keeps_referencing_rows"));
+        connection.exec("PRAGMA foreign_keys = ON").unwrap()().unwrap();
+
+        connection
+            .migrate(
+                "test",
+                &["CREATE TABLE test(id INTEGER PRIMARY KEY) STRICT;"],
+            )
+            .unwrap();
+        connection
+            .migrate(
+                "other",
+                &[indoc! {"
+                    CREATE TABLE other(
+                        test_id INTEGER REFERENCES test(id) ON DELETE CASCADE
+                    ) STRICT;
+                "}],
+            )
+            .unwrap();
+        connection.exec("INSERT INTO test(id) VALUES (1)").unwrap()().unwrap();
+        connection
+            .exec("INSERT INTO other(test_id) VALUES (1)")
+            .unwrap()()
+        .unwrap();
+
+        connection.back_up_and_reset_domain("test").unwrap();
+
+        // Dropping the domain's table doesn't cascade into other domains.
+        assert_eq!(
+            connection
+                .select::<i64>("SELECT test_id FROM other")
+                .unwrap()()
+            .unwrap(),
+            &[1]
+        );
+        assert_eq!(
+            connection
+                .select_row::<bool>("PRAGMA foreign_keys")
+                .unwrap()()
+            .unwrap(),
+            Some(true)
+        );
+    }
 }
@@ -10,7 +10,10 @@ use std::{
 };
 use thread_local::ThreadLocal;
 
-use crate::{connection::Connection, domain::Migrator, util::UnboundedSyncSender};
+use crate::{
+    connection::Connection, domain::Migrator, migrations::MigrationMismatch,
+    util::UnboundedSyncSender,
+};
 
 const MIGRATION_RETRIES: usize = 10;
 
@@ -41,6 +44,7 @@ unsafe impl<M: Migrator> Sync for ThreadSafeConnection<M> {}
 pub struct ThreadSafeConnectionBuilder<M: Migrator + 'static = ()> {
     db_initialize_query: Option<&'static str>,
     write_queue_constructor: Option<WriteQueueConstructor>,
+    recover_mismatched_migrations: bool,
     connection: ThreadSafeConnection<M>,
 }
 
@@ -72,11 +76,19 @@ impl<M: Migrator> ThreadSafeConnectionBuilder<M> {
         self
     }
 
+    /// When a domain's stored migrations differ from its current ones and can't be reverted,
+    /// back up and recreate the domain's tables instead of failing to build the connection.
+    pub fn with_migration_recovery(mut self) -> Self {
+        self.recover_mismatched_migrations = true;
+        self
+    }
+
     pub async fn build(self) -> anyhow::Result<ThreadSafeConnection<M>> {
         self.connection
             .initialize_queues(self.write_queue_constructor);
 
         let db_initialize_query = self.db_initialize_query;
+        let recover_mismatched_migrations = self.recover_mismatched_migrations;
 
         self.connection
             .write(move |connection| {
@@ -98,8 +110,21 @@ impl<M: Migrator> ThreadSafeConnectionBuilder<M> {
                     migration_result = connection
                         .with_savepoint("thread_safe_multi_migration", || M::migrate(connection));
 
-                    if migration_result.is_ok() {
-                        break;
+                    match &migration_result {
+                        Ok(()) => break,
+                        Err(error) if recover_mismatched_migrations => {
+                            if let Some(mismatch) = error.downcast_ref::<MigrationMismatch>() {
+                                let backup_tables =
+                                    connection.back_up_and_reset_domain(mismatch.domain)?;
+                                log::warn!(
+                                    "Recreated tables of {} after a migration mismatch at step {}, backed up to {:?}",
+                                    mismatch.domain,
+                                    mismatch.step,
+                                    backup_tables
+                                );
+                            }
+                        }
+                        Err(_) => {}
                     }
                 }
 
@@ -129,6 +154,7 @@ impl<M: Migrator> ThreadSafeConnection<M> {
         ThreadSafeConnectionBuilder::<M> {
             db_initialize_query: None,
             write_queue_constructor: None,
+            recover_mismatched_migrations: false,
             connection: Self {
                 uri: Arc::from(uri),
                 persistent,
@@ -1,7 +1,9 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
+mod task_graph;
 mod task_template;
 mod vscode_format;
 
@@ -13,7 +15,12 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use problem_matcher::{
+    CustomProblemMatcher, Problem, ProblemMatcher, ProblemMatcherPreset, ProblemPattern,
+    ProblemScanner, ProblemSeverity,
+};
+pub use task_graph::{TaskGraph, TaskGraphNode};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +65,8 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Matchers to find problems in the task output with, to show them as diagnostics.
+    pub problem_matcher: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particular [`TaskContext`] and now is ready to spawn the actual task.
@@ -71,6 +80,8 @@ pub struct ResolvedTask {
     pub id: TaskId,
     /// A template the task got resolved from.
     original_task: TaskTemplate,
+    /// A context the task got resolved with, used to resolve the tasks it depends on.
+    task_context: TaskContext,
     /// Full, unshortened label of the task after all resolutions are made.
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
@@ -86,6 +97,11 @@ impl ResolvedTask {
         &self.original_task
     }
 
+    /// A context the task got resolved with.
+    pub fn task_context(&self) -> &TaskContext {
+        &self.task_context
+    }
+
     /// Variables that were substituted during the task template resolution.
     pub fn substituted_variables(&self) -> &HashSet<VariableName> {
         &self.substituted_variables
@@ -0,0 +1,509 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A way to find problems (errors, warnings, etc.) in the output of a task, to show them as diagnostics.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the matchers for common tools, built into Zed.
+    Preset(ProblemMatcherPreset),
+    /// A matcher defined with regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// Problem matchers for common tools, built into Zed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemMatcherPreset {
+    /// Errors and warnings of `rustc`, also printed by `cargo build`, `cargo check`, etc.
+    Rustc,
+    /// Errors and warnings of the TypeScript compiler.
+    Tsc,
+    /// Errors, warnings and notes of `gcc` and `clang`.
+    Gcc,
+    /// Problems reported by ESLint with the `compact` formatter.
+    Eslint,
+}
+
+/// A problem matcher defined with regular expressions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// A built-in matcher to take the patterns from, if no `pattern` is specified.
+    #[serde(default)]
+    pub base: Option<ProblemMatcherPreset>,
+    /// Patterns to match against consecutive lines of the output, that describe a single problem together.
+    #[serde(default)]
+    pub pattern: Vec<ProblemPattern>,
+    /// The severity of the problems that don't have it captured by the patterns.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// A directory to resolve relative file paths against, defaults to the task's working directory.
+    #[serde(default)]
+    pub file_location: Option<String>,
+}
+
+/// A regular expression to match a line of the task output against,
+/// with the indices of its capture groups that contain the parts of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match a line against.
+    pub regexp: String,
+    /// The capture group with the path of the file the problem is in.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// The capture group with the 1-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// The capture group with the 1-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// The capture group with the 1-based line the problem ends at.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// The capture group with the 1-based column the problem ends at.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// The capture group with the severity of the problem, e.g. `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// The capture group with the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// The capture group with the message of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+}
+
+/// How severe a problem is.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+impl ProblemSeverity {
+    pub(crate) fn parse(severity: &str) -> Option<Self> {
+        let severity = severity.trim().to_lowercase();
+        if severity.starts_with("err") || severity.starts_with("fatal") {
+            Some(Self::Error)
+        } else if severity.starts_with("warn") {
+            Some(Self::Warning)
+        } else if severity.starts_with("info") || severity.starts_with("note") {
+            Some(Self::Info)
+        } else if severity.starts_with("hint") {
+            Some(Self::Hint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// The path of the file with the problem, relative to the task's working directory if not absolute.
+    pub path: PathBuf,
+    /// The 1-based line of the problem.
+    pub line: u32,
+    /// The 1-based column of the problem, if known.
+    pub column: Option<u32>,
+    /// The 1-based line the problem ends at, if known.
+    pub end_line: Option<u32>,
+    /// The 1-based column the problem ends at, if known.
+    pub end_column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The code of the problem, if any.
+    pub code: Option<String>,
+    /// The description of the problem.
+    pub message: String,
+}
+
+impl ProblemMatcherPreset {
+    /// The patterns and the default severity of the built-in matcher.
+    pub fn matcher(self) -> CustomProblemMatcher {
+        let pattern = match self {
+            Self::Rustc => vec![
+                ProblemPattern {
+                    regexp: r"^(warning|error)(?:\[(\w+)\])?:\s+(.*)$".to_string(),
+                    severity: Some(1),
+                    code: Some(2),
+                    message: Some(3),
+                    ..ProblemPattern::default()
+                },
+                ProblemPattern {
+                    regexp: r"^\s*-->\s+(.+?):(\d+):(\d+)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    ..ProblemPattern::default()
+                },
+            ],
+            Self::Tsc => vec![ProblemPattern {
+                regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                code: Some(5),
+                message: Some(6),
+                ..ProblemPattern::default()
+            }],
+            Self::Gcc => vec![ProblemPattern {
+                regexp: r"^(.+?):(\d+):(\d+):\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                ..ProblemPattern::default()
+            }],
+            Self::Eslint => vec![ProblemPattern {
+                regexp: r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$"
+                    .to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                severity: Some(4),
+                message: Some(5),
+                code: Some(6),
+                ..ProblemPattern::default()
+            }],
+        };
+        CustomProblemMatcher {
+            base: Some(self),
+            pattern,
+            ..CustomProblemMatcher::default()
+        }
+    }
+}
+
+impl ProblemMatcher {
+    /// The patterns of the matcher, taken from its base preset if it has none of its own.
+    pub fn to_custom(&self) -> CustomProblemMatcher {
+        match self {
+            Self::Preset(preset) => preset.matcher(),
+            Self::Custom(matcher) => match matcher.base {
+                Some(base) if matcher.pattern.is_empty() => CustomProblemMatcher {
+                    pattern: base.matcher().pattern,
+                    ..matcher.clone()
+                },
+                _ => matcher.clone(),
+            },
+        }
+    }
+}
+
+/// Finds problems in the task output, fed into it line by line.
+pub struct ProblemScanner {
+    matchers: Vec<CompiledMatcher>,
+}
+
+struct CompiledMatcher {
+    patterns: Vec<(Regex, ProblemPattern)>,
+    severity: ProblemSeverity,
+    file_location: Option<PathBuf>,
+    /// The index of the next pattern to match and the problem parts captured by the previous ones.
+    pending: Option<(usize, PartialProblem)>,
+}
+
+#[derive(Default)]
+struct PartialProblem {
+    path: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl ProblemScanner {
+    /// Creates a scanner for the matchers given, fails if any of their regular expressions is invalid.
+    pub fn new(matchers: &[ProblemMatcher]) -> Result<Self> {
+        let matchers = matchers
+            .iter()
+            .map(|matcher| {
+                let matcher = matcher.to_custom();
+                let patterns = matcher
+                    .pattern
+                    .into_iter()
+                    .map(|pattern| {
+                        let regex = Regex::new(&pattern.regexp).with_context(|| {
+                            format!("parsing problem matcher regex {:?}", pattern.regexp)
+                        })?;
+                        anyhow::Ok((regex, pattern))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(CompiledMatcher {
+                    patterns,
+                    severity: matcher.severity,
+                    file_location: matcher.file_location.map(PathBuf::from),
+                    pending: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { matchers })
+    }
+
+    /// Matches the next line of the output, returning the problems that end on it.
+    pub fn scan_line(&mut self, line: &str) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for matcher in &mut self.matchers {
+            if matcher.patterns.is_empty() {
+                continue;
+            }
+            if let Some((next_pattern, mut problem)) = matcher.pending.take() {
+                let (regex, pattern) = &matcher.patterns[next_pattern];
+                if let Some(captures) = regex.captures(line) {
+                    problem.capture(pattern, &captures);
+                    if next_pattern + 1 == matcher.patterns.len() {
+                        problems.extend(
+                            problem.finish(matcher.severity, matcher.file_location.as_deref()),
+                        );
+                    } else {
+                        matcher.pending = Some((next_pattern + 1, problem));
+                    }
+                    continue;
+                }
+            }
+
+            let (regex, pattern) = &matcher.patterns[0];
+            if let Some(captures) = regex.captures(line) {
+                let mut problem = PartialProblem::default();
+                problem.capture(pattern, &captures);
+                if matcher.patterns.len() == 1 {
+                    problems
+                        .extend(problem.finish(matcher.severity, matcher.file_location.as_deref()));
+                } else {
+                    matcher.pending = Some((1, problem));
+                }
+            }
+        }
+        problems
+    }
+}
+
+impl PartialProblem {
+    fn capture(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let group = |index: Option<usize>| {
+            Some(captures.get(index?)?.as_str().trim()).filter(|group| !group.is_empty())
+        };
+        let number = |index: Option<usize>| group(index)?.parse::<u32>().ok();
+
+        if let Some(path) = group(pattern.file) {
+            self.path = Some(path.to_string());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = group(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = group(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = group(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    fn finish(self, severity: ProblemSeverity, file_location: Option<&Path>) -> Option<Problem> {
+        let path = PathBuf::from(self.path?);
+        Some(Problem {
+            path: match file_location {
+                Some(file_location) if path.is_relative() => file_location.join(path),
+                _ => path,
+            },
+            line: self.line?,
+            column: self.column,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            severity: self.severity.unwrap_or(severity),
+            code: self.code,
+            message: self.message?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(matcher: ProblemMatcher, output: &str) -> Vec<Problem> {
+        let mut scanner = ProblemScanner::new(&[matcher]).unwrap();
+        output
+            .lines()
+            .flat_map(|line| scanner.scan_line(line))
+            .collect()
+    }
+
+    #[test]
+    fn test_rustc_problems() {
+        let output = r#"   Compiling my-crate v0.1.0 (/projects/my-crate)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:13
+  |
+2 |     let y = x + 1;
+  |             ^ not found in this scope
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+error: aborting due to 1 previous error
+"#;
+        assert_eq!(
+            scan(ProblemMatcher::Preset(ProblemMatcherPreset::Rustc), output),
+            vec![
+                Problem {
+                    path: PathBuf::from("src/main.rs"),
+                    line: 2,
+                    column: Some(13),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                },
+                Problem {
+                    path: PathBuf::from("src/lib.rs"),
+                    line: 10,
+                    column: Some(9),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_presets() {
+        let problems = scan(
+            ProblemMatcher::Preset(ProblemMatcherPreset::Tsc),
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+             src/util.ts:12:1 - warning TS6133: 'unused' is declared but its value is never read.",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().to_string(),
+                    problem.line,
+                    problem.column,
+                    problem.severity,
+                    problem.code.clone()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "src/index.ts".to_string(),
+                    3,
+                    Some(7),
+                    ProblemSeverity::Error,
+                    Some("TS2322".to_string())
+                ),
+                (
+                    "src/util.ts".to_string(),
+                    12,
+                    Some(1),
+                    ProblemSeverity::Warning,
+                    Some("TS6133".to_string())
+                ),
+            ]
+        );
+
+        let problems = scan(
+            ProblemMatcher::Preset(ProblemMatcherPreset::Gcc),
+            "main.c:4:5: error: use of undeclared identifier 'x'\n\
+             main.c:1:1: note: previous definition is here",
+        );
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].message, "use of undeclared identifier 'x'");
+        assert_eq!(problems[1].severity, ProblemSeverity::Info);
+
+        let problems = scan(
+            ProblemMatcher::Preset(ProblemMatcherPreset::Eslint),
+            "/project/src/app.js: line 5, col 10, Warning - 'foo' is defined but never used. (no-unused-vars)",
+        );
+        assert_eq!(
+            problems,
+            vec![Problem {
+                path: PathBuf::from("/project/src/app.js"),
+                line: 5,
+                column: Some(10),
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                code: Some("no-unused-vars".to_string()),
+                message: "'foo' is defined but never used.".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "pattern": [
+                    { "regexp": "^FAIL (.+)$", "file": 1 },
+                    { "regexp": "^  at line (\\d+): (.+)$", "line": 1, "message": 2 }
+                ],
+                "severity": "warning",
+                "file_location": "/project"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            scan(
+                matcher,
+                "FAIL tests/a.test\n  at line 3: expected 1\nFAIL tests/b.test\nPASS\n  at line 4: ignored"
+            ),
+            vec![Problem {
+                path: PathBuf::from("/project/tests/a.test"),
+                line: 3,
+                column: None,
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "expected 1".to_string(),
+            }]
+        );
+
+        let matcher: ProblemMatcher =
+            serde_json_lenient::from_str(r#"{ "base": "gcc", "file_location": "/project" }"#)
+                .unwrap();
+        assert_eq!(
+            scan(matcher, "main.c:4:5: error: oops")[0].path,
+            PathBuf::from("/project/main.c")
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use crate::ResolvedTask;
+
+/// A scheduled task together with all tasks it depends on, directly or transitively.
+/// Every task comes after all tasks it depends on, so the scheduled task is the last one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskGraph {
+    nodes: Vec<TaskGraphNode>,
+}
+
+/// A single task of a [`TaskGraph`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskGraphNode {
+    /// The task to spawn, may have no command if it only groups other tasks.
+    pub task: ResolvedTask,
+    /// Indices of the graph nodes this task depends on, in the order they are listed in the task's template.
+    pub depends_on: Vec<usize>,
+}
+
+impl TaskGraph {
+    /// Creates a graph from the nodes given, where every node may only depend on the nodes before it.
+    /// Returns `None` if there are no nodes or any node depends on itself or on the nodes after it.
+    pub fn new(nodes: Vec<TaskGraphNode>) -> Option<Self> {
+        if nodes.is_empty() {
+            return None;
+        }
+        let ordered = nodes
+            .iter()
+            .enumerate()
+            .all(|(index, node)| node.depends_on.iter().all(|&dependency| dependency < index));
+        ordered.then_some(Self { nodes })
+    }
+
+    /// All tasks of the graph, each one placed after the tasks it depends on.
+    pub fn nodes(&self) -> &[TaskGraphNode] {
+        &self.nodes
+    }
+
+    /// The task that was scheduled, depending on all other tasks of the graph.
+    pub fn root(&self) -> &TaskGraphNode {
+        self.nodes.last().expect("task graph cannot be empty")
+    }
+}
@@ -9,8 +9,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    CustomProblemMatcher, ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal,
+    TaskContext, TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -25,6 +25,8 @@ pub struct TaskTemplate {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    /// May be omitted for tasks that only run the tasks they depend on.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
@@ -70,6 +72,21 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks that have to finish successfully before this task is spawned.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks this task depends on:
+    /// * `parallel` — spawn all of them at once (default)
+    /// * `sequence` — spawn them one after another, in the order they are listed, stopping at the first failure
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Matchers to find problems (errors, warnings, etc.) in the task output with, to show them as diagnostics:
+    /// * names of the built-in matchers: `rustc`, `tsc`, `gcc`, `eslint`
+    /// * custom matchers, with `pattern` regular expressions and the indices of their capture groups
+    ///
+    /// The diagnostics are cleared when the task is rerun.
+    #[serde(default)]
+    pub problem_matcher: Vec<ProblemMatcher>,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -98,6 +115,17 @@ pub enum HideStrategy {
     OnSuccess,
 }
 
+/// How to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Spawn all dependencies at once.
+    #[default]
+    Parallel,
+    /// Spawn the dependencies one after another, in the order they are listed.
+    Sequence,
+}
+
 /// A group of Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct TaskTemplates(pub Vec<TaskTemplate>);
@@ -121,8 +149,12 @@ impl TaskTemplate {
     ///
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
+    ///
+    /// Templates without a command are resolved only if they depend on other tasks.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -198,6 +230,29 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
+        let problem_matcher = self
+            .problem_matcher
+            .iter()
+            .map(|matcher| match matcher {
+                ProblemMatcher::Custom(custom) => {
+                    let file_location = match custom.file_location.as_deref() {
+                        Some(file_location) => Some(substitute_all_template_variables_in_str(
+                            file_location,
+                            &task_variables,
+                            &variable_names,
+                            &mut substituted_variables,
+                        )?),
+                        None => None,
+                    };
+                    Some(ProblemMatcher::Custom(CustomProblemMatcher {
+                        file_location,
+                        ..custom.clone()
+                    }))
+                }
+                preset => Some(preset.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
         let task_hash = to_hex_hash(self)
             .context("hashing task template")
             .log_err()?;
@@ -230,6 +285,7 @@ impl TaskTemplate {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
+            task_context: cx.clone(),
             resolved_label: full_label.clone(),
             resolved: Some(SpawnInTerminal {
                 id,
@@ -255,6 +311,7 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                problem_matcher,
             }),
         })
     }
@@ -366,7 +423,7 @@ fn substitute_all_template_variables_in_map(
 mod tests {
     use std::{borrow::Cow, path::Path};
 
-    use crate::{TaskVariables, VariableName};
+    use crate::{ProblemMatcherPreset, TaskVariables, VariableName};
 
     use super::*;
 
@@ -405,6 +462,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_templates_without_command_with_dependencies() {
+        let compound_task = TaskTemplate {
+            label: "build and test".to_string(),
+            depends_on: vec!["build".to_string(), "test".to_string()],
+            depends_order: DependsOrder::Sequence,
+            ..TaskTemplate::default()
+        };
+        let resolved_task = compound_task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .expect("should resolve a task without a command if it has dependencies");
+        assert_eq!(
+            resolved_task.original_task().depends_on,
+            compound_task.depends_on
+        );
+        let spawn_in_terminal = resolved_task.resolved.unwrap();
+        assert_eq!(spawn_in_terminal.label, "build and test");
+        assert!(spawn_in_terminal.command.is_empty());
+
+        let parsed_task: TaskTemplate = serde_json_lenient::from_str(
+            r#"{ "label": "ci", "depends_on": ["lint", "test"], "depends_order": "sequence" }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed_task.command, "");
+        assert_eq!(parsed_task.depends_on, vec!["lint", "test"]);
+        assert_eq!(parsed_task.depends_order, DependsOrder::Sequence);
+    }
+
+    #[test]
+    fn test_problem_matcher_file_location_resolution() {
+        let template = TaskTemplate {
+            label: "build".to_string(),
+            command: "make".to_string(),
+            problem_matcher: vec![
+                ProblemMatcher::Preset(ProblemMatcherPreset::Gcc),
+                ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some(ProblemMatcherPreset::Gcc),
+                    file_location: Some(format!(
+                        "{}/src",
+                        VariableName::WorktreeRoot.template_value()
+                    )),
+                    ..CustomProblemMatcher::default()
+                }),
+            ],
+            ..TaskTemplate::default()
+        };
+        let context = TaskContext {
+            task_variables: TaskVariables::from_iter([(
+                VariableName::WorktreeRoot,
+                "/project".to_string(),
+            )]),
+            ..TaskContext::default()
+        };
+        let resolved = template
+            .resolve_task(TEST_ID_BASE, &context)
+            .unwrap()
+            .resolved
+            .unwrap();
+        assert_eq!(
+            resolved.problem_matcher,
+            vec![
+                ProblemMatcher::Preset(ProblemMatcherPreset::Gcc),
+                ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some(ProblemMatcherPreset::Gcc),
+                    file_location: Some("/project/src".to_string()),
+                    ..CustomProblemMatcher::default()
+                }),
+            ]
+        );
+
+        assert_eq!(
+            template.resolve_task(TEST_ID_BASE, &TaskContext::default()),
+            None,
+            "should not resolve a task with unknown variables in the problem matcher"
+        );
+    }
+
     #[test]
     fn test_template_cwd_resolution() {
         let task_without_cwd = TaskTemplate {
@@ -1,9 +1,12 @@
-use anyhow::bail;
+use anyhow::{anyhow, bail, Context as _};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    CustomProblemMatcher, DependsOrder, ProblemMatcher, ProblemMatcherPreset, ProblemPattern,
+    ProblemSeverity, TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +44,131 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcher {
+    base: Option<String>,
+    pattern: Option<OneOrMany<VsCodeProblemPattern>>,
+    severity: Option<String>,
+    file_location: Option<OneOrMany<String>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(item) => vec![item],
+            Self::Many(items) => items,
+        }
+    }
+}
+
+fn problem_matcher_preset(name: &str) -> Option<ProblemMatcherPreset> {
+    match name.trim_start_matches('$') {
+        "rustc" | "rustc-watch" => Some(ProblemMatcherPreset::Rustc),
+        "tsc" | "tsc-watch" => Some(ProblemMatcherPreset::Tsc),
+        "gcc" => Some(ProblemMatcherPreset::Gcc),
+        "eslint-compact" => Some(ProblemMatcherPreset::Eslint),
+        _ => None,
+    }
+}
+
+/// Converts the `problemMatcher` value of a task, skipping the matchers that Zed does not support.
+fn problem_matchers_from_vscode(
+    value: &serde_json_lenient::Value,
+    replacer: &EnvVariableReplacer,
+) -> Vec<ProblemMatcher> {
+    match value {
+        serde_json_lenient::Value::Array(matchers) => matchers
+            .iter()
+            .flat_map(|matcher| problem_matchers_from_vscode(matcher, replacer))
+            .collect(),
+        serde_json_lenient::Value::String(name) => problem_matcher_preset(name)
+            .map(ProblemMatcher::Preset)
+            .with_context(|| format!("Unsupported problem matcher {name:?}"))
+            .log_err()
+            .into_iter()
+            .collect(),
+        value => serde_json_lenient::from_value::<VsCodeProblemMatcher>(value.clone())
+            .context("Unsupported problem matcher")
+            .and_then(|matcher| problem_matcher_from_vscode(matcher, replacer))
+            .log_err()
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn problem_matcher_from_vscode(
+    matcher: VsCodeProblemMatcher,
+    replacer: &EnvVariableReplacer,
+) -> anyhow::Result<ProblemMatcher> {
+    let base = match matcher.base.as_deref() {
+        Some(base) => Some(
+            problem_matcher_preset(base)
+                .with_context(|| format!("Unsupported base problem matcher {base:?}"))?,
+        ),
+        None => None,
+    };
+    let pattern = matcher
+        .pattern
+        .map(OneOrMany::into_vec)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|pattern| ProblemPattern {
+            regexp: pattern.regexp,
+            file: pattern.file,
+            line: pattern.line,
+            column: pattern.column,
+            end_line: pattern.end_line,
+            end_column: pattern.end_column,
+            severity: pattern.severity,
+            code: pattern.code,
+            message: pattern.message,
+        })
+        .collect::<Vec<_>>();
+    if base.is_none() && pattern.is_empty() {
+        bail!("Problem matcher has neither `base` nor `pattern`");
+    }
+    // `fileLocation` is either a single kind, or a kind with the directory to resolve relative paths against.
+    let file_location = match matcher.file_location.map(OneOrMany::into_vec).as_deref() {
+        Some([kind]) if kind == "relative" => Some(VariableName::WorktreeRoot.template_value()),
+        Some([kind, directory]) if kind == "relative" || kind == "autoDetect" => {
+            Some(replacer.replace(directory))
+        }
+        _ => None,
+    };
+    Ok(ProblemMatcher::Custom(CustomProblemMatcher {
+        base,
+        pattern,
+        severity: matcher
+            .severity
+            .as_deref()
+            .and_then(ProblemSeverity::parse)
+            .unwrap_or_default(),
+        file_location,
+    }))
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -85,29 +213,47 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let depends_on = match self.other_attributes.get("dependsOn") {
+            None => Vec::new(),
+            Some(serde_json_lenient::Value::String(label)) => vec![label.clone()],
+            Some(serde_json_lenient::Value::Array(labels)) => labels
+                .iter()
+                .map(|label| match label {
+                    serde_json_lenient::Value::String(label) => Ok(label.clone()),
+                    _ => Err(anyhow!("Unsupported `dependsOn` entry: {label}")),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Some(depends_on) => bail!("Unsupported `dependsOn` value: {depends_on}"),
         };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        let depends_order = match self.other_attributes.get("dependsOrder") {
+            None => DependsOrder::default(),
+            Some(depends_order) => serde_json_lenient::from_value(depends_order.clone())
+                .with_context(|| format!("Unsupported `dependsOrder` value: {depends_order}"))?,
+        };
+        // `type` might not be set in tasks that only use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
+        // as that way we can provide more specific description of why deserialization failed.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
         let args = args.into_iter().map(|arg| replacer.replace(&arg)).collect();
+        let problem_matcher = self
+            .other_attributes
+            .get("problemMatcher")
+            .map(|problem_matcher| problem_matchers_from_vscode(problem_matcher, replacer))
+            .unwrap_or_default();
         let mut ret = TaskTemplate {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matcher,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -155,7 +301,8 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        CustomProblemMatcher, DependsOrder, ProblemMatcher, ProblemMatcherPreset, TaskTemplate,
+        TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -257,6 +404,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +416,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Tsc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Tsc)],
                 ..Default::default()
             },
         ];
@@ -360,32 +511,111 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matcher: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some(ProblemMatcherPreset::Tsc),
+                    file_location: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matcher: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some(ProblemMatcherPreset::Tsc),
+                    file_location: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Rustc)],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matcher: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some(ProblemMatcherPreset::Tsc),
+                    file_location: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                    ..Default::default()
+                })],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Rustc)],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                problem_matcher: vec![ProblemMatcher::Preset(ProblemMatcherPreset::Rustc)],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_task_dependencies() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "test",
+                        "type": "shell",
+                        "command": "cargo test",
+                        "dependsOn": "build"
+                    },
+                    {
+                        "label": "ci",
+                        "dependsOn": ["lint", "test"],
+                        "dependsOrder": "sequence"
+                    },
+                    {
+                        "label": "provider task",
+                        "dependsOn": [{ "type": "npm", "script": "build" }]
+                    },
+                    {
+                        "label": "no command"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                TaskTemplate {
+                    label: "test".to_string(),
+                    command: "cargo test".to_string(),
+                    depends_on: vec!["build".to_string()],
+                    ..Default::default()
+                },
+                TaskTemplate {
+                    label: "ci".to_string(),
+                    depends_on: vec!["lint".to_string(), "test".to_string()],
+                    depends_order: DependsOrder::Sequence,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
@@ -8,12 +8,12 @@ pub mod terminal_settings;
 use alacritty_terminal::{
     event::{Event as AlacTermEvent, EventListener, Notify, WindowSize},
     event_loop::{EventLoop, Msg, Notifier},
-    grid::{Dimensions, Scroll as AlacScroll},
+    grid::{Dimensions, Grid, Scroll as AlacScroll},
     index::{Boundary, Column, Direction as AlacDirection, Line, Point as AlacPoint},
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -42,7 +42,7 @@ use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use smol::channel::{Receiver, Sender};
-use task::{HideStrategy, Shell, TaskId};
+use task::{HideStrategy, Problem, ProblemScanner, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use util::{paths::home_dir, truncate_and_trailoff};
@@ -106,6 +106,8 @@ pub enum Event {
     Bell,
     Wakeup,
     BlinkChanged(bool),
+    /// New problems were found in the output of the terminal's task by its problem matchers.
+    TaskProblems(Vec<Problem>),
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
@@ -313,6 +315,9 @@ impl Display for TerminalError {
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
 const DEFAULT_SCROLL_HISTORY_LINES: usize = 10_000;
 const MAX_SCROLL_HISTORY_LINES: usize = 100_000;
+const TASK_OUTPUT_SCAN_DELAY: Duration = Duration::from_millis(100);
+/// How many of the last scanned task output rows are kept to find them again in a full scrollback.
+const TASK_OUTPUT_SCAN_ANCHOR_ROWS: usize = 3;
 const URL_REGEX: &str = r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#;
 // Optional suffix matches MSBuild diagnostic suffixes for path parsing in PathLikeWithPosition
 // https://learn.microsoft.com/en-us/visualstudio/msbuild/msbuild-diagnostic-format-for-tasks
@@ -455,6 +460,8 @@ impl TerminalBuilder {
 
         let terminal = Terminal {
             task,
+            task_output_scan: TaskOutputScan::default(),
+            pending_task_scan: None,
             pty_tx: Notifier(pty_tx),
             completion_tx,
             term,
@@ -629,6 +636,9 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    task_output_scan: TaskOutputScan,
+    /// The delayed scan of the task output, scheduled on terminal wakeups.
+    pending_task_scan: Option<Task<()>>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
 }
@@ -643,6 +653,113 @@ pub struct TaskState {
     pub hide: HideStrategy,
     pub show_summary: bool,
     pub show_command: bool,
+    pub problem_scanner: Option<ProblemScanner>,
+}
+
+/// The part of the task output already fed into the problem matchers.
+///
+/// Positions are tracked relative to the bottom of the output: every line scrolled into the history
+/// since the last scan moves the scanned lines up by one. While the history has room, that is how much
+/// the history grew; once it is full, the oldest lines are dropped as new ones come in and its size stays
+/// the same, so the last scanned rows are looked up again by their contents instead.
+#[derive(Default)]
+struct TaskOutputScan {
+    /// The first line that was not scanned, as of the last scan.
+    next_line: Option<Line>,
+    /// The history size as of the last scan.
+    history_size: usize,
+    /// The contents of the last scanned rows, the bottom one last.
+    last_rows: VecDeque<String>,
+}
+
+impl TaskOutputScan {
+    /// Feeds the complete lines up to `last_line` that were not scanned yet into the scanner.
+    fn scan(
+        &mut self,
+        grid: &Grid<Cell>,
+        last_line: Line,
+        max_history_size: usize,
+        scanner: &mut ProblemScanner,
+    ) -> Vec<Problem> {
+        let mut line = self.catch_up(grid, max_history_size);
+        let mut output_line = String::new();
+        let mut rows = Vec::new();
+        let mut problems = Vec::new();
+        while line <= last_line {
+            let row_text = row_text(grid, line);
+            output_line.push_str(&row_text);
+            rows.push(row_text);
+            let wrapped = grid[line][grid.last_column()]
+                .flags
+                .contains(Flags::WRAPLINE);
+            line = Line(line.0 + 1);
+            if wrapped {
+                continue;
+            }
+            problems.extend(scanner.scan_line(output_line.trim_end()));
+            output_line.clear();
+
+            self.next_line = Some(line);
+            for row in rows.drain(..) {
+                if self.last_rows.len() == TASK_OUTPUT_SCAN_ANCHOR_ROWS {
+                    self.last_rows.pop_front();
+                }
+                self.last_rows.push_back(row);
+            }
+        }
+        problems
+    }
+
+    /// Moves the scan position along with the lines scrolled in since the last scan, returning the first line to scan.
+    fn catch_up(&mut self, grid: &Grid<Cell>, max_history_size: usize) -> Line {
+        let history_size = grid.history_size();
+        let Some(next_line) = self.next_line else {
+            self.history_size = history_size;
+            return grid.topmost_line();
+        };
+        let scrolled_in = history_size.saturating_sub(self.history_size) as i32;
+        let mut next_line = Line(next_line.0 - scrolled_in);
+        if (history_size == max_history_size || history_size < self.history_size)
+            && !self.last_rows.is_empty()
+        {
+            // Some lines were dropped from the history, so at least `scrolled_in` lines came in:
+            // look for the last scanned rows, starting from their closest possible position.
+            let anchor_rows = self.last_rows.len() as i32;
+            loop {
+                if next_line.0 - anchor_rows < grid.topmost_line().0 {
+                    // The scanned rows are gone, all the output that is left was not scanned yet.
+                    self.last_rows.clear();
+                    next_line = grid.topmost_line();
+                    break;
+                }
+                let found = self.last_rows.iter().enumerate().all(|(ix, row)| {
+                    *row == row_text(grid, Line(next_line.0 - anchor_rows + ix as i32))
+                });
+                if found {
+                    break;
+                }
+                next_line = Line(next_line.0 - 1);
+            }
+        }
+
+        let next_line = next_line.max(grid.topmost_line());
+        self.next_line = Some(next_line);
+        self.history_size = history_size;
+        next_line
+    }
+}
+
+fn row_text(grid: &Grid<Cell>, line: Line) -> String {
+    let mut text = String::new();
+    for cell in &grid[line] {
+        if !cell
+            .flags
+            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+        {
+            text.push(cell.c);
+        }
+    }
+    text
 }
 
 /// A status of the current terminal tab's task.
@@ -712,6 +829,7 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.schedule_task_output_scan(cx);
 
                 if self.pty_info.has_changed() {
                     cx.emit(Event::TitleChanged);
@@ -1744,6 +1862,60 @@ impl Terminal {
         Task::ready(())
     }
 
+    /// Scans the task output for problems after a short delay, so that bursts of output
+    /// do not lock the terminal for a rescan on every wakeup.
+    fn schedule_task_output_scan(&mut self, cx: &mut Context<Self>) {
+        if self.pending_task_scan.is_some()
+            || !self.task.as_ref().is_some_and(|task| {
+                task.status == TaskStatus::Running && task.problem_scanner.is_some()
+            })
+        {
+            return;
+        }
+        self.pending_task_scan = Some(cx.spawn(|terminal, mut cx| async move {
+            cx.background_executor().timer(TASK_OUTPUT_SCAN_DELAY).await;
+            terminal
+                .update(&mut cx, |terminal, cx| {
+                    terminal.pending_task_scan = None;
+                    terminal.scan_task_output(false, cx);
+                })
+                .ok();
+        }));
+    }
+
+    /// Feeds the task output lines, that were not scanned yet, into the task's problem matchers.
+    /// Only the lines above the cursor are scanned while the task is running, as the cursor line may still change.
+    fn scan_task_output(&mut self, include_cursor_line: bool, cx: &mut Context<Self>) {
+        let Some(scanner) = self
+            .task
+            .as_mut()
+            .filter(|task| task.status == TaskStatus::Running)
+            .and_then(|task| task.problem_scanner.as_mut())
+        else {
+            return;
+        };
+
+        let term = self.term.clone();
+        let term = term.lock_unfair();
+        let grid = term.grid();
+        let last_line = if include_cursor_line {
+            grid.bottommost_line()
+        } else {
+            Line(grid.cursor.point.line.0 - 1)
+        };
+        let problems = self.task_output_scan.scan(
+            grid,
+            last_line,
+            self.term_config.scrolling_history,
+            scanner,
+        );
+        drop(term);
+
+        if !problems.is_empty() {
+            cx.emit(Event::TaskProblems(problems));
+        }
+    }
+
     fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<'_, Terminal>) {
         self.completion_tx.try_send(()).ok();
         let task = match &mut self.task {
@@ -1758,6 +1930,11 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        self.pending_task_scan = None;
+        self.scan_task_output(true, cx);
+        let Some(task) = self.task.as_mut() else {
+            return;
+        };
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -2003,15 +2180,22 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 
 #[cfg(test)]
 mod tests {
+    use std::ops::Range;
+
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        vte::ansi::Processor,
+        Term,
     };
-    use gpui::{bounds, point, size, Pixels, Point};
+    use gpui::{bounds, point, px, size, Pixels, Point};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
+    use task::{ProblemMatcher, ProblemMatcherPreset, ProblemScanner};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalBounds, TerminalContent,
+        content_index_for_mouse, rgb_for_index, IndexedCell, TaskOutputScan, TerminalBounds,
+        TerminalContent,
     };
 
     #[test]
@@ -2199,4 +2383,48 @@ mod tests {
             vec!["Main.cs:20:5:Error", "desc"],
         );
     }
+
+    #[test]
+    fn test_task_output_scan_with_full_scrollback() {
+        let bounds = TerminalBounds::new(
+            px(10.),
+            px(5.),
+            bounds(point(px(0.), px(0.)), size(px(200.), px(30.))),
+        );
+        let config = Config {
+            scrolling_history: 5,
+            ..Config::default()
+        };
+        let mut term = Term::new(config, &bounds, VoidListener);
+        let mut parser: Processor = Processor::new();
+        let mut scanner =
+            ProblemScanner::new(&[ProblemMatcher::Preset(ProblemMatcherPreset::Gcc)]).unwrap();
+        let mut scan = TaskOutputScan::default();
+        let mut print_and_scan = |term: &mut Term<VoidListener>, lines: Range<u32>| {
+            for line in lines {
+                let output = format!("src/main.c:{line}:1: error: problem {line}\r\n");
+                parser.advance(term, output.as_bytes());
+            }
+            let grid = term.grid();
+            let last_line = Line(grid.cursor.point.line.0 - 1);
+            scan.scan(grid, last_line, 5, &mut scanner)
+                .into_iter()
+                .map(|problem| problem.line)
+                .collect::<Vec<_>>()
+        };
+
+        // Keep printing after the history is full, so that the oldest lines are dropped in between the scans.
+        let mut scanned_lines = Vec::new();
+        for start in (0..40).step_by(4) {
+            scanned_lines.extend(print_and_scan(&mut term, start..start + 4));
+        }
+        assert_eq!(term.grid().history_size(), 5);
+        assert_eq!(scanned_lines, (0..40).collect::<Vec<_>>());
+
+        // Output that scrolled out of the history before it was scanned is lost,
+        // but what is left is scanned once.
+        let mut scanned_lines = print_and_scan(&mut term, 40..60);
+        scanned_lines.extend(print_and_scan(&mut term, 60..60));
+        assert_eq!(scanned_lines, (53..60).collect::<Vec<_>>());
+    }
 }
@@ -1,4 +1,4 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
+use std::{cmp, mem, ops::ControlFlow, path::PathBuf, rc::Rc, sync::Arc, time::Duration};
 
 use crate::{
     default_working_directory,
@@ -10,7 +10,11 @@ use crate::{
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    channel::oneshot,
+    future::{join_all, LocalBoxFuture, Shared},
+    FutureExt as _,
+};
 use gpui::{
     actions, Action, AnyView, App, AsyncApp, AsyncWindowContext, Context, Corner, Entity,
     EventEmitter, ExternalPaths, FocusHandle, Focusable, IntoElement, ParentElement, Pixels,
@@ -20,10 +24,13 @@ use itertools::Itertools;
 use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use task::{RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal, TaskId};
+use task::{
+    DependsOrder, ResolvedTask, RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal,
+    TaskGraph, TaskId,
+};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable,
@@ -73,12 +80,20 @@ pub struct TerminalPanel {
     pub(crate) height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
-    deferred_tasks: HashMap<TaskId, Task<()>>,
+    deferred_tasks: HashMap<TaskId, DeferredTask>,
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
 }
 
+/// A task waiting for the previous runs of the same task to finish before it is spawned.
+struct DeferredTask {
+    _spawn: Task<()>,
+    /// Everyone waiting for the task to be spawned, including callers whose spawn of the same
+    /// task was superseded by a later one.
+    spawned_txs: Vec<oneshot::Sender<Option<Entity<Terminal>>>>,
+}
+
 impl TerminalPanel {
     pub fn new(workspace: &Workspace, window: &mut Window, cx: &mut Context<Self>) -> Self {
         let project = workspace.project();
@@ -282,14 +297,23 @@ impl TerminalPanel {
         if let Some(workspace) = workspace.upgrade() {
             terminal_panel
                 .update_in(&mut cx, |_, window, cx| {
-                    cx.subscribe_in(&workspace, window, |terminal_panel, _, e, window, cx| {
-                        if let workspace::Event::SpawnTask {
-                            action: spawn_in_terminal,
-                        } = e
-                        {
-                            terminal_panel.spawn_task(spawn_in_terminal, window, cx);
-                        };
-                    })
+                    cx.subscribe_in(
+                        &workspace,
+                        window,
+                        |terminal_panel, _, e, window, cx| match e {
+                            workspace::Event::SpawnTask {
+                                action: spawn_in_terminal,
+                            } => {
+                                terminal_panel
+                                    .spawn_task(spawn_in_terminal, window, cx)
+                                    .detach();
+                            }
+                            workspace::Event::SpawnTaskGraph { graph } => {
+                                terminal_panel.spawn_task_graph(graph, window, cx);
+                            }
+                            _ => {}
+                        },
+                    )
                     .detach();
                 })
                 .ok();
@@ -487,12 +511,19 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
-    fn spawn_task(&mut self, task: &SpawnInTerminal, window: &mut Window, cx: &mut Context<Self>) {
+    /// Spawns the task in a terminal, reusing the terminals of the same task if possible.
+    /// Resolves to the terminal the task was spawned in, once it is spawned.
+    fn spawn_task(
+        &mut self,
+        task: &SpawnInTerminal,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Terminal>>> {
         let Ok(is_local) = self
             .workspace
             .update(cx, |workspace, cx| workspace.project().read(cx).is_local())
         else {
-            return;
+            return Task::ready(None);
         };
 
         let builder = ShellBuilder::new(is_local, &task.shell);
@@ -507,58 +538,92 @@ impl TerminalPanel {
         };
 
         if task.allow_concurrent_runs && task.use_new_terminal {
-            self.spawn_in_new_terminal(task, window, cx)
-                .detach_and_log_err(cx);
-            return;
+            let new_terminal = self.spawn_in_new_terminal(task, window, cx);
+            return cx.spawn(|_, _| async move { new_terminal.await.log_err() });
         }
 
         let mut terminals_for_task = self.terminals_for_task(&task.full_label, cx);
         let Some(existing) = terminals_for_task.pop() else {
-            self.spawn_in_new_terminal(task, window, cx)
-                .detach_and_log_err(cx);
-            return;
+            let new_terminal = self.spawn_in_new_terminal(task, window, cx);
+            return cx.spawn(|_, _| async move { new_terminal.await.log_err() });
         };
 
         let (existing_item_index, task_pane, existing_terminal) = existing;
         if task.allow_concurrent_runs {
-            self.replace_terminal(
+            return self.replace_terminal(
                 task,
                 task_pane,
                 existing_item_index,
                 existing_terminal,
                 window,
                 cx,
-            )
-            .detach();
-            return;
+            );
         }
 
-        self.deferred_tasks.insert(
-            task.id.clone(),
-            cx.spawn_in(window, |terminal_panel, mut cx| async move {
-                wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                let task = terminal_panel.update_in(&mut cx, |terminal_panel, window, cx| {
-                    if task.use_new_terminal {
-                        terminal_panel
-                            .spawn_in_new_terminal(task, window, cx)
-                            .detach_and_log_err(cx);
-                        None
-                    } else {
-                        Some(terminal_panel.replace_terminal(
-                            task,
-                            task_pane,
-                            existing_item_index,
-                            existing_terminal,
-                            window,
-                            cx,
-                        ))
-                    }
-                });
-                if let Ok(Some(task)) = task {
-                    task.await;
+        let (spawned_tx, spawned_rx) = oneshot::channel();
+        let mut spawned_txs = self
+            .deferred_tasks
+            .remove(&task.id)
+            .map(|deferred_task| deferred_task.spawned_txs)
+            .unwrap_or_default();
+        spawned_txs.push(spawned_tx);
+        let task_id = task.id.clone();
+        let spawn = cx.spawn_in(window, |terminal_panel, mut cx| async move {
+            wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
+            let task = terminal_panel.update_in(&mut cx, |terminal_panel, window, cx| {
+                if task.use_new_terminal {
+                    let new_terminal = terminal_panel.spawn_in_new_terminal(task, window, cx);
+                    cx.spawn(|_, _| async move { new_terminal.await.log_err() })
+                } else {
+                    terminal_panel.replace_terminal(
+                        task,
+                        task_pane,
+                        existing_item_index,
+                        existing_terminal,
+                        window,
+                        cx,
+                    )
                 }
-            }),
+            });
+            let Ok(task) = task else {
+                return;
+            };
+            let terminal = task.await;
+            terminal_panel
+                .update(&mut cx, |terminal_panel, _| {
+                    let spawned_txs = terminal_panel
+                        .deferred_tasks
+                        .get_mut(&task_id)
+                        .map(|deferred_task| mem::take(&mut deferred_task.spawned_txs))
+                        .unwrap_or_default();
+                    for spawned_tx in spawned_txs {
+                        spawned_tx.send(terminal.clone()).ok();
+                    }
+                })
+                .ok();
+        });
+        self.deferred_tasks.insert(
+            task_id,
+            DeferredTask {
+                _spawn: spawn,
+                spawned_txs,
+            },
         );
+        cx.spawn(|_, _| async move { spawned_rx.await.ok().flatten() })
+    }
+
+    /// Spawns the tasks of the graph, each one after all of the tasks it depends on have finished successfully.
+    /// If any task fails, none of the tasks depending on it are spawned.
+    fn spawn_task_graph(&mut self, graph: &TaskGraph, window: &mut Window, cx: &mut Context<Self>) {
+        let graph = graph.clone();
+        cx.spawn_in(window, |terminal_panel, cx| async move {
+            let run_task: RunTask = Rc::new(move |spawn_in_terminal: SpawnInTerminal| {
+                run_task_in_terminal(terminal_panel.clone(), spawn_in_terminal, cx.clone())
+                    .boxed_local()
+            });
+            run_task_graph(&graph, run_task).await;
+        })
+        .detach();
     }
 
     pub fn spawn_in_new_terminal(
@@ -813,7 +878,7 @@ impl TerminalPanel {
         terminal_to_replace: Entity<TerminalView>,
         window: &mut Window,
         cx: &mut Context<Self>,
-    ) -> Task<Option<()>> {
+    ) -> Task<Option<Entity<Terminal>>> {
         let reveal = spawn_task.reveal;
         let reveal_target = spawn_task.reveal_target;
         let window_handle = window.window_handle();
@@ -836,7 +901,7 @@ impl TerminalPanel {
                 .log_err()?;
             terminal_to_replace
                 .update_in(&mut cx, |terminal_to_replace, window, cx| {
-                    terminal_to_replace.set_terminal(new_terminal, window, cx);
+                    terminal_to_replace.set_terminal(new_terminal.clone(), window, cx);
                 })
                 .ok()?;
 
@@ -912,7 +977,7 @@ impl TerminalPanel {
                 RevealStrategy::Never => {}
             }
 
-            Some(())
+            Some(new_terminal)
         })
     }
 
@@ -1164,6 +1229,93 @@ async fn wait_for_terminals_tasks(
     let _: Vec<()> = join_all(pending_tasks).await;
 }
 
+/// Runs a single task to completion, resolving to whether it succeeded.
+type RunTask = Rc<dyn Fn(SpawnInTerminal) -> LocalBoxFuture<'static, bool>>;
+
+/// Runs the tasks of the graph with `run_task`, resolving to whether the scheduled task succeeded.
+async fn run_task_graph(graph: &TaskGraph, run_task: RunTask) -> bool {
+    let mut task_runs: Vec<Shared<LocalBoxFuture<'static, bool>>> =
+        Vec::with_capacity(graph.nodes().len());
+    for node in graph.nodes() {
+        let dependencies = node
+            .depends_on
+            .iter()
+            .map(|&dependency| task_runs[dependency].clone())
+            .collect();
+        let task_run = run_task_graph_node(node.task.clone(), dependencies, run_task.clone());
+        task_runs.push(task_run.boxed_local().shared());
+    }
+    match task_runs.pop() {
+        Some(root_run) => root_run.await,
+        None => false,
+    }
+}
+
+async fn run_task_graph_node(
+    task: ResolvedTask,
+    dependencies: Vec<Shared<LocalBoxFuture<'static, bool>>>,
+    run_task: RunTask,
+) -> bool {
+    let dependencies_succeeded = match task.original_task().depends_order {
+        DependsOrder::Parallel => join_all(dependencies)
+            .await
+            .into_iter()
+            .all(|succeeded| succeeded),
+        DependsOrder::Sequence => {
+            let mut succeeded = true;
+            for dependency in dependencies {
+                if !dependency.await {
+                    succeeded = false;
+                    break;
+                }
+            }
+            succeeded
+        }
+    };
+    if !dependencies_succeeded {
+        log::warn!(
+            "Not spawning task {:?}, as the tasks it depends on have failed",
+            task.resolved_label
+        );
+        return false;
+    }
+
+    let Some(spawn_in_terminal) = task.resolved else {
+        return false;
+    };
+    // Tasks without a command only group the tasks they depend on.
+    if spawn_in_terminal.command.trim().is_empty() {
+        return true;
+    }
+    run_task(spawn_in_terminal).await
+}
+
+async fn run_task_in_terminal(
+    terminal_panel: WeakEntity<TerminalPanel>,
+    spawn_in_terminal: SpawnInTerminal,
+    mut cx: AsyncWindowContext,
+) -> bool {
+    let Ok(spawned_terminal) = terminal_panel.update_in(&mut cx, |terminal_panel, window, cx| {
+        terminal_panel.spawn_task(&spawn_in_terminal, window, cx)
+    }) else {
+        return false;
+    };
+    let Some(terminal) = spawned_terminal.await else {
+        return false;
+    };
+    let Ok(completed_task) =
+        terminal.update(&mut cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+    else {
+        return false;
+    };
+    completed_task.await;
+    terminal
+        .read_with(&cx, |terminal, _| {
+            terminal.task().map(|task| task.status) == Some(TaskStatus::Completed { success: true })
+        })
+        .unwrap_or(false)
+}
+
 fn add_paths_to_terminal(
     pane: &mut Pane,
     paths: &[PathBuf],
@@ -1498,3 +1650,109 @@ impl Render for InlineAssistTabBarButton {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use std::cell::RefCell;
+    use task::{TaskContext, TaskGraphNode, TaskTemplate};
+
+    fn node(command: &str, depends_order: DependsOrder, depends_on: &[usize]) -> TaskGraphNode {
+        let template = TaskTemplate {
+            label: format!("task {command}"),
+            command: command.to_string(),
+            depends_on: depends_on
+                .iter()
+                .map(|dependency| format!("task {dependency}"))
+                .collect(),
+            depends_order,
+            ..TaskTemplate::default()
+        };
+        TaskGraphNode {
+            task: template
+                .resolve_task("test", &TaskContext::default())
+                .unwrap(),
+            depends_on: depends_on.to_vec(),
+        }
+    }
+
+    /// Runs the graph with tasks that take as many seconds as their command says,
+    /// failing if the command is `fail`, and returns the events logged by the tasks.
+    async fn run_graph(nodes: Vec<TaskGraphNode>, cx: &mut TestAppContext) -> (bool, Vec<String>) {
+        let graph = TaskGraph::new(nodes).unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let executor = cx.executor();
+        let run_task: RunTask = Rc::new({
+            let log = log.clone();
+            move |spawn_in_terminal: SpawnInTerminal| {
+                let log = log.clone();
+                let executor = executor.clone();
+                async move {
+                    let command = spawn_in_terminal.command;
+                    log.borrow_mut().push(format!("start {command}"));
+                    let seconds = command.parse().unwrap_or(1);
+                    executor.timer(Duration::from_secs(seconds)).await;
+                    log.borrow_mut().push(format!("end {command}"));
+                    command != "fail"
+                }
+                .boxed_local()
+            }
+        });
+
+        let run = cx
+            .foreground_executor()
+            .spawn(async move { run_task_graph(&graph, run_task).await });
+        cx.executor().advance_clock(Duration::from_secs(60));
+        let succeeded = run.await;
+        let log = log.borrow().clone();
+        (succeeded, log)
+    }
+
+    #[gpui::test]
+    async fn test_task_graph_order(cx: &mut TestAppContext) {
+        let (succeeded, log) = run_graph(
+            vec![
+                node("2", DependsOrder::Parallel, &[]),
+                node("1", DependsOrder::Parallel, &[]),
+                node("", DependsOrder::Parallel, &[0, 1]),
+                node("3", DependsOrder::Parallel, &[]),
+                node("4", DependsOrder::Sequence, &[2, 3]),
+            ],
+            cx,
+        )
+        .await;
+        assert!(succeeded);
+        assert_eq!(
+            log,
+            ["start 2", "start 1", "end 1", "end 2", "start 3", "end 3", "start 4", "end 4",]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_task_graph_stops_after_failure(cx: &mut TestAppContext) {
+        let (succeeded, log) = run_graph(
+            vec![
+                node("fail", DependsOrder::Parallel, &[]),
+                node("2", DependsOrder::Parallel, &[]),
+                node("3", DependsOrder::Sequence, &[0, 1]),
+            ],
+            cx,
+        )
+        .await;
+        assert!(!succeeded);
+        assert_eq!(log, ["start fail", "end fail"]);
+
+        let (succeeded, log) = run_graph(
+            vec![
+                node("fail", DependsOrder::Parallel, &[]),
+                node("2", DependsOrder::Parallel, &[]),
+                node("3", DependsOrder::Parallel, &[0, 1]),
+            ],
+            cx,
+        )
+        .await;
+        assert!(!succeeded);
+        assert_eq!(log, ["start fail", "start 2", "end fail", "end 2"]);
+    }
+}
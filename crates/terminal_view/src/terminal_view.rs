@@ -964,6 +964,7 @@ fn subscribe_for_terminal_events(
                 window.invalidate_character_coordinates();
                 cx.emit(SearchEvent::ActiveMatchChanged)
             }
+            Event::TaskProblems(_) => {}
         },
     );
     vec![terminal_subscription, terminal_events_subscription]
@@ -1570,6 +1571,7 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
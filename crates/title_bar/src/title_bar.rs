@@ -522,7 +522,19 @@ impl TitleBar {
             .as_ref()
             .and_then(|entry| entry.branch())
             .map(|branch| branch.name.clone())
-            .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH))?;
+            .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH));
+        // HEAD is usually detached while a rebase is in progress, so show the operation instead.
+        let operation = entry
+            .as_ref()
+            .and_then(|entry| entry.operation_in_progress());
+        let branch_name = match (branch_name, operation) {
+            (Some(branch_name), Some(operation)) => {
+                format!("{branch_name} ({})", operation.description())
+            }
+            (Some(branch_name), None) => branch_name,
+            (None, Some(operation)) => operation.description(),
+            (None, None) => return None,
+        };
         Some(
             Button::new("project_branch_trigger", branch_name)
                 .color(Color::Muted)
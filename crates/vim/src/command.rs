@@ -7,7 +7,10 @@ use editor::{
     scroll::Autoscroll,
     Bias, Editor, ToPoint,
 };
-use gpui::{actions, impl_internal_actions, Action, App, AppContext as _, Context, Global, Window};
+use gpui::{
+    actions, impl_internal_actions, Action, App, AppContext as _, Context, Global, Keystroke,
+    Modifiers, Window,
+};
 use itertools::Itertools;
 use language::Point;
 use multi_buffer::MultiBufferRow;
@@ -166,6 +169,7 @@ impl_internal_actions!(
         WithRange,
         WithCount,
         OnMatchingLines,
+        NormalCommand,
         ShellExec,
         VimSet,
     ]
@@ -350,6 +354,10 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         action.run(vim, window, cx)
     });
 
+    Vim::action(editor, cx, |vim, action: &NormalCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
     })
@@ -993,6 +1001,9 @@ pub fn command_interceptor(mut input: &str, cx: &App) -> Vec<CommandInterceptRes
         } else {
             None
         }
+    } else if query.starts_with("norm") {
+        let keys = input[range_prefix.len()..].trim_start();
+        NormalCommand::parse(keys, range.clone()).map(|action| action.boxed_clone())
     } else if query.contains('!') {
         ShellExec::parse(query, range.clone())
     } else {
@@ -1240,6 +1251,157 @@ impl OnMatchingLines {
     }
 }
 
+/// `:[range]norm[al][!] {keys}`, replays `keys` as if typed in normal mode on every line of the range.
+/// Without a range it runs on the line of every cursor, which is how it composes with `:g`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NormalCommand {
+    range: Option<CommandRange>,
+    keys: String,
+}
+
+impl NormalCommand {
+    fn parse(query: &str, range: Option<CommandRange>) -> Option<Self> {
+        let mut command = "normal".chars().peekable();
+        let mut query = query.chars().peekable();
+        while command
+            .peek()
+            .is_some_and(|char| Some(char) == query.peek())
+        {
+            command.next();
+            query.next();
+        }
+        // "norm" is the shortest abbreviation that is not ambiguous.
+        if command.count() > "normal".len() - "norm".len() {
+            return None;
+        }
+        // keys are always resolved with the current keymap, so the bang has no effect.
+        if query.peek() == Some(&'!') {
+            query.next();
+        }
+        if query.peek().is_some_and(|char| !char.is_whitespace()) {
+            return None;
+        }
+        query.next();
+
+        let keys: String = query.collect();
+        if keys.is_empty() {
+            return None;
+        }
+        Some(Self { range, keys })
+    }
+
+    fn keystrokes(&self) -> Vec<Keystroke> {
+        self.keys
+            .chars()
+            .map(|char| {
+                let (modifiers, key) = match char {
+                    ' ' => (Modifiers::default(), "space".to_string()),
+                    '\t' => (Modifiers::default(), "tab".to_string()),
+                    char if char.is_ascii_uppercase() => {
+                        (Modifiers::shift(), char.to_ascii_lowercase().to_string())
+                    }
+                    char => (Modifiers::default(), char.to_string()),
+                };
+                Keystroke {
+                    modifiers,
+                    key,
+                    key_char: Some(char.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let result = vim.update_editor(window, cx, |vim, editor, window, cx| {
+            let rows = if let Some(range) = &self.range {
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                (range.start.0..=range.end.0).collect::<Vec<_>>()
+            } else {
+                editor
+                    .selections
+                    .all::<Point>(cx)
+                    .iter()
+                    .map(|selection| selection.head().row)
+                    .dedup()
+                    .collect()
+            };
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            // Anchor to the first character of each line, so that lines deleted by
+            // the keys replayed on the lines before them can be skipped.
+            anyhow::Ok(
+                rows.into_iter()
+                    .map(|row| snapshot.anchor_after(Point::new(row, 0)))
+                    .collect::<Vec<_>>(),
+            )
+        });
+
+        let line_starts = match result {
+            None => return,
+            Some(e @ Err(_)) => {
+                let Some(workspace) = vim.workspace(window) else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    e.notify_err(workspace, cx);
+                });
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+
+        vim.switch_mode(Mode::Normal, false, window, cx);
+        vim.update_editor(window, cx, |_, editor, window, cx| {
+            editor.start_transaction_at(Instant::now(), window, cx);
+        });
+
+        // An incomplete command is aborted, as if escape was typed after the keys.
+        let mut keystrokes = self.keystrokes();
+        keystrokes.push(Keystroke {
+            modifiers: Modifiers::default(),
+            key: "escape".to_string(),
+            key_char: None,
+        });
+        cx.spawn_in(window, |vim, mut cx| async move {
+            for line_start in line_starts {
+                let is_valid = vim.update_in(&mut cx, |vim, window, cx| {
+                    vim.update_editor(window, cx, |_, editor, window, cx| {
+                        let snapshot = editor.buffer().read(cx).snapshot(cx);
+                        if !line_start.is_valid(&snapshot) {
+                            return false;
+                        }
+                        let start = Point::new(line_start.to_point(&snapshot).row, 0);
+                        editor.change_selections(None, window, cx, |s| {
+                            s.select_ranges([start..start]);
+                        });
+                        true
+                    })
+                })?;
+                if is_valid != Some(true) {
+                    continue;
+                }
+
+                for keystroke in &keystrokes {
+                    cx.update(|window, cx| {
+                        let focused = window.focused(cx);
+                        window.dispatch_keystroke(keystroke.clone(), cx);
+                        if window.focused(cx) != focused {
+                            // Let vim observe the focus change before the next keystroke.
+                            window.draw(cx);
+                        }
+                    })?;
+                }
+            }
+
+            vim.update_in(&mut cx, |vim, window, cx| {
+                vim.update_editor(window, cx, |_, editor, _, cx| {
+                    editor.end_transaction_at(Instant::now(), cx);
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct ShellExec {
     command: String,
@@ -1434,6 +1596,7 @@ impl ShellExec {
                         shell,
                         show_summary: false,
                         show_command: false,
+                        problem_matcher: Vec::new(),
                     }),
                 });
             });
@@ -1895,4 +2058,56 @@ mod test {
             a
             ˇa"});
     }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % n o r m space shift-a ; enter");
+        cx.assert_state(
+            indoc! {"
+            a;
+            b;
+            cˇ;"},
+            Mode::Normal,
+        );
+
+        // all lines are changed in a single transaction
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "a\nb\nc");
+
+        cx.simulate_keystrokes(": 2 , 3 n o r m a l space shift-i # enter");
+        assert_eq!(cx.buffer_text(), "a\n#b\n#c");
+
+        // `:normal!` behaves like `:normal`
+        cx.simulate_keystrokes(": 1 n o r m ! space shift-a ! enter");
+        assert_eq!(cx.buffer_text(), "a!\n#b\n#c");
+    }
+
+    #[gpui::test]
+    async fn test_command_normal_on_matching_lines(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            a
+            b
+            a
+            b"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": g / a / n o r m space d d enter");
+        assert_eq!(cx.buffer_text(), "b\nb");
+
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "a\na\nb\na\nb");
+    }
 }
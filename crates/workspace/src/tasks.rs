@@ -47,21 +47,43 @@ pub fn schedule_resolved_task(
     cx: &mut Context<Workspace>,
 ) {
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
+        resolved_task.resolved = Some(spawn_in_terminal.clone());
         if !omit_history {
-            resolved_task.resolved = Some(spawn_in_terminal.clone());
             workspace.project().update(cx, |project, cx| {
                 if let Some(task_inventory) =
                     project.task_store().read(cx).task_inventory().cloned()
                 {
                     task_inventory.update(cx, |inventory, _| {
-                        inventory.task_scheduled(task_source_kind, resolved_task);
+                        inventory.task_scheduled(task_source_kind.clone(), resolved_task.clone());
                     })
                 }
             });
         }
 
-        cx.emit(crate::Event::SpawnTask {
-            action: Box::new(spawn_in_terminal),
-        });
+        if resolved_task.original_task().depends_on.is_empty() {
+            cx.emit(crate::Event::SpawnTask {
+                action: Box::new(spawn_in_terminal),
+            });
+            return;
+        }
+
+        let task_graph = workspace
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .resolve_task_graph(&task_source_kind, resolved_task, cx);
+        match task_graph {
+            Ok(task_graph) => cx.emit(crate::Event::SpawnTaskGraph {
+                graph: Box::new(task_graph),
+            }),
+            Err(e) => {
+                log::error!(
+                    "Failed to resolve dependencies of task {:?}: {e:#}",
+                    spawn_in_terminal.full_label
+                );
+                workspace.show_error(&e, cx);
+            }
+        }
     }
 }
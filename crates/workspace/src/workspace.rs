@@ -89,7 +89,7 @@ use std::{
     sync::{atomic::AtomicUsize, Arc, LazyLock, Weak},
     time::Duration,
 };
-use task::SpawnInTerminal;
+use task::{SpawnInTerminal, TaskGraph};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -760,6 +760,9 @@ pub enum Event {
     SpawnTask {
         action: Box<SpawnInTerminal>,
     },
+    SpawnTaskGraph {
+        graph: Box<TaskGraph>,
+    },
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
@@ -20,7 +20,7 @@ use futures::{
 };
 use fuzzy::CharBag;
 use git::{
-    repository::{Branch, GitRepository, RepoPath, UpstreamTrackingStatus},
+    repository::{Branch, GitRepository, OperationInProgress, RepoPath, UpstreamTrackingStatus},
     status::{
         FileStatus, GitSummary, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode,
     },
@@ -205,6 +205,7 @@ pub struct RepositoryEntry {
     pub work_directory: WorkDirectory,
     pub(crate) current_branch: Option<Branch>,
     pub current_merge_conflicts: TreeSet<RepoPath>,
+    pub(crate) operation_in_progress: Option<OperationInProgress>,
 }
 
 impl RepositoryEntry {
@@ -224,6 +225,11 @@ impl RepositoryEntry {
         self.current_branch.as_ref()
     }
 
+    /// The rebase or cherry-pick that is waiting to be continued or aborted, if any.
+    pub fn operation_in_progress(&self) -> Option<OperationInProgress> {
+        self.operation_in_progress
+    }
+
     pub fn work_directory_id(&self) -> ProjectEntryId {
         self.work_directory_id
     }
@@ -265,6 +271,7 @@ impl RepositoryEntry {
                 .iter()
                 .map(|repo_path| repo_path.to_proto())
                 .collect(),
+            operation_in_progress: self.operation_in_progress.map(operation_to_proto),
         }
     }
 
@@ -324,6 +331,7 @@ impl RepositoryEntry {
                 .iter()
                 .map(|path| path.as_ref().to_proto())
                 .collect(),
+            operation_in_progress: self.operation_in_progress.map(operation_to_proto),
         }
     }
 }
@@ -387,6 +395,31 @@ pub fn proto_to_branch(proto: &proto::Branch) -> git::repository::Branch {
     }
 }
 
+fn operation_to_proto(operation: OperationInProgress) -> proto::OperationInProgress {
+    match operation {
+        OperationInProgress::Rebase { step, total } => proto::OperationInProgress {
+            kind: proto::operation_in_progress::Kind::Rebase as i32,
+            step: step as u64,
+            total: total as u64,
+        },
+        OperationInProgress::CherryPick => proto::OperationInProgress {
+            kind: proto::operation_in_progress::Kind::CherryPick as i32,
+            step: 0,
+            total: 0,
+        },
+    }
+}
+
+fn proto_to_operation(proto: &proto::OperationInProgress) -> OperationInProgress {
+    match proto.kind() {
+        proto::operation_in_progress::Kind::Rebase => OperationInProgress::Rebase {
+            step: proto.step as usize,
+            total: proto.total as usize,
+        },
+        proto::operation_in_progress::Kind::CherryPick => OperationInProgress::CherryPick,
+    }
+}
+
 /// This path corresponds to the 'content path' of a repository in relation
 /// to Zed's project root.
 /// In the majority of the cases, this is the folder that contains the .git folder.
@@ -2716,7 +2749,11 @@ impl Snapshot {
                             repo.current_branch =
                                 repository.branch_summary.as_ref().map(proto_to_branch);
                             repo.statuses_by_path.edit(edits, &());
-                            repo.current_merge_conflicts = conflicted_paths
+                            repo.current_merge_conflicts = conflicted_paths;
+                            repo.operation_in_progress = repository
+                                .operation_in_progress
+                                .as_ref()
+                                .map(proto_to_operation);
                         });
                 } else {
                     let statuses = SumTree::from_iter(
@@ -2739,6 +2776,10 @@ impl Snapshot {
                             current_branch: repository.branch_summary.as_ref().map(proto_to_branch),
                             statuses_by_path: statuses,
                             current_merge_conflicts: conflicted_paths,
+                            operation_in_progress: repository
+                                .operation_in_progress
+                                .as_ref()
+                                .map(proto_to_operation),
                         },
                         &(),
                     );
@@ -3541,6 +3582,7 @@ impl BackgroundScannerState {
                 current_branch: None,
                 statuses_by_path: Default::default(),
                 current_merge_conflicts: Default::default(),
+                operation_in_progress: None,
             },
             &(),
         );
@@ -5505,6 +5547,7 @@ impl BackgroundScanner {
                 if merge_head_shas != local_repository.current_merge_head_shas {
                     mem::take(&mut repository.current_merge_conflicts);
                 }
+                repository.operation_in_progress = local_repository.repo().operation_in_progress();
 
                 let mut new_entries_by_path = SumTree::new(&());
                 for (repo_path, status) in statuses.entries.iter() {
@@ -525,6 +525,7 @@ fn main() {
         notifications::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         collab_ui::init(&app_state, cx);
         git_ui::init(cx);
+        local_history::init(cx);
         feedback::init(cx);
         markdown_preview::init(cx);
         welcome::init(cx);
@@ -45,8 +45,8 @@ use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
     initial_project_settings_content, initial_tasks_content, update_settings_file,
-    InvalidSettingsError, KeymapFile, KeymapFileLoadResult, Settings, SettingsStore,
-    DEFAULT_KEYMAP_PATH, VIM_KEYMAP_PATH,
+    InvalidSettingsError, KeymapFile, KeymapFileLoadResult, KeymapImportSource, Settings,
+    SettingsStore, DEFAULT_KEYMAP_PATH, VIM_KEYMAP_PATH,
 };
 use std::any::TypeId;
 use std::path::PathBuf;
@@ -78,6 +78,8 @@ actions!(
         DebugElements,
         Hide,
         HideOthers,
+        ImportJetBrainsKeymap,
+        ImportVsCodeKeymap,
         Minimize,
         OpenDefaultSettings,
         OpenProjectSettings,
@@ -713,6 +715,12 @@ fn register_actions(
                 );
             },
         )
+        .register_action(|workspace, _: &ImportVsCodeKeymap, window, cx| {
+            import_keymap(workspace, KeymapImportSource::VsCode, window, cx);
+        })
+        .register_action(|workspace, _: &ImportJetBrainsKeymap, window, cx| {
+            import_keymap(workspace, KeymapImportSource::JetBrains, window, cx);
+        })
         .register_action(
             move |_: &mut Workspace,
                   _: &OpenSettings,
@@ -1687,6 +1695,60 @@ fn open_bundled_file(
     .detach_and_log_err(cx);
 }
 
+/// Prompts for another editor's keymap file and opens its translation in a new buffer, so that it
+/// can be reviewed before being copied into the user's keymap.
+fn import_keymap(
+    workspace: &mut Workspace,
+    source: KeymapImportSource,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let languages = workspace.app_state().languages.clone();
+    let paths = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Local(fs.clone()),
+        window,
+        cx,
+    );
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let Some(path) = paths.await.ok().flatten().and_then(|mut paths| paths.pop()) else {
+            return Ok(());
+        };
+        let content = fs.load(&path).await?;
+        let imported = KeymapFile::import(source, &content)
+            .with_context(|| format!("failed to read {} keymap", source.name()))?;
+        let text = imported.to_json_text(source)?;
+        let language = languages.language_for_name("JSONC").await.log_err();
+        let title = format!("Imported {} Keymap", source.name());
+        workspace
+            .update_in(&mut cx, move |workspace, window, cx| {
+                workspace.with_local_workspace(window, cx, move |workspace, window, cx| {
+                    let project = workspace.project().clone();
+                    let buffer = project.update(cx, |project, cx| {
+                        project.create_local_buffer(&text, language, cx)
+                    });
+                    let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                    workspace.add_item_to_active_pane(
+                        Box::new(cx.new(|cx| {
+                            Editor::for_multibuffer(buffer, Some(project), true, window, cx)
+                        })),
+                        None,
+                        true,
+                        window,
+                        cx,
+                    );
+                })
+            })?
+            .await
+    })
+    .detach_and_prompt_err("Failed to import keymap", window, cx, |_, _, _| None);
+}
+
 fn open_settings_file(
     abs_path: &'static Path,
     default_content: impl FnOnce() -> Rope + Send + 'static,
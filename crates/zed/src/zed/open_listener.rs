@@ -10,13 +10,15 @@ use editor::Editor;
 use fs::Fs;
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::channel::{mpsc, oneshot};
-use futures::future::join_all;
+use futures::future::{join_all, FusedFuture};
 use futures::{FutureExt, SinkExt, StreamExt};
-use gpui::{App, AsyncApp, Global, WindowHandle};
+use git_ui::file_diff_view::FileDiffView;
+use gpui::{App, AsyncApp, BackgroundExecutor, Global, WindowHandle};
 use language::Point;
 use recent_projects::{open_ssh_project, SshSettings};
 use remote::SshConnectionOptions;
 use settings::Settings;
+use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
@@ -25,7 +27,7 @@ use util::paths::PathWithPosition;
 use util::ResultExt;
 use welcome::{show_welcome_view, FIRST_OPEN};
 use workspace::item::ItemHandle;
-use workspace::{AppState, OpenOptions, SerializedWorkspaceLocation, Workspace};
+use workspace::{AppState, OpenOptions, SaveIntent, SerializedWorkspaceLocation, Workspace};
 
 #[derive(Default, Debug)]
 pub struct OpenRequest {
@@ -294,6 +296,25 @@ pub async fn handle_cli_connection(
                 let status = if open_workspace_result.is_err() { 1 } else { 0 };
                 responses.send(CliResponse::Exit { status }).log_err();
             }
+            CliRequest::Diff { left, right, wait } => {
+                let errored = open_diff(left, right, wait, &responses, &app_state, &mut cx).await;
+                let status = if errored { 1 } else { 0 };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
+            CliRequest::Merge {
+                local,
+                remote,
+                base,
+                merged,
+                wait,
+            } => {
+                let errored = open_merge(
+                    local, remote, base, merged, wait, &responses, &app_state, &mut cx,
+                )
+                .await;
+                let status = if errored { 1 } else { 0 };
+                responses.send(CliResponse::Exit { status }).log_err();
+            }
         }
     }
 }
@@ -478,21 +499,7 @@ async fn open_local_workspace(
                 }
                 .fuse();
 
-                futures::pin_mut!(wait);
-
-                loop {
-                    // Repeatedly check if CLI is still open to avoid wasting resources
-                    // waiting for files or workspaces to close.
-                    let mut timer = background.timer(Duration::from_secs(1)).fuse();
-                    futures::select_biased! {
-                        _ = wait => break,
-                        _ = timer => {
-                            if responses.send(CliResponse::Ping).is_err() {
-                                break;
-                            }
-                        }
-                    }
-                }
+                wait_while_cli_is_connected(wait, responses, &background).await;
             }
         }
         Err(error) => {
@@ -507,6 +514,222 @@ async fn open_local_workspace(
     errored
 }
 
+async fn open_diff(
+    left: String,
+    right: String,
+    wait: bool,
+    responses: &IpcSender<CliResponse>,
+    app_state: &Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> bool {
+    let result = async {
+        // Reuse any existing window rather than opening a workspace for the compared files.
+        let (workspace, _) = cx
+            .update(|cx| {
+                workspace::open_paths(
+                    &[],
+                    app_state.clone(),
+                    workspace::OpenOptions {
+                        open_new_workspace: Some(false),
+                        ..Default::default()
+                    },
+                    cx,
+                )
+            })?
+            .await?;
+        let diff_view = workspace
+            .update(cx, |workspace, window, cx| {
+                FileDiffView::open(
+                    PathBuf::from(&left),
+                    PathBuf::from(&right),
+                    cx.weak_entity(),
+                    window,
+                    cx,
+                )
+            })?
+            .await?;
+        anyhow::Ok(diff_view)
+    }
+    .await;
+
+    match result {
+        Ok(diff_view) => {
+            if wait {
+                let (released_tx, released_rx) = oneshot::channel();
+                cx.update(|cx| {
+                    diff_view
+                        .on_release(
+                            cx,
+                            Box::new(move |_| {
+                                let _ = released_tx.send(());
+                            }),
+                        )
+                        .detach();
+                })
+                .log_err();
+                drop(diff_view);
+
+                let background = cx.background_executor().clone();
+                let wait = released_rx.map(|_| ()).fuse();
+                wait_while_cli_is_connected(wait, responses, &background).await;
+            }
+            false
+        }
+        Err(error) => {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("error comparing {left:?} with {right:?}: {error}"),
+                })
+                .log_err();
+            true
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn open_merge(
+    local: String,
+    remote: String,
+    base: String,
+    merged: String,
+    wait: bool,
+    responses: &IpcSender<CliResponse>,
+    app_state: &Arc<AppState>,
+    cx: &mut AsyncApp,
+) -> bool {
+    let result = async {
+        let (workspace, items) = cx
+            .update(|cx| {
+                workspace::open_paths(
+                    &[PathBuf::from(&merged)],
+                    app_state.clone(),
+                    workspace::OpenOptions::default(),
+                    cx,
+                )
+            })?
+            .await?;
+        let merged_item = items
+            .into_iter()
+            .next()
+            .flatten()
+            .with_context(|| format!("opening {merged:?}"))??;
+
+        // Show what each side changed since the merge base next to the file being resolved.
+        let mut diff_views = Vec::new();
+        for new_path in [&local, &remote] {
+            let diff_view = workspace
+                .update(cx, |workspace, window, cx| {
+                    FileDiffView::open(
+                        PathBuf::from(&base),
+                        PathBuf::from(new_path),
+                        cx.weak_entity(),
+                        window,
+                        cx,
+                    )
+                })?
+                .await?;
+            diff_views.push(diff_view);
+        }
+        workspace.update(cx, |workspace, window, cx| {
+            workspace.activate_item(merged_item.as_ref(), true, true, window, cx);
+        })?;
+        anyhow::Ok((workspace, merged_item, diff_views))
+    }
+    .await;
+
+    let (workspace, merged_item, diff_views) = match result {
+        Ok(result) => result,
+        Err(error) => {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("error opening {merged:?} for merging: {error}"),
+                })
+                .log_err();
+            return true;
+        }
+    };
+    if !wait {
+        return false;
+    }
+
+    let (released_tx, released_rx) = oneshot::channel();
+    cx.update(|cx| {
+        merged_item
+            .on_release(
+                cx,
+                Box::new(move |_| {
+                    let _ = released_tx.send(());
+                }),
+            )
+            .detach();
+    })
+    .log_err();
+    drop(merged_item);
+
+    let background = cx.background_executor().clone();
+    let wait = released_rx.map(|_| ()).fuse();
+    wait_while_cli_is_connected(wait, responses, &background).await;
+
+    // The diffs of each side are only useful while resolving, so close them along with the
+    // merged file, unless they've been closed already.
+    workspace
+        .update(cx, |workspace, window, cx| {
+            for diff_view in diff_views {
+                if let Some(pane) = workspace.pane_for(&diff_view) {
+                    pane.update(cx, |pane, cx| {
+                        pane.close_item_by_id(diff_view.item_id(), SaveIntent::Skip, window, cx)
+                    })
+                    .detach_and_log_err(cx);
+                }
+            }
+        })
+        .ok();
+
+    // Report an unfinished merge, so that git keeps the file marked as conflicted.
+    match app_state.fs.load(Path::new(&merged)).await {
+        Ok(text) if editor::has_conflict_markers(&text) => {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("{merged:?} still contains conflict markers"),
+                })
+                .log_err();
+            true
+        }
+        Ok(_) => false,
+        Err(error) => {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("error reading {merged:?}: {error}"),
+                })
+                .log_err();
+            true
+        }
+    }
+}
+
+/// Waits for `wait` to complete, giving up early if the CLI disconnects.
+async fn wait_while_cli_is_connected(
+    wait: impl Future<Output = ()> + FusedFuture,
+    responses: &IpcSender<CliResponse>,
+    background: &BackgroundExecutor,
+) {
+    futures::pin_mut!(wait);
+
+    loop {
+        // Repeatedly check if CLI is still open to avoid wasting resources
+        // waiting for files or workspaces to close.
+        let mut timer = background.timer(Duration::from_secs(1)).fuse();
+        futures::select_biased! {
+            _ = wait => break,
+            _ = timer => {
+                if responses.send(CliResponse::Ping).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 pub async fn derive_paths_with_position(
     fs: &dyn Fs,
     path_strings: impl IntoIterator<Item = impl AsRef<str>>,